@@ -0,0 +1,134 @@
+import { ref, computed, readonly } from 'vue';
+import { invoke } from '@tauri-apps/api/core';
+import type { Annotation, AnnotationDraft, AnnotationQuery } from '~/types/project';
+import type { TimelineEvent } from '~/composables/useTimeline';
+
+// 全局状态 - 在模块级别创建，确保所有组件共享
+const annotations = ref<Annotation[]>([]);
+const error = ref<string | null>(null);
+
+const NS_PER_MS = 1_000_000;
+
+export const useAnnotations = () => {
+  const { currentProject } = useProject();
+
+  const projectPath = computed(() => currentProject.value?.path ?? '');
+
+  // 工程起始时间（纳秒），取各数据集首个数据包时间戳的最小值
+  const projectStart = computed(() => {
+    const starts = (currentProject.value?.datasets ?? [])
+      .map(dataset => dataset.start_timestamp)
+      .filter((start): start is number => start !== undefined && start !== null);
+    return starts.length > 0 ? Math.min(...starts) : 0;
+  });
+
+  // 标注转换为时间轴标记（相对工程起始时间的毫秒）
+  const timelineMarkers = computed<TimelineEvent[]>(() =>
+    annotations.value.map(annotation => ({
+      id: `annotation-${annotation.id}`,
+      type: 'info',
+      startTime: (annotation.start_timestamp - projectStart.value) / NS_PER_MS,
+      endTime:
+        annotation.end_timestamp !== undefined && annotation.end_timestamp !== null
+          ? (annotation.end_timestamp - projectStart.value) / NS_PER_MS
+          : undefined,
+      title: annotation.text,
+      description: [annotation.category, annotation.author, annotation.dataset]
+        .filter(Boolean)
+        .join(' · '),
+      color: annotation.color,
+    }))
+  );
+
+  // 时间轴上的相对时间（毫秒）换算为标注时间戳（纳秒）
+  const toTimestamp = (milliseconds: number): number =>
+    projectStart.value + Math.round(milliseconds * NS_PER_MS);
+
+  const run = async <T>(action: () => Promise<T>, label: string): Promise<T | null> => {
+    error.value = null;
+    try {
+      return await action();
+    } catch (err) {
+      error.value =
+        typeof err === 'object' && err !== null && 'message' in err
+          ? String((err as { message: unknown }).message)
+          : String(err);
+      console.error(`${label}失败:`, error.value);
+      return null;
+    }
+  };
+
+  // 加载当前工程的标注
+  const loadAnnotations = async (query?: AnnotationQuery) => {
+    if (!projectPath.value) {
+      annotations.value = [];
+      return annotations.value;
+    }
+    const result = await run(
+      () => invoke<Annotation[]>('list_annotations', { projectPath: projectPath.value, query }),
+      '加载标注'
+    );
+    annotations.value = result ?? [];
+    return annotations.value;
+  };
+
+  // 新建标注
+  const createAnnotation = async (draft: AnnotationDraft) => {
+    const created = await run(
+      () =>
+        invoke<Annotation>('create_annotation', {
+          projectPath: projectPath.value,
+          annotation: draft,
+        }),
+      '新建标注'
+    );
+    if (created) {
+      annotations.value = [...annotations.value, created].sort(
+        (a, b) => a.start_timestamp - b.start_timestamp || a.id - b.id
+      );
+    }
+    return created;
+  };
+
+  // 修改标注
+  const updateAnnotation = async (id: number, draft: AnnotationDraft) => {
+    const updated = await run(
+      () =>
+        invoke<Annotation>('update_annotation', {
+          projectPath: projectPath.value,
+          id,
+          annotation: draft,
+        }),
+      '修改标注'
+    );
+    if (updated) {
+      annotations.value = annotations.value
+        .map(annotation => (annotation.id === id ? updated : annotation))
+        .sort((a, b) => a.start_timestamp - b.start_timestamp || a.id - b.id);
+    }
+    return updated;
+  };
+
+  // 删除标注
+  const deleteAnnotation = async (id: number) => {
+    const deleted = await run(
+      () => invoke('delete_annotation', { projectPath: projectPath.value, id }).then(() => true),
+      '删除标注'
+    );
+    if (deleted) {
+      annotations.value = annotations.value.filter(annotation => annotation.id !== id);
+    }
+    return deleted ?? false;
+  };
+
+  return {
+    annotations: readonly(annotations),
+    error: readonly(error),
+    timelineMarkers,
+    toTimestamp,
+    loadAnnotations,
+    createAnnotation,
+    updateAnnotation,
+    deleteAnnotation,
+  };
+};
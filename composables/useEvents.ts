@@ -1,46 +1,129 @@
-import { ref } from 'vue';
-import type { Event } from '~/types';
+import { ref, computed, readonly } from 'vue';
+import { invoke } from '@tauri-apps/api/core';
+import type { Event, EventLevel } from '~/types';
+import type { DetectedEvent, EventQuery, EventRequest, EventSummary } from '~/types/project';
+import type { TimelineEvent } from '~/composables/useTimeline';
+
+// 全局状态 - 在模块级别创建，确保所有组件共享
+const detectedEvents = ref<DetectedEvent[]>([]);
+const error = ref<string | null>(null);
+
+const NS_PER_MS = 1_000_000;
+
+// 事件级别对应的时间轴标记类型
+const LEVEL_MARKER_TYPES: Record<EventLevel, TimelineEvent['type']> = {
+  high: 'error',
+  warning: 'warning',
+  normal: 'info',
+  low: 'success',
+};
+
+// 事件时间（纳秒）格式化为本地时分秒
+const formatTime = (timestamp: number): string =>
+  new Date(timestamp / NS_PER_MS).toLocaleTimeString('zh-CN', { hour12: false });
 
 export const useEvents = () => {
-  const events = ref<Event[]>([
-    {
-      id: 1,
-      time: '14:35:22',
-      title: '新目标发现',
-      description: '雷达发现不明目标',
-      level: 'high',
-    },
-    {
-      id: 2,
-      time: '14:33:15',
-      title: '航迹更新',
-      description: '目标743航迹已更新',
-      level: 'normal',
-    },
-    {
-      id: 3,
-      time: '14:31:08',
-      title: '通信恢复',
-      description: '与指挥所通信恢复正常',
-      level: 'normal',
-    },
-    {
-      id: 4,
-      time: '14:28:45',
-      title: '系统告警',
-      description: '雷达系统出现异常',
-      level: 'warning',
-    },
-    {
-      id: 5,
-      time: '14:25:30',
-      title: '目标离开',
-      description: '目标1691已离开监控区域',
-      level: 'low',
-    },
-  ]);
+  const { currentProject } = useProject();
+
+  const projectPath = computed(() => currentProject.value?.path ?? '');
+
+  // 工程起始时间（纳秒），取各数据集首个数据包时间戳的最小值
+  const projectStart = computed(() => {
+    const starts = (currentProject.value?.datasets ?? [])
+      .map(dataset => dataset.start_timestamp)
+      .filter((start): start is number => start !== undefined && start !== null);
+    return starts.length > 0 ? Math.min(...starts) : 0;
+  });
+
+  // 事件列表面板显示的事件
+  const events = computed<Event[]>(() =>
+    detectedEvents.value.map(event => ({
+      id: event.id,
+      time: formatTime(event.timestamp),
+      title: event.title,
+      description: event.description,
+      level: event.level,
+    }))
+  );
+
+  // 事件转换为时间轴标记（相对工程起始时间的毫秒）
+  const timelineMarkers = computed<TimelineEvent[]>(() =>
+    detectedEvents.value.map(event => ({
+      id: `event-${event.id}`,
+      type: LEVEL_MARKER_TYPES[event.level] ?? 'info',
+      startTime: (event.timestamp - projectStart.value) / NS_PER_MS,
+      endTime:
+        event.end_timestamp !== undefined && event.end_timestamp !== null
+          ? (event.end_timestamp - projectStart.value) / NS_PER_MS
+          : undefined,
+      title: event.title,
+      description: [event.description, event.dataset].filter(Boolean).join(' · '),
+    }))
+  );
+
+  const run = async <T>(action: () => Promise<T>, label: string): Promise<T | null> => {
+    error.value = null;
+    try {
+      return await action();
+    } catch (err) {
+      error.value =
+        typeof err === 'object' && err !== null && 'message' in err
+          ? String((err as { message: unknown }).message)
+          : String(err);
+      console.error(`${label}失败:`, error.value);
+      return null;
+    }
+  };
+
+  // 加载当前工程的事件列表，可按时间窗口、类型和级别筛选
+  const loadEvents = async (query?: EventQuery) => {
+    if (!projectPath.value) {
+      detectedEvents.value = [];
+      return detectedEvents.value;
+    }
+    const result = await run(
+      () => invoke<DetectedEvent[]>('list_events', { projectPath: projectPath.value, query }),
+      '加载事件列表'
+    );
+    detectedEvents.value = result ?? [];
+    return detectedEvents.value;
+  };
+
+  // 按规则重新检测事件，完成后重新加载事件列表
+  const detectEvents = async (request: EventRequest) => {
+    const summary = await run(
+      () =>
+        invoke<EventSummary>('detect_events', {
+          projectPath: projectPath.value,
+          request,
+        }),
+      '检测事件'
+    );
+    if (summary) {
+      await loadEvents();
+    }
+    return summary;
+  };
+
+  // 回放跳转到事件发生时间，返回跳转到的时间戳（纳秒）
+  const seekToEvent = (id: number, preRollNs?: number) =>
+    run(
+      () =>
+        invoke<number>('seek_to_event', {
+          projectPath: projectPath.value,
+          eventId: id,
+          preRollNs,
+        }),
+      '跳转到事件'
+    );
 
   return {
     events,
+    detectedEvents: readonly(detectedEvents),
+    error: readonly(error),
+    timelineMarkers,
+    loadEvents,
+    detectEvents,
+    seekToEvent,
   };
 };
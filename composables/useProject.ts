@@ -1,12 +1,21 @@
 import { ref, computed, readonly } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
-import type { ProjectInfo, AppInfo } from '~/types/project';
+import type { ProjectInfo, AppInfo, CommandError } from '~/types/project';
 
 // 全局状态 - 在模块级别创建，确保所有组件共享
 const currentProject = ref<ProjectInfo | null>(null);
 const isLoading = ref(false);
 const error = ref<string | null>(null);
 
+// 提取命令错误信息
+const getErrorMessage = (err: unknown): string => {
+  if (err instanceof Error) return err.message;
+  if (typeof err === 'object' && err !== null && 'message' in err) {
+    return (err as CommandError).message;
+  }
+  return String(err);
+};
+
 export const useProject = () => {
   // 计算属性
   const isProjectLoaded = computed(() => currentProject.value !== null);
@@ -46,7 +55,7 @@ export const useProject = () => {
 
       return projectInfo;
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
+      const errorMessage = getErrorMessage(err);
       error.value = errorMessage;
       console.error('打开工程失败:', errorMessage);
       return null;
@@ -68,7 +77,7 @@ export const useProject = () => {
       console.log('工程已关闭');
       return true;
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
+      const errorMessage = getErrorMessage(err);
       error.value = errorMessage;
       console.error('关闭工程失败:', errorMessage);
       return false;
@@ -14,6 +14,7 @@ export interface TimelineEvent {
   endTime?: number; // 结束时间，用于区间事件
   title: string;
   description?: string;
+  color?: string; // 自定义颜色（如标注颜色），覆盖类型颜色
 }
 
 // 平台数据类型
@@ -0,0 +1,79 @@
+use log::{error, info};
+
+use crate::project::annotation::{Annotation, AnnotationDraft, AnnotationQuery, AnnotationStore};
+use crate::types::{CommandError, PlaybackError};
+
+/// 按开始时间列出工程中的标注，可按时间窗口、类别、数据集和作者筛选
+#[tauri::command]
+pub async fn list_annotations(
+    project_path: String,
+    query: Option<AnnotationQuery>,
+) -> std::result::Result<Vec<Annotation>, CommandError> {
+    let path = project_path.clone();
+    let annotations = tokio::task::spawn_blocking(move || {
+        AnnotationStore::new(&path).list(&query.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| PlaybackError::ProjectError(format!("读取标注任务异常退出: {}", e)))?
+    .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    Ok(annotations)
+}
+
+/// 新建标注，返回分配了编号的标注
+#[tauri::command]
+pub async fn create_annotation(
+    project_path: String,
+    annotation: AnnotationDraft,
+) -> std::result::Result<Annotation, CommandError> {
+    info!(
+        "新建标注: {} @ {}",
+        project_path, annotation.start_timestamp
+    );
+
+    let path = project_path.clone();
+    tokio::task::spawn_blocking(move || AnnotationStore::new(&path).create(annotation))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("保存标注任务异常退出: {}", e)))?
+        .map_err(|e| {
+            error!("新建标注失败: {}", e);
+            CommandError::from(e).with_path(&project_path)
+        })
+}
+
+/// 修改标注
+#[tauri::command]
+pub async fn update_annotation(
+    project_path: String,
+    id: u64,
+    annotation: AnnotationDraft,
+) -> std::result::Result<Annotation, CommandError> {
+    info!("修改标注: {} #{}", project_path, id);
+
+    let path = project_path.clone();
+    tokio::task::spawn_blocking(move || AnnotationStore::new(&path).update(id, annotation))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("保存标注任务异常退出: {}", e)))?
+        .map_err(|e| {
+            error!("修改标注失败: {}", e);
+            CommandError::from(e).with_path(&project_path)
+        })
+}
+
+/// 删除标注
+#[tauri::command]
+pub async fn delete_annotation(
+    project_path: String,
+    id: u64,
+) -> std::result::Result<(), CommandError> {
+    info!("删除标注: {} #{}", project_path, id);
+
+    let path = project_path.clone();
+    tokio::task::spawn_blocking(move || AnnotationStore::new(&path).delete(id))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("删除标注任务异常退出: {}", e)))?
+        .map_err(|e| {
+            error!("删除标注失败: {}", e);
+            CommandError::from(e).with_path(&project_path)
+        })
+}
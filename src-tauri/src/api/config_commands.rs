@@ -1,34 +0,0 @@
-use serde_json;
-use tauri::{AppHandle, Manager};
-
-use crate::state::app_state::AppState;
-
-/// 列出所有数据集（简化版本）
-#[tauri::command]
-pub fn list_datasets(app: AppHandle) -> std::result::Result<Vec<String>, String> {
-    let state = app.state::<std::sync::Mutex<AppState>>();
-    let _state_guard = state.lock().unwrap();
-
-    // 简化实现，返回空列表
-    Ok(vec![])
-}
-
-/// 获取数据集统计信息（简化版本）
-#[tauri::command]
-pub fn get_dataset_stats(
-    _app: AppHandle,
-    _dataset_name: String,
-) -> std::result::Result<Option<u64>, String> {
-    // 简化实现，返回空
-    Ok(None)
-}
-
-/// 获取数据集详细信息（简化版本）
-#[tauri::command]
-pub fn get_dataset_info(
-    _app: AppHandle,
-    _dataset_name: String,
-) -> std::result::Result<Option<serde_json::Value>, String> {
-    // 简化实现，返回空
-    Ok(None)
-}
@@ -0,0 +1,528 @@
+use log::{error, info};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::api::task_commands::run_task;
+use crate::dataset::convert::{CaptureConverter, CaptureFilter, ConvertSummary};
+use crate::dataset::export::{DatasetExporter, ExportOptions, ExportSummary};
+use crate::dataset::extract::{DatasetExtractor, ExtractFilter, ExtractRequest, ExtractSummary};
+use crate::dataset::import::{DatasetImporter, ImportMode, ImportSummary};
+use crate::dataset::merge::{DatasetSplitter, MergeRequest, SplitRequest, SplitSummary};
+use crate::dataset::repair::{DatasetRepairer, RepairOptions, RepairSummary};
+use crate::dataset::stats::{DatasetStats, FileStats, StatsOptions, StatsService};
+use crate::dataset::verify::{DataVerifier, VerifyReport};
+use crate::project::structure::ProjectStructure;
+use crate::state::app_state::AppState;
+use crate::state::config_state::UDPConfig;
+use crate::types::{CommandError, PlaybackError};
+
+/// 列出所有数据集
+#[tauri::command]
+pub async fn list_datasets(
+    state: State<'_, AppState>,
+) -> std::result::Result<Vec<String>, CommandError> {
+    Ok(state.list_datasets().await)
+}
+
+/// 获取数据集统计信息
+///
+/// 包括文件数、数据包数、时间范围、包长分布、速率和数据中断，未变化的文件直接使用缓存结果
+#[tauri::command]
+pub async fn get_dataset_stats(
+    state: State<'_, AppState>,
+    dataset_name: String,
+    options: Option<StatsOptions>,
+) -> std::result::Result<DatasetStats, CommandError> {
+    let service = stats_service(&state).await?;
+    let options = options.unwrap_or_default();
+    let name = dataset_name.clone();
+
+    tokio::task::spawn_blocking(move || service.dataset_stats(&name, &options))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("统计任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_dataset(dataset_name))
+}
+
+/// 获取单个数据文件的统计信息
+#[tauri::command]
+pub async fn get_file_stats(
+    state: State<'_, AppState>,
+    dataset_name: String,
+    file_name: String,
+    options: Option<StatsOptions>,
+) -> std::result::Result<FileStats, CommandError> {
+    let service = stats_service(&state).await?;
+    let options = options.unwrap_or_default();
+    let name = dataset_name.clone();
+
+    tokio::task::spawn_blocking(move || service.file_stats(&name, &file_name, &options))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("统计任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_dataset(dataset_name))
+}
+
+/// 获取数据集详细信息
+#[tauri::command]
+pub async fn get_dataset_info(
+    state: State<'_, AppState>,
+    dataset_name: String,
+) -> std::result::Result<Option<serde_json::Value>, CommandError> {
+    let project_manager = state.project_manager.read().await;
+
+    Ok(project_manager
+        .get_dataset_reader(&dataset_name)
+        .map(|reader| {
+            serde_json::json!({
+                "name": dataset_name,
+                "config": reader.config,
+                "pcap_files": reader
+                    .pcap_files
+                    .iter()
+                    .map(|p| p.to_string_lossy())
+                    .collect::<Vec<_>>(),
+                "total_packets": reader.total_packets,
+            })
+        }))
+}
+
+/// 导入外部PCAP文件到数据集
+///
+/// 进度通过 `task://progress` 事件推送，可用 `cancel_task` 取消；数据集正在回放或被其他操作占用时拒绝导入
+#[tauri::command]
+pub async fn import_pcap_files<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    files: Vec<String>,
+    mode: Option<ImportMode>,
+) -> std::result::Result<ImportSummary, CommandError> {
+    info!(
+        "导入 {} 个文件到数据集 {}/{}",
+        files.len(),
+        project_path,
+        dataset_name
+    );
+
+    // 导入期间占用数据集，直到重新加载工程
+    let _guard = state
+        .lock_dataset(&project_path, &dataset_name)
+        .await
+        .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+    let dataset_dir = Path::new(&project_path).join(&dataset_name);
+    let importer = DatasetImporter::new(&dataset_dir, mode.unwrap_or_default())
+        .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+
+    let files: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    let summary = run_task(app, &state, "import", move |cancel, emit| {
+        importer.import(&files, cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("导入数据集失败: {}", e);
+        CommandError::from(e).with_dataset(&dataset_name)
+    })?;
+
+    if !summary.imported.is_empty() {
+        state.refresh_project(&project_path).await?;
+    }
+
+    Ok(summary)
+}
+
+/// 从标准抓包文件（libpcap/pcapng）提取UDP负载导入数据集
+///
+/// 数据集不存在时自动创建；推断出的目的地址和端口写入数据集的网络配置。
+/// 数据集正在回放或被其他操作占用时拒绝导入
+#[tauri::command]
+pub async fn import_capture_files<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    files: Vec<String>,
+    filter: Option<CaptureFilter>,
+) -> std::result::Result<ConvertSummary, CommandError> {
+    info!(
+        "转换 {} 个抓包文件到数据集 {}/{}",
+        files.len(),
+        project_path,
+        dataset_name
+    );
+
+    // 转换期间占用数据集，直到重新加载工程
+    let _guard = state
+        .lock_dataset(&project_path, &dataset_name)
+        .await
+        .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+    let dataset_dir = Path::new(&project_path).join(&dataset_name);
+    if !dataset_dir.exists() {
+        ProjectStructure::create_dataset(&project_path, &dataset_name)
+            .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+    }
+    let converter = CaptureConverter::new(&dataset_dir, filter.unwrap_or_default())
+        .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+
+    let files: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    let summary = run_task(app, &state, "convert", move |cancel, emit| {
+        converter.convert(&files, cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("转换抓包文件失败: {}", e);
+        CommandError::from(e).with_dataset(&dataset_name)
+    })?;
+
+    if let Some(network) = &summary.network {
+        info!(
+            "数据集 {} 的网络配置推断为 {} {}:{}",
+            dataset_name, network.network_type, network.ip_address, network.port
+        );
+        ProjectStructure::update_dataset_network(&project_path, &dataset_name, network.clone())?;
+
+        // 已注册的数据集直接更新发送配置，未注册的在刷新工程时按工程文件注册
+        let mut config_manager = state.playback_engine.config_manager_mut();
+        if config_manager
+            .get_config()
+            .get_dataset_config(&dataset_name)
+            .is_some()
+        {
+            config_manager.update_dataset_config(dataset_name.clone(), UDPConfig::from(network));
+        }
+    }
+
+    if !summary.files.is_empty() {
+        state.refresh_project(&project_path).await?;
+    }
+
+    Ok(summary)
+}
+
+/// 导出数据集为标准 pcapng 文件
+///
+/// 负载按数据集网络配置封装为UDP报文，可按时间范围、数据文件和负载长度筛选
+#[tauri::command]
+pub async fn export_dataset_pcapng<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    output_path: String,
+    options: Option<ExportOptions>,
+) -> std::result::Result<ExportSummary, CommandError> {
+    info!(
+        "导出数据集 {}/{} 到 {}",
+        project_path, dataset_name, output_path
+    );
+
+    let exporter = ProjectStructure::dataset_config(&project_path, &dataset_name)
+        .and_then(|dataset| DatasetExporter::new(dataset, options.unwrap_or_default()))
+        .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+
+    let output = PathBuf::from(&output_path);
+    let summary = run_task(app, &state, "export", move |cancel, emit| {
+        exporter.export(&output, cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("导出数据集失败: {}", e);
+        CommandError::from(e)
+            .with_dataset(&dataset_name)
+            .with_path(&output_path)
+    })?;
+
+    Ok(summary)
+}
+
+/// 校验数据完整性
+///
+/// 未指定数据集时校验整个工程，指定 `file_names` 时只校验数据集中的这些文件
+#[tauri::command]
+pub async fn verify_data<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: Option<String>,
+    file_names: Option<Vec<String>>,
+) -> std::result::Result<VerifyReport, CommandError> {
+    info!(
+        "校验数据: {}/{}",
+        project_path,
+        dataset_name.as_deref().unwrap_or("*")
+    );
+
+    let verifier = match &dataset_name {
+        Some(name) => {
+            DataVerifier::for_dataset(&project_path, name, &file_names.unwrap_or_default())
+                .map_err(|e| CommandError::from(e).with_dataset(name))?
+        }
+        None => DataVerifier::for_project(&project_path)
+            .map_err(|e| CommandError::from(e).with_path(&project_path))?,
+    };
+
+    let report = run_task(app, &state, "verify", move |cancel, emit| {
+        verifier.verify(cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?;
+    info!(
+        "校验完成: {} 个文件, {} 个有问题, 损坏数据包 {} 个",
+        report.total_files, report.invalid_files, report.corrupt_packets
+    );
+    Ok(report)
+}
+
+/// 修复数据集中损坏的数据文件
+///
+/// 丢弃无法解析的数据并重新生成索引，原文件默认移到工程的 `.trash` 目录保留
+#[tauri::command]
+pub async fn repair_dataset<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    file_names: Option<Vec<String>>,
+    options: Option<RepairOptions>,
+) -> std::result::Result<RepairSummary, CommandError> {
+    info!("修复数据集: {}/{}", project_path, dataset_name);
+
+    let options = options.unwrap_or_default();
+    // 修复期间占用数据集，直到重新加载工程
+    let _guard = if options.dry_run {
+        None
+    } else {
+        Some(
+            state
+                .lock_dataset(&project_path, &dataset_name)
+                .await
+                .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?,
+        )
+    };
+    let repairer = DatasetRepairer::new(
+        &project_path,
+        &dataset_name,
+        &file_names.unwrap_or_default(),
+        options,
+    )
+    .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+
+    let summary = run_task(app, &state, "repair", move |cancel, emit| {
+        repairer.repair(cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("修复数据集失败: {}", e);
+        CommandError::from(e).with_dataset(&dataset_name)
+    })?;
+
+    if summary.files.iter().any(|f| f.repaired) {
+        state.refresh_project(&project_path).await?;
+    }
+    Ok(summary)
+}
+
+/// 从数据集中抽取子数据集
+///
+/// 按时间窗口和字节模式筛选源数据集的数据包，按时间顺序合并写入目标数据集，目标数据集不存在时自动创建
+#[tauri::command]
+pub async fn extract_dataset<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    request: ExtractRequest,
+) -> std::result::Result<ExtractSummary, CommandError> {
+    info!(
+        "抽取数据集 {:?} 到 {}/{}",
+        request.sources, project_path, request.target
+    );
+
+    let target = request.target.clone();
+    // 抽取期间占用目标数据集，直到重新加载工程
+    let _guard = state
+        .lock_dataset(&project_path, &target)
+        .await
+        .map_err(|e| CommandError::from(e).with_dataset(&target))?;
+    let extractor = DatasetExtractor::for_project(&project_path, &request)
+        .map_err(|e| CommandError::from(e).with_dataset(&target))?;
+
+    let path = PathBuf::from(&project_path);
+    let dataset = target.clone();
+    let summary = run_task(app, &state, "extract", move |cancel, emit| {
+        extractor.extract_to_dataset(&path, &dataset, cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("抽取数据集失败: {}", e);
+        CommandError::from(e).with_dataset(&target)
+    })?;
+
+    state.refresh_project(&project_path).await?;
+    Ok(summary)
+}
+
+/// 合并多个数据集
+///
+/// 各数据集的数据包按时间戳合并写入目标数据集，并记录每个数据包的来源数据集
+#[tauri::command]
+pub async fn merge_datasets<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    request: MergeRequest,
+) -> std::result::Result<ExtractSummary, CommandError> {
+    info!(
+        "合并数据集 {:?} 到 {}/{}",
+        request.sources, project_path, request.target
+    );
+
+    let target = request.target.clone();
+    // 合并期间占用目标数据集，直到重新加载工程
+    let _guard = state
+        .lock_dataset(&project_path, &target)
+        .await
+        .map_err(|e| CommandError::from(e).with_dataset(&target))?;
+    let extractor = request
+        .extractor(&project_path)
+        .map_err(|e| CommandError::from(e).with_dataset(&target))?;
+
+    let path = PathBuf::from(&project_path);
+    let dataset = target.clone();
+    let summary = run_task(app, &state, "merge", move |cancel, emit| {
+        extractor.extract_to_dataset(&path, &dataset, cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("合并数据集失败: {}", e);
+        CommandError::from(e).with_dataset(&target)
+    })?;
+
+    state.refresh_project(&project_path).await?;
+    Ok(summary)
+}
+
+/// 按时长、大小或数据包数拆分数据集
+///
+/// 未指定目标数据集时原地替换原文件，原文件默认移到工程的 `.trash` 目录保留
+#[tauri::command]
+pub async fn split_dataset<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    request: SplitRequest,
+) -> std::result::Result<SplitSummary, CommandError> {
+    info!("拆分数据集: {}/{}", project_path, request.dataset);
+
+    let dataset_name = request.dataset.clone();
+    let splitter = DatasetSplitter::new(&project_path, request)
+        .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+    // 原地拆分期间占用数据集，直到重新加载工程
+    let _guard = if splitter.in_place() {
+        Some(
+            state
+                .lock_dataset(&project_path, &dataset_name)
+                .await
+                .map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?,
+        )
+    } else {
+        None
+    };
+
+    let summary = run_task(app, &state, "split", move |cancel, emit| {
+        splitter.split(cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("拆分数据集失败: {}", e);
+        CommandError::from(e).with_dataset(&dataset_name)
+    })?;
+
+    if !summary.result.files.is_empty() {
+        state.refresh_project(&project_path).await?;
+    }
+    Ok(summary)
+}
+
+/// 把数据集的时间校正固化到新数据集
+///
+/// 按工程文件中的时间校正改写数据包时间戳，写入目标数据集，目标数据集不存在时自动创建并沿用源数据集的网络配置
+#[tauri::command]
+pub async fn bake_time_correction<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    target: String,
+) -> std::result::Result<ExtractSummary, CommandError> {
+    info!(
+        "固化数据集时间校正: {}/{} -> {}",
+        project_path, dataset_name, target
+    );
+
+    // 固化期间占用目标数据集，直到重新加载工程
+    let _guard = state
+        .lock_dataset(&project_path, &target)
+        .await
+        .map_err(|e| CommandError::from(e).with_dataset(&target))?;
+    let prepare = || {
+        let config = ProjectStructure::dataset_config(&project_path, &dataset_name)?;
+        let correction = config.time_correction.ok_or_else(|| {
+            PlaybackError::ProjectError(format!("数据集未设置时间校正: {}", dataset_name))
+        })?;
+        let request = ExtractRequest {
+            sources: vec![dataset_name.clone()],
+            target: target.clone(),
+            filter: ExtractFilter::default(),
+            max_file_size: None,
+        };
+        let extractor = DatasetExtractor::for_project(&project_path, &request)?
+            .with_time_correction(&dataset_name, correction);
+        Ok::<_, PlaybackError>((extractor, config.network_config))
+    };
+    let (extractor, network) =
+        prepare().map_err(|e| CommandError::from(e).with_dataset(&dataset_name))?;
+
+    let created = !Path::new(&project_path).join(&target).exists();
+
+    let path = PathBuf::from(&project_path);
+    let dataset = target.clone();
+    let summary = run_task(app, &state, "bake", move |cancel, emit| {
+        extractor.extract_to_dataset(&path, &dataset, cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("固化时间校正失败: {}", e);
+        CommandError::from(e).with_dataset(&target)
+    })?;
+
+    if created && !summary.files.is_empty() {
+        ProjectStructure::update_dataset_network(&project_path, &target, network)?;
+    }
+    state.refresh_project(&project_path).await?;
+    Ok(summary)
+}
+
+/// 当前工程的统计服务
+async fn stats_service(state: &AppState) -> std::result::Result<StatsService, CommandError> {
+    let project = state
+        .current_project()
+        .await
+        .ok_or_else(|| PlaybackError::ProjectError("未打开工程".to_string()))?;
+    Ok(StatsService::new(project.path))
+}
@@ -0,0 +1,93 @@
+use log::{error, info};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::api::task_commands::run_task;
+use crate::event::detector::{EventDetector, EventRequest, EventSummary};
+use crate::event::rule::EventRule;
+use crate::event::store::{DetectedEvent, EventQuery, EventStore};
+use crate::state::app_state::AppState;
+use crate::types::{CommandError, PlaybackError};
+
+/// 按规则检测工程中的关键事件，结果保存为工程的事件列表并替换上一次的检测结果
+#[tauri::command]
+pub async fn detect_events<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    request: EventRequest,
+) -> std::result::Result<EventSummary, CommandError> {
+    info!("检测事件: {}，{} 条规则", project_path, request.rules.len());
+
+    let detector = EventDetector::new(&project_path, request)
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    let summary = run_task(app, &state, "events", move |cancel, emit| {
+        detector.detect(cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("事件检测失败: {}", e);
+        CommandError::from(e).with_path(&project_path)
+    })?;
+
+    Ok(summary)
+}
+
+/// 按时间顺序列出检测出的事件，可按时间窗口、类型、级别、规则和数据集筛选
+#[tauri::command]
+pub async fn list_events(
+    project_path: String,
+    query: Option<EventQuery>,
+) -> std::result::Result<Vec<DetectedEvent>, CommandError> {
+    let path = project_path.clone();
+    let events = tokio::task::spawn_blocking(move || {
+        EventStore::new(&path).list(&query.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| PlaybackError::ProjectError(format!("读取事件列表任务异常退出: {}", e)))?
+    .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    Ok(events)
+}
+
+/// 获取上一次检测使用的规则，尚未检测过时为空
+#[tauri::command]
+pub async fn get_event_rules(
+    project_path: String,
+) -> std::result::Result<Vec<EventRule>, CommandError> {
+    let path = project_path.clone();
+    let list = tokio::task::spawn_blocking(move || EventStore::new(&path).read())
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("读取事件列表任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    Ok(list.map(|list| list.rules).unwrap_or_default())
+}
+
+/// 回放跳转到事件发生时间，`pre_roll_ns` 为提前的时长，返回跳转到的时间戳
+#[tauri::command]
+pub async fn seek_to_event(
+    state: State<'_, AppState>,
+    project_path: String,
+    event_id: u64,
+    pre_roll_ns: Option<u64>,
+) -> std::result::Result<u64, CommandError> {
+    let path = project_path.clone();
+    let event = tokio::task::spawn_blocking(move || EventStore::new(&path).get(event_id))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("读取事件列表任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    let timestamp = event.timestamp.saturating_sub(pre_roll_ns.unwrap_or(0));
+    info!("跳转到事件 {} ({}): {}", event_id, event.title, timestamp);
+
+    state
+        .playback_engine
+        .seek_to(timestamp)
+        .await
+        .map_err(|e| CommandError::from(e).with_timestamp(timestamp))?;
+
+    Ok(timestamp)
+}
@@ -2,6 +2,10 @@
 //!
 //! 提供前端调用的所有命令接口
 
-pub mod config_commands;
+pub mod annotation_commands;
+pub mod dataset_commands;
+pub mod event_commands;
 pub mod playback_commands;
+pub mod product_commands;
 pub mod project_commands;
+pub mod task_commands;
@@ -1,73 +1,83 @@
 use log::info;
-use std::sync::Arc;
-use tauri::{AppHandle, Manager};
-use tokio::sync::Mutex;
+use tauri::State;
 
+use crate::playback::engine::PlaybackOptions;
+use crate::playback::reader::CorruptPacketPolicy;
+use crate::state::app_state::AppState;
 use crate::state::playback_state::PlaybackState;
+use crate::types::CommandError;
 
 /// 开始回放
+///
+/// 指定 `corrupt_packets` 或 `live_latency`（纳秒）时按选项重新开始回放，否则暂停状态下继续回放；
+/// 指定 `live_latency` 时实时跟随正在录制的数据集
 #[tauri::command]
 pub async fn start_playback(
-    app: AppHandle,
+    state: State<'_, AppState>,
     dataset_name: String,
-) -> std::result::Result<(), String> {
+    corrupt_packets: Option<CorruptPacketPolicy>,
+    live_latency: Option<u64>,
+) -> std::result::Result<(), CommandError> {
     info!("开始回放数据集: {}", dataset_name);
 
-    let state = app.state::<Arc<Mutex<crate::state::app_state::AppState>>>();
-    let mut state_guard = state.lock().await;
-
-    state_guard.playback_engine.start(dataset_name).await
+    let options = match (corrupt_packets, live_latency) {
+        (None, None) => None,
+        (policy, live_latency) => Some(PlaybackOptions {
+            corrupt_packets: policy.unwrap_or_default(),
+            live_latency,
+            ..Default::default()
+        }),
+    };
+    let result = state.start_playback(dataset_name.clone(), options).await;
+    result.map_err(|e| CommandError::from(e).with_dataset(dataset_name))
 }
 
 /// 暂停回放
 #[tauri::command]
-pub async fn pause_playback(app: AppHandle) -> std::result::Result<(), String> {
+pub async fn pause_playback(state: State<'_, AppState>) -> std::result::Result<(), CommandError> {
     info!("暂停回放");
 
-    let state = app.state::<Arc<Mutex<crate::state::app_state::AppState>>>();
-    let mut state_guard = state.lock().await;
-
-    state_guard.playback_engine.pause().await
+    Ok(state.playback_engine.pause().await?)
 }
 
 /// 停止回放
 #[tauri::command]
-pub async fn stop_playback(app: AppHandle) -> std::result::Result<(), String> {
+pub async fn stop_playback(state: State<'_, AppState>) -> std::result::Result<(), CommandError> {
     info!("停止回放");
 
-    let state = app.state::<Arc<Mutex<crate::state::app_state::AppState>>>();
-    let mut state_guard = state.lock().await;
-
-    state_guard.playback_engine.stop().await
+    Ok(state.playback_engine.stop().await?)
 }
 
 /// 跳转到指定时间
 #[tauri::command]
-pub async fn seek_to_time(app: AppHandle, timestamp: u64) -> std::result::Result<(), String> {
+pub async fn seek_to_time(
+    state: State<'_, AppState>,
+    timestamp: u64,
+) -> std::result::Result<(), CommandError> {
     info!("跳转到时间戳: {}", timestamp);
 
-    let state = app.state::<Arc<Mutex<crate::state::app_state::AppState>>>();
-    let mut state_guard = state.lock().await;
-
-    state_guard.playback_engine.seek_to(timestamp).await
+    state
+        .playback_engine
+        .seek_to(timestamp)
+        .await
+        .map_err(|e| CommandError::from(e).with_timestamp(timestamp))
 }
 
 /// 设置回放速度
 #[tauri::command]
-pub async fn set_playback_speed(app: AppHandle, speed: f64) -> std::result::Result<(), String> {
+pub async fn set_playback_speed(
+    state: State<'_, AppState>,
+    speed: f64,
+) -> std::result::Result<(), CommandError> {
     info!("设置回放速度: {}", speed);
 
-    let state = app.state::<Arc<Mutex<crate::state::app_state::AppState>>>();
-    let mut state_guard = state.lock().await;
-
-    state_guard.playback_engine.set_speed(speed).await
+    Ok(state.playback_engine.set_speed(speed).await?)
 }
 
 /// 获取当前回放状态
 #[tauri::command]
-pub async fn get_playback_state(app: AppHandle) -> std::result::Result<PlaybackState, String> {
-    let state = app.state::<Arc<Mutex<crate::state::app_state::AppState>>>();
-    let state_guard = state.lock().await;
-
-    Ok(state_guard.playback_engine.get_state().await)
+pub async fn get_playback_state(
+    state: State<'_, AppState>,
+) -> std::result::Result<PlaybackState, CommandError> {
+    Ok(state.playback_engine.get_state().await)
 }
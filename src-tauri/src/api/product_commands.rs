@@ -0,0 +1,97 @@
+use log::{error, info};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::api::task_commands::run_task;
+use crate::product::generator::{ProductGenerator, ProductRequest, ProductSummary};
+use crate::product::store::{ProductInfo, ProductStore};
+use crate::state::app_state::AppState;
+use crate::types::{CommandError, PlaybackError};
+
+/// 列出工程中的数据产品
+#[tauri::command]
+pub async fn list_products(
+    project_path: String,
+) -> std::result::Result<Vec<ProductInfo>, CommandError> {
+    let path = project_path.clone();
+    let products = tokio::task::spawn_blocking(move || ProductStore::new(&path).list())
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("读取数据产品任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    Ok(products)
+}
+
+/// 打开数据产品，返回各数据集的生成状态和抽取结果信息
+#[tauri::command]
+pub async fn open_product(
+    project_path: String,
+    product_name: String,
+) -> std::result::Result<ProductInfo, CommandError> {
+    info!("打开数据产品: {}/{}", project_path, product_name);
+
+    let (path, name) = (project_path.clone(), product_name.clone());
+    let product = tokio::task::spawn_blocking(move || ProductStore::new(&path).open(&name))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("读取数据产品任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    Ok(product)
+}
+
+/// 生成数据产品
+///
+/// 从选定数据集抽取时间窗口内的数据到 `.result/<数据集>/<产品名>/`，同名产品会被重新生成
+#[tauri::command]
+pub async fn generate_product<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_path: String,
+    request: ProductRequest,
+) -> std::result::Result<ProductSummary, CommandError> {
+    info!("生成数据产品: {}/{}", project_path, request.name);
+
+    let name = request.name.clone();
+    let generator = ProductGenerator::new(&project_path, request)
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    // 生成期间占用源数据集，避免抽取过程中数据集被修改、重命名或移除
+    let mut guards = Vec::new();
+    for dataset in generator.datasets() {
+        guards.push(
+            state
+                .lock_dataset(&project_path, dataset)
+                .await
+                .map_err(|e| CommandError::from(e).with_dataset(dataset))?,
+        );
+    }
+
+    let summary = run_task(app, &state, "product", move |cancel, emit| {
+        generator.generate(cancel, |stage, current, total, item| {
+            emit(stage.as_str(), current, total, item)
+        })
+    })
+    .await?
+    .map_err(|e| {
+        error!("生成数据产品失败 {}: {}", name, e);
+        CommandError::from(e).with_path(&project_path)
+    })?;
+
+    Ok(summary)
+}
+
+/// 删除数据产品及其抽取结果和生成状态
+#[tauri::command]
+pub async fn delete_product(
+    project_path: String,
+    product_name: String,
+) -> std::result::Result<(), CommandError> {
+    info!("删除数据产品: {}/{}", project_path, product_name);
+
+    let (path, name) = (project_path.clone(), product_name.clone());
+    tokio::task::spawn_blocking(move || ProductStore::new(&path).delete(&name))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("删除数据产品任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    Ok(())
+}
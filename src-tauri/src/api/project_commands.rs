@@ -1,17 +1,23 @@
-use log::{error, info};
+use log::{error, info, warn};
 use serde_json::json;
-use tauri::{AppHandle, Manager};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
-use crate::project::loader::ProjectLoader;
+use crate::api::task_commands::progress_emitter;
 use crate::project::structure::ProjectStructure;
+use crate::project::validator::{ProjectValidator, ValidationReport};
+use crate::project::watcher::PROJECT_CHANGED_EVENT;
 use crate::state::app_state::AppState;
-use crate::types::ProjectInfo;
+use crate::types::{
+    CommandError, CorrectionModel, PlaybackError, ProjectInfo, TimeCorrection, TimeReference,
+};
 
 /// 选择项目目录
 #[tauri::command]
-pub async fn select_project_directory(
-    app: AppHandle,
-) -> std::result::Result<Option<String>, String> {
+pub async fn select_project_directory<R: Runtime>(
+    app: AppHandle<R>,
+) -> std::result::Result<Option<String>, CommandError> {
     use tauri_plugin_dialog::DialogExt;
     use tokio::sync::oneshot;
 
@@ -32,54 +38,87 @@ pub async fn select_project_directory(
     // 等待用户选择结果
     match rx.await {
         Ok(result) => Ok(result),
-        Err(_) => Err("对话框操作失败".to_string()),
+        Err(_) => Err(PlaybackError::ProjectError("对话框操作失败".to_string()).into()),
     }
 }
 
 /// 打开工程目录
+///
+/// 扫描数据文件的进度通过 `task://progress` 事件推送
 #[tauri::command]
-pub async fn open_project(
-    app: AppHandle,
+pub async fn open_project<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
     path: String,
-) -> std::result::Result<ProjectInfo, String> {
+) -> std::result::Result<ProjectInfo, CommandError> {
     info!("接收到打开工程请求: {}", path);
 
-    let mut loader = ProjectLoader::new();
-    match loader.open_project(&path).await {
-        Ok(project_info) => {
-            // 保存到状态管理器
-            let state = app.state::<std::sync::Mutex<AppState>>();
-            {
-                let mut state_guard = state.lock().unwrap();
-                state_guard.set_current_project(Some(project_info.clone()));
-            }
+    let (task_id, _) = state.tasks.register("open");
+    let handle = app.clone();
+    let emit = Mutex::new(progress_emitter(app, task_id.clone(), "open"));
+    let progress = move |done: u64, total: u64, file: &Path| {
+        if let Ok(mut emit) = emit.lock() {
+            emit("scanning", done, total, Some(&file.to_string_lossy()));
+        }
+    };
+    let result = state.open_project_with_progress(&path, progress).await;
+    state.tasks.finish(&task_id);
 
+    match result {
+        Ok(project_info) => {
             info!("工程打开成功: {}", project_info.name);
+            // 监视工程目录，数据文件增长或数据集增删时增量更新并通知前端
+            let watched = state.watch_project(&project_info.path, move |changes| {
+                let state = handle.state::<AppState>();
+                tauri::async_runtime::block_on(state.apply_project_changes(&changes));
+                let _ = handle.emit(PROJECT_CHANGED_EVENT, changes);
+            });
+            if let Err(e) = watched {
+                warn!("监视工程目录失败: {}", e);
+            }
             Ok(project_info)
         }
         Err(e) => {
             error!("打开工程失败: {}", e);
-            Err(e.to_string())
+            Err(CommandError::from(e).with_path(path))
         }
     }
 }
 
+/// 检查工程并返回分级的问题报告
+///
+/// 打开工程后由界面调用展示，工程路径无效时返回错误
+#[tauri::command]
+pub async fn validate_project(
+    project_path: String,
+) -> std::result::Result<ValidationReport, CommandError> {
+    info!("检查工程: {}", project_path);
+
+    let path = project_path.clone();
+    let report = tokio::task::spawn_blocking(move || ProjectValidator::validate_project(&path))
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("检查任务异常退出: {}", e)))?
+        .map_err(|e| CommandError::from(e).with_path(&project_path))?;
+
+    info!(
+        "工程检查完成: {} 个错误, {} 个警告",
+        report.errors, report.warnings
+    );
+    Ok(report)
+}
+
 /// 获取当前工程信息
 #[tauri::command]
-pub fn get_project_info(app: AppHandle) -> std::result::Result<Option<ProjectInfo>, String> {
-    let state = app.state::<std::sync::Mutex<AppState>>();
-    let state_guard = state.lock().unwrap();
-    Ok(state_guard.current_project())
+pub async fn get_project_info(
+    state: State<'_, AppState>,
+) -> std::result::Result<Option<ProjectInfo>, CommandError> {
+    Ok(state.current_project().await)
 }
 
 /// 关闭当前工程
 #[tauri::command]
-pub fn close_project(app: AppHandle) -> std::result::Result<(), String> {
-    let state = app.state::<std::sync::Mutex<AppState>>();
-    {
-        let mut state_guard = state.lock().unwrap();
-        state_guard.set_current_project(None);
-    }
+pub async fn close_project(state: State<'_, AppState>) -> std::result::Result<(), CommandError> {
+    state.close_project().await?;
     info!("工程已关闭");
     Ok(())
 }
@@ -88,7 +127,7 @@ pub fn close_project(app: AppHandle) -> std::result::Result<(), String> {
 #[tauri::command]
 pub async fn get_project_structure(
     project_path: String,
-) -> std::result::Result<serde_json::Value, String> {
+) -> std::result::Result<serde_json::Value, CommandError> {
     info!("获取项目结构信息: {}", project_path);
 
     match ProjectStructure::from_path(&project_path) {
@@ -148,7 +187,44 @@ pub async fn get_project_structure(
         }
         Err(e) => {
             error!("获取项目结构失败: {}", e);
-            Err(e.to_string())
+            Err(CommandError::from(e).with_path(project_path))
+        }
+    }
+}
+
+/// 新建工程
+#[tauri::command]
+pub async fn create_project(
+    parent_path: String,
+    project_name: String,
+    description: Option<String>,
+    datasets: Option<Vec<String>>,
+) -> std::result::Result<serde_json::Value, CommandError> {
+    info!("新建工程: {} in {}", project_name, parent_path);
+
+    let datasets = datasets.unwrap_or_default();
+    match ProjectStructure::create(&parent_path, &project_name, description, &datasets) {
+        Ok(structure) => {
+            info!("工程创建成功: {:?}", structure.root_path);
+            Ok(json!({
+                "success": true,
+                "message": "工程创建成功",
+                "project_name": structure.name,
+                "project_path": structure.root_path.to_string_lossy(),
+                "datasets": structure
+                    .datasets
+                    .iter()
+                    .map(|d| d.name.clone())
+                    .collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("新建工程失败: {}", e);
+            Err(CommandError::from(e).with_path(
+                Path::new(&parent_path)
+                    .join(&project_name)
+                    .to_string_lossy(),
+            ))
         }
     }
 }
@@ -158,43 +234,188 @@ pub async fn get_project_structure(
 pub async fn create_dataset(
     project_path: String,
     dataset_name: String,
-) -> std::result::Result<serde_json::Value, String> {
-    use std::fs;
-    use std::path::Path;
-
+) -> std::result::Result<serde_json::Value, CommandError> {
     info!("创建数据集: {} in project {}", dataset_name, project_path);
 
-    // 验证数据集名称
-    if dataset_name.is_empty() || dataset_name.len() > 50 {
-        return Err("数据集名称无效".to_string());
+    match ProjectStructure::create_dataset(&project_path, &dataset_name) {
+        Ok(dataset_path) => Ok(json!({
+            "success": true,
+            "message": "数据集创建成功",
+            "dataset_name": dataset_name,
+            "dataset_path": dataset_path.to_string_lossy()
+        })),
+        Err(e) => {
+            error!("创建数据集失败: {}", e);
+            Err(CommandError::from(e)
+                .with_dataset(dataset_name)
+                .with_path(project_path))
+        }
+    }
+}
+
+/// 移除数据集
+///
+/// 默认移到工程的 `.trash` 目录，`permanent` 为 `true` 时直接删除；数据集正在回放或被其他操作占用时拒绝操作
+#[tauri::command]
+pub async fn remove_dataset(
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    permanent: Option<bool>,
+) -> std::result::Result<serde_json::Value, CommandError> {
+    info!("移除数据集: {} in project {}", dataset_name, project_path);
+
+    let result = async {
+        // 占用数据集直到重新加载工程
+        let _guard = state.lock_dataset(&project_path, &dataset_name).await?;
+        let trash_path = ProjectStructure::remove_dataset(
+            &project_path,
+            &dataset_name,
+            permanent.unwrap_or(false),
+        )?;
+        state.refresh_project(&project_path).await?;
+        Ok::<_, PlaybackError>(trash_path)
     }
+    .await;
 
-    // 检查数据集名称是否包含非法字符
-    if dataset_name.contains('/') || dataset_name.contains('\\') || dataset_name.contains(':') {
-        return Err("数据集名称包含非法字符".to_string());
+    match result {
+        Ok(trash_path) => Ok(json!({
+            "success": true,
+            "message": "数据集已移除",
+            "dataset_name": dataset_name,
+            "trash_path": trash_path.map(|p| p.to_string_lossy().to_string())
+        })),
+        Err(e) => {
+            error!("移除数据集失败: {}", e);
+            Err(CommandError::from(e)
+                .with_dataset(dataset_name)
+                .with_path(project_path))
+        }
     }
+}
 
-    let dataset_path = Path::new(&project_path).join(&dataset_name);
+/// 重命名数据集，数据集正在回放或被其他操作占用时拒绝操作
+#[tauri::command]
+pub async fn rename_dataset(
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    new_name: String,
+) -> std::result::Result<serde_json::Value, CommandError> {
+    info!(
+        "重命名数据集: {} -> {} in project {}",
+        dataset_name, new_name, project_path
+    );
 
-    // 检查数据集是否已存在
-    if dataset_path.exists() {
-        return Err("数据集已存在".to_string());
+    let result = async {
+        // 占用新旧名称的数据集直到重新加载工程
+        let _guard = state.lock_dataset(&project_path, &dataset_name).await?;
+        let _target = state.lock_dataset(&project_path, &new_name).await?;
+        // 先把回放配置转到新名称，工程监视器先于重新加载发现改名时也沿用原有配置
+        state
+            .rename_dataset_config(&project_path, &dataset_name, &new_name)
+            .await;
+        let dataset_path =
+            match ProjectStructure::rename_dataset(&project_path, &dataset_name, &new_name) {
+                Ok(path) => path,
+                Err(e) => {
+                    state
+                        .rename_dataset_config(&project_path, &new_name, &dataset_name)
+                        .await;
+                    return Err(e);
+                }
+            };
+        state.refresh_project(&project_path).await?;
+        Ok::<_, PlaybackError>(dataset_path)
     }
+    .await;
 
-    // 创建数据集目录
-    match fs::create_dir(&dataset_path) {
-        Ok(_) => {
-            info!("数据集目录创建成功: {:?}", dataset_path);
-            Ok(json!({
-                "success": true,
-                "message": "数据集创建成功",
-                "dataset_name": dataset_name,
-                "dataset_path": dataset_path.to_string_lossy()
-            }))
+    match result {
+        Ok(dataset_path) => Ok(json!({
+            "success": true,
+            "message": "数据集重命名成功",
+            "dataset_name": new_name,
+            "dataset_path": dataset_path.to_string_lossy()
+        })),
+        Err(e) => {
+            error!("重命名数据集失败: {}", e);
+            Err(CommandError::from(e)
+                .with_dataset(dataset_name)
+                .with_path(project_path))
         }
+    }
+}
+
+/// 由参考事件拟合数据集的时间校正，只计算不保存
+#[tauri::command]
+pub async fn fit_time_correction(
+    model: CorrectionModel,
+    references: Vec<TimeReference>,
+) -> std::result::Result<TimeCorrection, CommandError> {
+    info!(
+        "拟合时间校正: 模型 {}, {} 个参考事件",
+        model.as_str(),
+        references.len()
+    );
+
+    TimeCorrection::fit(model, &references).map_err(|e| {
+        error!("拟合时间校正失败: {}", e);
+        CommandError::from(e)
+    })
+}
+
+/// 设置数据集的时间校正并保存到工程文件，`correction` 为空时清除
+///
+/// 校正在回放时虚拟应用，正在进行的回放在下次开始时生效
+#[tauri::command]
+pub async fn set_time_correction(
+    state: State<'_, AppState>,
+    project_path: String,
+    dataset_name: String,
+    correction: Option<TimeCorrection>,
+) -> std::result::Result<serde_json::Value, CommandError> {
+    info!(
+        "设置数据集时间校正: {} in project {}: {:?}",
+        dataset_name, project_path, correction
+    );
+
+    let result = async {
+        let saved = ProjectStructure::update_dataset_time_correction(
+            &project_path,
+            &dataset_name,
+            correction.clone(),
+        )?;
+        if !saved {
+            return Err(PlaybackError::ProjectError(format!(
+                "工程文件不存在: {}",
+                project_path
+            )));
+        }
+        state
+            .playback_engine
+            .config_manager_mut()
+            .set_time_correction(&dataset_name, correction.clone());
+        state.refresh_project(&project_path).await
+    }
+    .await;
+
+    let message = if correction.is_some() {
+        "时间校正已保存"
+    } else {
+        "时间校正已清除"
+    };
+    match result {
+        Ok(()) => Ok(json!({
+            "success": true,
+            "message": message,
+            "dataset_name": dataset_name,
+            "correction": correction
+        })),
         Err(e) => {
-            error!("创建数据集目录失败: {}", e);
-            Err(format!("创建数据集目录失败: {}", e))
+            error!("设置时间校正失败: {}", e);
+            Err(CommandError::from(e)
+                .with_dataset(dataset_name)
+                .with_path(project_path))
         }
     }
 }
@@ -0,0 +1,69 @@
+use log::info;
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+use crate::state::app_state::AppState;
+use crate::state::task_state::CancelToken;
+use crate::types::{
+    CommandError, ErrorCategory, PlaybackError, Result, TaskProgress, TASK_PROGRESS_EVENT,
+};
+
+/// 取消后台任务
+#[tauri::command]
+pub async fn cancel_task(
+    state: State<'_, AppState>,
+    task_id: String,
+) -> std::result::Result<(), CommandError> {
+    if !state.tasks.cancel(&task_id) {
+        return Err(CommandError::new(
+            "TASK_NOT_FOUND",
+            ErrorCategory::Engine,
+            format!("任务不存在或已结束: {}", task_id),
+        ));
+    }
+
+    info!("已请求取消任务: {}", task_id);
+    Ok(())
+}
+
+/// 生成推送任务进度事件的回调，参数依次为阶段、当前进度、总数和当前条目
+pub(crate) fn progress_emitter<R: Runtime>(
+    app: AppHandle<R>,
+    task_id: String,
+    kind: &'static str,
+) -> impl FnMut(&str, u64, u64, Option<&str>) + Send + 'static {
+    move |stage, current, total, item| {
+        let progress = TaskProgress {
+            task_id: task_id.clone(),
+            kind: kind.to_string(),
+            stage: stage.to_string(),
+            current,
+            total,
+            item: item.map(str::to_string),
+        };
+        let _ = app.emit(TASK_PROGRESS_EVENT, progress);
+    }
+}
+
+/// 登记后台任务并在阻塞线程中执行 `task`，进度通过 `task://progress` 事件推送，可用 `cancel_task` 取消
+///
+/// `task` 的参数为取消标记和进度回调（阶段、当前进度、总数、当前条目）；任务结束后移除登记，
+/// 任务线程异常退出时返回错误
+pub(crate) async fn run_task<R, T, F>(
+    app: AppHandle<R>,
+    state: &AppState,
+    kind: &'static str,
+    task: F,
+) -> Result<T>
+where
+    R: Runtime,
+    T: Send + 'static,
+    F: FnOnce(&CancelToken, &mut dyn FnMut(&str, u64, u64, Option<&str>)) -> T + Send + 'static,
+{
+    let (task_id, cancel) = state.tasks.register(kind);
+    let mut emit = progress_emitter(app, task_id.clone(), kind);
+
+    let result = tokio::task::spawn_blocking(move || task(&cancel, &mut emit)).await;
+    state.tasks.finish(&task_id);
+
+    result.map_err(|e| PlaybackError::ProjectError(format!("任务 {} 异常退出: {}", task_id, e)))
+}
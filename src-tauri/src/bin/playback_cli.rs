@@ -0,0 +1,315 @@
+//! 无界面命令行回放工具
+//!
+//! 打开工程目录并按指定速度、时间范围和发送目标回放数据集，
+//! 不启动窗口和瓦片服务，适用于无显示环境的服务器。
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use playback_engine_lib::playback::engine::{
+    PlaybackEngine, PlaybackOptions, DEFAULT_LIVE_LATENCY,
+};
+use playback_engine_lib::playback::reader::CorruptPacketPolicy;
+use playback_engine_lib::state::config_state::UDPConfig;
+use playback_engine_lib::state::playback_state::{PlaybackState, PlaybackStatus};
+use playback_engine_lib::ProjectManager;
+
+/// 回放正常完成
+const EXIT_OK: u8 = 0;
+/// 回放过程出错
+const EXIT_FAILURE: u8 = 1;
+/// 命令行参数错误
+const EXIT_USAGE: u8 = 2;
+/// 用户中断
+const EXIT_INTERRUPTED: u8 = 130;
+
+/// 回放速度范围，与时间轴的限制一致
+const MIN_SPEED: f64 = 0.1;
+const MAX_SPEED: f64 = 10.0;
+
+const USAGE: &str = "\
+用法: playback-cli <工程目录> [选项]
+
+选项:
+  -d, --dataset <名称>                 回放的数据集，可重复指定（默认全部）
+  -t, --target <名称>=<模式>:<IP>:<端口>  指定数据集的发送目标，可重复指定
+                                       （IPv6地址写作 [::1]）
+      --default-target <模式>:<IP>:<端口>  其他数据集的发送目标
+                                       （默认使用数据集的网络配置）
+  -s, --speed <倍速>                   回放速度，0.1 - 10（默认 1）
+      --from <秒>                      起始偏移，相对数据起点
+      --to <秒>                        结束偏移，相对数据起点
+      --on-corrupt <abort|skip|flag>   损坏数据包的处理方式：停止回放、跳过或照常发送
+                                       （默认 abort）
+      --live                           实时跟随正在录制的数据集，直到中断
+      --latency <秒>                   实时跟随时落后当前时间的延迟（默认 1）
+  -l, --list                           列出工程中的数据集后退出
+  -q, --quiet                          不输出回放进度
+  -h, --help                           显示帮助
+
+退出码: 0 完成, 1 回放失败, 2 参数错误, 130 被中断";
+
+/// 命令行参数
+#[derive(Debug, Default)]
+struct CliArgs {
+    project_path: String,
+    datasets: Vec<String>,
+    targets: HashMap<String, UDPConfig>,
+    default_target: Option<UDPConfig>,
+    speed: Option<f64>,
+    from_secs: Option<f64>,
+    to_secs: Option<f64>,
+    corrupt_packets: CorruptPacketPolicy,
+    live: bool,
+    latency_secs: Option<f64>,
+    list_only: bool,
+    quiet: bool,
+}
+
+/// 解析命令行参数
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<CliArgs>, String> {
+    let mut cli = CliArgs::default();
+    let mut project_path = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("参数 {} 缺少取值", name));
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-l" | "--list" => cli.list_only = true,
+            "-q" | "--quiet" => cli.quiet = true,
+            "-d" | "--dataset" => cli.datasets.push(value(&arg)?),
+            "-t" | "--target" => {
+                let spec = value(&arg)?;
+                let (name, target) = spec
+                    .split_once('=')
+                    .ok_or_else(|| format!("无效的发送目标: {}", spec))?;
+                cli.targets.insert(name.to_string(), target.parse()?);
+            }
+            "--default-target" => cli.default_target = Some(value(&arg)?.parse()?),
+            "-s" | "--speed" => cli.speed = Some(parse_speed(&arg, &value(&arg)?)?),
+            "--from" => cli.from_secs = Some(parse_number(&arg, &value(&arg)?)?),
+            "--to" => cli.to_secs = Some(parse_number(&arg, &value(&arg)?)?),
+            "--on-corrupt" => cli.corrupt_packets = value(&arg)?.parse()?,
+            "--live" => cli.live = true,
+            "--latency" => cli.latency_secs = Some(parse_number(&arg, &value(&arg)?)?),
+            _ if arg.starts_with('-') => return Err(format!("未知参数: {}", arg)),
+            _ if project_path.is_none() => project_path = Some(arg.clone()),
+            _ => return Err(format!("多余的参数: {}", arg)),
+        }
+    }
+
+    cli.project_path = project_path.ok_or_else(|| "未指定工程目录".to_string())?;
+
+    if cli.live && (cli.from_secs.is_some() || cli.to_secs.is_some()) {
+        return Err("实时跟随时不能指定起止偏移".to_string());
+    }
+    if cli.latency_secs.is_some() && !cli.live {
+        return Err("参数 --latency 需要与 --live 一起使用".to_string());
+    }
+
+    if let (Some(from), Some(to)) = (cli.from_secs, cli.to_secs) {
+        if to <= from {
+            return Err("结束偏移必须大于起始偏移".to_string());
+        }
+    }
+
+    Ok(Some(cli))
+}
+
+fn parse_number(name: &str, value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(v) if v.is_finite() && v >= 0.0 => Ok(v),
+        _ => Err(format!("参数 {} 的取值无效: {}", name, value)),
+    }
+}
+
+/// 解析回放速度，须在 0.1 - 10 之间
+fn parse_speed(name: &str, value: &str) -> Result<f64, String> {
+    let speed = parse_number(name, value)?;
+    if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        return Err(format!(
+            "参数 {} 超出范围 {} - {}: {}",
+            name, MIN_SPEED, MAX_SPEED, value
+        ));
+    }
+    Ok(speed)
+}
+
+fn secs_to_ns(secs: f64) -> u64 {
+    (secs * 1_000_000_000.0) as u64
+}
+
+/// 格式化纳秒时长为秒
+fn format_secs(ns: u64) -> String {
+    format!("{:.3}s", ns as f64 / 1_000_000_000.0)
+}
+
+fn print_progress(state: &PlaybackState) {
+    let elapsed = state
+        .current_timestamp
+        .saturating_sub(state.start_timestamp);
+    // 实时跟随时总时长不断增长，显示落后数据末尾的时间
+    let position = if state.live {
+        format!(
+            "[实时 落后 {}]",
+            format_secs(state.total_duration.saturating_sub(elapsed))
+        )
+    } else {
+        format!("[{:>6.2}%]", state.progress() * 100.0)
+    };
+    println!(
+        "{} {} / {}  已发送 {} 个数据包{}",
+        position,
+        format_secs(elapsed),
+        format_secs(state.total_duration),
+        state.current_packet_index,
+        if state.corrupt_packets > 0 {
+            format!("，损坏 {} 个", state.corrupt_packets)
+        } else {
+            String::new()
+        }
+    );
+}
+
+async fn run(cli: CliArgs) -> Result<u8, String> {
+    let mut manager = ProjectManager::new();
+    let project = manager
+        .open_project(&cli.project_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    let dataset_names = manager.list_dataset_names();
+
+    if cli.list_only {
+        println!("工程: {} ({})", project.name, project.path);
+        for name in &dataset_names {
+            if let Some(reader) = manager.get_dataset_reader(name) {
+                println!(
+                    "  {}\t{} 个PCAP文件\t{} 个数据包",
+                    name,
+                    reader.pcap_files.len(),
+                    reader.total_packets
+                );
+            }
+        }
+        return Ok(EXIT_OK);
+    }
+
+    // 确定回放的数据集
+    let selected: Vec<String> = if cli.datasets.is_empty() {
+        dataset_names
+            .into_iter()
+            .filter(|name| {
+                manager
+                    .get_dataset_reader(name)
+                    .is_some_and(|reader| !reader.pcap_files.is_empty())
+            })
+            .collect()
+    } else {
+        cli.datasets.clone()
+    };
+    if selected.is_empty() {
+        return Err("工程中没有可回放的数据集".to_string());
+    }
+
+    for name in cli.targets.keys() {
+        if !selected.contains(name) {
+            return Err(format!("发送目标指定的数据集未被选中: {}", name));
+        }
+    }
+
+    // 注册数据集配置，未指定目标时使用数据集自身的网络配置
+    let engine = PlaybackEngine::new();
+    for name in &selected {
+        let reader = manager
+            .get_dataset_reader(name)
+            .ok_or_else(|| format!("数据集不存在: {}", name))?;
+        let target = cli
+            .targets
+            .get(name)
+            .or(cli.default_target.as_ref())
+            .cloned()
+            .unwrap_or_else(|| UDPConfig::from(&reader.config.network_config));
+
+        println!(
+            "数据集 {} -> {}:{}:{}",
+            name, target.mode, target.target_ip, target.target_port
+        );
+        let mut config_manager = engine.config_manager_mut();
+        config_manager.register_dataset(name.clone(), reader.config.path.clone(), target);
+        config_manager.set_time_correction(name, reader.config.time_correction.clone());
+    }
+
+    let options = PlaybackOptions {
+        start_offset: cli.from_secs.map(secs_to_ns),
+        end_offset: cli.to_secs.map(secs_to_ns),
+        speed: cli.speed,
+        corrupt_packets: cli.corrupt_packets,
+        live_latency: cli
+            .live
+            .then(|| cli.latency_secs.map_or(DEFAULT_LIVE_LATENCY, secs_to_ns)),
+    };
+    engine
+        .start_with_options(selected, options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 等待回放结束，定期输出进度
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let state = engine.get_state().await;
+                if !cli.quiet {
+                    print_progress(&state);
+                }
+                match state.status {
+                    PlaybackStatus::Completed => {
+                        println!("回放完成，共发送 {} 个数据包", state.current_packet_index);
+                        return Ok(EXIT_OK);
+                    }
+                    PlaybackStatus::Stopped => {
+                        return Err(state.error.unwrap_or_else(|| "回放意外停止".to_string()))
+                    }
+                    _ => {}
+                }
+            }
+            _ = &mut ctrl_c => {
+                engine.stop().await.map_err(|e| e.to_string())?;
+                eprintln!("回放已中断");
+                return Ok(EXIT_INTERRUPTED);
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_default_env()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    let cli = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(cli)) => cli,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::from(EXIT_OK);
+        }
+        Err(e) => {
+            eprintln!("错误: {}\n\n{}", e, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    match run(cli).await {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
@@ -1,8 +0,0 @@
-//! 命令接口层
-//! 
-//! 该模块提供Tauri命令的统一导出，用于前端与后端的通信接口。
-
-pub mod project;
-pub mod dataset;
-pub mod playback;
-pub mod system;
\ No newline at end of file
@@ -1,59 +0,0 @@
-//! 播放控制相关命令
-//! 
-//! 该模块提供播放控制相关的命令接口，包括播放、暂停、跳转等操作。
-
-use tauri::AppHandle;
-use log::info;
-
-/// 开始播放
-#[tauri::command]
-pub fn start_playback(
-    _app: AppHandle,
-    dataset_name: String,
-) -> std::result::Result<(), String> {
-    info!("开始播放数据集: {}", dataset_name);
-    // TODO: 实现播放控制逻辑
-    Ok(())
-}
-
-/// 暂停播放
-#[tauri::command]
-pub fn pause_playback(
-    _app: AppHandle,
-) -> std::result::Result<(), String> {
-    info!("暂停播放");
-    // TODO: 实现暂停逻辑
-    Ok(())
-}
-
-/// 停止播放
-#[tauri::command]
-pub fn stop_playback(
-    _app: AppHandle,
-) -> std::result::Result<(), String> {
-    info!("停止播放");
-    // TODO: 实现停止逻辑
-    Ok(())
-}
-
-/// 跳转到指定时间
-#[tauri::command]
-pub fn seek_to_time(
-    _app: AppHandle,
-    timestamp: u64,
-) -> std::result::Result<(), String> {
-    info!("跳转到时间戳: {}", timestamp);
-    // TODO: 实现跳转逻辑
-    Ok(())
-}
-
-/// 设置播放速度
-#[tauri::command]
-pub fn set_playback_speed(
-    _app: AppHandle,
-    speed: f64,
-) -> std::result::Result<(), String> {
-    info!("设置播放速度: {}", speed);
-    // TODO: 实现播放速度设置逻辑
-    Ok(())
-}
\ No newline at end of file
@@ -0,0 +1,20 @@
+//! 控制服务配置管理
+
+use crate::types::ControlApiConfig;
+use std::env;
+
+/// 获取控制服务配置
+pub fn get_control_config() -> ControlApiConfig {
+    let defaults = ControlApiConfig::default();
+
+    ControlApiConfig {
+        enabled: env::var("CONTROL_API_ENABLED")
+            .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(defaults.enabled),
+        bind_addr: env::var("CONTROL_API_BIND").unwrap_or(defaults.bind_addr),
+        token: env::var("CONTROL_API_TOKEN").ok().filter(|s| !s.is_empty()),
+        push_interval_ms: env::var("CONTROL_API_PUSH_INTERVAL")
+            .and_then(|s| s.parse().map_err(|_| env::VarError::NotPresent))
+            .unwrap_or(defaults.push_interval_ms),
+    }
+}
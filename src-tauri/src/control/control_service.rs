@@ -0,0 +1,707 @@
+//! 本地控制服务
+//!
+//! 通过HTTP接口提供与Tauri命令一致的工程和回放操作，
+//! 并通过WebSocket推送回放进度、状态和工程目录变化
+
+use crate::control::config;
+use crate::project::watcher::ProjectChangeSet;
+use crate::state::app_state::AppState;
+use crate::state::playback_state::PlaybackState;
+use crate::types::{
+    CommandError, ControlApiConfig, ControlResponse, ErrorCategory, OpenProjectRequest,
+    SeekRequest, SpeedRequest, StartPlaybackRequest,
+};
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{sleep, Duration};
+use warp::http::StatusCode;
+use warp::ws::{Message, WebSocket};
+use warp::{Filter, Rejection, Reply};
+
+/// 共享的应用状态
+type SharedState = AppState;
+
+/// 工程目录变化的推送通道
+type ChangeSender = broadcast::Sender<ProjectChangeSet>;
+
+/// 变化推送通道的缓冲条数，WebSocket 客户端落后更多时丢弃最早的变化
+const CHANGE_CHANNEL_CAPACITY: usize = 64;
+
+/// 令牌校验失败
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// 控制服务
+pub struct ControlService {
+    /// 服务配置
+    config: ControlApiConfig,
+    /// 应用状态
+    state: SharedState,
+    /// 服务器地址
+    server_addr: SocketAddr,
+}
+
+impl ControlService {
+    /// 创建新的控制服务
+    pub fn new(state: SharedState) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let config = config::get_control_config();
+        let server_addr: SocketAddr = config.bind_addr.parse()?;
+
+        // 非本机地址必须配置访问令牌
+        if !server_addr.ip().is_loopback() && config.token.is_none() {
+            return Err(format!("监听非本机地址 {} 时必须设置访问令牌", server_addr).into());
+        }
+
+        Ok(Self {
+            config,
+            state,
+            server_addr,
+        })
+    }
+
+    /// 是否启用控制服务
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 获取服务URL
+    pub fn get_service_url(&self) -> String {
+        format!("http://{}", self.server_addr)
+    }
+
+    /// 启动HTTP服务器
+    pub async fn start_server(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let push_interval = Duration::from_millis(self.config.push_interval_ms.max(10));
+        let routes = routes(self.state.clone(), self.config.token.clone(), push_interval);
+
+        info!("启动控制服务器: {}", self.get_service_url());
+
+        // 启动服务器
+        warp::serve(routes).run(self.server_addr).await;
+
+        Ok(())
+    }
+}
+
+/// 控制接口的全部路由，`/health` 之外的接口须通过令牌校验
+fn routes(
+    state: SharedState,
+    token: Option<String>,
+    push_interval: Duration,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let auth = with_auth(token);
+    let (changes, _) = broadcast::channel::<ProjectChangeSet>(CHANGE_CHANNEL_CAPACITY);
+
+    // 工程相关路由
+    let open_project = warp::path!("api" / "project" / "open")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and(with_changes(changes.clone()))
+        .and_then(handle_open_project);
+
+    let project_info = warp::path!("api" / "project")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_project_info);
+
+    let close_project = warp::path!("api" / "project" / "close")
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_close_project);
+
+    let list_datasets = warp::path!("api" / "datasets")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_list_datasets);
+
+    // 回放相关路由
+    let start_playback = warp::path!("api" / "playback" / "start")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_start_playback);
+
+    let pause_playback = warp::path!("api" / "playback" / "pause")
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_pause_playback);
+
+    let stop_playback = warp::path!("api" / "playback" / "stop")
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_stop_playback);
+
+    let seek = warp::path!("api" / "playback" / "seek")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_seek);
+
+    let speed = warp::path!("api" / "playback" / "speed")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_speed);
+
+    let playback_state = warp::path!("api" / "playback" / "state")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_playback_state);
+
+    // 状态和工程目录变化推送
+    let ws_route = warp::path("ws")
+        .and(warp::ws())
+        .and(with_state(state))
+        .and(with_changes(changes))
+        .map(
+            move |ws: warp::ws::Ws, state: SharedState, changes: ChangeSender| {
+                let changes = changes.subscribe();
+                ws.on_upgrade(move |socket| push_status(socket, state, changes, push_interval))
+            },
+        );
+
+    let health_route = warp::path("health").map(|| "OK");
+
+    let api_routes = open_project
+        .or(project_info)
+        .or(close_project)
+        .or(list_datasets)
+        .or(start_playback)
+        .or(pause_playback)
+        .or(stop_playback)
+        .or(seek)
+        .or(speed)
+        .or(playback_state)
+        .or(ws_route);
+
+    health_route
+        .or(auth.and(api_routes))
+        .recover(handle_rejection)
+}
+
+// 依赖注入过滤器
+fn with_state(
+    state: SharedState,
+) -> impl Filter<Extract = (SharedState,), Error = Infallible> + Clone {
+    warp::any().map(move || state.clone())
+}
+
+fn with_changes(
+    changes: ChangeSender,
+) -> impl Filter<Extract = (ChangeSender,), Error = Infallible> + Clone {
+    warp::any().map(move || changes.clone())
+}
+
+/// 令牌校验过滤器，支持 `Authorization: Bearer <token>` 请求头或 `?token=` 查询参数
+fn with_auth(token: Option<String>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(
+            move |header: Option<String>, query: HashMap<String, String>| {
+                let token = token.clone();
+                async move {
+                    let expected = match token {
+                        Some(token) => token,
+                        None => return Ok(()),
+                    };
+
+                    let provided = header
+                        .as_deref()
+                        .and_then(|h| h.strip_prefix("Bearer "))
+                        .map(str::to_string)
+                        .or_else(|| query.get("token").cloned());
+
+                    if provided.is_some_and(|p| token_matches(&p, &expected)) {
+                        Ok(())
+                    } else {
+                        Err(warp::reject::custom(Unauthorized))
+                    }
+                }
+            },
+        )
+        .untuple_one()
+}
+
+/// 比较访问令牌，先取摘要再逐字节累积差异，耗时与令牌的内容和长度无关
+fn token_matches(provided: &str, expected: &str) -> bool {
+    let provided = Sha256::digest(provided.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    provided
+        .iter()
+        .zip(expected.iter())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+/// 生成JSON响应
+fn json_reply<T: Serialize>(
+    result: Result<T, CommandError>,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    match result {
+        Ok(data) => warp::reply::with_status(
+            warp::reply::json(&ControlResponse::success(data)),
+            StatusCode::OK,
+        ),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&ControlResponse::<()>::failure(e)),
+            StatusCode::BAD_REQUEST,
+        ),
+    }
+}
+
+/// 处理打开工程请求
+///
+/// 与 Tauri 命令一样监视工程目录，变化应用到当前工程后推送给 WebSocket 客户端
+async fn handle_open_project(
+    request: OpenProjectRequest,
+    state: SharedState,
+    changes: ChangeSender,
+) -> Result<impl Reply, Rejection> {
+    info!("控制接口: 打开工程 {}", request.path);
+    let result = state
+        .open_project(&request.path)
+        .await
+        .map_err(|e| CommandError::from(e).with_path(request.path));
+
+    if let Ok(project_info) = &result {
+        // 监视线程不在异步运行时内，通过运行时句柄等待更新完成
+        let runtime = tokio::runtime::Handle::current();
+        let watch_state = state.clone();
+        let watched = state.watch_project(&project_info.path, move |change_set| {
+            runtime.block_on(watch_state.apply_project_changes(&change_set));
+            // 没有客户端连接时发送失败，忽略即可
+            let _ = changes.send(change_set);
+        });
+        if let Err(e) = watched {
+            warn!("监视工程目录失败: {}", e);
+        }
+    }
+    Ok(json_reply(result))
+}
+
+/// 处理获取工程信息请求
+async fn handle_project_info(state: SharedState) -> Result<impl Reply, Rejection> {
+    Ok(json_reply(Ok(state.current_project().await)))
+}
+
+/// 处理关闭工程请求
+async fn handle_close_project(state: SharedState) -> Result<impl Reply, Rejection> {
+    info!("控制接口: 关闭工程");
+    Ok(json_reply(
+        state.close_project().await.map_err(CommandError::from),
+    ))
+}
+
+/// 处理数据集列表请求
+async fn handle_list_datasets(state: SharedState) -> Result<impl Reply, Rejection> {
+    Ok(json_reply(Ok(state.list_datasets().await)))
+}
+
+/// 处理开始回放请求
+async fn handle_start_playback(
+    request: StartPlaybackRequest,
+    state: SharedState,
+) -> Result<impl Reply, Rejection> {
+    info!("控制接口: 开始回放 {}", request.dataset_name);
+    let result = state
+        .start_playback(request.dataset_name.clone(), None)
+        .await
+        .map_err(|e| CommandError::from(e).with_dataset(request.dataset_name));
+    Ok(json_reply(result))
+}
+
+/// 处理暂停回放请求
+async fn handle_pause_playback(state: SharedState) -> Result<impl Reply, Rejection> {
+    Ok(json_reply(
+        state
+            .playback_engine
+            .pause()
+            .await
+            .map_err(CommandError::from),
+    ))
+}
+
+/// 处理停止回放请求
+async fn handle_stop_playback(state: SharedState) -> Result<impl Reply, Rejection> {
+    Ok(json_reply(
+        state
+            .playback_engine
+            .stop()
+            .await
+            .map_err(CommandError::from),
+    ))
+}
+
+/// 处理跳转请求
+async fn handle_seek(request: SeekRequest, state: SharedState) -> Result<impl Reply, Rejection> {
+    let result = state
+        .playback_engine
+        .seek_to(request.timestamp)
+        .await
+        .map_err(|e| CommandError::from(e).with_timestamp(request.timestamp));
+    Ok(json_reply(result))
+}
+
+/// 处理设置速度请求
+async fn handle_speed(request: SpeedRequest, state: SharedState) -> Result<impl Reply, Rejection> {
+    Ok(json_reply(
+        state
+            .playback_engine
+            .set_speed(request.speed)
+            .await
+            .map_err(CommandError::from),
+    ))
+}
+
+/// 处理获取回放状态请求
+async fn handle_playback_state(state: SharedState) -> Result<impl Reply, Rejection> {
+    Ok(json_reply(Ok(state.playback_engine.get_state().await)))
+}
+
+/// 推送回放状态和工程目录变化
+///
+/// 回放状态仅在变化时发送，两次推送间隔不小于 `push_interval`
+async fn push_status(
+    socket: WebSocket,
+    state: SharedState,
+    mut changes: broadcast::Receiver<ProjectChangeSet>,
+    push_interval: Duration,
+) {
+    let mut playback_state = state.playback_engine.subscribe();
+    let (mut tx, mut rx) = socket.split();
+
+    debug!("控制接口: WebSocket 客户端已连接");
+
+    // 连接后先推送一次当前状态
+    playback_state.mark_changed();
+
+    loop {
+        tokio::select! {
+            changed = playback_state.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+
+                let message = status_message(&playback_state.borrow_and_update());
+                if let Err(e) = tx.send(Message::text(message)).await {
+                    debug!("WebSocket 推送失败: {}", e);
+                    break;
+                }
+                sleep(push_interval).await;
+            }
+            change_set = changes.recv() => {
+                let change_set = match change_set {
+                    Ok(change_set) => change_set,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("WebSocket 客户端落后，丢弃 {} 次工程目录变化", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                let message = project_changed_message(&change_set);
+                if let Err(e) = tx.send(Message::text(message)).await {
+                    debug!("WebSocket 推送失败: {}", e);
+                    break;
+                }
+            }
+            incoming = rx.next() => {
+                match incoming {
+                    Some(Ok(msg)) if msg.is_close() => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        warn!("WebSocket 接收失败: {}", e);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+
+    debug!("控制接口: WebSocket 客户端已断开");
+}
+
+/// 生成状态推送消息
+fn status_message(state: &PlaybackState) -> String {
+    serde_json::json!({
+        "type": "playback_state",
+        "progress": state.progress(),
+        "state": state,
+    })
+    .to_string()
+}
+
+/// 生成工程目录变化推送消息
+fn project_changed_message(changes: &ProjectChangeSet) -> String {
+    serde_json::json!({
+        "type": "project_changed",
+        "changes": changes,
+    })
+    .to_string()
+}
+
+/// 处理拒绝的请求
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+    let (status, code, category, message) = if err.find::<Unauthorized>().is_some() {
+        (
+            StatusCode::UNAUTHORIZED,
+            "UNAUTHORIZED",
+            ErrorCategory::Network,
+            "访问令牌无效".to_string(),
+        )
+    } else if err.is_not_found() {
+        (
+            StatusCode::NOT_FOUND,
+            "NOT_FOUND",
+            ErrorCategory::Network,
+            "接口不存在".to_string(),
+        )
+    } else if let Some(e) = err.find::<warp::filters::body::BodyDeserializeError>() {
+        (
+            StatusCode::BAD_REQUEST,
+            "INVALID_REQUEST",
+            ErrorCategory::Parse,
+            format!("请求参数无效: {}", e),
+        )
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            "METHOD_NOT_ALLOWED",
+            ErrorCategory::Network,
+            "请求方法不支持".to_string(),
+        )
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            ErrorCategory::Engine,
+            format!("{:?}", err),
+        )
+    };
+
+    let error = CommandError::new(code, category, message);
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ControlResponse::<()>::failure(error)),
+        status,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::DataFileWriter;
+    use crate::dataset::naming::data_file_name;
+    use crate::project::structure::ProjectStructure;
+    use std::path::Path;
+    use warp::http::Response;
+    use warp::hyper::body::Bytes;
+
+    const TOKEN: &str = "secret-token";
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    fn test_routes(
+        state: SharedState,
+        token: Option<&str>,
+    ) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+        routes(state, token.map(str::to_string), Duration::from_millis(10))
+    }
+
+    fn body(response: &Response<Bytes>) -> serde_json::Value {
+        serde_json::from_slice(response.body()).unwrap()
+    }
+
+    fn error_code(response: &Response<Bytes>) -> String {
+        body(response)["error"]["code"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn create_project(parent: &Path) -> String {
+        let project = ProjectStructure::create(parent, "proj", None, &["radar".to_string()])
+            .unwrap()
+            .root_path;
+        write_data_file(&project.join("radar"));
+        project.to_string_lossy().to_string()
+    }
+
+    fn write_data_file(dir: &Path) {
+        let mut writer = DataFileWriter::create(dir.join(data_file_name(BASE_TIMESTAMP))).unwrap();
+        writer.write_packet(BASE_TIMESTAMP, &[1, 2, 3]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn token_comparison() {
+        assert!(token_matches(TOKEN, TOKEN));
+        assert!(!token_matches("secret-tokem", TOKEN));
+        assert!(!token_matches("secret", TOKEN));
+        assert!(!token_matches("", TOKEN));
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_or_wrong_token() {
+        let routes = test_routes(AppState::new(), Some(TOKEN));
+
+        let response = warp::test::request()
+            .path("/api/project")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(&response), "UNAUTHORIZED");
+
+        for authorization in ["Bearer wrong", TOKEN, "Basic secret-token"] {
+            let response = warp::test::request()
+                .path("/api/project")
+                .header("authorization", authorization)
+                .reply(&routes)
+                .await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = warp::test::request()
+            .path("/api/project?token=wrong")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn accepts_header_or_query_token() {
+        let routes = test_routes(AppState::new(), Some(TOKEN));
+
+        let response = warp::test::request()
+            .path("/api/project")
+            .header("authorization", format!("Bearer {}", TOKEN))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = body(&response);
+        assert_eq!(json["ok"], true);
+        assert!(json["data"].is_null());
+
+        let response = warp::test::request()
+            .path(&format!("/api/playback/state?token={}", TOKEN))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 健康检查不需要令牌
+        let response = warp::test::request().path("/health").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_ref(), b"OK");
+    }
+
+    #[tokio::test]
+    async fn no_token_configured_allows_requests() {
+        let routes = test_routes(AppState::new(), None);
+        let response = warp::test::request()
+            .path("/api/datasets")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(&response)["data"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn routes_unknown_paths_methods_and_bodies() {
+        let routes = test_routes(AppState::new(), None);
+
+        let response = warp::test::request()
+            .path("/api/missing")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error_code(&response), "NOT_FOUND");
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/api/playback/pause")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/api/project/open")
+            .json(&serde_json::json!({ "dir": "/tmp" }))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&response), "INVALID_REQUEST");
+
+        // 操作失败时返回命令错误
+        let response = warp::test::request()
+            .method("POST")
+            .path("/api/project/open")
+            .json(&serde_json::json!({ "path": "/nonexistent/project" }))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body(&response)["ok"], false);
+    }
+
+    #[tokio::test]
+    async fn open_project_watches_directory_and_pushes_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path());
+        let state = AppState::new();
+        let routes = test_routes(state.clone(), None);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/api/project/open")
+            .json(&serde_json::json!({ "path": project }))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(&response)["data"]["name"], "proj");
+        assert!(state.watcher.lock().unwrap().is_some());
+
+        let response = warp::test::request()
+            .path("/api/datasets")
+            .reply(&routes)
+            .await;
+        assert_eq!(body(&response)["data"].as_array().unwrap().len(), 1);
+
+        let mut client = warp::test::ws()
+            .path("/ws")
+            .handshake(routes.clone())
+            .await
+            .unwrap();
+        let ais = ProjectStructure::create_dataset(&project, "ais").unwrap();
+        write_data_file(&ais);
+
+        // 先收到回放状态，监视线程发现新数据集的数据文件后收到工程目录变化
+        let message = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let message = client.recv().await.unwrap();
+                let json: serde_json::Value =
+                    serde_json::from_str(message.to_str().unwrap()).unwrap();
+                if json["type"] == "project_changed" && json["changes"]["datasets"][0].is_object() {
+                    return json;
+                }
+            }
+        })
+        .await
+        .expect("未收到工程目录变化");
+        assert_eq!(message["changes"]["datasets"][0]["name"], "ais");
+
+        // 变化已应用到当前工程
+        let response = warp::test::request()
+            .path("/api/datasets")
+            .reply(&routes)
+            .await;
+        assert_eq!(body(&response)["data"].as_array().unwrap().len(), 2);
+    }
+}
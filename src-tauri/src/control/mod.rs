@@ -0,0 +1,8 @@
+//! 控制接口服务
+//!
+//! 提供本地HTTP/WebSocket控制接口，供测试脚本和其他控制台驱动回放引擎
+
+mod config;
+pub mod control_service;
+
+pub use config::get_control_config;
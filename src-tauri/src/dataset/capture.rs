@@ -0,0 +1,525 @@
+//! 标准抓包文件读取
+//!
+//! 支持 libpcap（微秒/纳秒精度，大小端）和 pcapng 格式，按链路帧逐个读取；
+//! 导出时写入纳秒精度的 pcapng。与本项目的数据文件格式（见 `format`）无关
+
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::path::Path;
+
+use crate::types::{PlaybackError, Result};
+
+/// libpcap 微秒精度魔数
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+/// libpcap 纳秒精度魔数
+const PCAP_MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+/// pcapng 节头块类型
+const PCAPNG_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+/// pcapng 字节序魔数
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const PCAPNG_OBSOLETE_PACKET: u32 = 0x0000_0002;
+const PCAPNG_ENHANCED_PACKET: u32 = 0x0000_0006;
+
+/// pcapng 选项结束标记
+const PCAPNG_OPTION_END: u16 = 0;
+/// pcapng 注释选项
+const PCAPNG_OPTION_COMMENT: u16 = 1;
+/// pcapng 节头块的生成程序选项
+const PCAPNG_OPTION_USER_APPLICATION: u16 = 4;
+/// pcapng 接口名称选项
+const PCAPNG_OPTION_IF_NAME: u16 = 2;
+/// pcapng 接口时间精度选项
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+
+/// 单个块或帧的长度上限，防止损坏文件导致超大内存分配
+const MAX_BLOCK_SIZE: usize = 64 * 1024 * 1024;
+
+/// 链路层类型（LINKTYPE_*）
+pub const LINKTYPE_NULL: u32 = 0;
+pub const LINKTYPE_ETHERNET: u32 = 1;
+pub const LINKTYPE_RAW: u32 = 101;
+pub const LINKTYPE_LINUX_SLL: u32 = 113;
+pub const LINKTYPE_IPV4: u32 = 228;
+pub const LINKTYPE_IPV6: u32 = 229;
+
+/// 抓包文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureFormat {
+    Pcap,
+    PcapNg,
+}
+
+/// 链路帧
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    pub timestamp_ns: u64,
+    pub link_type: u32,
+    pub data: Vec<u8>,
+}
+
+/// 字节序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    fn u16(self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        match self {
+            Endian::Little => u16::from_le_bytes(bytes),
+            Endian::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn u32(self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// pcapng 接口信息
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+    link_type: u32,
+    /// 每秒的时间单位数
+    units_per_second: u64,
+}
+
+#[derive(Debug)]
+enum State {
+    Pcap {
+        endian: Endian,
+        nanos: bool,
+        link_type: u32,
+    },
+    PcapNg {
+        endian: Endian,
+        interfaces: Vec<Interface>,
+    },
+}
+
+/// 标准抓包文件读取器
+#[derive(Debug)]
+pub struct CaptureReader<R> {
+    reader: R,
+    state: State,
+}
+
+impl CaptureReader<BufReader<File>> {
+    /// 打开抓包文件并识别格式
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// 读取文件头并识别格式
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        read_exact_or_format(&mut reader, &mut magic, "文件长度不足，无法识别格式")?;
+
+        let state = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAPNG_SECTION_HEADER, _) => State::PcapNg {
+                endian: read_section_header(&mut reader)?,
+                interfaces: Vec::new(),
+            },
+            (le, be) => {
+                let (endian, magic) = if le == PCAP_MAGIC_MICROS || le == PCAP_MAGIC_NANOS {
+                    (Endian::Little, le)
+                } else if be == PCAP_MAGIC_MICROS || be == PCAP_MAGIC_NANOS {
+                    (Endian::Big, be)
+                } else {
+                    return Err(PlaybackError::FormatError(format!(
+                        "无法识别的抓包文件格式: 魔数 0x{:08X}",
+                        le
+                    )));
+                };
+
+                // 版本(2+2) 时区(4) 精度(4) 快照长度(4) 链路类型(4)
+                let mut header = [0u8; 20];
+                read_exact_or_format(&mut reader, &mut header, "libpcap文件头不完整")?;
+                State::Pcap {
+                    endian,
+                    nanos: magic == PCAP_MAGIC_NANOS,
+                    link_type: endian.u32(&header[16..20]) & 0x0FFF_FFFF,
+                }
+            }
+        };
+
+        Ok(Self { reader, state })
+    }
+
+    /// 文件格式
+    pub fn format(&self) -> CaptureFormat {
+        match self.state {
+            State::Pcap { .. } => CaptureFormat::Pcap,
+            State::PcapNg { .. } => CaptureFormat::PcapNg,
+        }
+    }
+
+    /// 读取下一帧，到达文件末尾时返回 `None`
+    pub fn next_frame(&mut self) -> Result<Option<CapturedFrame>> {
+        match self.state {
+            State::Pcap {
+                endian,
+                nanos,
+                link_type,
+            } => self.next_pcap_frame(endian, nanos, link_type),
+            State::PcapNg { .. } => self.next_pcapng_frame(),
+        }
+    }
+
+    fn next_pcap_frame(
+        &mut self,
+        endian: Endian,
+        nanos: bool,
+        link_type: u32,
+    ) -> Result<Option<CapturedFrame>> {
+        let mut header = [0u8; 16];
+        if !read_or_eof(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+
+        let seconds = endian.u32(&header[0..4]) as u64;
+        let fraction = endian.u32(&header[4..8]) as u64;
+        let captured_length = endian.u32(&header[8..12]) as usize;
+        if captured_length > MAX_BLOCK_SIZE {
+            return Err(PlaybackError::FormatError(format!(
+                "帧长度异常: {} 字节",
+                captured_length
+            )));
+        }
+
+        let mut data = vec![0u8; captured_length];
+        read_exact_or_format(&mut self.reader, &mut data, "帧数据不完整")?;
+
+        let fraction_ns = if nanos { fraction } else { fraction * 1_000 };
+        Ok(Some(CapturedFrame {
+            timestamp_ns: seconds * 1_000_000_000 + fraction_ns,
+            link_type,
+            data,
+        }))
+    }
+
+    fn next_pcapng_frame(&mut self) -> Result<Option<CapturedFrame>> {
+        loop {
+            let mut type_bytes = [0u8; 4];
+            if !read_or_eof(&mut self.reader, &mut type_bytes)? {
+                return Ok(None);
+            }
+
+            // 新的节可能改变字节序并重置接口列表
+            if u32::from_le_bytes(type_bytes) == PCAPNG_SECTION_HEADER {
+                let endian = read_section_header(&mut self.reader)?;
+                self.state = State::PcapNg {
+                    endian,
+                    interfaces: Vec::new(),
+                };
+                continue;
+            }
+
+            let State::PcapNg {
+                endian,
+                ref mut interfaces,
+            } = self.state
+            else {
+                unreachable!("pcapng读取状态不一致");
+            };
+
+            let block_type = endian.u32(&type_bytes);
+            let body = read_block_body(&mut self.reader, endian)?;
+
+            match block_type {
+                PCAPNG_INTERFACE_DESCRIPTION => {
+                    if body.len() < 8 {
+                        return Err(PlaybackError::FormatError("接口描述块长度不足".to_string()));
+                    }
+                    interfaces.push(Interface {
+                        link_type: endian.u16(&body[0..2]) as u32,
+                        units_per_second: parse_tsresol(&body[8..], endian),
+                    });
+                }
+                PCAPNG_ENHANCED_PACKET | PCAPNG_OBSOLETE_PACKET => {
+                    if body.len() < 20 {
+                        return Err(PlaybackError::FormatError("数据包块长度不足".to_string()));
+                    }
+
+                    let interface_id = if block_type == PCAPNG_ENHANCED_PACKET {
+                        endian.u32(&body[0..4]) as usize
+                    } else {
+                        endian.u16(&body[0..2]) as usize
+                    };
+                    let interface = *interfaces.get(interface_id).ok_or_else(|| {
+                        PlaybackError::FormatError(format!("未定义的接口: {}", interface_id))
+                    })?;
+
+                    let timestamp =
+                        ((endian.u32(&body[4..8]) as u64) << 32) | endian.u32(&body[8..12]) as u64;
+                    let captured_length = endian.u32(&body[12..16]) as usize;
+                    let data = body.get(20..20 + captured_length).ok_or_else(|| {
+                        PlaybackError::FormatError("数据包块数据不完整".to_string())
+                    })?;
+
+                    let timestamp_ns = units_to_nanos(timestamp, interface.units_per_second)
+                        .ok_or_else(|| {
+                            PlaybackError::FormatError(format!("时间戳超出范围: {}", timestamp))
+                        })?;
+
+                    return Ok(Some(CapturedFrame {
+                        timestamp_ns,
+                        link_type: interface.link_type,
+                        data: data.to_vec(),
+                    }));
+                }
+                // 简单数据包块没有时间戳，其余块（统计、名称解析等）与回放无关
+                _ => {}
+            }
+        }
+    }
+}
+
+/// pcapng 写入器
+///
+/// 输出单个节、单个接口，时间精度为纳秒，字节序为小端
+#[derive(Debug)]
+pub struct PcapNgWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapNgWriter<W> {
+    /// 写入节头块和接口描述块
+    ///
+    /// `comment` 写入节头块的注释选项，`interface_name` 写入接口描述块，超过 65535 字节时失败
+    pub fn new(
+        mut writer: W,
+        link_type: u16,
+        comment: Option<&str>,
+        interface_name: Option<&str>,
+    ) -> Result<Self> {
+        let mut section = Vec::new();
+        section.extend_from_slice(&PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes());
+        section.extend_from_slice(&1u16.to_le_bytes());
+        section.extend_from_slice(&0u16.to_le_bytes());
+        // 节长度未知
+        section.extend_from_slice(&(-1i64).to_le_bytes());
+        if let Some(comment) = comment {
+            push_option(&mut section, PCAPNG_OPTION_COMMENT, comment.as_bytes())?;
+        }
+        push_option(
+            &mut section,
+            PCAPNG_OPTION_USER_APPLICATION,
+            concat!("playback-engine ", env!("CARGO_PKG_VERSION")).as_bytes(),
+        )?;
+        push_option(&mut section, PCAPNG_OPTION_END, &[])?;
+        write_block(&mut writer, PCAPNG_SECTION_HEADER, &section)?;
+
+        let mut interface = Vec::new();
+        interface.extend_from_slice(&link_type.to_le_bytes());
+        interface.extend_from_slice(&0u16.to_le_bytes());
+        // 快照长度不限
+        interface.extend_from_slice(&0u32.to_le_bytes());
+        if let Some(name) = interface_name {
+            push_option(&mut interface, PCAPNG_OPTION_IF_NAME, name.as_bytes())?;
+        }
+        push_option(&mut interface, PCAPNG_OPTION_TSRESOL, &[9])?;
+        push_option(&mut interface, PCAPNG_OPTION_END, &[])?;
+        write_block(&mut writer, PCAPNG_INTERFACE_DESCRIPTION, &interface)?;
+
+        Ok(Self { writer })
+    }
+
+    /// 写入增强数据包块
+    pub fn write_frame(&mut self, timestamp_ns: u64, frame: &[u8]) -> Result<()> {
+        let mut body = Vec::with_capacity(20 + frame.len() + 3);
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&((timestamp_ns >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(timestamp_ns as u32).to_le_bytes());
+        body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        body.extend_from_slice(frame);
+        pad_to_word(&mut body);
+        write_block(&mut self.writer, PCAPNG_ENHANCED_PACKET, &body)
+    }
+
+    /// 刷新并取回底层写入器
+    pub fn into_inner(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// 追加选项，选项值超出长度字段（16位）的范围时失败
+fn push_option(buffer: &mut Vec<u8>, code: u16, value: &[u8]) -> Result<()> {
+    let length = u16::try_from(value.len()).map_err(|_| {
+        PlaybackError::ParseError(format!(
+            "pcapng 选项过长: {} 字节，最多 {} 字节",
+            value.len(),
+            u16::MAX
+        ))
+    })?;
+    buffer.extend_from_slice(&code.to_le_bytes());
+    buffer.extend_from_slice(&length.to_le_bytes());
+    buffer.extend_from_slice(value);
+    pad_to_word(buffer);
+    Ok(())
+}
+
+fn pad_to_word(buffer: &mut Vec<u8>) {
+    while !buffer.len().is_multiple_of(4) {
+        buffer.push(0);
+    }
+}
+
+fn write_block<W: Write>(writer: &mut W, block_type: u32, body: &[u8]) -> Result<()> {
+    let total_length = (body.len() + 12) as u32;
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&total_length.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.write_all(&total_length.to_le_bytes())?;
+    Ok(())
+}
+
+/// 读取节头块（类型字段之后），返回节的字节序
+fn read_section_header<R: Read>(reader: &mut R) -> Result<Endian> {
+    let mut prefix = [0u8; 8];
+    read_exact_or_format(reader, &mut prefix, "pcapng节头块不完整")?;
+
+    let endian = if u32::from_le_bytes([prefix[4], prefix[5], prefix[6], prefix[7]])
+        == PCAPNG_BYTE_ORDER_MAGIC
+    {
+        Endian::Little
+    } else if u32::from_be_bytes([prefix[4], prefix[5], prefix[6], prefix[7]])
+        == PCAPNG_BYTE_ORDER_MAGIC
+    {
+        Endian::Big
+    } else {
+        return Err(PlaybackError::FormatError(
+            "pcapng字节序标记无效".to_string(),
+        ));
+    };
+
+    // 跳过块的剩余部分（版本、节长度、选项和结尾长度）
+    let total_length = endian.u32(&prefix[0..4]) as usize;
+    if !(12..=MAX_BLOCK_SIZE).contains(&total_length) {
+        return Err(PlaybackError::FormatError(format!(
+            "pcapng节头块长度异常: {}",
+            total_length
+        )));
+    }
+    let mut rest = vec![0u8; total_length - 12];
+    read_exact_or_format(reader, &mut rest, "pcapng节头块不完整")?;
+    Ok(endian)
+}
+
+/// 读取块长度之后的内容（不含结尾长度字段）
+fn read_block_body<R: Read>(reader: &mut R, endian: Endian) -> Result<Vec<u8>> {
+    let mut length = [0u8; 4];
+    read_exact_or_format(reader, &mut length, "pcapng块不完整")?;
+
+    let total_length = endian.u32(&length) as usize;
+    if !(12..=MAX_BLOCK_SIZE).contains(&total_length) || !total_length.is_multiple_of(4) {
+        return Err(PlaybackError::FormatError(format!(
+            "pcapng块长度异常: {}",
+            total_length
+        )));
+    }
+
+    let mut body = vec![0u8; total_length - 8];
+    read_exact_or_format(reader, &mut body, "pcapng块不完整")?;
+    body.truncate(total_length - 12);
+    Ok(body)
+}
+
+/// 解析接口描述块选项中的时间精度，默认微秒
+fn parse_tsresol(mut options: &[u8], endian: Endian) -> u64 {
+    while options.len() >= 4 {
+        let code = endian.u16(&options[0..2]);
+        let length = endian.u16(&options[2..4]) as usize;
+        let value = &options[4..];
+
+        if code == 0 {
+            break;
+        }
+        if code == PCAPNG_OPTION_TSRESOL && length >= 1 && !value.is_empty() {
+            let resolution = value[0];
+            let exponent = (resolution & 0x7F) as u32;
+            // 最高位表示以2为底，否则以10为底
+            let base: u64 = if resolution & 0x80 != 0 { 2 } else { 10 };
+            return base.checked_pow(exponent).unwrap_or(1_000_000);
+        }
+
+        let padded = (length + 3) & !3;
+        options = options.get(4 + padded..).unwrap_or(&[]);
+    }
+
+    1_000_000
+}
+
+/// 按时间精度换算为纳秒，超出 `u64` 范围时返回 `None`
+fn units_to_nanos(value: u64, units_per_second: u64) -> Option<u64> {
+    if units_per_second == 1_000_000_000 {
+        return Some(value);
+    }
+    let nanos = value as u128 * 1_000_000_000 / units_per_second.max(1) as u128;
+    u64::try_from(nanos).ok()
+}
+
+/// 读满缓冲区，文件在开头即结束时返回 `false`
+fn read_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => {
+                return Err(PlaybackError::FormatError(
+                    "文件在记录中间意外结束".to_string(),
+                ))
+            }
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(true)
+}
+
+fn read_exact_or_format<R: Read>(reader: &mut R, buf: &mut [u8], message: &str) -> Result<()> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => PlaybackError::FormatError(message.to_string()),
+        _ => PlaybackError::FileError(e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_overlong_options() {
+        let comment = "a".repeat(u16::MAX as usize + 1);
+        let result = PcapNgWriter::new(Vec::new(), LINKTYPE_ETHERNET as u16, Some(&comment), None);
+        assert!(matches!(result, Err(PlaybackError::ParseError(_))));
+
+        let comment = "a".repeat(u16::MAX as usize);
+        assert!(
+            PcapNgWriter::new(Vec::new(), LINKTYPE_ETHERNET as u16, Some(&comment), None).is_ok()
+        );
+    }
+
+    #[test]
+    fn converts_timestamp_units() {
+        assert_eq!(units_to_nanos(1_500_000, 1_000_000), Some(1_500_000_000));
+        assert_eq!(units_to_nanos(3, 2), Some(1_500_000_000));
+        assert_eq!(units_to_nanos(u64::MAX, 1_000_000_000), Some(u64::MAX));
+        // 微秒精度下超出纳秒表示范围
+        assert_eq!(units_to_nanos(u64::MAX, 1_000_000), None);
+    }
+}
@@ -0,0 +1,462 @@
+//! 标准抓包文件转换
+//!
+//! 从 libpcap/pcapng 抓包中提取UDP负载，保留原始时间戳写入数据集。
+//! 每个抓包文件生成一个数据文件，按首个数据报的时间戳命名，同名文件已存在时推后命名时间；
+//! 分片重组会使数据报晚于其时间戳输出，写入前在重排窗口内按时间戳排序。
+//! 数据集的网络配置由出现次数最多的目的地址和端口推断。
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use crate::dataset::capture::CaptureReader;
+use crate::dataset::format::DataFileWriter;
+use crate::dataset::import::ImportFailure;
+use crate::dataset::index::DatasetIndex;
+use crate::dataset::naming::unique_data_file_name;
+use crate::dataset::net::{UdpDatagram, UdpDecoder, REASSEMBLY_TIMEOUT_NS};
+use crate::state::task_state::CancelToken;
+use crate::types::{NetworkConfig, PlaybackError, Result};
+
+/// 检查取消标记的帧间隔
+const CANCEL_CHECK_INTERVAL: u64 = 1024;
+
+/// 重排窗口（纳秒），数据报最多晚于其时间戳一个分片重组超时输出
+const REORDER_WINDOW_NS: u64 = REASSEMBLY_TIMEOUT_NS;
+
+/// 重排缓冲的负载总字节数上限，超出时提前写出最早的数据报
+const REORDER_BUFFER_BYTES: usize = 64 * 1024 * 1024;
+
+/// 目的地址过滤条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptureFilter {
+    /// 目的地址（组播组或单播地址）
+    #[serde(default)]
+    pub destination: Option<IpAddr>,
+    /// 目的端口
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+impl CaptureFilter {
+    pub fn matches(&self, datagram: &UdpDatagram) -> bool {
+        self.destination
+            .is_none_or(|destination| destination == datagram.destination)
+            && self
+                .port
+                .is_none_or(|port| port == datagram.destination_port)
+    }
+}
+
+/// 转换结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConvertSummary {
+    /// 生成的数据文件名
+    pub files: Vec<String>,
+    pub failed: Vec<ImportFailure>,
+    /// 读取的链路帧数
+    pub frames: u64,
+    /// 写入的数据报数
+    pub datagrams: u64,
+    /// 非UDP、不满足过滤条件或无法解析的帧数
+    pub skipped: u64,
+    /// 未能完成重组的分片数据报数
+    pub incomplete: u64,
+    /// 超出重排窗口、早于已写入数据报的数据报数，按到达顺序写入
+    pub out_of_order: u64,
+    pub cancelled: bool,
+    /// 推断的网络配置
+    pub network: Option<NetworkConfig>,
+    pub index_path: Option<String>,
+}
+
+/// 转换进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertStage {
+    Converting,
+    Indexing,
+    Completed,
+}
+
+impl ConvertStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConvertStage::Converting => "converting",
+            ConvertStage::Indexing => "indexing",
+            ConvertStage::Completed => "completed",
+        }
+    }
+}
+
+/// 单个抓包文件的转换结果
+enum FileOutcome {
+    Written(String),
+    Empty,
+    Cancelled,
+}
+
+/// 按时间戳重排数据报的缓冲，时间相同时保持到达顺序
+#[derive(Debug, Default)]
+struct ReorderBuffer {
+    heap: BinaryHeap<Reverse<(u64, u64, Vec<u8>)>>,
+    sequence: u64,
+    bytes: usize,
+}
+
+impl ReorderBuffer {
+    fn push(&mut self, timestamp_ns: u64, payload: Vec<u8>) {
+        self.bytes += payload.len();
+        self.heap
+            .push(Reverse((timestamp_ns, self.sequence, payload)));
+        self.sequence += 1;
+    }
+
+    /// 取出早于 `now_ns` 一个重排窗口的数据报，缓冲超出上限时取出最早的数据报；
+    /// `now_ns` 为空时取出全部
+    fn pop(&mut self, now_ns: Option<u64>) -> Option<(u64, Vec<u8>)> {
+        let Reverse((timestamp_ns, _, _)) = self.heap.peek()?;
+        let ready = match now_ns {
+            Some(now_ns) => {
+                now_ns.saturating_sub(*timestamp_ns) > REORDER_WINDOW_NS
+                    || self.bytes > REORDER_BUFFER_BYTES
+            }
+            None => true,
+        };
+        if !ready {
+            return None;
+        }
+        let Reverse((timestamp_ns, _, payload)) = self.heap.pop()?;
+        self.bytes -= payload.len();
+        Some((timestamp_ns, payload))
+    }
+}
+
+/// 抓包文件转换器
+#[derive(Debug)]
+pub struct CaptureConverter {
+    dataset_dir: PathBuf,
+    filter: CaptureFilter,
+    destinations: HashMap<(IpAddr, u16), u64>,
+}
+
+impl CaptureConverter {
+    pub fn new<P: AsRef<Path>>(dataset_dir: P, filter: CaptureFilter) -> Result<Self> {
+        let dataset_dir = dataset_dir.as_ref().to_path_buf();
+        if !dataset_dir.is_dir() {
+            return Err(PlaybackError::DatasetNotFound(
+                dataset_dir.to_string_lossy().to_string(),
+            ));
+        }
+
+        Ok(Self {
+            dataset_dir,
+            filter,
+            destinations: HashMap::new(),
+        })
+    }
+
+    /// 转换抓包文件
+    ///
+    /// 单个文件失败不会中断整批转换；`progress` 参数依次为阶段、已完成文件数、文件总数和当前文件
+    pub fn convert<F>(
+        mut self,
+        files: &[PathBuf],
+        cancel: &CancelToken,
+        mut progress: F,
+    ) -> Result<ConvertSummary>
+    where
+        F: FnMut(ConvertStage, u64, u64, Option<&str>),
+    {
+        let total = files.len() as u64;
+        let mut summary = ConvertSummary::default();
+
+        for (i, source) in files.iter().enumerate() {
+            if cancel.is_cancelled() {
+                summary.cancelled = true;
+                break;
+            }
+
+            let item = source.to_string_lossy().to_string();
+            progress(ConvertStage::Converting, i as u64, total, Some(&item));
+
+            match self.convert_file(source, cancel, &mut summary) {
+                Ok(FileOutcome::Written(name)) => {
+                    info!("转换抓包文件: {:?} -> {}", source, name);
+                    summary.files.push(name);
+                }
+                Ok(FileOutcome::Empty) => {
+                    warn!("抓包文件中没有符合条件的UDP数据报: {:?}", source);
+                    summary.failed.push(ImportFailure {
+                        path: item,
+                        reason: "没有符合条件的UDP数据报".to_string(),
+                    });
+                }
+                Ok(FileOutcome::Cancelled) => {
+                    summary.cancelled = true;
+                    break;
+                }
+                Err(e) => {
+                    warn!("转换抓包文件失败 {:?}: {}", source, e);
+                    summary.failed.push(ImportFailure {
+                        path: item,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        summary.network = self
+            .destinations
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|((address, port), _)| infer_network(*address, *port));
+
+        if !summary.files.is_empty() {
+            progress(
+                ConvertStage::Indexing,
+                summary.files.len() as u64,
+                total,
+                None,
+            );
+            let index_path = DatasetIndex::rebuild(&self.dataset_dir)?;
+            summary.index_path = Some(index_path.to_string_lossy().to_string());
+        }
+
+        progress(
+            ConvertStage::Completed,
+            summary.files.len() as u64,
+            total,
+            None,
+        );
+        info!(
+            "抓包转换完成: 生成 {} 个文件，写入 {} 个数据报，跳过 {} 帧",
+            summary.files.len(),
+            summary.datagrams,
+            summary.skipped
+        );
+        Ok(summary)
+    }
+
+    fn convert_file(
+        &mut self,
+        source: &Path,
+        cancel: &CancelToken,
+        summary: &mut ConvertSummary,
+    ) -> Result<FileOutcome> {
+        let mut reader = CaptureReader::open(source)?;
+        let mut decoder = UdpDecoder::new();
+        let mut buffer = ReorderBuffer::default();
+        let mut writer: Option<DataFileWriter> = None;
+        let mut last_written = 0u64;
+        let mut frames = 0u64;
+
+        let result: Result<bool> = {
+            let dataset_dir = &self.dataset_dir;
+            let mut write = |timestamp_ns: u64, payload: &[u8]| -> Result<()> {
+                let writer = match writer.as_mut() {
+                    Some(writer) => writer,
+                    None => writer.insert(DataFileWriter::create(
+                        dataset_dir.join(unique_data_file_name(dataset_dir, timestamp_ns)),
+                    )?),
+                };
+                if timestamp_ns < last_written {
+                    summary.out_of_order += 1;
+                }
+                last_written = last_written.max(timestamp_ns);
+                writer.write_packet(timestamp_ns, payload)
+            };
+
+            (|| {
+                let mut now_ns = 0u64;
+                while let Some(frame) = reader.next_frame()? {
+                    frames += 1;
+                    if frames.is_multiple_of(CANCEL_CHECK_INTERVAL) && cancel.is_cancelled() {
+                        return Ok(false);
+                    }
+                    now_ns = now_ns.max(frame.timestamp_ns);
+
+                    match decoder.decode(frame.link_type, frame.timestamp_ns, &frame.data) {
+                        Some(datagram) if self.filter.matches(&datagram) => {
+                            *self
+                                .destinations
+                                .entry((datagram.destination, datagram.destination_port))
+                                .or_default() += 1;
+                            buffer.push(datagram.timestamp_ns, datagram.payload);
+                        }
+                        _ => summary.skipped += 1,
+                    }
+                    while let Some((timestamp_ns, payload)) = buffer.pop(Some(now_ns)) {
+                        write(timestamp_ns, &payload)?;
+                    }
+                }
+                while let Some((timestamp_ns, payload)) = buffer.pop(None) {
+                    write(timestamp_ns, &payload)?;
+                }
+                Ok(true)
+            })()
+        };
+
+        summary.frames += frames;
+        summary.incomplete += decoder.pending_count() as u64;
+
+        match (result, writer) {
+            (Ok(true), Some(writer)) => {
+                summary.datagrams += writer.packet_count();
+                let path = writer.finish()?;
+                Ok(FileOutcome::Written(
+                    path.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                ))
+            }
+            (Ok(true), None) => Ok(FileOutcome::Empty),
+            (Ok(false), writer) => {
+                if let Some(writer) = writer {
+                    writer.discard();
+                }
+                Ok(FileOutcome::Cancelled)
+            }
+            (Err(e), writer) => {
+                if let Some(writer) = writer {
+                    writer.discard();
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// 根据目的地址推断网络配置
+fn infer_network(address: IpAddr, port: u16) -> NetworkConfig {
+    let ip = address.to_string();
+    match address {
+        IpAddr::V4(v4) if v4.is_broadcast() => NetworkConfig::broadcast(port),
+        _ if address.is_multicast() => NetworkConfig::multicast(&ip, port),
+        _ => NetworkConfig::unicast(&ip, port),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::capture::{PcapNgWriter, LINKTYPE_ETHERNET};
+    use crate::dataset::format::PacketReader;
+    use crate::dataset::naming::data_file_name;
+    use crate::dataset::net::UdpFrameBuilder;
+    use std::fs;
+    use std::net::SocketAddr;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    /// 写入抓包文件，帧按给定顺序和时间戳写入
+    fn write_capture(path: &Path, frames: &[(u64, Vec<u8>)]) {
+        let mut writer =
+            PcapNgWriter::new(Vec::new(), LINKTYPE_ETHERNET as u16, None, None).unwrap();
+        for (timestamp, frame) in frames {
+            writer.write_frame(*timestamp, frame).unwrap();
+        }
+        fs::write(path, writer.into_inner().unwrap()).unwrap();
+    }
+
+    fn read_packets(path: &Path) -> Vec<(u64, Vec<u8>)> {
+        let mut reader = PacketReader::open(path).unwrap();
+        let mut packets = Vec::new();
+        while let Some(packet) = reader.next_packet().unwrap() {
+            packets.push((packet.header.timestamp_ns(), packet.data));
+        }
+        packets
+    }
+
+    /// 分片数据报的后续分片晚于另一个数据报到达
+    fn interleaved_capture(path: &Path) -> (Vec<u8>, Vec<u8>) {
+        let mut builder = UdpFrameBuilder::new(
+            SocketAddr::from(([10, 0, 0, 1], 5000)),
+            SocketAddr::from(([239, 0, 0, 1], 6000)),
+        )
+        .unwrap();
+        let large: Vec<u8> = (0..3000).map(|i| i as u8).collect();
+        let small = b"small".to_vec();
+        let fragments = builder.frames(&large).unwrap();
+        let single = builder.frames(&small).unwrap();
+        assert_eq!(fragments.len(), 3);
+
+        write_capture(
+            path,
+            &[
+                (BASE_TIMESTAMP, fragments[0].clone()),
+                (BASE_TIMESTAMP + 1_000, single[0].clone()),
+                (BASE_TIMESTAMP + 2_000, fragments[1].clone()),
+                (BASE_TIMESTAMP + 3_000, fragments[2].clone()),
+            ],
+        );
+        (large, small)
+    }
+
+    #[test]
+    fn reorder_buffer_releases_in_timestamp_order() {
+        let mut buffer = ReorderBuffer::default();
+        buffer.push(300, vec![3]);
+        buffer.push(100, vec![1]);
+        buffer.push(100, vec![2]);
+        // 窗口内的数据报暂不写出
+        assert_eq!(buffer.pop(Some(300)), None);
+
+        let now = 100 + REORDER_WINDOW_NS + 1;
+        assert_eq!(buffer.pop(Some(now)), Some((100, vec![1])));
+        assert_eq!(buffer.pop(Some(now)), Some((100, vec![2])));
+        assert_eq!(buffer.pop(Some(now)), None);
+        assert_eq!(buffer.pop(None), Some((300, vec![3])));
+        assert_eq!(buffer.pop(None), None);
+    }
+
+    #[test]
+    fn converted_datagrams_are_time_ordered() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("capture.pcapng");
+        let dataset = dir.path().join("radar");
+        fs::create_dir(&dataset).unwrap();
+        let (large, small) = interleaved_capture(&source);
+
+        let summary = CaptureConverter::new(&dataset, CaptureFilter::default())
+            .unwrap()
+            .convert(&[source], &CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+        assert_eq!(summary.datagrams, 2);
+        assert_eq!(summary.out_of_order, 0);
+        assert_eq!(summary.files, vec![data_file_name(BASE_TIMESTAMP)]);
+
+        let packets = read_packets(&dataset.join(&summary.files[0]));
+        assert_eq!(
+            packets,
+            vec![(BASE_TIMESTAMP, large), (BASE_TIMESTAMP + 1_000, small)]
+        );
+    }
+
+    #[test]
+    fn existing_file_name_is_not_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("capture.pcapng");
+        let dataset = dir.path().join("radar");
+        fs::create_dir(&dataset).unwrap();
+        interleaved_capture(&source);
+
+        let convert = || {
+            CaptureConverter::new(&dataset, CaptureFilter::default())
+                .unwrap()
+                .convert(
+                    std::slice::from_ref(&source),
+                    &CancelToken::new(),
+                    |_, _, _, _| {},
+                )
+                .unwrap()
+        };
+        let first = convert();
+        let second = convert();
+        assert!(second.failed.is_empty());
+        assert_eq!(first.files, vec![data_file_name(BASE_TIMESTAMP)]);
+        assert_eq!(second.files, vec![data_file_name(BASE_TIMESTAMP + 100)]);
+    }
+}
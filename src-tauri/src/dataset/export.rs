@@ -0,0 +1,268 @@
+//! 数据集导出为标准 pcapng
+//!
+//! 每个数据包的负载按数据集网络配置中的目的地址封装为以太网/IP/UDP帧，
+//! 时间戳保持纳秒精度，数据集名称和描述写入节头块注释，便于在 Wireshark 中查看
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use crate::dataset::capture::{PcapNgWriter, LINKTYPE_ETHERNET};
+use crate::dataset::format::PacketReader;
+use crate::dataset::index::{data_files, DatasetIndex, PIDX_FILE_NAME};
+use crate::dataset::net::UdpFrameBuilder;
+use crate::state::task_state::CancelToken;
+use crate::types::{DatasetConfig, PlaybackError, Result};
+
+/// 未指定源地址时使用的IPv4源地址
+const DEFAULT_SOURCE_V4: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 1);
+/// 未指定源地址时使用的IPv6源地址
+const DEFAULT_SOURCE_V6: Ipv6Addr = Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1);
+
+/// 导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// 起始时间（纳秒，含）
+    #[serde(default)]
+    pub start_timestamp: Option<u64>,
+    /// 结束时间（纳秒，含）
+    #[serde(default)]
+    pub end_timestamp: Option<u64>,
+    /// 仅导出指定的数据文件（文件名）
+    #[serde(default)]
+    pub files: Option<Vec<String>>,
+    /// 最小负载长度（字节）
+    #[serde(default)]
+    pub min_size: Option<u32>,
+    /// 最大负载长度（字节）
+    #[serde(default)]
+    pub max_size: Option<u32>,
+    /// 合成报文的源地址
+    #[serde(default)]
+    pub source_address: Option<IpAddr>,
+    /// 合成报文的源端口，默认与目的端口相同
+    #[serde(default)]
+    pub source_port: Option<u16>,
+}
+
+impl ExportOptions {
+    fn in_time_range(&self, start: u64, end: u64) -> bool {
+        self.start_timestamp.is_none_or(|s| end >= s)
+            && self.end_timestamp.is_none_or(|e| start <= e)
+    }
+
+    fn accepts(&self, timestamp_ns: u64, size: u32) -> bool {
+        self.in_time_range(timestamp_ns, timestamp_ns)
+            && self.min_size.is_none_or(|min| size >= min)
+            && self.max_size.is_none_or(|max| size <= max)
+    }
+
+    fn includes_file(&self, name: &str) -> bool {
+        self.files
+            .as_ref()
+            .is_none_or(|files| files.iter().any(|f| f == name))
+    }
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSummary {
+    /// 导出文件，取消时为空
+    pub output_path: Option<String>,
+    /// 导出的数据包数
+    pub packets: u64,
+    /// 写入的帧数（分片后可能多于数据包数）
+    pub frames: u64,
+    /// 超过UDP负载上限而跳过的数据包数
+    pub oversized: u64,
+    pub cancelled: bool,
+}
+
+/// 导出进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportStage {
+    Exporting,
+    Completed,
+}
+
+impl ExportStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportStage::Exporting => "exporting",
+            ExportStage::Completed => "completed",
+        }
+    }
+}
+
+/// 数据集导出器
+#[derive(Debug)]
+pub struct DatasetExporter {
+    dataset: DatasetConfig,
+    options: ExportOptions,
+}
+
+impl DatasetExporter {
+    pub fn new(dataset: DatasetConfig, options: ExportOptions) -> Result<Self> {
+        if !Path::new(&dataset.path).is_dir() {
+            return Err(PlaybackError::DatasetNotFound(dataset.name));
+        }
+
+        Ok(Self { dataset, options })
+    }
+
+    /// 导出到 `output_path`
+    ///
+    /// 先写入 `.part` 临时文件，完成后替换目标文件；`progress` 参数依次为阶段、
+    /// 已处理文件数、文件总数和当前文件
+    pub fn export<F>(
+        &self,
+        output_path: &Path,
+        cancel: &CancelToken,
+        mut progress: F,
+    ) -> Result<ExportSummary>
+    where
+        F: FnMut(ExportStage, u64, u64, Option<&str>),
+    {
+        let network = &self.dataset.network_config;
+        let destination_ip: IpAddr = network.ip_address.parse().map_err(|_| {
+            PlaybackError::ParseError(format!("无效的IP地址: {}", network.ip_address))
+        })?;
+        let source_ip = self.options.source_address.unwrap_or(match destination_ip {
+            IpAddr::V4(_) => IpAddr::V4(DEFAULT_SOURCE_V4),
+            IpAddr::V6(_) => IpAddr::V6(DEFAULT_SOURCE_V6),
+        });
+        let mut builder = UdpFrameBuilder::new(
+            SocketAddr::new(source_ip, self.options.source_port.unwrap_or(network.port)),
+            SocketAddr::new(destination_ip, network.port),
+        )?;
+
+        let files = self.selected_files()?;
+        let total = files.len() as u64;
+
+        let temp_path = PathBuf::from(format!("{}.part", output_path.to_string_lossy()));
+        let mut writer = PcapNgWriter::new(
+            BufWriter::new(File::create(&temp_path)?),
+            LINKTYPE_ETHERNET as u16,
+            Some(&self.comment()),
+            Some(&self.dataset.name),
+        )?;
+
+        let mut summary = ExportSummary::default();
+        let result: Result<bool> = (|| {
+            for (i, path) in files.iter().enumerate() {
+                let item = path.to_string_lossy().to_string();
+                progress(ExportStage::Exporting, i as u64, total, Some(&item));
+
+                let mut reader = PacketReader::open(path)?;
+                while let Some(packet) = reader.next_packet()? {
+                    if cancel.is_cancelled() {
+                        return Ok(false);
+                    }
+                    if !self
+                        .options
+                        .accepts(packet.header.timestamp_ns(), packet.header.packet_length)
+                    {
+                        continue;
+                    }
+
+                    let Some(frames) = builder.frames(&packet.data) else {
+                        summary.oversized += 1;
+                        continue;
+                    };
+                    for frame in &frames {
+                        writer.write_frame(packet.header.timestamp_ns(), frame)?;
+                    }
+                    summary.packets += 1;
+                    summary.frames += frames.len() as u64;
+                }
+            }
+            Ok(true)
+        })();
+
+        let completed = match result.and_then(|completed| {
+            let file = writer
+                .into_inner()?
+                .into_inner()
+                .map_err(|e| PlaybackError::FileError(e.into_error()))?;
+            file.sync_all()?;
+            Ok(completed)
+        }) {
+            Ok(completed) => completed,
+            Err(e) => {
+                let _ = fs::remove_file(&temp_path);
+                return Err(e);
+            }
+        };
+
+        if completed {
+            fs::rename(&temp_path, output_path)?;
+            summary.output_path = Some(output_path.to_string_lossy().to_string());
+        } else {
+            let _ = fs::remove_file(&temp_path);
+            summary.cancelled = true;
+        }
+
+        if summary.oversized > 0 {
+            warn!("{} 个数据包超过UDP负载上限，未导出", summary.oversized);
+        }
+        progress(ExportStage::Completed, total, total, None);
+        info!(
+            "导出数据集 {}: {} 个数据包，{} 帧{}",
+            self.dataset.name,
+            summary.packets,
+            summary.frames,
+            if summary.cancelled {
+                "（已取消）"
+            } else {
+                ""
+            }
+        );
+        Ok(summary)
+    }
+
+    /// 按文件选择和时间范围筛选数据文件，索引有效时据此跳过时间范围外的文件
+    fn selected_files(&self) -> Result<Vec<PathBuf>> {
+        let dataset_dir = Path::new(&self.dataset.path);
+        let index = DatasetIndex::read(dataset_dir.join(PIDX_FILE_NAME)).ok();
+
+        Ok(data_files(dataset_dir)?
+            .into_iter()
+            .filter(|path| {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if !self.options.includes_file(&name) {
+                    return false;
+                }
+
+                let size = fs::metadata(path).map(|m| m.len()).ok();
+                index
+                    .as_ref()
+                    .and_then(|index| index.files.iter().find(|f| f.name == name))
+                    .filter(|entry| Some(entry.size) == size && entry.packet_count > 0)
+                    .is_none_or(|entry| {
+                        self.options
+                            .in_time_range(entry.start_timestamp, entry.end_timestamp)
+                    })
+            })
+            .collect())
+    }
+
+    /// 节头块注释
+    fn comment(&self) -> String {
+        let network = &self.dataset.network_config;
+        let mut comment = format!("数据集: {}", self.dataset.name);
+        if let Some(description) = &self.dataset.description {
+            comment.push_str(&format!("\n描述: {}", description));
+        }
+        comment.push_str(&format!(
+            "\n网络: {} {}:{}",
+            network.network_type, network.ip_address, network.port
+        ));
+        comment
+    }
+}
@@ -0,0 +1,1173 @@
+//! 数据抽取
+//!
+//! 从一个或多个数据集中按时间窗口和字节模式筛选数据包，按时间戳顺序合并写入目标目录。
+//! 保留原始时间戳，输出文件按首个数据包时间命名、超过大小上限时轮转，
+//! 完成后生成索引，并在输出目录的 `.provenance.json` 中记录来源文件、时间范围和过滤条件；
+//! 需要时还可在 `.origin` 目录中为每个数据包记录来源数据集标签。
+//! 为源数据集设置时间校正时，按校正后的时间筛选、排序并写入，即把校正固化到输出文件中。
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::dataset::format::{
+    DataFileWriter, PacketReader, RawPacket, FILE_HEADER_SIZE, PACKET_HEADER_SIZE,
+};
+use crate::dataset::import::ImportFailure;
+use crate::dataset::index::{data_files, DatasetIndex, FileIndex};
+use crate::dataset::info::{file_index_entry, read_dataset_index};
+use crate::dataset::naming::unique_data_file_name;
+use crate::project::structure::ProjectStructure;
+use crate::project::validator::ProjectValidator;
+use crate::state::task_state::CancelToken;
+use crate::types::{PlaybackError, Result, TimeCorrection};
+
+/// 来源记录文件名
+pub const PROVENANCE_FILE_NAME: &str = ".provenance.json";
+
+/// 默认的单个输出文件大小上限（1 GiB）
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1 << 30;
+
+/// 来源标签目录，每个数据文件对应一个 `<文件名>.origin`，按数据包顺序存放小端 `u16` 标签
+pub const ORIGIN_DIR: &str = ".origin";
+
+const ORIGIN_EXTENSION: &str = "origin";
+
+/// 检查取消标记的数据包间隔
+const CANCEL_CHECK_INTERVAL: u64 = 1024;
+
+/// 抽取过滤条件，未设置的条件不参与筛选
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtractFilter {
+    /// 起始时间戳（纳秒，含）
+    #[serde(default)]
+    pub start_timestamp: Option<u64>,
+    /// 结束时间戳（纳秒，含）
+    #[serde(default)]
+    pub end_timestamp: Option<u64>,
+    /// 字节模式，十六进制表示，可用空格分隔，`??` 匹配任意字节，如 `"EB 90 ?? 01"`
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// 字节模式在数据内容中的偏移，未设置时匹配任意位置
+    #[serde(default)]
+    pub pattern_offset: Option<usize>,
+}
+
+impl ExtractFilter {
+    /// 仅按时间窗口筛选
+    pub fn time_window(start_timestamp: u64, end_timestamp: u64) -> Self {
+        Self {
+            start_timestamp: Some(start_timestamp),
+            end_timestamp: Some(end_timestamp),
+            ..Default::default()
+        }
+    }
+
+    /// 时间戳是否落在时间窗口内
+    pub fn contains(&self, timestamp: u64) -> bool {
+        self.start_timestamp.is_none_or(|start| timestamp >= start)
+            && self.end_timestamp.is_none_or(|end| timestamp <= end)
+    }
+
+    /// 时间范围是否与时间窗口重叠
+    pub fn overlaps(&self, start: u64, end: u64) -> bool {
+        self.start_timestamp.is_none_or(|s| end >= s)
+            && self.end_timestamp.is_none_or(|e| start <= e)
+    }
+
+    /// 校验时间窗口并编译字节模式
+    pub fn compile(&self) -> Result<Option<BytePattern>> {
+        if let (Some(start), Some(end)) = (self.start_timestamp, self.end_timestamp) {
+            if end < start {
+                return Err(PlaybackError::ParseError(format!(
+                    "抽取的结束时间 {} 早于起始时间 {}",
+                    end, start
+                )));
+            }
+        }
+        self.pattern
+            .as_deref()
+            .map(|pattern| BytePattern::parse(pattern, self.pattern_offset))
+            .transpose()
+    }
+}
+
+/// 编译后的字节模式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytePattern {
+    /// `None` 表示通配字节
+    bytes: Vec<Option<u8>>,
+    offset: Option<usize>,
+}
+
+impl BytePattern {
+    /// 解析十六进制字节模式
+    pub fn parse(pattern: &str, offset: Option<usize>) -> Result<Self> {
+        let digits: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).collect();
+        if digits.is_empty() || !digits.len().is_multiple_of(2) {
+            return Err(PlaybackError::ParseError(format!(
+                "字节模式须为成对的十六进制字符: {:?}",
+                pattern
+            )));
+        }
+
+        let bytes = digits
+            .chunks(2)
+            .map(|pair| match pair {
+                ['?', '?'] => Ok(None),
+                [high, low] => match (high.to_digit(16), low.to_digit(16)) {
+                    (Some(high), Some(low)) => Ok(Some((high * 16 + low) as u8)),
+                    _ => Err(PlaybackError::ParseError(format!(
+                        "字节模式中有无效的字节 {}{}: {:?}",
+                        high, low, pattern
+                    ))),
+                },
+                _ => unreachable!(),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { bytes, offset })
+    }
+
+    /// 数据内容是否匹配
+    pub fn matches(&self, data: &[u8]) -> bool {
+        match self.offset {
+            Some(offset) => data
+                .get(offset..offset + self.bytes.len())
+                .is_some_and(|window| self.matches_at(window)),
+            None => data.windows(self.bytes.len()).any(|w| self.matches_at(w)),
+        }
+    }
+
+    fn matches_at(&self, window: &[u8]) -> bool {
+        self.bytes
+            .iter()
+            .zip(window)
+            .all(|(expected, actual)| expected.is_none_or(|b| b == *actual))
+    }
+}
+
+/// 输出文件的轮转条件，任一条件达到时开始新文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileLimits {
+    /// 文件大小上限（字节）
+    pub max_size: Option<u64>,
+    /// 数据包数上限
+    pub max_packets: Option<u64>,
+    /// 时长上限（纳秒），从文件的首个数据包起算
+    pub max_duration: Option<u64>,
+}
+
+impl Default for FileLimits {
+    fn default() -> Self {
+        Self {
+            max_size: Some(DEFAULT_MAX_FILE_SIZE),
+            max_packets: None,
+            max_duration: None,
+        }
+    }
+}
+
+impl FileLimits {
+    /// 写入长度为 `length` 的数据包前是否需要开始新文件，空文件总是继续写入
+    fn reached(&self, current: &CurrentFile, timestamp: u64, length: u64) -> bool {
+        let packets = current.writer.packet_count();
+        packets > 0
+            && (self.max_size.is_some_and(|max| current.size + length > max)
+                || self.max_packets.is_some_and(|max| packets >= max)
+                || self
+                    .max_duration
+                    .is_some_and(|max| timestamp.saturating_sub(current.first_timestamp) >= max))
+    }
+}
+
+/// 抽取请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractRequest {
+    /// 源数据集
+    pub sources: Vec<String>,
+    /// 目标数据集，不存在时自动创建；已存在时须没有数据文件
+    pub target: String,
+    #[serde(default)]
+    pub filter: ExtractFilter,
+    /// 单个输出文件大小上限（字节），默认 [`DEFAULT_MAX_FILE_SIZE`]
+    #[serde(default)]
+    pub max_file_size: Option<u64>,
+}
+
+/// 来源数据文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceFile {
+    pub dataset: String,
+    pub file_name: String,
+    pub file_size: u64,
+    /// 从该文件抽取的数据包数
+    pub packets: u64,
+}
+
+/// 抽取来源记录，保存在输出目录的 [`PROVENANCE_FILE_NAME`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub created_time: String,
+    pub filter: ExtractFilter,
+    /// 读取过的源数据文件
+    pub sources: Vec<SourceFile>,
+    pub packets: u64,
+    /// 抽取结果的实际起始时间戳
+    pub start_timestamp: Option<u64>,
+    /// 抽取结果的实际结束时间戳
+    pub end_timestamp: Option<u64>,
+    pub cancelled: bool,
+    /// 记录数据包来源时各标签对应的源数据集，按标签顺序
+    #[serde(default)]
+    pub origins: Vec<String>,
+    /// 写入时应用的各源数据集时间校正
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub time_corrections: BTreeMap<String, TimeCorrection>,
+}
+
+/// 读取目录中的来源记录，不存在或无法解析时返回 `None`
+pub fn read_provenance(dir: &Path) -> Option<Provenance> {
+    fs::read_to_string(dir.join(PROVENANCE_FILE_NAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// 数据文件中各数据包的来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PacketOrigins {
+    /// 标签对应的源数据集
+    pub datasets: Vec<String>,
+    /// 按数据包顺序的来源标签
+    pub tags: Vec<u16>,
+}
+
+impl PacketOrigins {
+    /// 第 `packet` 个数据包的来源数据集
+    pub fn dataset(&self, packet: usize) -> Option<&str> {
+        let tag = *self.tags.get(packet)?;
+        self.datasets.get(tag as usize).map(String::as_str)
+    }
+}
+
+/// 读取数据文件的来源标签，数据集未记录来源时返回 `None`
+pub fn read_packet_origins(dataset_dir: &Path, file_name: &str) -> Result<Option<PacketOrigins>> {
+    let Some(provenance) = read_provenance(dataset_dir).filter(|p| !p.origins.is_empty()) else {
+        return Ok(None);
+    };
+    let tags = read_origin_tags(&dataset_dir.join(file_name))?;
+    if tags.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(PacketOrigins {
+        datasets: provenance.origins,
+        tags,
+    }))
+}
+
+/// 抽取结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractSummary {
+    /// 生成的数据文件名
+    pub files: Vec<String>,
+    pub packets: u64,
+    pub total_size: u64,
+    /// 读取的源数据文件数
+    pub source_files: u64,
+    /// 校验和不匹配而未写入的数据包数
+    pub corrupt_packets: u64,
+    /// 读取中断的源数据文件，中断前的数据包已抽取
+    pub failed: Vec<ImportFailure>,
+    pub cancelled: bool,
+    pub index_path: Option<String>,
+    pub provenance_path: Option<String>,
+}
+
+/// 抽取进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractStage {
+    Extracting,
+    Indexing,
+    Completed,
+}
+
+impl ExtractStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExtractStage::Extracting => "extracting",
+            ExtractStage::Indexing => "indexing",
+            ExtractStage::Completed => "completed",
+        }
+    }
+}
+
+/// 单个源数据集的读取状态
+struct SourceReader {
+    dataset: String,
+    /// 与时间窗口重叠的数据文件及其在来源记录中的位置和索引条目
+    files: Vec<(PathBuf, usize, Option<FileIndex>)>,
+    next_file: usize,
+    reader: Option<OpenFile>,
+    head: Option<Head>,
+    /// 源数据集自身记录的来源标签到输出标签的映射，源数据集未记录来源时为空
+    origin_map: Vec<u16>,
+    /// 源数据集本身的输出标签，用于没有继承来源标签的数据包
+    own_tag: Option<u16>,
+    time_correction: Option<TimeCorrection>,
+}
+
+impl SourceReader {
+    fn corrected(&self, timestamp: u64) -> u64 {
+        self.time_correction
+            .as_ref()
+            .map_or(timestamp, |c| c.apply(timestamp))
+    }
+}
+
+/// 下一个待写入的数据包
+struct Head {
+    packet: RawPacket,
+    /// 校正后的时间戳
+    timestamp: u64,
+    /// 在来源记录中的位置
+    position: usize,
+    /// 继承的来源标签
+    tag: Option<u16>,
+}
+
+/// 正在读取的源数据文件
+struct OpenFile {
+    /// 在来源记录中的位置
+    position: usize,
+    reader: PacketReader<BufReader<File>>,
+    /// 下一个数据包在文件中的序号
+    packet: usize,
+    /// 源文件记录的来源标签
+    tags: Vec<u16>,
+}
+
+/// 数据抽取器
+#[derive(Debug)]
+pub struct DatasetExtractor {
+    /// 源数据集名称和目录
+    sources: Vec<(String, PathBuf)>,
+    filter: ExtractFilter,
+    pattern: Option<BytePattern>,
+    limits: FileLimits,
+    tag_origins: bool,
+    /// 按源数据集名称的时间校正
+    time_corrections: HashMap<String, TimeCorrection>,
+}
+
+impl DatasetExtractor {
+    /// 校验过滤条件和源数据集目录
+    pub fn new(sources: Vec<(String, PathBuf)>, filter: ExtractFilter) -> Result<Self> {
+        if sources.is_empty() {
+            return Err(PlaybackError::ProjectError("未指定源数据集".to_string()));
+        }
+        for (name, dir) in &sources {
+            if !dir.is_dir() {
+                return Err(PlaybackError::DatasetNotFound(name.clone()));
+            }
+        }
+        let pattern = filter.compile()?;
+
+        Ok(Self {
+            sources,
+            filter,
+            pattern,
+            limits: FileLimits::default(),
+            tag_origins: false,
+            time_corrections: HashMap::new(),
+        })
+    }
+
+    /// 按工程中的抽取请求创建，校验数据集名称并确认目标数据集可写入
+    pub fn for_project<P: AsRef<Path>>(project_path: P, request: &ExtractRequest) -> Result<Self> {
+        let project_path = project_path.as_ref();
+        ProjectValidator::validate_name(&request.target)?;
+
+        let mut sources = Vec::new();
+        for name in &request.sources {
+            ProjectValidator::validate_name(name)?;
+            if name == &request.target {
+                return Err(PlaybackError::ProjectError(format!(
+                    "目标数据集不能同时是源数据集: {}",
+                    name
+                )));
+            }
+            if sources.iter().any(|(source, _)| source == name) {
+                return Err(PlaybackError::ProjectError(format!(
+                    "重复的源数据集: {}",
+                    name
+                )));
+            }
+            sources.push((name.clone(), project_path.join(name)));
+        }
+
+        let target_dir = project_path.join(&request.target);
+        if target_dir.is_dir() && !data_files(&target_dir)?.is_empty() {
+            return Err(PlaybackError::ProjectError(format!(
+                "目标数据集已有数据文件: {}",
+                request.target
+            )));
+        }
+
+        let extractor = Self::new(sources, request.filter.clone())?;
+        Ok(match request.max_file_size {
+            Some(size) => extractor.with_max_file_size(size),
+            None => extractor,
+        })
+    }
+
+    /// 设置单个输出文件大小上限
+    pub fn with_max_file_size(self, max_file_size: u64) -> Self {
+        let limits = FileLimits {
+            max_size: Some(max_file_size),
+            ..self.limits
+        };
+        self.with_limits(limits)
+    }
+
+    /// 设置输出文件的轮转条件
+    pub fn with_limits(mut self, limits: FileLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// 为每个数据包记录来源数据集，写入输出目录的 [`ORIGIN_DIR`]
+    ///
+    /// 标签按源数据集顺序编号；源数据集本身记录了来源时（如合并结果）沿用其中的来源，
+    /// 这种情况下即使未调用本方法也会记录来源
+    pub fn with_origin_tags(mut self) -> Self {
+        self.tag_origins = true;
+        self
+    }
+
+    /// 按校正后的时间读取源数据集，过滤条件中的时间窗口同样按校正后的时间比较
+    pub fn with_time_correction(mut self, dataset: &str, correction: TimeCorrection) -> Self {
+        self.time_corrections
+            .insert(dataset.to_string(), correction);
+        self
+    }
+
+    /// 抽取到工程中的目标数据集，目标数据集不存在时创建；抽取失败时删除新建的数据集
+    pub fn extract_to_dataset<F>(
+        &self,
+        project_path: &Path,
+        target: &str,
+        cancel: &CancelToken,
+        progress: F,
+    ) -> Result<ExtractSummary>
+    where
+        F: FnMut(ExtractStage, u64, u64, Option<&str>),
+    {
+        let target_dir = project_path.join(target);
+        let created = !target_dir.exists();
+        if created {
+            ProjectStructure::create_dataset(project_path, target)?;
+        }
+
+        let result = self.extract_into(&target_dir, cancel, progress);
+        if result.is_err() && created {
+            if let Err(e) = ProjectStructure::remove_dataset(project_path, target, true) {
+                warn!("删除抽取失败的数据集 {} 失败: {}", target, e);
+            }
+        }
+        result
+    }
+
+    /// 抽取到指定目录，目录不存在时创建，已有数据文件时失败
+    ///
+    /// 单个源文件读取中断不会影响其他文件；取消时保留已写入的数据包。
+    /// `progress` 参数依次为阶段、已读取源文件数、源文件总数和当前文件
+    pub fn extract_into<F>(
+        &self,
+        output_dir: &Path,
+        cancel: &CancelToken,
+        mut progress: F,
+    ) -> Result<ExtractSummary>
+    where
+        F: FnMut(ExtractStage, u64, u64, Option<&str>),
+    {
+        fs::create_dir_all(output_dir)?;
+        if !data_files(output_dir)?.is_empty() {
+            return Err(PlaybackError::ProjectError(format!(
+                "输出目录已有数据文件: {:?}",
+                output_dir
+            )));
+        }
+
+        let mut provenance = Provenance {
+            created_time: chrono::Utc::now().to_rfc3339(),
+            filter: self.filter.clone(),
+            sources: Vec::new(),
+            packets: 0,
+            start_timestamp: None,
+            end_timestamp: None,
+            cancelled: false,
+            origins: Vec::new(),
+            time_corrections: self
+                .time_corrections
+                .iter()
+                .map(|(name, c)| (name.clone(), c.clone()))
+                .collect(),
+        };
+        let mut readers = self.open_sources(&mut provenance)?;
+        let total = provenance.sources.len() as u64;
+        let tag_origins = !provenance.origins.is_empty();
+
+        let mut summary = ExtractSummary::default();
+        let mut output = OutputFiles::new(output_dir, self.limits, tag_origins);
+        let result = (|| -> Result<()> {
+            for reader in readers.iter_mut() {
+                self.advance(reader, &provenance, &mut summary, &mut progress, total)?;
+            }
+
+            loop {
+                if summary.packets.is_multiple_of(CANCEL_CHECK_INTERVAL) && cancel.is_cancelled() {
+                    summary.cancelled = true;
+                    return Ok(());
+                }
+
+                // 多个源数据集按时间戳合并，时间戳相同时按源数据集顺序
+                let next = readers
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, r)| r.head.as_ref().map(|h| (h.timestamp, i)))
+                    .min();
+                let Some((timestamp, i)) = next else {
+                    return Ok(());
+                };
+
+                let reader = &mut readers[i];
+                let Some(head) = reader.head.take() else {
+                    continue;
+                };
+                let tag = match head.tag.or(reader.own_tag) {
+                    Some(tag) => tag,
+                    None if tag_origins => {
+                        let tag = intern_origin(&mut provenance.origins, &reader.dataset);
+                        *reader.own_tag.insert(tag)
+                    }
+                    None => 0,
+                };
+                output.write(timestamp, &head.packet.data, tag)?;
+                provenance.sources[head.position].packets += 1;
+                provenance.start_timestamp.get_or_insert(timestamp);
+                provenance.end_timestamp = Some(timestamp);
+                summary.packets += 1;
+
+                self.advance(
+                    &mut readers[i],
+                    &provenance,
+                    &mut summary,
+                    &mut progress,
+                    total,
+                )?;
+            }
+        })();
+
+        if let Err(e) = result {
+            output.discard();
+            return Err(e);
+        }
+        summary.files = output.finish()?;
+        summary.total_size = summary
+            .files
+            .iter()
+            .map(|name| fs::metadata(output_dir.join(name)).map(|m| m.len()))
+            .sum::<std::io::Result<u64>>()?;
+
+        if !summary.files.is_empty() {
+            progress(ExtractStage::Indexing, summary.source_files, total, None);
+            let index_path = DatasetIndex::rebuild(output_dir)?;
+            summary.index_path = Some(index_path.to_string_lossy().to_string());
+        }
+
+        provenance.packets = summary.packets;
+        provenance.cancelled = summary.cancelled;
+        let provenance_path = output_dir.join(PROVENANCE_FILE_NAME);
+        fs::write(&provenance_path, serde_json::to_string_pretty(&provenance)?)?;
+        summary.provenance_path = Some(provenance_path.to_string_lossy().to_string());
+
+        progress(ExtractStage::Completed, summary.source_files, total, None);
+        info!(
+            "数据抽取{}: 读取 {} 个源文件，生成 {} 个文件，{} 个数据包",
+            if summary.cancelled {
+                "已取消"
+            } else {
+                "完成"
+            },
+            summary.source_files,
+            summary.files.len(),
+            summary.packets
+        );
+        Ok(summary)
+    }
+
+    /// 列出各源数据集中与时间窗口重叠的数据文件，登记到来源记录；需要记录来源时同时编排来源标签
+    fn open_sources(&self, provenance: &mut Provenance) -> Result<Vec<SourceReader>> {
+        let inherited: Vec<Vec<String>> = self
+            .sources
+            .iter()
+            .map(|(_, dir)| read_provenance(dir).map(|p| p.origins).unwrap_or_default())
+            .collect();
+        let tag_origins = self.tag_origins || inherited.iter().any(|o| !o.is_empty());
+
+        let mut readers = Vec::new();
+        for ((dataset, dir), inherited) in self.sources.iter().zip(inherited) {
+            let (origin_map, own_tag) = match (tag_origins, inherited.is_empty()) {
+                (false, _) => (Vec::new(), None),
+                (true, true) => (
+                    Vec::new(),
+                    Some(intern_origin(&mut provenance.origins, dataset)),
+                ),
+                (true, false) => (
+                    inherited
+                        .iter()
+                        .map(|name| intern_origin(&mut provenance.origins, name))
+                        .collect(),
+                    None,
+                ),
+            };
+
+            let time_correction = self.time_corrections.get(dataset).cloned();
+            let corrected = |t: u64| time_correction.as_ref().map_or(t, |c| c.apply(t));
+            let index = read_dataset_index(dir);
+            let mut files = Vec::new();
+
+            for path in data_files(dir)? {
+                let file_name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let file_size = fs::metadata(&path)?.len();
+                let entry = index
+                    .as_ref()
+                    .and_then(|index| file_index_entry(index, &file_name, file_size));
+
+                // 索引记录的是文件内的最小和最大时间戳；没有索引时首尾数据包的时间
+                // 不能代表文件的时间范围（时间戳不一定递增），逐包读取
+                let range = match entry {
+                    Some(entry) if entry.packet_count == 0 => None,
+                    Some(entry) => Some((entry.start_timestamp, entry.end_timestamp)),
+                    None => Some((0, u64::MAX)),
+                }
+                .map(|(start, end)| match (start, end) {
+                    (0, u64::MAX) => (start, end),
+                    _ => (corrected(start), corrected(end)),
+                });
+                if !range.is_some_and(|(start, end)| self.filter.overlaps(start, end)) {
+                    continue;
+                }
+
+                provenance.sources.push(SourceFile {
+                    dataset: dataset.clone(),
+                    file_name,
+                    file_size,
+                    packets: 0,
+                });
+                files.push((path, provenance.sources.len() - 1, entry.cloned()));
+            }
+
+            readers.push(SourceReader {
+                dataset: dataset.clone(),
+                files,
+                next_file: 0,
+                reader: None,
+                head: None,
+                origin_map,
+                own_tag,
+                time_correction,
+            });
+        }
+        Ok(readers)
+    }
+
+    /// 读取源数据集中下一个满足条件的数据包，所有文件读完时 `head` 为空
+    fn advance<F>(
+        &self,
+        source: &mut SourceReader,
+        provenance: &Provenance,
+        summary: &mut ExtractSummary,
+        progress: &mut F,
+        total: u64,
+    ) -> Result<()>
+    where
+        F: FnMut(ExtractStage, u64, u64, Option<&str>),
+    {
+        loop {
+            let Some(file) = source.reader.as_mut() else {
+                let Some((path, position, entry)) = source.files.get(source.next_file) else {
+                    return Ok(());
+                };
+                source.next_file += 1;
+
+                let item = path.to_string_lossy().to_string();
+                progress(
+                    ExtractStage::Extracting,
+                    summary.source_files,
+                    total,
+                    Some(&item),
+                );
+                summary.source_files += 1;
+
+                match self.open_file(path, entry.as_ref(), source.time_correction.as_ref()) {
+                    Ok((reader, packet)) => {
+                        let tags = if source.origin_map.is_empty() {
+                            Vec::new()
+                        } else {
+                            read_origin_tags(path).unwrap_or_else(|e| {
+                                warn!("读取来源标签失败 {:?}: {}", path, e);
+                                Vec::new()
+                            })
+                        };
+                        source.reader = Some(OpenFile {
+                            position: *position,
+                            reader,
+                            packet,
+                            tags,
+                        });
+                    }
+                    Err(e) => {
+                        warn!("打开源数据文件失败 {:?}: {}", path, e);
+                        summary.failed.push(ImportFailure {
+                            path: item,
+                            reason: e.to_string(),
+                        });
+                    }
+                }
+                continue;
+            };
+            let position = file.position;
+            let tag = file
+                .tags
+                .get(file.packet)
+                .and_then(|tag| source.origin_map.get(*tag as usize))
+                .copied();
+
+            let packet = match file.reader.next_packet() {
+                Ok(Some(packet)) => {
+                    file.packet += 1;
+                    packet
+                }
+                Ok(None) => {
+                    source.reader = None;
+                    continue;
+                }
+                Err(e) => {
+                    let file = &provenance.sources[position];
+                    warn!(
+                        "读取源数据文件中断 {}/{}: {}",
+                        source.dataset, file.file_name, e
+                    );
+                    summary.failed.push(ImportFailure {
+                        path: format!("{}/{}", source.dataset, file.file_name),
+                        reason: e.to_string(),
+                    });
+                    source.reader = None;
+                    continue;
+                }
+            };
+
+            // 文件内的时间戳不一定递增，窗口外的数据包逐个跳过而不是结束读取
+            let timestamp = source.corrected(packet.header.timestamp_ns());
+            if !self.filter.contains(timestamp) {
+                continue;
+            }
+            if !packet.checksum_matches() {
+                summary.corrupt_packets += 1;
+                continue;
+            }
+            if self
+                .pattern
+                .as_ref()
+                .is_some_and(|p| !p.matches(&packet.data))
+            {
+                continue;
+            }
+
+            source.head = Some(Head {
+                packet,
+                timestamp,
+                position,
+                tag,
+            });
+            return Ok(());
+        }
+    }
+
+    /// 打开源数据文件，有索引时直接定位到第一个不早于起始时间的数据包，返回读取器和该数据包的序号
+    fn open_file(
+        &self,
+        path: &Path,
+        entry: Option<&FileIndex>,
+        time_correction: Option<&TimeCorrection>,
+    ) -> Result<(PacketReader<BufReader<File>>, usize)> {
+        let mut reader = PacketReader::open(path)?;
+        if let (Some(start), Some(entry)) = (self.filter.start_timestamp, entry) {
+            let corrected = |t: u64| time_correction.map_or(t, |c| c.apply(t));
+            // 时间戳不一定递增，逐个查找；之前的数据包都早于起始时间，跳过不影响结果
+            if let Some((first, packet)) = entry
+                .packets
+                .iter()
+                .enumerate()
+                .find(|(_, p)| corrected(p.timestamp_ns) >= start)
+            {
+                reader.seek_to(packet.byte_offset)?;
+                return Ok((reader, first));
+            }
+        }
+        Ok((reader, 0))
+    }
+}
+
+/// 正在写入的输出文件
+struct CurrentFile {
+    writer: DataFileWriter,
+    size: u64,
+    first_timestamp: u64,
+    /// 各数据包的来源标签，未记录来源时为空
+    tags: Vec<u16>,
+}
+
+/// 按轮转条件切分的输出文件
+struct OutputFiles<'a> {
+    dir: &'a Path,
+    limits: FileLimits,
+    tag_origins: bool,
+    current: Option<CurrentFile>,
+    files: Vec<String>,
+}
+
+impl<'a> OutputFiles<'a> {
+    fn new(dir: &'a Path, limits: FileLimits, tag_origins: bool) -> Self {
+        Self {
+            dir,
+            limits,
+            tag_origins,
+            current: None,
+            files: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, timestamp: u64, data: &[u8], origin: u16) -> Result<()> {
+        let length = PACKET_HEADER_SIZE + data.len() as u64;
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|current| self.limits.reached(current, timestamp, length))
+        {
+            self.finish_current()?;
+        }
+
+        let current = match self.current.as_mut() {
+            Some(current) => current,
+            None => {
+                // 轮转后的首个数据包可能与上一个文件的首个数据包落在同一个100纳秒内
+                let path = self.dir.join(unique_data_file_name(self.dir, timestamp));
+                self.current.insert(CurrentFile {
+                    writer: DataFileWriter::create(path)?,
+                    size: FILE_HEADER_SIZE,
+                    first_timestamp: timestamp,
+                    tags: Vec::new(),
+                })
+            }
+        };
+        current.writer.write_packet(timestamp, data)?;
+        current.size += length;
+        if self.tag_origins {
+            current.tags.push(origin);
+        }
+        Ok(())
+    }
+
+    fn finish_current(&mut self) -> Result<()> {
+        let Some(current) = self.current.take() else {
+            return Ok(());
+        };
+        let path = current.writer.finish()?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.files.push(name.clone());
+
+        if self.tag_origins {
+            let origin_path = origin_path(self.dir, &name);
+            if let Some(parent) = origin_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let bytes: Vec<u8> = current.tags.iter().flat_map(|t| t.to_le_bytes()).collect();
+            fs::write(origin_path, bytes)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<String>> {
+        self.finish_current()?;
+        Ok(self.files)
+    }
+
+    /// 丢弃正在写入的文件并删除已完成的文件
+    fn discard(self) {
+        if let Some(current) = self.current {
+            current.writer.discard();
+        }
+        for name in &self.files {
+            let mut paths = vec![self.dir.join(name)];
+            if self.tag_origins {
+                paths.push(origin_path(self.dir, name));
+            }
+            for path in paths.iter().filter(|p| p.exists()) {
+                if let Err(e) = fs::remove_file(path) {
+                    warn!("删除输出文件失败 {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+}
+
+/// 来源名称在列表中的标签，不在列表中时追加
+fn intern_origin(origins: &mut Vec<String>, name: &str) -> u16 {
+    let position = origins.iter().position(|o| o == name).unwrap_or_else(|| {
+        origins.push(name.to_string());
+        origins.len() - 1
+    });
+    position as u16
+}
+
+/// 读取数据文件对应的来源标签，没有标签文件时为空
+fn read_origin_tags(data_file: &Path) -> Result<Vec<u16>> {
+    let (Some(dir), Some(name)) = (data_file.parent(), data_file.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let path = origin_path(dir, &name.to_string_lossy());
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    Ok(fs::read(&path)?
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect())
+}
+
+/// 数据文件的来源标签文件路径
+fn origin_path(dir: &Path, file_name: &str) -> PathBuf {
+    dir.join(ORIGIN_DIR)
+        .join(Path::new(file_name).with_extension(ORIGIN_EXTENSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::PacketReader;
+    use crate::dataset::naming::data_file_name;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    /// 在数据集目录中写入一个数据文件，数据包按给定顺序写入
+    fn write_data_file(dir: &Path, packets: &[(u64, &[u8])]) {
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join(data_file_name(packets[0].0));
+        let mut writer = DataFileWriter::create(path).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    /// 按文件顺序读出目录中所有数据包
+    fn read_output(dir: &Path) -> Vec<(u64, Vec<u8>)> {
+        let mut packets = Vec::new();
+        for path in data_files(dir).unwrap() {
+            let mut reader = PacketReader::open(&path).unwrap();
+            while let Some(packet) = reader.next_packet().unwrap() {
+                packets.push((packet.header.timestamp_ns(), packet.data));
+            }
+        }
+        packets
+    }
+
+    fn extract(
+        sources: &[(&str, &Path)],
+        filter: ExtractFilter,
+        output: &Path,
+    ) -> Result<ExtractSummary> {
+        let sources = sources
+            .iter()
+            .map(|(name, dir)| (name.to_string(), dir.to_path_buf()))
+            .collect();
+        DatasetExtractor::new(sources, filter)?.extract_into(
+            output,
+            &CancelToken::new(),
+            |_, _, _, _| {},
+        )
+    }
+
+    #[test]
+    fn merges_sources_by_timestamp_within_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        write_data_file(
+            &a,
+            &[
+                (BASE_TIMESTAMP, b"a0"),
+                (BASE_TIMESTAMP + 20, b"a1"),
+                (BASE_TIMESTAMP + 40, b"a2"),
+            ],
+        );
+        write_data_file(
+            &b,
+            &[(BASE_TIMESTAMP + 10, b"b0"), (BASE_TIMESTAMP + 30, b"b1")],
+        );
+
+        let output = dir.path().join("out");
+        let filter = ExtractFilter::time_window(BASE_TIMESTAMP + 10, BASE_TIMESTAMP + 30);
+        let summary = extract(&[("a", &a), ("b", &b)], filter, &output).unwrap();
+
+        assert_eq!(summary.packets, 3);
+        assert_eq!(summary.source_files, 2);
+        assert_eq!(
+            read_output(&output),
+            vec![
+                (BASE_TIMESTAMP + 10, b"b0".to_vec()),
+                (BASE_TIMESTAMP + 20, b"a1".to_vec()),
+                (BASE_TIMESTAMP + 30, b"b1".to_vec()),
+            ]
+        );
+        let provenance = read_provenance(&output).unwrap();
+        assert_eq!(provenance.packets, 3);
+        assert_eq!(provenance.start_timestamp, Some(BASE_TIMESTAMP + 10));
+        assert_eq!(provenance.end_timestamp, Some(BASE_TIMESTAMP + 30));
+    }
+
+    #[test]
+    fn keeps_reading_past_out_of_window_timestamps() {
+        // 时间戳不递增，窗口之后的数据包后面仍有窗口内的数据包
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("radar");
+        write_data_file(
+            &source,
+            &[
+                (BASE_TIMESTAMP + 10, b"p0"),
+                (BASE_TIMESTAMP + 50, b"p1"),
+                (BASE_TIMESTAMP + 20, b"p2"),
+                (BASE_TIMESTAMP + 30, b"p3"),
+            ],
+        );
+
+        let output = dir.path().join("out");
+        let filter = ExtractFilter::time_window(BASE_TIMESTAMP + 15, BASE_TIMESTAMP + 35);
+        let summary = extract(&[("radar", &source)], filter, &output).unwrap();
+
+        assert_eq!(summary.packets, 2);
+        assert_eq!(
+            read_output(&output),
+            vec![
+                (BASE_TIMESTAMP + 20, b"p2".to_vec()),
+                (BASE_TIMESTAMP + 30, b"p3".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn indexed_seek_does_not_skip_unordered_packets() {
+        // 有索引时按起始时间定位，首个数据包晚于起始时间但后面仍有窗口内的数据包
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("radar");
+        write_data_file(
+            &source,
+            &[
+                (BASE_TIMESTAMP + 40, b"p0"),
+                (BASE_TIMESTAMP + 10, b"p1"),
+                (BASE_TIMESTAMP + 20, b"p2"),
+            ],
+        );
+        DatasetIndex::rebuild(&source).unwrap();
+
+        let output = dir.path().join("out");
+        let filter = ExtractFilter::time_window(BASE_TIMESTAMP + 15, BASE_TIMESTAMP + 30);
+        extract(&[("radar", &source)], filter, &output).unwrap();
+
+        assert_eq!(
+            read_output(&output),
+            vec![(BASE_TIMESTAMP + 20, b"p2".to_vec())]
+        );
+    }
+
+    #[test]
+    fn filters_by_byte_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("radar");
+        write_data_file(
+            &source,
+            &[
+                (BASE_TIMESTAMP, &[0xEB, 0x90, 0x01, 0x01]),
+                (BASE_TIMESTAMP + 10, &[0xEB, 0x91, 0x01, 0x01]),
+                (BASE_TIMESTAMP + 20, &[0xEB, 0x90, 0x7F, 0x01]),
+            ],
+        );
+
+        let output = dir.path().join("out");
+        let filter = ExtractFilter {
+            pattern: Some("EB 90 ?? 01".to_string()),
+            pattern_offset: Some(0),
+            ..ExtractFilter::default()
+        };
+        let summary = extract(&[("radar", &source)], filter, &output).unwrap();
+
+        assert_eq!(summary.packets, 2);
+        let timestamps: Vec<u64> = read_output(&output).into_iter().map(|(t, _)| t).collect();
+        assert_eq!(timestamps, vec![BASE_TIMESTAMP, BASE_TIMESTAMP + 20]);
+    }
+
+    #[test]
+    fn rejects_output_with_data_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("radar");
+        let output = dir.path().join("out");
+        write_data_file(&source, &[(BASE_TIMESTAMP, b"p0")]);
+        write_data_file(&output, &[(BASE_TIMESTAMP, b"old")]);
+
+        let result = extract(&[("radar", &source)], ExtractFilter::default(), &output);
+
+        assert!(matches!(result, Err(PlaybackError::ProjectError(_))));
+        assert_eq!(
+            read_output(&output),
+            vec![(BASE_TIMESTAMP, b"old".to_vec())]
+        );
+    }
+
+    #[test]
+    fn cancelled_extraction_writes_nothing_further() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("radar");
+        write_data_file(
+            &source,
+            &[(BASE_TIMESTAMP, b"p0"), (BASE_TIMESTAMP + 10, b"p1")],
+        );
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let output = dir.path().join("out");
+        let summary = DatasetExtractor::new(
+            vec![("radar".to_string(), source)],
+            ExtractFilter::default(),
+        )
+        .unwrap()
+        .extract_into(&output, &cancel, |_, _, _, _| {})
+        .unwrap();
+
+        assert!(summary.cancelled);
+        assert_eq!(summary.packets, 0);
+        assert!(summary.files.is_empty());
+        assert!(read_provenance(&output).unwrap().cancelled);
+    }
+}
@@ -0,0 +1,416 @@
+//! PCAP数据文件格式
+//!
+//! 按 `docs/文件协议.md` 读写文件头和数据包头，所有字段均为小端字节序
+
+use std::fs;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::types::{PlaybackError, Result};
+
+/// 文件头魔数
+pub const PCAP_MAGIC: u32 = 0xD4C3_B2A1;
+/// 主版本号
+pub const MAJOR_VERSION: u16 = 2;
+/// 次版本号
+pub const MINOR_VERSION: u16 = 4;
+/// 文件头长度
+pub const FILE_HEADER_SIZE: u64 = 16;
+/// 数据包头长度
+pub const PACKET_HEADER_SIZE: u64 = 16;
+/// 单个数据包最大长度（10MB）
+pub const MAX_PACKET_SIZE: u32 = 10 * 1024 * 1024;
+
+/// 跳过数据包时不超过该长度的内容直接读取丢弃，更长的内容通过定位跳过
+const SKIP_READ_LIMIT: u64 = 4096;
+
+/// 文件头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    pub magic: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub timezone_offset: i32,
+    pub timestamp_accuracy: u32,
+}
+
+impl Default for FileHeader {
+    fn default() -> Self {
+        Self {
+            magic: PCAP_MAGIC,
+            major_version: MAJOR_VERSION,
+            minor_version: MINOR_VERSION,
+            timezone_offset: 0,
+            timestamp_accuracy: 0,
+        }
+    }
+}
+
+impl FileHeader {
+    /// 读取文件头（不校验）
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut buf = [0u8; FILE_HEADER_SIZE as usize];
+        reader.read_exact(&mut buf).map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => {
+                PlaybackError::FormatError("文件长度不足，缺少文件头".to_string())
+            }
+            _ => PlaybackError::FileError(e),
+        })?;
+
+        Ok(Self {
+            magic: u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            major_version: u16::from_le_bytes([buf[4], buf[5]]),
+            minor_version: u16::from_le_bytes([buf[6], buf[7]]),
+            timezone_offset: i32::from_le_bytes([buf[8], buf[9], buf[10], buf[11]]),
+            timestamp_accuracy: u32::from_le_bytes([buf[12], buf[13], buf[14], buf[15]]),
+        })
+    }
+
+    /// 校验魔数和版本号
+    pub fn validate(&self) -> Result<()> {
+        if self.magic != PCAP_MAGIC {
+            return Err(PlaybackError::FormatError(format!(
+                "文件魔数无效: 0x{:08X}（应为 0x{:08X}）",
+                self.magic, PCAP_MAGIC
+            )));
+        }
+
+        if self.major_version != MAJOR_VERSION || self.minor_version != MINOR_VERSION {
+            return Err(PlaybackError::FormatError(format!(
+                "不支持的文件版本: {}.{}（应为 {}.{}）",
+                self.major_version, self.minor_version, MAJOR_VERSION, MINOR_VERSION
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 写入文件头
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.magic.to_le_bytes())?;
+        writer.write_all(&self.major_version.to_le_bytes())?;
+        writer.write_all(&self.minor_version.to_le_bytes())?;
+        writer.write_all(&self.timezone_offset.to_le_bytes())?;
+        writer.write_all(&self.timestamp_accuracy.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+/// 数据包头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    pub timestamp_sec: u32,
+    pub timestamp_nsec: u32,
+    pub packet_length: u32,
+    pub checksum: u32,
+}
+
+impl PacketHeader {
+    /// 根据数据内容创建包头
+    pub fn new(timestamp_ns: u64, data: &[u8]) -> Self {
+        Self {
+            timestamp_sec: (timestamp_ns / 1_000_000_000) as u32,
+            timestamp_nsec: (timestamp_ns % 1_000_000_000) as u32,
+            packet_length: data.len() as u32,
+            checksum: crc32fast::hash(data),
+        }
+    }
+
+    /// 读取包头，到达文件末尾时返回 `None`
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        let mut buf = [0u8; PACKET_HEADER_SIZE as usize];
+        let mut filled = 0;
+        while filled < buf.len() {
+            match reader.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        match filled {
+            0 => Ok(None),
+            n if n < buf.len() => Err(PlaybackError::FormatError(format!(
+                "数据包头不完整: 仅 {} 字节",
+                n
+            ))),
+            _ => Ok(Some(Self {
+                timestamp_sec: u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
+                timestamp_nsec: u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]),
+                packet_length: u32::from_le_bytes([buf[8], buf[9], buf[10], buf[11]]),
+                checksum: u32::from_le_bytes([buf[12], buf[13], buf[14], buf[15]]),
+            })),
+        }
+    }
+
+    /// 写入包头
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.timestamp_sec.to_le_bytes())?;
+        writer.write_all(&self.timestamp_nsec.to_le_bytes())?;
+        writer.write_all(&self.packet_length.to_le_bytes())?;
+        writer.write_all(&self.checksum.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// 时间戳（纳秒）
+    pub fn timestamp_ns(&self) -> u64 {
+        self.timestamp_sec as u64 * 1_000_000_000 + self.timestamp_nsec as u64
+    }
+
+    /// 校验包头字段
+    pub fn validate(&self) -> Result<()> {
+        if self.timestamp_nsec >= 1_000_000_000 {
+            return Err(PlaybackError::FormatError(format!(
+                "纳秒字段超出范围: {}",
+                self.timestamp_nsec
+            )));
+        }
+
+        if self.packet_length > MAX_PACKET_SIZE {
+            return Err(PlaybackError::FormatError(format!(
+                "数据包长度超出限制: {} 字节",
+                self.packet_length
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// 原始数据包
+#[derive(Debug, Clone)]
+pub struct RawPacket {
+    pub header: PacketHeader,
+    /// 包头在文件中的偏移
+    pub offset: u64,
+    pub data: Vec<u8>,
+}
+
+impl RawPacket {
+    /// 校验和是否匹配
+    pub fn checksum_matches(&self) -> bool {
+        crc32fast::hash(&self.data) == self.header.checksum
+    }
+}
+
+/// 数据包顺序读取器
+#[derive(Debug)]
+pub struct PacketReader<R> {
+    reader: R,
+    header: FileHeader,
+    offset: u64,
+}
+
+impl PacketReader<BufReader<File>> {
+    /// 打开并校验数据文件
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> PacketReader<R> {
+    /// 读取并校验文件头
+    pub fn new(mut reader: R) -> Result<Self> {
+        let header = FileHeader::read_from(&mut reader)?;
+        header.validate()?;
+
+        Ok(Self {
+            reader,
+            header,
+            offset: FILE_HEADER_SIZE,
+        })
+    }
+
+    /// 文件头
+    pub fn file_header(&self) -> &FileHeader {
+        &self.header
+    }
+
+    /// 当前读取位置
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// 读取下一个数据包
+    pub fn next_packet(&mut self) -> Result<Option<RawPacket>> {
+        let offset = self.offset;
+        let header = match self.read_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        self.read_data(header, offset).map(Some)
+    }
+
+    /// 读取 `end` 之前的下一个完整数据包
+    ///
+    /// 用于读取仍在写入的文件：剩余内容不足一个完整数据包时返回 `None`，读取位置不变
+    pub fn next_complete_packet(&mut self, end: u64) -> Result<Option<RawPacket>> {
+        let offset = self.offset;
+        if offset + PACKET_HEADER_SIZE > end {
+            return Ok(None);
+        }
+        let header = match self.read_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        if self.offset + header.packet_length as u64 > end {
+            self.seek_to(offset)?;
+            return Ok(None);
+        }
+
+        self.read_data(header, offset).map(Some)
+    }
+
+    /// 读取下一个包头并跳过数据内容，返回包头及其偏移
+    pub fn skip_packet(&mut self) -> Result<Option<(PacketHeader, u64)>> {
+        let offset = self.offset;
+        let header = match self.read_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        // 小数据包直接从缓冲区丢弃，避免每次定位都清空读缓冲
+        let length = header.packet_length as u64;
+        if length <= SKIP_READ_LIMIT {
+            let skipped = io::copy(&mut (&mut self.reader).take(length), &mut io::sink())?;
+            if skipped < length {
+                return Err(PlaybackError::FormatError(format!(
+                    "偏移 {} 处的数据包内容不完整",
+                    offset
+                )));
+            }
+        } else {
+            self.reader.seek(SeekFrom::Current(length as i64))?;
+        }
+        self.offset += length;
+        Ok(Some((header, offset)))
+    }
+
+    /// 定位到指定偏移（须为包头起始位置）
+    pub fn seek_to(&mut self, offset: u64) -> Result<()> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.offset = offset;
+        Ok(())
+    }
+
+    fn read_data(&mut self, header: PacketHeader, offset: u64) -> Result<RawPacket> {
+        let mut data = vec![0u8; header.packet_length as usize];
+        self.reader
+            .read_exact(&mut data)
+            .map_err(|e| match e.kind() {
+                ErrorKind::UnexpectedEof => {
+                    PlaybackError::FormatError(format!("偏移 {} 处的数据包内容不完整", offset))
+                }
+                _ => PlaybackError::FileError(e),
+            })?;
+        self.offset += header.packet_length as u64;
+
+        Ok(RawPacket {
+            header,
+            offset,
+            data,
+        })
+    }
+
+    fn read_header(&mut self) -> Result<Option<PacketHeader>> {
+        let header = match PacketHeader::read_from(&mut self.reader)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        header.validate().map_err(|e| {
+            PlaybackError::FormatError(format!("偏移 {} 处的数据包头无效: {}", self.offset, e))
+        })?;
+        self.offset += PACKET_HEADER_SIZE;
+        Ok(Some(header))
+    }
+}
+
+/// 数据文件写入器
+///
+/// 先写入 `.part` 临时文件，`finish` 时再改为目标文件名，中断时不会留下不完整的数据文件
+#[derive(Debug)]
+pub struct DataFileWriter {
+    writer: BufWriter<File>,
+    temp_path: PathBuf,
+    path: PathBuf,
+    packet_count: u64,
+}
+
+impl DataFileWriter {
+    /// 创建数据文件并写入文件头，目标文件已存在时失败
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::create_with_header(path, &FileHeader::default())
+    }
+
+    /// 创建数据文件并写入指定的文件头，目标文件已存在时失败
+    pub fn create_with_header<P: AsRef<Path>>(path: P, header: &FileHeader) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            return Err(PlaybackError::FileError(std::io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("目标文件已存在: {:?}", path),
+            )));
+        }
+
+        let temp_path = path.with_extension("pcap.part");
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        header.write_to(&mut writer)?;
+
+        Ok(Self {
+            writer,
+            temp_path,
+            path,
+            packet_count: 0,
+        })
+    }
+
+    /// 写入数据包
+    pub fn write_packet(&mut self, timestamp_ns: u64, data: &[u8]) -> Result<()> {
+        if data.len() > MAX_PACKET_SIZE as usize {
+            return Err(PlaybackError::FormatError(format!(
+                "数据包长度超出限制: {} 字节",
+                data.len()
+            )));
+        }
+
+        PacketHeader::new(timestamp_ns, data).write_to(&mut self.writer)?;
+        self.writer.write_all(data)?;
+        self.packet_count += 1;
+        Ok(())
+    }
+
+    /// 已写入的数据包数量
+    pub fn packet_count(&self) -> u64 {
+        self.packet_count
+    }
+
+    /// 完成写入，返回数据文件路径
+    pub fn finish(self) -> Result<PathBuf> {
+        let file = self
+            .writer
+            .into_inner()
+            .map_err(|e| PlaybackError::FileError(e.into_error()))?;
+        file.sync_all()?;
+        fs::rename(&self.temp_path, &self.path)?;
+        Ok(self.path)
+    }
+
+    /// 放弃写入并删除临时文件
+    pub fn discard(self) {
+        let temp_path = self.temp_path.clone();
+        drop(self.writer);
+        let _ = fs::remove_file(temp_path);
+    }
+}
+
+/// 读取并校验数据文件头
+pub fn read_file_header<P: AsRef<Path>>(path: P) -> Result<FileHeader> {
+    let mut file = File::open(path)?;
+    let header = FileHeader::read_from(&mut file)?;
+    header.validate()?;
+    Ok(header)
+}
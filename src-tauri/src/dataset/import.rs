@@ -0,0 +1,479 @@
+//! 外部PCAP文件导入
+//!
+//! 导入前校验文件头和数据包结构，按首个数据包时间戳重命名后复制或移动到数据集目录，
+//! 完成后重新生成数据集索引。取消时保留已导入的文件，当前文件的临时文件会被删除。
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::dataset::format::PacketReader;
+use crate::dataset::index::DatasetIndex;
+use crate::dataset::naming::unique_data_file_name;
+use crate::state::task_state::CancelToken;
+use crate::types::{PlaybackError, Result};
+
+/// 复制时的缓冲区大小
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// 导入方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    #[default]
+    Copy,
+    Move,
+}
+
+/// 导入失败的文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportFailure {
+    pub path: String,
+    pub reason: String,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// 导入后的文件名
+    pub imported: Vec<String>,
+    pub failed: Vec<ImportFailure>,
+    pub cancelled: bool,
+    /// 重新生成的索引文件
+    pub index_path: Option<String>,
+}
+
+/// 导入进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStage {
+    Validating,
+    Copying,
+    Indexing,
+    Completed,
+}
+
+impl ImportStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportStage::Validating => "validating",
+            ImportStage::Copying => "copying",
+            ImportStage::Indexing => "indexing",
+            ImportStage::Completed => "completed",
+        }
+    }
+}
+
+/// 数据集导入器
+#[derive(Debug)]
+pub struct DatasetImporter {
+    dataset_dir: PathBuf,
+    mode: ImportMode,
+}
+
+impl DatasetImporter {
+    pub fn new<P: AsRef<Path>>(dataset_dir: P, mode: ImportMode) -> Result<Self> {
+        let dataset_dir = dataset_dir.as_ref().to_path_buf();
+        if !dataset_dir.is_dir() {
+            return Err(PlaybackError::DatasetNotFound(
+                dataset_dir.to_string_lossy().to_string(),
+            ));
+        }
+
+        Ok(Self { dataset_dir, mode })
+    }
+
+    /// 导入文件
+    ///
+    /// 单个文件失败不会中断整批导入，失败原因记录在结果中；
+    /// `progress` 参数依次为阶段、已完成文件数、文件总数和当前文件
+    pub fn import<F>(
+        &self,
+        files: &[PathBuf],
+        cancel: &CancelToken,
+        mut progress: F,
+    ) -> Result<ImportSummary>
+    where
+        F: FnMut(ImportStage, u64, u64, Option<&str>),
+    {
+        let total = files.len() as u64;
+        let mut summary = ImportSummary::default();
+
+        for (i, source) in files.iter().enumerate() {
+            if cancel.is_cancelled() {
+                summary.cancelled = true;
+                break;
+            }
+
+            let item = source.to_string_lossy().to_string();
+            progress(ImportStage::Validating, i as u64, total, Some(&item));
+
+            let result = self.validate(source).and_then(|timestamp| {
+                progress(ImportStage::Copying, i as u64, total, Some(&item));
+                // 首个数据包落在已有文件的同一个100纳秒内时推后文件名，不覆盖已有文件
+                let file_name = unique_data_file_name(&self.dataset_dir, timestamp);
+                self.transfer(source, &file_name, cancel)
+            });
+
+            match result {
+                Ok(Some(name)) => {
+                    info!("导入数据文件: {:?} -> {}", source, name);
+                    summary.imported.push(name);
+                }
+                Ok(None) => {
+                    summary.cancelled = true;
+                    break;
+                }
+                Err(e) => {
+                    warn!("导入数据文件失败 {:?}: {}", source, e);
+                    summary.failed.push(ImportFailure {
+                        path: item,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        // 取消时也重建索引，保证已导入的文件被收录
+        if !summary.imported.is_empty() {
+            progress(
+                ImportStage::Indexing,
+                summary.imported.len() as u64,
+                total,
+                None,
+            );
+            let index_path = DatasetIndex::rebuild(&self.dataset_dir)?;
+            summary.index_path = Some(index_path.to_string_lossy().to_string());
+        }
+
+        progress(
+            ImportStage::Completed,
+            summary.imported.len() as u64,
+            total,
+            None,
+        );
+        info!(
+            "导入完成: 成功 {} 个，失败 {} 个{}",
+            summary.imported.len(),
+            summary.failed.len(),
+            if summary.cancelled {
+                "（已取消）"
+            } else {
+                ""
+            }
+        );
+        Ok(summary)
+    }
+
+    /// 校验文件结构，返回首个数据包的时间戳
+    fn validate(&self, source: &Path) -> Result<u64> {
+        if !source.is_file() {
+            return Err(PlaybackError::FormatError(format!(
+                "不是有效的文件: {:?}",
+                source
+            )));
+        }
+
+        let mut reader = PacketReader::open(source)?;
+        let first = reader
+            .skip_packet()?
+            .map(|(header, _)| header.timestamp_ns())
+            .ok_or_else(|| PlaybackError::FormatError("文件中没有数据包".to_string()))?;
+
+        // 遍历全部包头，避免结构损坏的文件进入数据集
+        while reader.skip_packet()?.is_some() {}
+
+        Ok(first)
+    }
+
+    /// 复制或移动文件到数据集目录中未被占用的 `file_name`，取消时返回 `None`
+    fn transfer(
+        &self,
+        source: &Path,
+        file_name: &str,
+        cancel: &CancelToken,
+    ) -> Result<Option<String>> {
+        let target = self.dataset_dir.join(file_name);
+        if self.mode == ImportMode::Move && fs::rename(source, &target).is_ok() {
+            return Ok(Some(file_name.to_string()));
+        }
+
+        // 复制，或跨文件系统移动时先复制再删除源文件
+        if !copy_with_cancel(source, &target, cancel)? {
+            return Ok(None);
+        }
+        if self.mode == ImportMode::Move {
+            fs::remove_file(source)?;
+        }
+
+        Ok(Some(file_name.to_string()))
+    }
+}
+
+/// 经临时文件分块复制，取消时删除临时文件并返回 `false`
+fn copy_with_cancel(source: &Path, target: &Path, cancel: &CancelToken) -> Result<bool> {
+    let temp_path = target.with_extension("pcap.part");
+    let result = copy_chunks(source, &temp_path, cancel);
+
+    match result {
+        Ok(true) => {
+            fs::rename(&temp_path, target)?;
+            Ok(true)
+        }
+        Ok(false) => {
+            let _ = fs::remove_file(&temp_path);
+            Ok(false)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+fn copy_chunks(source: &Path, target: &Path, cancel: &CancelToken) -> Result<bool> {
+    let mut input = File::open(source)?;
+    let mut output = File::create(target)?;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+
+    loop {
+        if cancel.is_cancelled() {
+            return Ok(false);
+        }
+
+        let n = input.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        output.write_all(&buffer[..n])?;
+    }
+
+    output.sync_all()?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::{DataFileWriter, FileHeader, MAJOR_VERSION};
+    use crate::dataset::index::{data_files, PIDX_FILE_NAME};
+    use crate::dataset::naming::data_file_name;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    /// 写入外部PCAP文件，文件名与命名规则无关
+    fn write_source(path: &Path, packets: &[(u64, &[u8])]) {
+        let mut writer = DataFileWriter::create(path).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn import(dataset: &Path, mode: ImportMode, files: &[PathBuf]) -> ImportSummary {
+        DatasetImporter::new(dataset, mode)
+            .unwrap()
+            .import(files, &CancelToken::new(), |_, _, _, _| {})
+            .unwrap()
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        data_files(dir)
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    /// 导入目录和数据集目录
+    fn dirs() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        let dataset = dir.path().join("dataset");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&dataset).unwrap();
+        (dir, source, dataset)
+    }
+
+    #[test]
+    fn rejects_missing_dataset_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            DatasetImporter::new(dir.path().join("missing"), ImportMode::Copy),
+            Err(PlaybackError::DatasetNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn copies_and_renames_by_first_packet() {
+        let (_dir, source, dataset) = dirs();
+        let file = source.join("capture.pcap");
+        write_source(
+            &file,
+            &[(BASE_TIMESTAMP, b"a"), (BASE_TIMESTAMP + 1_000, b"b")],
+        );
+
+        let mut stages = Vec::new();
+        let summary = DatasetImporter::new(&dataset, ImportMode::Copy)
+            .unwrap()
+            .import(
+                std::slice::from_ref(&file),
+                &CancelToken::new(),
+                |stage, _, _, _| stages.push(stage),
+            )
+            .unwrap();
+
+        let name = data_file_name(BASE_TIMESTAMP);
+        assert_eq!(summary.imported, vec![name.clone()]);
+        assert!(summary.failed.is_empty());
+        assert_eq!(file_names(&dataset), vec![name.clone()]);
+        assert_eq!(
+            fs::read(&file).unwrap(),
+            fs::read(dataset.join(&name)).unwrap()
+        );
+        assert_eq!(stages.last(), Some(&ImportStage::Completed));
+
+        let index_path = dataset.join(PIDX_FILE_NAME);
+        assert_eq!(
+            summary.index_path.as_deref(),
+            Some(index_path.to_string_lossy().as_ref())
+        );
+        let index = DatasetIndex::read(&index_path).unwrap();
+        assert_eq!(index.total_packets, 2);
+        assert_eq!(index.files[0].name, name);
+    }
+
+    #[test]
+    fn move_removes_source() {
+        let (_dir, source, dataset) = dirs();
+        let file = source.join("capture.pcap");
+        write_source(&file, &[(BASE_TIMESTAMP, b"a")]);
+        let content = fs::read(&file).unwrap();
+
+        let summary = import(&dataset, ImportMode::Move, std::slice::from_ref(&file));
+
+        assert_eq!(summary.imported.len(), 1);
+        assert!(!file.exists());
+        assert_eq!(
+            fs::read(dataset.join(&summary.imported[0])).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_files_and_continues() {
+        let (_dir, source, dataset) = dirs();
+        let valid = source.join("valid.pcap");
+        write_source(&valid, &[(BASE_TIMESTAMP, b"a")]);
+
+        let bad_magic = source.join("bad_magic.pcap");
+        fs::write(&bad_magic, [0u8; 64]).unwrap();
+
+        let bad_version = source.join("bad_version.pcap");
+        let header = FileHeader {
+            major_version: MAJOR_VERSION + 1,
+            ..Default::default()
+        };
+        DataFileWriter::create_with_header(&bad_version, &header)
+            .unwrap()
+            .finish()
+            .unwrap();
+
+        let short_header = source.join("short_header.pcap");
+        fs::write(&short_header, [0xD4, 0xC3, 0xB2]).unwrap();
+
+        let no_packets = source.join("no_packets.pcap");
+        DataFileWriter::create(&no_packets)
+            .unwrap()
+            .finish()
+            .unwrap();
+
+        // 最后一个数据包的内容不完整
+        let truncated = source.join("truncated.pcap");
+        write_source(
+            &truncated,
+            &[
+                (BASE_TIMESTAMP + 1_000_000, b"a"),
+                (BASE_TIMESTAMP + 2_000_000, b"bbbb"),
+            ],
+        );
+        let len = fs::metadata(&truncated).unwrap().len();
+        File::options()
+            .write(true)
+            .open(&truncated)
+            .unwrap()
+            .set_len(len - 2)
+            .unwrap();
+
+        let files = vec![
+            bad_magic,
+            bad_version,
+            short_header,
+            no_packets,
+            truncated,
+            source.join("missing.pcap"),
+            source.clone(),
+            valid,
+        ];
+        let summary = import(&dataset, ImportMode::Move, &files);
+
+        assert_eq!(summary.imported, vec![data_file_name(BASE_TIMESTAMP)]);
+        let failed: Vec<&str> = summary.failed.iter().map(|f| f.path.as_str()).collect();
+        let expected: Vec<String> = files[..7]
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(failed, expected);
+        // 校验失败的文件不移动
+        assert!(files[..5].iter().all(|p| p.exists()));
+        assert_eq!(file_names(&dataset), summary.imported);
+    }
+
+    #[test]
+    fn colliding_names_are_shifted() {
+        let (_dir, source, dataset) = dirs();
+        let existing = dataset.join(data_file_name(BASE_TIMESTAMP));
+        write_source(&existing, &[(BASE_TIMESTAMP, b"old")]);
+        let existing_content = fs::read(&existing).unwrap();
+
+        // 两个文件的首个数据包与已有文件落在同一个100纳秒内
+        let first = source.join("first.pcap");
+        let second = source.join("second.pcap");
+        write_source(&first, &[(BASE_TIMESTAMP + 10, b"a")]);
+        write_source(&second, &[(BASE_TIMESTAMP + 20, b"b")]);
+
+        let summary = import(&dataset, ImportMode::Copy, &[first, second]);
+
+        assert_eq!(
+            summary.imported,
+            vec![
+                data_file_name(BASE_TIMESTAMP + 100),
+                data_file_name(BASE_TIMESTAMP + 200)
+            ]
+        );
+        assert!(summary.failed.is_empty());
+        assert_eq!(fs::read(&existing).unwrap(), existing_content);
+        assert_eq!(file_names(&dataset).len(), 3);
+    }
+
+    #[test]
+    fn cancelled_import_keeps_nothing() {
+        let (_dir, source, dataset) = dirs();
+        let file = source.join("capture.pcap");
+        write_source(&file, &[(BASE_TIMESTAMP, b"a")]);
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let summary = DatasetImporter::new(&dataset, ImportMode::Move)
+            .unwrap()
+            .import(std::slice::from_ref(&file), &cancel, |_, _, _, _| {})
+            .unwrap();
+
+        assert!(summary.cancelled);
+        assert!(summary.imported.is_empty());
+        assert!(summary.index_path.is_none());
+        assert!(file.exists());
+        assert_eq!(fs::read_dir(&dataset).unwrap().count(), 0);
+    }
+}
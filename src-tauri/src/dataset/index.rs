@@ -0,0 +1,375 @@
+//! PIDX索引文件
+//!
+//! 索引文件位于数据集目录下，记录每个数据文件的哈希、包数量、时间范围
+//! 以及每个数据包的时间戳和偏移，用于快速统计和定位：
+//!
+//! ```xml
+//! <index>
+//!   <description>数据集: test_dataset</description>
+//!   <created_time>2025-07-31T07:56:20.582683700+00:00</created_time>
+//!   <start_timestamp>1753948580493641300</start_timestamp>
+//!   <end_timestamp>1753948580582562700</end_timestamp>
+//!   <total_packets>2500</total_packets>
+//!   <total_duration>88921400</total_duration>
+//!   <data_files>
+//!     <file name="..." hash="..." size="..." packet_count="..." start_timestamp="..." end_timestamp="...">
+//!       <packet timestamp_ns="..." byte_offset="16" packet_size="256" />
+//!     </file>
+//!   </data_files>
+//! </index>
+//! ```
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::dataset::format::PacketReader;
+use crate::dataset::naming::DATA_FILE_EXTENSION;
+use crate::project::xml::XmlWriter;
+use crate::types::{PlaybackError, Result};
+
+/// 索引文件名
+pub const PIDX_FILE_NAME: &str = ".pidx";
+
+/// 数据包索引
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PacketIndex {
+    pub timestamp_ns: u64,
+    /// 包头在文件中的偏移
+    pub byte_offset: u64,
+    /// 数据内容长度
+    pub packet_size: u32,
+}
+
+/// 数据文件索引
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileIndex {
+    pub name: String,
+    /// 文件内容的SHA-256（十六进制）
+    pub hash: String,
+    pub size: u64,
+    pub packet_count: u64,
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+    pub packets: Vec<PacketIndex>,
+}
+
+impl FileIndex {
+    /// 扫描数据文件生成索引
+    pub fn build<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| PlaybackError::FormatError(format!("无效的文件路径: {:?}", path)))?;
+
+        let mut reader = PacketReader::open(path)?;
+        let mut packets = Vec::new();
+        while let Some((header, offset)) = reader.skip_packet()? {
+            packets.push(PacketIndex {
+                timestamp_ns: header.timestamp_ns(),
+                byte_offset: offset,
+                packet_size: header.packet_length,
+            });
+        }
+
+        let start_timestamp = packets.iter().map(|p| p.timestamp_ns).min().unwrap_or(0);
+        let end_timestamp = packets.iter().map(|p| p.timestamp_ns).max().unwrap_or(0);
+
+        Ok(Self {
+            name,
+            hash: file_hash(path)?,
+            size: fs::metadata(path)?.len(),
+            packet_count: packets.len() as u64,
+            start_timestamp,
+            end_timestamp,
+            packets,
+        })
+    }
+}
+
+/// 数据集索引
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatasetIndex {
+    pub description: String,
+    pub created_time: String,
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+    pub total_packets: u64,
+    pub total_duration: u64,
+    pub files: Vec<FileIndex>,
+}
+
+impl DatasetIndex {
+    /// 由文件索引汇总数据集索引
+    pub fn from_files(description: impl Into<String>, files: Vec<FileIndex>) -> Self {
+        let non_empty = || files.iter().filter(|f| f.packet_count > 0);
+        let start_timestamp = non_empty().map(|f| f.start_timestamp).min().unwrap_or(0);
+        let end_timestamp = non_empty().map(|f| f.end_timestamp).max().unwrap_or(0);
+
+        Self {
+            description: description.into(),
+            created_time: chrono::Utc::now().to_rfc3339(),
+            start_timestamp,
+            end_timestamp,
+            total_packets: files.iter().map(|f| f.packet_count).sum(),
+            total_duration: end_timestamp.saturating_sub(start_timestamp),
+            files,
+        }
+    }
+
+    /// 扫描数据集目录下的全部数据文件生成索引
+    pub fn build<P: AsRef<Path>>(dataset_dir: P) -> Result<Self> {
+        let dataset_dir = dataset_dir.as_ref();
+        let files = data_files(dataset_dir)?
+            .iter()
+            .map(FileIndex::build)
+            .collect::<Result<Vec<_>>>()?;
+
+        let name = dataset_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Ok(Self::from_files(format!("数据集: {}", name), files))
+    }
+
+    /// 重新生成并写入数据集目录下的索引文件
+    pub fn rebuild<P: AsRef<Path>>(dataset_dir: P) -> Result<PathBuf> {
+        let dataset_dir = dataset_dir.as_ref();
+        let index = Self::build(dataset_dir)?;
+        let path = dataset_dir.join(PIDX_FILE_NAME);
+        index.write(&path)?;
+
+        info!(
+            "生成索引文件: {:?}，共 {} 个文件 {} 个数据包",
+            path,
+            index.files.len(),
+            index.total_packets
+        );
+        Ok(path)
+    }
+
+    /// 增量更新数据集目录下已有的索引文件
+    ///
+    /// 名称和大小未变的文件沿用原有条目，只重新扫描新增或变化的文件并移除已删除文件的条目；
+    /// 没有索引文件或内容无变化时不写入，返回是否已更新
+    pub fn update<P: AsRef<Path>>(dataset_dir: P) -> Result<bool> {
+        let dataset_dir = dataset_dir.as_ref();
+        let path = dataset_dir.join(PIDX_FILE_NAME);
+        if !path.is_file() {
+            return Ok(false);
+        }
+
+        let index = Self::read(&path)?;
+        let mut changed = false;
+        let files = data_files(dataset_dir)?
+            .iter()
+            .map(|file| {
+                let name = file.file_name().map(|n| n.to_string_lossy().to_string());
+                let size = fs::metadata(file)?.len();
+                let existing = index
+                    .files
+                    .iter()
+                    .find(|f| Some(&f.name) == name.as_ref() && f.size == size);
+                match existing {
+                    Some(entry) => Ok(entry.clone()),
+                    None => {
+                        changed = true;
+                        FileIndex::build(file)
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if !changed && files.len() == index.files.len() {
+            return Ok(false);
+        }
+
+        let updated = Self::from_files(index.description, files);
+        updated.write(&path)?;
+        info!(
+            "更新索引文件: {:?}，共 {} 个文件 {} 个数据包",
+            path,
+            updated.files.len(),
+            updated.total_packets
+        );
+        Ok(true)
+    }
+
+    /// 读取索引文件
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// 解析索引文件内容
+    pub fn parse(content: &str) -> Result<Self> {
+        let document: IndexDocument = serde_xml_rs::from_str(content)
+            .map_err(|e| PlaybackError::XmlError(format!("解析索引文件失败: {}", e)))?;
+
+        let files = document
+            .data_files
+            .items
+            .into_iter()
+            .map(|file| FileIndex {
+                name: file.name,
+                hash: file.hash,
+                size: file.size,
+                packet_count: file.packet_count,
+                start_timestamp: file.start_timestamp,
+                end_timestamp: file.end_timestamp,
+                packets: file
+                    .packets
+                    .into_iter()
+                    .map(|p| PacketIndex {
+                        timestamp_ns: p.timestamp_ns,
+                        byte_offset: p.byte_offset,
+                        packet_size: p.packet_size,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            description: document.description.unwrap_or_default(),
+            created_time: document.created_time.unwrap_or_default(),
+            start_timestamp: document.start_timestamp,
+            end_timestamp: document.end_timestamp,
+            total_packets: document.total_packets,
+            total_duration: document.total_duration,
+            files,
+        })
+    }
+
+    /// 写入索引文件
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let temp_path = path.with_extension("pidx.tmp");
+        fs::write(&temp_path, self.to_xml())?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// 生成索引文件内容
+    pub fn to_xml(&self) -> String {
+        let mut writer = XmlWriter::new();
+        writer
+            .open("index")
+            .element("description", &self.description)
+            .element("created_time", &self.created_time)
+            .element("start_timestamp", self.start_timestamp)
+            .element("end_timestamp", self.end_timestamp)
+            .element("total_packets", self.total_packets)
+            .element("total_duration", self.total_duration)
+            .open("data_files");
+
+        for file in &self.files {
+            writer.open_with_attributes(
+                "file",
+                &[
+                    ("name", file.name.clone()),
+                    ("hash", file.hash.clone()),
+                    ("size", file.size.to_string()),
+                    ("packet_count", file.packet_count.to_string()),
+                    ("start_timestamp", file.start_timestamp.to_string()),
+                    ("end_timestamp", file.end_timestamp.to_string()),
+                ],
+            );
+            for packet in &file.packets {
+                writer.empty_element(
+                    "packet",
+                    &[
+                        ("timestamp_ns", packet.timestamp_ns.to_string()),
+                        ("byte_offset", packet.byte_offset.to_string()),
+                        ("packet_size", packet.packet_size.to_string()),
+                    ],
+                );
+            }
+            writer.close("file");
+        }
+
+        writer.close("data_files").close("index");
+        writer.finish()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexDocument {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    created_time: Option<String>,
+    #[serde(default)]
+    start_timestamp: u64,
+    #[serde(default)]
+    end_timestamp: u64,
+    #[serde(default)]
+    total_packets: u64,
+    #[serde(default)]
+    total_duration: u64,
+    #[serde(default)]
+    data_files: DataFilesDocument,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DataFilesDocument {
+    #[serde(default, rename = "file")]
+    items: Vec<FileDocument>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileDocument {
+    name: String,
+    #[serde(default)]
+    hash: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    packet_count: u64,
+    #[serde(default)]
+    start_timestamp: u64,
+    #[serde(default)]
+    end_timestamp: u64,
+    #[serde(default, rename = "packet")]
+    packets: Vec<PacketDocument>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PacketDocument {
+    timestamp_ns: u64,
+    byte_offset: u64,
+    packet_size: u32,
+}
+
+/// 数据集目录下的数据文件（按文件名排序）
+pub fn data_files<P: AsRef<Path>>(dataset_dir: P) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dataset_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(|e| e.to_str()) == Some(DATA_FILE_EXTENSION)
+        })
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// 计算文件内容的SHA-256
+pub fn file_hash<P: AsRef<Path>>(path: P) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,464 @@
+//! 数据集与数据文件信息
+//!
+//! 数据包数量和时间范围优先取自 PIDX 索引；索引缺失或与文件不符时只读取16字节的包头并跳过数据内容，
+//! 多个文件并行扫描。只需要时间范围时，[`file_time_range`] 读取首个包头并从文件尾部向前查找最后一个
+//! 完整数据包，无需遍历整个文件。
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::warn;
+use std::fs::{self, File, Metadata};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::SystemTime;
+
+use crate::dataset::format::{
+    FileHeader, PacketHeader, PacketReader, FILE_HEADER_SIZE, MAX_PACKET_SIZE, PACKET_HEADER_SIZE,
+};
+use crate::dataset::index::{data_files, DatasetIndex, FileIndex, PIDX_FILE_NAME};
+use crate::dataset::stats::CachedFileInfos;
+use crate::types::{DatasetInfo, FileInfo, PlaybackError, Result};
+
+/// 尾部查找的初始窗口
+const TAIL_WINDOW: u64 = 64 * 1024;
+
+/// 扫描进度回调，参数依次为已完成文件数、文件总数和刚完成的文件；可能在多个线程中调用
+pub type ScanProgress<'a> = &'a (dyn Fn(u64, u64, &Path) + Sync);
+
+/// 包头扫描结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacketSummary {
+    pub packet_count: u64,
+    pub start_timestamp: Option<u64>,
+    pub end_timestamp: Option<u64>,
+}
+
+impl PacketSummary {
+    /// 计入一个数据包
+    pub fn record(&mut self, timestamp: u64) {
+        self.packet_count += 1;
+        self.start_timestamp = Some(self.start_timestamp.map_or(timestamp, |t| t.min(timestamp)));
+        self.end_timestamp = Some(self.end_timestamp.map_or(timestamp, |t| t.max(timestamp)));
+    }
+}
+
+/// 时间戳（纳秒）格式化为 ISO 8601（UTC，纳秒精度）
+pub fn iso_timestamp(timestamp_ns: u64) -> String {
+    let secs = (timestamp_ns / 1_000_000_000) as i64;
+    let nanos = (timestamp_ns % 1_000_000_000) as u32;
+    DateTime::<Utc>::from_timestamp(secs, nanos)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+/// 数据文件首、末数据包的时间戳，文件中没有数据包时返回 `None`
+pub fn file_time_range<P: AsRef<Path>>(path: P) -> Result<Option<(u64, u64)>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let file_len = file.get_ref().metadata()?.len();
+
+    let mut reader = PacketReader::new(&mut file)?;
+    let first = match reader.skip_packet()? {
+        Some((header, _)) => header.timestamp_ns(),
+        None => return Ok(None),
+    };
+
+    let last = match find_last_packet(&mut file, file_len)? {
+        Some(timestamp) => timestamp,
+        None => last_packet_by_scan(&mut file, file_len)?.unwrap_or(first),
+    };
+    Ok(Some((first, last)))
+}
+
+/// 扫描数据集目录，返回数据集信息及各数据文件信息
+pub fn scan_dataset<P: AsRef<Path>>(
+    name: &str,
+    dataset_dir: P,
+    progress: ScanProgress,
+) -> Result<(DatasetInfo, Vec<FileInfo>)> {
+    let dataset_dir = dataset_dir.as_ref();
+    if !dataset_dir.is_dir() {
+        return Err(PlaybackError::DatasetNotFound(name.to_string()));
+    }
+
+    let index = read_dataset_index(dataset_dir);
+    let cached = match (dataset_dir.parent(), dataset_dir.file_name()) {
+        (Some(project_path), Some(dir_name)) => {
+            CachedFileInfos::load(project_path, &dir_name.to_string_lossy())
+        }
+        _ => CachedFileInfos::default(),
+    };
+    let files = scan_files(&data_files(dataset_dir)?, index.as_ref(), &cached, progress);
+
+    let info = dataset_info(name, dataset_dir, &files, index.is_some())?;
+    Ok((info, files))
+}
+
+/// 读取数据集目录下的索引，索引不存在或无法解析时返回 `None`
+pub fn read_dataset_index(dataset_dir: &Path) -> Option<DatasetIndex> {
+    let index_path = dataset_dir.join(PIDX_FILE_NAME);
+    if !index_path.is_file() {
+        return None;
+    }
+
+    DatasetIndex::read(&index_path)
+        .map_err(|e| warn!("读取索引失败 {:?}: {}", index_path, e))
+        .ok()
+}
+
+/// 由数据文件信息汇总数据集信息
+pub fn dataset_info(
+    name: &str,
+    dataset_dir: &Path,
+    files: &[FileInfo],
+    has_index: bool,
+) -> Result<DatasetInfo> {
+    let metadata = fs::metadata(dataset_dir)?;
+    let mut info = DatasetInfo {
+        name: name.to_string(),
+        path: dataset_dir.to_string_lossy().to_string(),
+        file_count: files.len(),
+        total_packets: files.iter().map(|f| f.packet_count).sum(),
+        total_size: files.iter().map(|f| f.file_size).sum(),
+        start_timestamp: files.iter().filter_map(|f| f.start_timestamp).min(),
+        end_timestamp: files.iter().filter_map(|f| f.end_timestamp).max(),
+        created_time: created_time(&metadata),
+        modified_time: modified_time(&metadata),
+        has_index,
+        ..Default::default()
+    };
+    if let (Some(start), Some(end)) = (info.start_timestamp, info.end_timestamp) {
+        info.total_duration = end.saturating_sub(start);
+        info.start_time = iso_timestamp(start);
+        info.end_time = iso_timestamp(end);
+    }
+
+    Ok(info)
+}
+
+/// 并行扫描数据文件，结果与 `paths` 顺序一致；统计缓存中未变化的文件不再扫描
+pub fn scan_files(
+    paths: &[PathBuf],
+    index: Option<&DatasetIndex>,
+    cached: &CachedFileInfos,
+    progress: ScanProgress,
+) -> Vec<FileInfo> {
+    let total = paths.len() as u64;
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(paths.len());
+    let next = AtomicUsize::new(0);
+    let done = AtomicU64::new(0);
+
+    // 每个文件扫描完立即送出结果，扫描线程异常退出时只丢失正在扫描的文件
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                let sender = sender.clone();
+                let (next, done) = (&next, &done);
+                scope.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(i) else {
+                        break;
+                    };
+                    let info = cached.get(path).unwrap_or_else(|| scan_file(path, index));
+                    let _ = sender.send((i, info));
+                    progress(done.fetch_add(1, Ordering::Relaxed) + 1, total, path);
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            if handle.join().is_err() {
+                warn!("数据文件扫描线程异常退出");
+            }
+        }
+    });
+    drop(sender);
+
+    let mut results: Vec<Option<FileInfo>> = vec![None; paths.len()];
+    for (i, info) in receiver {
+        results[i] = Some(info);
+    }
+
+    // 没有得到结果的文件按无效文件报告，不从列表中丢弃
+    results
+        .into_iter()
+        .zip(paths)
+        .map(|(info, path)| {
+            info.unwrap_or_else(|| {
+                warn!("数据文件扫描失败: {:?}", path);
+                let mut info = file_metadata_info(path);
+                info.is_valid = false;
+                info
+            })
+        })
+        .collect()
+}
+
+/// 读取数据文件信息，索引中有大小一致的条目时直接使用索引记录，否则扫描包头
+pub fn scan_file(path: &Path, index: Option<&DatasetIndex>) -> FileInfo {
+    let mut info = file_metadata_info(path);
+
+    let entry = index.and_then(|index| file_index_entry(index, &info.file_name, info.file_size));
+    if let Some(entry) = entry {
+        info.packet_count = entry.packet_count;
+        info.file_hash = Some(entry.hash.clone());
+        if entry.packet_count > 0 {
+            info.start_timestamp = Some(entry.start_timestamp);
+            info.end_timestamp = Some(entry.end_timestamp);
+        }
+        info.is_valid = true;
+        return info;
+    }
+
+    match summarize_packets(path) {
+        Ok((summary, error)) => {
+            if let Some(e) = &error {
+                warn!(
+                    "数据文件 {:?} 在第 {} 个数据包后损坏: {}",
+                    path, summary.packet_count, e
+                );
+            }
+            info.packet_count = summary.packet_count;
+            info.start_timestamp = summary.start_timestamp;
+            info.end_timestamp = summary.end_timestamp;
+            info.is_valid = error.is_none();
+        }
+        Err(e) => warn!("读取数据文件失败 {:?}: {}", path, e),
+    }
+    info
+}
+
+/// 数据文件的名称、路径、大小和时间，数据包相关字段为默认值
+pub fn file_metadata_info(path: &Path) -> FileInfo {
+    let metadata = fs::metadata(path).ok();
+    FileInfo {
+        file_name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        file_path: path.to_string_lossy().to_string(),
+        file_size: metadata.as_ref().map_or(0, Metadata::len),
+        created_time: metadata.as_ref().map(created_time).unwrap_or_default(),
+        modified_time: metadata.as_ref().map(modified_time).unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+/// 只读取包头统计数据包数量和时间范围
+///
+/// 文件头无效时返回错误；中途遇到损坏或不完整的数据包时停止，返回已统计的部分及该错误
+pub fn summarize_packets<P: AsRef<Path>>(
+    path: P,
+) -> Result<(PacketSummary, Option<PlaybackError>)> {
+    let path = path.as_ref();
+    let file_len = fs::metadata(path)?.len();
+    let mut reader = PacketReader::open(path)?;
+    let mut summary = PacketSummary::default();
+
+    loop {
+        let (header, offset) = match reader.skip_packet() {
+            Ok(Some(packet)) => packet,
+            Ok(None) => return Ok((summary, None)),
+            Err(e) => return Ok((summary, Some(e))),
+        };
+        if offset + PACKET_HEADER_SIZE + header.packet_length as u64 > file_len {
+            let error = PlaybackError::FormatError(format!("偏移 {} 处的数据包内容不完整", offset));
+            return Ok((summary, Some(error)));
+        }
+
+        summary.record(header.timestamp_ns());
+    }
+}
+
+/// 从 `offset` 处继续统计包头，结果累加到 `summary`，返回最后一个完整数据包之后的偏移
+///
+/// 用于仍在写入的文件：`offset` 为0时先校验文件头（文件头尚未写完时返回0）；
+/// 末尾不完整的数据包不计入也不报错，下次从返回的偏移继续；包头无效时返回错误
+pub fn summarize_from<P: AsRef<Path>>(
+    path: P,
+    offset: u64,
+    summary: &mut PacketSummary,
+) -> Result<u64> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let file_len = reader.get_ref().metadata()?.len();
+
+    let mut offset = offset;
+    if offset < FILE_HEADER_SIZE {
+        if file_len < FILE_HEADER_SIZE {
+            return Ok(0);
+        }
+        FileHeader::read_from(&mut reader)?.validate()?;
+        offset = FILE_HEADER_SIZE;
+    } else {
+        reader.seek(SeekFrom::Start(offset))?;
+    }
+
+    while offset + PACKET_HEADER_SIZE <= file_len {
+        let Some(header) = PacketHeader::read_from(&mut reader)? else {
+            break;
+        };
+        header.validate().map_err(|e| {
+            PlaybackError::FormatError(format!("偏移 {} 处的数据包头无效: {}", offset, e))
+        })?;
+
+        let end = offset + PACKET_HEADER_SIZE + header.packet_length as u64;
+        if end > file_len {
+            break;
+        }
+        reader.seek_relative(header.packet_length as i64)?;
+        summary.record(header.timestamp_ns());
+        offset = end;
+    }
+    Ok(offset)
+}
+
+/// 索引中与文件名和文件大小一致的条目
+pub fn file_index_entry<'a>(
+    index: &'a DatasetIndex,
+    file_name: &str,
+    file_size: u64,
+) -> Option<&'a FileIndex> {
+    index
+        .files
+        .iter()
+        .find(|f| f.name == file_name && f.size == file_size)
+}
+
+/// 从文件尾部向前查找恰好结束于文件末尾且校验和匹配的数据包，返回其时间戳
+///
+/// 窗口从 [`TAIL_WINDOW`] 开始逐步扩大到最大数据包长度，仍未找到时返回 `None`
+/// （例如文件末尾有未写完的数据包）
+fn find_last_packet<R: Read + Seek>(reader: &mut R, file_len: u64) -> Result<Option<u64>> {
+    let data_len = file_len.saturating_sub(FILE_HEADER_SIZE);
+    let max_window = MAX_PACKET_SIZE as u64 + PACKET_HEADER_SIZE;
+    let mut window = TAIL_WINDOW.min(data_len);
+    let header_size = PACKET_HEADER_SIZE as usize;
+
+    loop {
+        let start = file_len - window;
+        let mut buf = vec![0u8; window as usize];
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(&mut buf)?;
+
+        for pos in (0..buf.len().saturating_sub(header_size - 1)).rev() {
+            let Some(header) = PacketHeader::read_from(&mut &buf[pos..pos + header_size])? else {
+                continue;
+            };
+            let data = &buf[pos + header_size..];
+            // 空数据包的校验和恒为0，数据内容末尾的全0字节会被误认为空数据包，
+            // 不据此确认；最后一个数据包确为空数据包时由逐包扫描确定
+            if header.packet_length > 0
+                && header.packet_length as usize == data.len()
+                && header.validate().is_ok()
+                && crc32fast::hash(data) == header.checksum
+            {
+                return Ok(Some(header.timestamp_ns()));
+            }
+        }
+
+        if window >= data_len.min(max_window) {
+            return Ok(None);
+        }
+        window = (window * 4).min(data_len).min(max_window);
+    }
+}
+
+/// 逐个跳过数据包查找最后一个完整数据包的时间戳，遇到损坏或不完整的数据包时停止
+fn last_packet_by_scan<R: Read + Seek>(reader: &mut R, file_len: u64) -> Result<Option<u64>> {
+    reader.seek(SeekFrom::Start(0))?;
+    let mut reader = PacketReader::new(reader)?;
+
+    let mut last = None;
+    while let Ok(Some((header, offset))) = reader.skip_packet() {
+        if offset + PACKET_HEADER_SIZE + header.packet_length as u64 > file_len {
+            break;
+        }
+        last = Some(header.timestamp_ns());
+    }
+    Ok(last)
+}
+
+/// 创建时间（ISO 8601），平台不支持时为空
+fn created_time(metadata: &Metadata) -> String {
+    metadata.created().map(system_time_iso).unwrap_or_default()
+}
+
+/// 最后修改时间（ISO 8601）
+fn modified_time(metadata: &Metadata) -> String {
+    metadata.modified().map(system_time_iso).unwrap_or_default()
+}
+
+fn system_time_iso(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::DataFileWriter;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    fn write_file(dir: &Path, packets: &[(u64, &[u8])]) -> PathBuf {
+        let mut writer = DataFileWriter::create(dir.join("data.pcap")).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn time_range_ignores_zero_padded_payload() {
+        // 最后16字节可解析为时间戳1秒5纳秒、长度和校验和为0的空数据包
+        let dir = tempfile::tempdir().unwrap();
+        let payload = [1, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let path = write_file(
+            dir.path(),
+            &[(BASE_TIMESTAMP, b"first"), (BASE_TIMESTAMP + 10, &payload)],
+        );
+
+        assert_eq!(
+            file_time_range(&path).unwrap(),
+            Some((BASE_TIMESTAMP, BASE_TIMESTAMP + 10))
+        );
+    }
+
+    #[test]
+    fn time_range_includes_trailing_empty_packet() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(
+            dir.path(),
+            &[(BASE_TIMESTAMP, b"first"), (BASE_TIMESTAMP + 10, b"")],
+        );
+
+        assert_eq!(
+            file_time_range(&path).unwrap(),
+            Some((BASE_TIMESTAMP, BASE_TIMESTAMP + 10))
+        );
+    }
+
+    #[test]
+    fn time_range_skips_unfinished_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(
+            dir.path(),
+            &[(BASE_TIMESTAMP, b"first"), (BASE_TIMESTAMP + 10, b"second")],
+        );
+        let len = fs::metadata(&path).unwrap().len();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        assert_eq!(
+            file_time_range(&path).unwrap(),
+            Some((BASE_TIMESTAMP, BASE_TIMESTAMP))
+        );
+    }
+}
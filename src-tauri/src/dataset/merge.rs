@@ -0,0 +1,599 @@
+//! 数据集合并与拆分
+//!
+//! 合并：多个数据集的数据包按时间戳合并写入新数据集，每个数据包记录来源数据集标签；
+//! 可按工程文件中各数据集的时间校正对齐后再合并。
+//! 拆分：按时长、大小或数据包数重新切分数据集的数据文件，新文件按首个数据包时间命名；
+//! 可写入新数据集，也可原地替换，原文件默认移到工程的 `.trash` 目录保留。
+//! 两者的读取与写入过程与数据抽取相同（见 [`crate::dataset::extract`]）。
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dataset::extract::{
+    DatasetExtractor, ExtractFilter, ExtractRequest, ExtractStage, ExtractSummary, FileLimits,
+    ORIGIN_DIR, PROVENANCE_FILE_NAME,
+};
+use crate::dataset::index::{data_files, DatasetIndex, PIDX_FILE_NAME};
+use crate::project::structure::{move_all, undo_moves, ProjectStructure, TRASH_DIR};
+use crate::project::validator::ProjectValidator;
+use crate::state::task_state::CancelToken;
+use crate::types::{PlaybackError, Result};
+
+/// 原地拆分时的临时输出目录
+const SPLIT_TEMP_DIR: &str = ".split";
+
+/// 合并请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequest {
+    /// 源数据集，顺序即来源标签
+    pub sources: Vec<String>,
+    /// 目标数据集，不存在时自动创建；已存在时须没有数据文件
+    pub target: String,
+    /// 单个输出文件大小上限（字节）
+    #[serde(default)]
+    pub max_file_size: Option<u64>,
+    /// 按工程文件中各源数据集的时间校正写入校正后的时间
+    #[serde(default)]
+    pub apply_time_corrections: bool,
+}
+
+impl MergeRequest {
+    /// 创建记录来源标签的抽取器
+    pub fn extractor<P: AsRef<Path>>(&self, project_path: P) -> Result<DatasetExtractor> {
+        if self.sources.len() < 2 {
+            return Err(PlaybackError::ProjectError(
+                "合并至少需要两个数据集".to_string(),
+            ));
+        }
+
+        let request = ExtractRequest {
+            sources: self.sources.clone(),
+            target: self.target.clone(),
+            filter: ExtractFilter::default(),
+            max_file_size: self.max_file_size,
+        };
+        let project_path = project_path.as_ref();
+        let mut extractor =
+            DatasetExtractor::for_project(project_path, &request)?.with_origin_tags();
+        if self.apply_time_corrections {
+            for name in &self.sources {
+                let config = ProjectStructure::dataset_config(project_path, name)?;
+                if let Some(correction) = config.time_correction {
+                    extractor = extractor.with_time_correction(name, correction);
+                }
+            }
+        }
+        Ok(extractor)
+    }
+}
+
+/// 拆分规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum SplitRule {
+    /// 每个文件的时长（纳秒）
+    Duration(u64),
+    /// 每个文件的大小上限（字节）
+    Size(u64),
+    /// 每个文件的数据包数
+    Packets(u64),
+}
+
+impl SplitRule {
+    fn limits(&self) -> FileLimits {
+        let mut limits = FileLimits {
+            max_size: None,
+            max_packets: None,
+            max_duration: None,
+        };
+        match *self {
+            SplitRule::Duration(value) => limits.max_duration = Some(value),
+            SplitRule::Size(value) => limits.max_size = Some(value),
+            SplitRule::Packets(value) => limits.max_packets = Some(value),
+        }
+        limits
+    }
+
+    fn value(&self) -> u64 {
+        match *self {
+            SplitRule::Duration(value) | SplitRule::Size(value) | SplitRule::Packets(value) => {
+                value
+            }
+        }
+    }
+}
+
+/// 拆分请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitRequest {
+    pub dataset: String,
+    pub rule: SplitRule,
+    /// 写入的新数据集，未设置时原地替换
+    #[serde(default)]
+    pub target: Option<String>,
+    /// 原地替换时原文件移到工程的 `.trash` 目录保留，为 `false` 时在新文件装入后删除
+    #[serde(default = "default_keep_original")]
+    pub keep_original: bool,
+}
+
+fn default_keep_original() -> bool {
+    true
+}
+
+/// 拆分结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitSummary {
+    /// 写入的数据集
+    pub dataset: String,
+    #[serde(flatten)]
+    pub result: ExtractSummary,
+    /// 原地替换时原文件的回收位置
+    pub backup_path: Option<String>,
+}
+
+/// 数据集拆分器
+#[derive(Debug)]
+pub struct DatasetSplitter {
+    project_path: PathBuf,
+    request: SplitRequest,
+    extractor: DatasetExtractor,
+}
+
+impl DatasetSplitter {
+    /// 校验拆分规则和数据集，写入新数据集时目标须不存在或没有数据文件
+    pub fn new<P: AsRef<Path>>(project_path: P, request: SplitRequest) -> Result<Self> {
+        let project_path = project_path.as_ref().to_path_buf();
+        if request.rule.value() == 0 {
+            return Err(PlaybackError::ParseError("拆分间隔必须大于0".to_string()));
+        }
+
+        let extractor = match &request.target {
+            Some(target) => {
+                let request = ExtractRequest {
+                    sources: vec![request.dataset.clone()],
+                    target: target.clone(),
+                    filter: ExtractFilter::default(),
+                    max_file_size: None,
+                };
+                DatasetExtractor::for_project(&project_path, &request)?
+            }
+            None => {
+                ProjectValidator::validate_name(&request.dataset)?;
+                DatasetExtractor::new(
+                    vec![(request.dataset.clone(), project_path.join(&request.dataset))],
+                    ExtractFilter::default(),
+                )?
+            }
+        }
+        .with_limits(request.rule.limits());
+
+        Ok(Self {
+            project_path,
+            request,
+            extractor,
+        })
+    }
+
+    /// 是否原地替换源数据集的文件
+    pub fn in_place(&self) -> bool {
+        self.request.target.is_none()
+    }
+
+    /// 执行拆分，`progress` 参数依次为阶段、已读取源文件数、源文件总数和当前文件
+    ///
+    /// 原地拆分时新文件先写入临时目录，全部写完后才替换原文件；取消时原文件保持不变。
+    /// 替换时原文件先移到回收目录再装入新文件，装入失败时还原原文件
+    pub fn split<F>(&self, cancel: &CancelToken, progress: F) -> Result<SplitSummary>
+    where
+        F: FnMut(ExtractStage, u64, u64, Option<&str>),
+    {
+        let summary = match &self.request.target {
+            Some(target) => SplitSummary {
+                dataset: target.clone(),
+                result: self.extractor.extract_to_dataset(
+                    &self.project_path,
+                    target,
+                    cancel,
+                    progress,
+                )?,
+                backup_path: None,
+            },
+            None => self.split_in_place(cancel, progress)?,
+        };
+
+        info!(
+            "数据集 {} 拆分{}: 生成 {} 个文件",
+            self.request.dataset,
+            if summary.result.cancelled {
+                "已取消"
+            } else {
+                "完成"
+            },
+            summary.result.files.len()
+        );
+        Ok(summary)
+    }
+
+    fn split_in_place<F>(&self, cancel: &CancelToken, progress: F) -> Result<SplitSummary>
+    where
+        F: FnMut(ExtractStage, u64, u64, Option<&str>),
+    {
+        let dataset_dir = self.project_path.join(&self.request.dataset);
+        let temp_dir = dataset_dir.join(SPLIT_TEMP_DIR);
+        if temp_dir.exists() {
+            fs::remove_dir_all(&temp_dir)?;
+        }
+
+        let mut result = match self.extractor.extract_into(&temp_dir, cancel, progress) {
+            Ok(result) if !result.cancelled => result,
+            Ok(mut result) => {
+                fs::remove_dir_all(&temp_dir)?;
+                result.files.clear();
+                result.index_path = None;
+                result.provenance_path = None;
+                return Ok(SplitSummary {
+                    dataset: self.request.dataset.clone(),
+                    result,
+                    backup_path: None,
+                });
+            }
+            Err(e) => {
+                remove_temp_dir(&temp_dir);
+                return Err(e);
+            }
+        };
+
+        // 先把原文件移到回收目录，再装入新文件，任一步失败都还原原文件
+        let backup_dir = self.backup_dir();
+        let backups = match self.originals(&dataset_dir, &backup_dir) {
+            Ok(backups) => backups,
+            Err(e) => {
+                remove_temp_dir(&temp_dir);
+                return Err(e);
+            }
+        };
+        if let Err(e) = move_all(&backups) {
+            remove_empty_dirs(&backup_dir, &self.request.dataset);
+            remove_temp_dir(&temp_dir);
+            return Err(e);
+        }
+        let installs = fs::read_dir(&temp_dir)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| {
+                        let path = entry?.path();
+                        let name = path.file_name().unwrap_or_default().to_os_string();
+                        Ok((path, dataset_dir.join(name)))
+                    })
+                    .collect::<std::io::Result<Vec<_>>>()
+            })
+            .map_err(PlaybackError::from)
+            .and_then(|installs| move_all(&installs));
+        if let Err(e) = installs {
+            undo_moves(&backups);
+            remove_empty_dirs(&backup_dir, &self.request.dataset);
+            remove_temp_dir(&temp_dir);
+            return Err(e);
+        }
+        remove_temp_dir(&temp_dir);
+
+        let backup_path = if self.request.keep_original {
+            Some(backup_dir.to_string_lossy().to_string())
+        } else {
+            if let Err(e) = fs::remove_dir_all(&backup_dir) {
+                warn!("删除拆分前的原文件失败 {:?}: {}", backup_dir, e);
+            }
+            None
+        };
+
+        // 索引在临时目录中生成，描述改回数据集名称
+        if result.index_path.is_some() {
+            let index_path = dataset_dir.join(PIDX_FILE_NAME);
+            let mut index = DatasetIndex::read(&index_path)?;
+            index.description = format!("数据集: {}", self.request.dataset);
+            index.write(&index_path)?;
+            result.index_path = Some(index_path.to_string_lossy().to_string());
+        }
+        result.provenance_path = Some(
+            dataset_dir
+                .join(PROVENANCE_FILE_NAME)
+                .to_string_lossy()
+                .to_string(),
+        );
+
+        Ok(SplitSummary {
+            dataset: self.request.dataset.clone(),
+            result,
+            backup_path,
+        })
+    }
+
+    /// 本次拆分的回收目录，原文件按工程目录结构放在其中
+    fn backup_dir(&self) -> PathBuf {
+        self.project_path.join(TRASH_DIR).join(format!(
+            "{}_split_{}",
+            self.request.dataset,
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        ))
+    }
+
+    /// 原数据文件及其索引、来源记录与其在回收目录中的位置
+    fn originals(&self, dataset_dir: &Path, backup_dir: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let mut originals = data_files(dataset_dir)?;
+        originals.extend(
+            [PIDX_FILE_NAME, PROVENANCE_FILE_NAME, ORIGIN_DIR]
+                .iter()
+                .map(|name| dataset_dir.join(name))
+                .filter(|path| path.exists()),
+        );
+
+        let target_dir = backup_dir.join(&self.request.dataset);
+        Ok(originals
+            .into_iter()
+            .filter_map(|path| {
+                let backup = target_dir.join(path.file_name()?);
+                Some((path, backup))
+            })
+            .collect())
+    }
+}
+
+/// 删除拆分临时目录，尽力而为
+fn remove_temp_dir(temp_dir: &Path) {
+    if temp_dir.exists() {
+        if let Err(e) = fs::remove_dir_all(temp_dir) {
+            warn!("删除拆分临时目录失败 {:?}: {}", temp_dir, e);
+        }
+    }
+}
+
+/// 原文件还原后删除留下的空回收目录，目录非空时保留
+fn remove_empty_dirs(backup_dir: &Path, dataset: &str) {
+    let _ = fs::remove_dir(backup_dir.join(dataset));
+    let _ = fs::remove_dir(backup_dir);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::extract::read_packet_origins;
+    use crate::dataset::format::{
+        DataFileWriter, PacketReader, FILE_HEADER_SIZE, PACKET_HEADER_SIZE,
+    };
+    use crate::dataset::naming::data_file_name;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    /// 在数据集目录中写入一个数据文件
+    fn write_data_file(dir: &Path, packets: &[(u64, &[u8])]) {
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join(data_file_name(packets[0].0));
+        let mut writer = DataFileWriter::create(path).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    /// 各数据文件中的数据包时间戳，按文件名排序
+    fn file_timestamps(dir: &Path) -> Vec<Vec<u64>> {
+        data_files(dir)
+            .unwrap()
+            .iter()
+            .map(|path| {
+                let mut reader = PacketReader::open(path).unwrap();
+                let mut timestamps = Vec::new();
+                while let Some(packet) = reader.next_packet().unwrap() {
+                    timestamps.push(packet.header.timestamp_ns());
+                }
+                timestamps
+            })
+            .collect()
+    }
+
+    /// 五个间隔1微秒、内容4字节的数据包，各在不同的文件名时间段内
+    fn write_radar(project: &Path) {
+        let packets: Vec<(u64, &[u8])> = (0..5)
+            .map(|i| (BASE_TIMESTAMP + i * 1_000, b"data".as_slice()))
+            .collect();
+        write_data_file(&project.join("radar"), &packets);
+    }
+
+    fn split(project: &Path, rule: SplitRule, target: Option<&str>) -> Result<SplitSummary> {
+        let request = SplitRequest {
+            dataset: "radar".to_string(),
+            rule,
+            target: target.map(str::to_string),
+            keep_original: true,
+        };
+        DatasetSplitter::new(project, request)?.split(&CancelToken::new(), |_, _, _, _| {})
+    }
+
+    #[test]
+    fn merges_in_time_order_with_origin_tags() {
+        let project = tempfile::tempdir().unwrap();
+        write_data_file(
+            &project.path().join("a"),
+            &[(BASE_TIMESTAMP, b"a0"), (BASE_TIMESTAMP + 20, b"a1")],
+        );
+        write_data_file(
+            &project.path().join("b"),
+            &[(BASE_TIMESTAMP + 10, b"b0"), (BASE_TIMESTAMP + 30, b"b1")],
+        );
+
+        let request = MergeRequest {
+            sources: vec!["a".to_string(), "b".to_string()],
+            target: "merged".to_string(),
+            max_file_size: None,
+            apply_time_corrections: false,
+        };
+        let summary = request
+            .extractor(project.path())
+            .unwrap()
+            .extract_to_dataset(
+                project.path(),
+                "merged",
+                &CancelToken::new(),
+                |_, _, _, _| {},
+            )
+            .unwrap();
+
+        let merged = project.path().join("merged");
+        assert_eq!(summary.packets, 4);
+        assert_eq!(
+            file_timestamps(&merged),
+            vec![vec![
+                BASE_TIMESTAMP,
+                BASE_TIMESTAMP + 10,
+                BASE_TIMESTAMP + 20,
+                BASE_TIMESTAMP + 30
+            ]]
+        );
+        let origins = read_packet_origins(&merged, &summary.files[0])
+            .unwrap()
+            .unwrap();
+        let datasets: Vec<_> = (0..4).map(|i| origins.dataset(i).unwrap()).collect();
+        assert_eq!(datasets, vec!["a", "b", "a", "b"]);
+    }
+
+    #[test]
+    fn merge_needs_two_sources() {
+        let project = tempfile::tempdir().unwrap();
+        write_radar(project.path());
+        let request = MergeRequest {
+            sources: vec!["radar".to_string()],
+            target: "merged".to_string(),
+            max_file_size: None,
+            apply_time_corrections: false,
+        };
+
+        assert!(request.extractor(project.path()).is_err());
+    }
+
+    #[test]
+    fn splits_by_packets() {
+        let project = tempfile::tempdir().unwrap();
+        write_radar(project.path());
+
+        let summary = split(project.path(), SplitRule::Packets(2), Some("out")).unwrap();
+
+        assert_eq!(summary.result.files.len(), 3);
+        let counts: Vec<usize> = file_timestamps(&project.path().join("out"))
+            .iter()
+            .map(Vec::len)
+            .collect();
+        assert_eq!(counts, vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn splits_by_size() {
+        let project = tempfile::tempdir().unwrap();
+        write_radar(project.path());
+
+        // 每个文件容纳两个数据包
+        let size = FILE_HEADER_SIZE + 2 * (PACKET_HEADER_SIZE + 4);
+        split(project.path(), SplitRule::Size(size), Some("out")).unwrap();
+
+        let out = project.path().join("out");
+        let counts: Vec<usize> = file_timestamps(&out).iter().map(Vec::len).collect();
+        assert_eq!(counts, vec![2, 2, 1]);
+        for path in data_files(&out).unwrap() {
+            assert!(fs::metadata(path).unwrap().len() <= size);
+        }
+    }
+
+    #[test]
+    fn splits_by_duration() {
+        let project = tempfile::tempdir().unwrap();
+        write_radar(project.path());
+
+        split(project.path(), SplitRule::Duration(2_000), Some("out")).unwrap();
+
+        assert_eq!(
+            file_timestamps(&project.path().join("out")),
+            vec![
+                vec![BASE_TIMESTAMP, BASE_TIMESTAMP + 1_000],
+                vec![BASE_TIMESTAMP + 2_000, BASE_TIMESTAMP + 3_000],
+                vec![BASE_TIMESTAMP + 4_000],
+            ]
+        );
+    }
+
+    #[test]
+    fn split_files_in_same_name_slot_get_distinct_names() {
+        // 同一个100纳秒内的数据包各成一个文件，文件名依次推后
+        let project = tempfile::tempdir().unwrap();
+        write_data_file(
+            &project.path().join("radar"),
+            &[(BASE_TIMESTAMP, b"p0"), (BASE_TIMESTAMP + 1, b"p1")],
+        );
+
+        let summary = split(project.path(), SplitRule::Packets(1), Some("out")).unwrap();
+
+        assert_eq!(
+            summary.result.files,
+            vec![
+                data_file_name(BASE_TIMESTAMP),
+                data_file_name(BASE_TIMESTAMP + 100)
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_zero_rule() {
+        let project = tempfile::tempdir().unwrap();
+        write_radar(project.path());
+
+        assert!(matches!(
+            split(project.path(), SplitRule::Packets(0), None),
+            Err(PlaybackError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn in_place_split_keeps_backup_in_trash() {
+        let project = tempfile::tempdir().unwrap();
+        write_radar(project.path());
+        let radar = project.path().join("radar");
+        let original_name = data_file_name(BASE_TIMESTAMP);
+        let original = fs::read(radar.join(&original_name)).unwrap();
+
+        let summary = split(project.path(), SplitRule::Packets(2), None).unwrap();
+
+        assert_eq!(summary.dataset, "radar");
+        assert_eq!(file_timestamps(&radar).len(), 3);
+        assert!(!radar.join(SPLIT_TEMP_DIR).exists());
+        assert!(radar.join(PIDX_FILE_NAME).is_file());
+
+        let backup = PathBuf::from(summary.backup_path.unwrap());
+        assert!(backup.starts_with(project.path().join(TRASH_DIR)));
+        assert_eq!(
+            fs::read(backup.join("radar").join(&original_name)).unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn in_place_split_restores_originals_when_install_fails() {
+        let project = tempfile::tempdir().unwrap();
+        write_radar(project.path());
+        let radar = project.path().join("radar");
+        let original_name = data_file_name(BASE_TIMESTAMP);
+        let original = fs::read(radar.join(&original_name)).unwrap();
+        // 第二个新文件的位置被非空目录占用，装入时失败
+        let blocker = radar.join(data_file_name(BASE_TIMESTAMP + 2_000));
+        fs::create_dir_all(blocker.join("keep")).unwrap();
+
+        assert!(split(project.path(), SplitRule::Packets(2), None).is_err());
+
+        let files = data_files(&radar).unwrap();
+        assert_eq!(files, vec![radar.join(&original_name)]);
+        assert_eq!(fs::read(&files[0]).unwrap(), original);
+        assert!(!radar.join(SPLIT_TEMP_DIR).exists());
+        // 回收目录中没有留下空目录
+        let trash = project.path().join(TRASH_DIR);
+        assert!(!trash.exists() || fs::read_dir(&trash).unwrap().next().is_none());
+    }
+}
@@ -0,0 +1,18 @@
+//! 数据集模块
+//!
+//! 按文件协议直接读写数据文件，提供索引生成、信息统计、完整性校验与修复、外部文件导入、按时间窗口和字节模式抽取、合并与拆分以及与标准抓包格式的互相转换
+
+pub mod capture;
+pub mod convert;
+pub mod export;
+pub mod extract;
+pub mod format;
+pub mod import;
+pub mod index;
+pub mod info;
+pub mod merge;
+pub mod naming;
+pub mod net;
+pub mod repair;
+pub mod stats;
+pub mod verify;
@@ -0,0 +1,66 @@
+//! 数据文件命名
+//!
+//! 数据文件按首个数据包的时间戳命名：`data_yyMMdd_HHmmss_fffffff.pcap`，
+//! 其中 `fffffff` 为秒内的100纳秒计数（7位）
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::path::Path;
+
+/// 数据文件扩展名
+pub const DATA_FILE_EXTENSION: &str = "pcap";
+
+const DATA_FILE_PREFIX: &str = "data_";
+const DATE_TIME_FORMAT: &str = "%y%m%d_%H%M%S";
+
+/// 根据时间戳（纳秒）生成数据文件名
+pub fn data_file_name(timestamp_ns: u64) -> String {
+    let secs = (timestamp_ns / 1_000_000_000) as i64;
+    let nanos = (timestamp_ns % 1_000_000_000) as u32;
+    let date_time = DateTime::<Utc>::from_timestamp(secs, nanos).unwrap_or_default();
+
+    format!(
+        "{}{}_{:07}.{}",
+        DATA_FILE_PREFIX,
+        date_time.format(DATE_TIME_FORMAT),
+        nanos / 100,
+        DATA_FILE_EXTENSION
+    )
+}
+
+/// 目录中未被占用的数据文件名，时间戳对应的文件名已存在时依次推后100纳秒，保持命名格式
+pub fn unique_data_file_name<P: AsRef<Path>>(dir: P, timestamp_ns: u64) -> String {
+    let dir = dir.as_ref();
+    let mut timestamp_ns = timestamp_ns;
+    loop {
+        let name = data_file_name(timestamp_ns);
+        if !dir.join(&name).exists() {
+            return name;
+        }
+        timestamp_ns = timestamp_ns.saturating_add(100);
+    }
+}
+
+/// 从数据文件名解析时间戳（纳秒，精度100纳秒），格式不符时返回 `None`
+pub fn parse_data_file_name(file_name: &str) -> Option<u64> {
+    let stem = file_name
+        .strip_prefix(DATA_FILE_PREFIX)?
+        .strip_suffix(DATA_FILE_EXTENSION)?
+        .strip_suffix('.')?;
+
+    // yyMMdd_HHmmss_fffffff
+    let (date_time, fraction) = stem.rsplit_once('_')?;
+    if fraction.len() != 7 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let date_time = NaiveDateTime::parse_from_str(date_time, DATE_TIME_FORMAT).ok()?;
+    let secs = u64::try_from(date_time.and_utc().timestamp()).ok()?;
+    let fraction: u64 = fraction.parse().ok()?;
+
+    Some(secs * 1_000_000_000 + fraction * 100)
+}
+
+/// 是否为符合命名规范的数据文件名
+pub fn is_data_file_name(file_name: &str) -> bool {
+    parse_data_file_name(file_name).is_some()
+}
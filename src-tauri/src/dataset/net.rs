@@ -0,0 +1,716 @@
+//! 链路层、IP和UDP报文解析
+//!
+//! 支持以太网（含VLAN标签）、Linux cooked、Loopback和裸IP链路，IPv4/IPv6分片重组；
+//! 导出时按相反方向构造以太网/IP/UDP帧
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::types::{PlaybackError, Result};
+
+use crate::dataset::capture::{
+    LINKTYPE_ETHERNET, LINKTYPE_IPV4, LINKTYPE_IPV6, LINKTYPE_LINUX_SLL, LINKTYPE_NULL,
+    LINKTYPE_RAW,
+};
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88A8;
+const ETHERTYPE_VLAN_LEGACY: u16 = 0x9100;
+
+const IP_PROTOCOL_UDP: u8 = 17;
+
+const IPV6_HOP_BY_HOP: u8 = 0;
+const IPV6_ROUTING: u8 = 43;
+const IPV6_FRAGMENT: u8 = 44;
+const IPV6_DESTINATION_OPTIONS: u8 = 60;
+
+/// 构造帧时使用的链路MTU
+const ETHERNET_MTU: usize = 1500;
+/// UDP数据报的最大负载（UDP长度字段为16位）
+const MAX_UDP_PAYLOAD: usize = 65535 - 8;
+
+/// 构造帧时使用的源MAC地址（本地管理地址）
+const SOURCE_MAC: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+/// 单播目的地址使用的MAC地址
+const UNICAST_MAC: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x02];
+
+/// 分片重组超时（纳秒），超时未完成的分片被丢弃
+pub const REASSEMBLY_TIMEOUT_NS: u64 = 30 * 1_000_000_000;
+
+/// UDP数据报
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdpDatagram {
+    /// 首个分片的时间戳
+    pub timestamp_ns: u64,
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub source_port: u16,
+    pub destination_port: u16,
+    pub payload: Vec<u8>,
+}
+
+/// 解析后的IP报文（可能是分片）
+#[derive(Debug)]
+struct IpPacket<'a> {
+    source: IpAddr,
+    destination: IpAddr,
+    protocol: u8,
+    /// 分片信息：标识、偏移（字节）、是否还有后续分片
+    fragment: Option<(u32, usize, bool)>,
+    payload: &'a [u8],
+}
+
+/// 从链路帧中取出IP报文
+pub fn link_payload(link_type: u32, frame: &[u8]) -> Option<&[u8]> {
+    match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut ether_type = read_u16(frame, 12)?;
+            let mut offset = 14;
+            while matches!(
+                ether_type,
+                ETHERTYPE_VLAN | ETHERTYPE_QINQ | ETHERTYPE_VLAN_LEGACY
+            ) {
+                ether_type = read_u16(frame, offset + 2)?;
+                offset += 4;
+            }
+            matches!(ether_type, ETHERTYPE_IPV4 | ETHERTYPE_IPV6).then(|| &frame[offset..])
+        }
+        LINKTYPE_LINUX_SLL => {
+            let protocol = read_u16(frame, 14)?;
+            matches!(protocol, ETHERTYPE_IPV4 | ETHERTYPE_IPV6).then(|| &frame[16..])
+        }
+        // 4字节主机字节序的地址族
+        LINKTYPE_NULL => frame.get(4..),
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => Some(frame),
+        _ => None,
+    }
+}
+
+fn parse_ip(data: &[u8]) -> Option<IpPacket<'_>> {
+    match data.first()? >> 4 {
+        4 => parse_ipv4(data),
+        6 => parse_ipv6(data),
+        _ => None,
+    }
+}
+
+fn parse_ipv4(data: &[u8]) -> Option<IpPacket<'_>> {
+    let header_length = ((data.first()? & 0x0F) as usize) * 4;
+    let total_length = read_u16(data, 2)? as usize;
+    if header_length < 20 || total_length < header_length || data.len() < header_length {
+        return None;
+    }
+    // 以太网最小帧可能带填充，按总长度截断；抓包截断时取实际长度
+    let end = total_length.min(data.len());
+
+    let identification = read_u16(data, 4)? as u32;
+    let flags_offset = read_u16(data, 6)?;
+    let more_fragments = flags_offset & 0x2000 != 0;
+    let fragment_offset = ((flags_offset & 0x1FFF) as usize) * 8;
+
+    Some(IpPacket {
+        source: IpAddr::V4(Ipv4Addr::new(data[12], data[13], data[14], data[15])),
+        destination: IpAddr::V4(Ipv4Addr::new(data[16], data[17], data[18], data[19])),
+        protocol: data[9],
+        fragment: (more_fragments || fragment_offset > 0).then_some((
+            identification,
+            fragment_offset,
+            more_fragments,
+        )),
+        payload: &data[header_length..end],
+    })
+}
+
+fn parse_ipv6(data: &[u8]) -> Option<IpPacket<'_>> {
+    if data.len() < 40 {
+        return None;
+    }
+    let payload_length = read_u16(data, 4)? as usize;
+    let end = (40 + payload_length).min(data.len());
+    let source = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&data[8..24]).ok()?));
+    let destination = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&data[24..40]).ok()?));
+
+    let mut next_header = data[6];
+    let mut offset = 40;
+    let mut fragment = None;
+
+    // 跳过扩展头
+    loop {
+        match next_header {
+            IPV6_HOP_BY_HOP | IPV6_ROUTING | IPV6_DESTINATION_OPTIONS => {
+                next_header = *data.get(offset)?;
+                offset += (*data.get(offset + 1)? as usize + 1) * 8;
+            }
+            IPV6_FRAGMENT => {
+                next_header = *data.get(offset)?;
+                let offset_flags = read_u16(data, offset + 2)?;
+                let identification = read_u32(data, offset + 4)?;
+                fragment = Some((
+                    identification,
+                    ((offset_flags >> 3) as usize) * 8,
+                    offset_flags & 0x1 != 0,
+                ));
+                offset += 8;
+            }
+            _ => break,
+        }
+    }
+
+    Some(IpPacket {
+        source,
+        destination,
+        protocol: next_header,
+        fragment,
+        payload: data.get(offset..end)?,
+    })
+}
+
+/// 解析UDP报文，返回源端口、目的端口和负载
+fn parse_udp(data: &[u8]) -> Option<(u16, u16, &[u8])> {
+    let source_port = read_u16(data, 0)?;
+    let destination_port = read_u16(data, 2)?;
+    let length = read_u16(data, 4)? as usize;
+    if length < 8 {
+        return None;
+    }
+    let end = length.min(data.len());
+    Some((source_port, destination_port, data.get(8..end)?))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FragmentKey {
+    source: IpAddr,
+    destination: IpAddr,
+    protocol: u8,
+    identification: u32,
+}
+
+#[derive(Debug)]
+struct PendingDatagram {
+    first_timestamp_ns: u64,
+    /// 按偏移保存的分片
+    fragments: Vec<(usize, Vec<u8>)>,
+    /// 收到最后一个分片后确定的总长度
+    total_length: Option<usize>,
+}
+
+impl PendingDatagram {
+    /// 加入分片，与已收到的分片矛盾时返回 `false`：超出最后分片确定的总长度、
+    /// 多个最后分片给出的总长度不同，或重叠部分的内容不同
+    fn add(&mut self, offset: usize, data: &[u8], last: bool) -> bool {
+        let end = offset + data.len();
+        if last {
+            if self.total_length.is_some_and(|total| total != end)
+                || self.fragments.iter().any(|(o, d)| o + d.len() > end)
+            {
+                return false;
+            }
+            self.total_length = Some(end);
+        } else if self.total_length.is_some_and(|total| end > total) {
+            return false;
+        }
+
+        for (o, d) in &self.fragments {
+            let start = offset.max(*o);
+            let stop = end.min(o + d.len());
+            if start < stop && data[start - offset..stop - offset] != d[start - o..stop - o] {
+                return false;
+            }
+        }
+        self.fragments.push((offset, data.to_vec()));
+        true
+    }
+
+    /// 分片齐全时拼接负载，各分片均不超出总长度
+    fn assemble(&mut self) -> Option<Vec<u8>> {
+        let total_length = self.total_length?;
+        self.fragments.sort_by_key(|(offset, _)| *offset);
+
+        let mut covered = 0;
+        for (offset, data) in &self.fragments {
+            if *offset > covered {
+                return None;
+            }
+            covered = covered.max(offset + data.len());
+        }
+        if covered < total_length {
+            return None;
+        }
+
+        let mut payload = vec![0u8; total_length];
+        for (offset, data) in &self.fragments {
+            payload[*offset..offset + data.len()].copy_from_slice(data);
+        }
+        Some(payload)
+    }
+}
+
+/// UDP数据报解码器，负责IP分片重组
+#[derive(Debug, Default)]
+pub struct UdpDecoder {
+    pending: HashMap<FragmentKey, PendingDatagram>,
+}
+
+impl UdpDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解码链路帧，非UDP或分片未齐时返回 `None`
+    pub fn decode(
+        &mut self,
+        link_type: u32,
+        timestamp_ns: u64,
+        frame: &[u8],
+    ) -> Option<UdpDatagram> {
+        self.expire(timestamp_ns);
+
+        let ip = parse_ip(link_payload(link_type, frame)?)?;
+        if ip.protocol != IP_PROTOCOL_UDP {
+            return None;
+        }
+
+        let (timestamp_ns, payload) = match ip.fragment {
+            None => (timestamp_ns, ip.payload.to_vec()),
+            Some((identification, offset, more_fragments)) => {
+                let key = FragmentKey {
+                    source: ip.source,
+                    destination: ip.destination,
+                    protocol: ip.protocol,
+                    identification,
+                };
+                let pending = self.pending.entry(key.clone()).or_insert(PendingDatagram {
+                    first_timestamp_ns: timestamp_ns,
+                    fragments: Vec::new(),
+                    total_length: None,
+                });
+                if !pending.add(offset, ip.payload, !more_fragments) {
+                    // 分片相互矛盾时无法确定正确内容，丢弃整个数据报
+                    self.pending.remove(&key);
+                    return None;
+                }
+
+                let payload = pending.assemble()?;
+                let first_timestamp_ns = pending.first_timestamp_ns;
+                self.pending.remove(&key);
+                (first_timestamp_ns, payload)
+            }
+        };
+
+        let (source_port, destination_port, udp_payload) = parse_udp(&payload)?;
+        Some(UdpDatagram {
+            timestamp_ns,
+            source: ip.source,
+            destination: ip.destination,
+            source_port,
+            destination_port,
+            payload: udp_payload.to_vec(),
+        })
+    }
+
+    /// 未完成重组的数据报数量
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn expire(&mut self, now_ns: u64) {
+        self.pending.retain(|_, pending| {
+            now_ns.saturating_sub(pending.first_timestamp_ns) <= REASSEMBLY_TIMEOUT_NS
+        });
+    }
+}
+
+/// UDP帧构造器
+///
+/// 将负载封装为以太网/IP/UDP帧，超过MTU时按IP分片拆成多帧
+#[derive(Debug)]
+pub struct UdpFrameBuilder {
+    source: SocketAddr,
+    destination: SocketAddr,
+    identification: u32,
+}
+
+impl UdpFrameBuilder {
+    /// 创建构造器，源地址和目的地址须为同一地址族
+    pub fn new(source: SocketAddr, destination: SocketAddr) -> Result<Self> {
+        if source.is_ipv4() != destination.is_ipv4() {
+            return Err(PlaybackError::ParseError(format!(
+                "源地址 {} 与目的地址 {} 的地址族不一致",
+                source, destination
+            )));
+        }
+
+        Ok(Self {
+            source,
+            destination,
+            identification: 0,
+        })
+    }
+
+    /// 构造负载对应的帧，负载超过UDP上限时返回 `None`
+    pub fn frames(&mut self, payload: &[u8]) -> Option<Vec<Vec<u8>>> {
+        let max_payload = match self.destination {
+            SocketAddr::V4(_) => MAX_UDP_PAYLOAD - 20,
+            SocketAddr::V6(_) => MAX_UDP_PAYLOAD,
+        };
+        if payload.len() > max_payload {
+            return None;
+        }
+
+        self.identification = self.identification.wrapping_add(1);
+        let datagram = self.udp_datagram(payload);
+
+        let frames = match (self.source.ip(), self.destination.ip()) {
+            (IpAddr::V4(source), IpAddr::V4(destination)) => {
+                ipv4_fragments(source, destination, self.identification as u16, &datagram)
+            }
+            (IpAddr::V6(source), IpAddr::V6(destination)) => {
+                ipv6_fragments(source, destination, self.identification, &datagram)
+            }
+            _ => unreachable!("地址族已在创建时校验"),
+        };
+
+        let ether_type = if self.destination.is_ipv4() {
+            ETHERTYPE_IPV4
+        } else {
+            ETHERTYPE_IPV6
+        };
+        let mac = destination_mac(self.destination.ip());
+
+        Some(
+            frames
+                .into_iter()
+                .map(|packet| {
+                    let mut frame = Vec::with_capacity(14 + packet.len());
+                    frame.extend_from_slice(&mac);
+                    frame.extend_from_slice(&SOURCE_MAC);
+                    frame.extend_from_slice(&ether_type.to_be_bytes());
+                    frame.extend_from_slice(&packet);
+                    frame
+                })
+                .collect(),
+        )
+    }
+
+    /// 构造带校验和的UDP数据报
+    fn udp_datagram(&self, payload: &[u8]) -> Vec<u8> {
+        let length = (8 + payload.len()) as u16;
+        let mut datagram = Vec::with_capacity(length as usize);
+        datagram.extend_from_slice(&self.source.port().to_be_bytes());
+        datagram.extend_from_slice(&self.destination.port().to_be_bytes());
+        datagram.extend_from_slice(&length.to_be_bytes());
+        datagram.extend_from_slice(&[0, 0]);
+        datagram.extend_from_slice(payload);
+
+        // 伪首部：源地址、目的地址、协议号和UDP长度
+        let mut pseudo = Vec::with_capacity(40);
+        match (self.source.ip(), self.destination.ip()) {
+            (IpAddr::V4(source), IpAddr::V4(destination)) => {
+                pseudo.extend_from_slice(&source.octets());
+                pseudo.extend_from_slice(&destination.octets());
+                pseudo.extend_from_slice(&[0, IP_PROTOCOL_UDP]);
+                pseudo.extend_from_slice(&length.to_be_bytes());
+            }
+            (IpAddr::V6(source), IpAddr::V6(destination)) => {
+                pseudo.extend_from_slice(&source.octets());
+                pseudo.extend_from_slice(&destination.octets());
+                pseudo.extend_from_slice(&(length as u32).to_be_bytes());
+                pseudo.extend_from_slice(&[0, 0, 0, IP_PROTOCOL_UDP]);
+            }
+            _ => unreachable!("地址族已在创建时校验"),
+        }
+
+        let checksum = match internet_checksum(&[&pseudo, &datagram]) {
+            // 计算结果为0时按规范写为全1
+            0 => 0xFFFF,
+            checksum => checksum,
+        };
+        datagram[6..8].copy_from_slice(&checksum.to_be_bytes());
+        datagram
+    }
+}
+
+/// 按MTU拆分IPv4分片
+fn ipv4_fragments(
+    source: Ipv4Addr,
+    destination: Ipv4Addr,
+    identification: u16,
+    datagram: &[u8],
+) -> Vec<Vec<u8>> {
+    let chunk_size = (ETHERNET_MTU - 20) / 8 * 8;
+    let chunks: Vec<&[u8]> = datagram.chunks(chunk_size).collect();
+    let count = chunks.len();
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let more_fragments = i + 1 < count;
+            let offset = (i * chunk_size / 8) as u16;
+            let flags_offset = offset | if more_fragments { 0x2000 } else { 0 };
+            let total_length = (20 + chunk.len()) as u16;
+
+            let mut packet = Vec::with_capacity(total_length as usize);
+            packet.extend_from_slice(&[0x45, 0]);
+            packet.extend_from_slice(&total_length.to_be_bytes());
+            packet.extend_from_slice(&identification.to_be_bytes());
+            packet.extend_from_slice(&flags_offset.to_be_bytes());
+            packet.extend_from_slice(&[64, IP_PROTOCOL_UDP, 0, 0]);
+            packet.extend_from_slice(&source.octets());
+            packet.extend_from_slice(&destination.octets());
+
+            let checksum = internet_checksum(&[&packet]);
+            packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+            packet.extend_from_slice(chunk);
+            packet
+        })
+        .collect()
+}
+
+/// 按MTU拆分IPv6分片（不超过MTU时不添加分片扩展头）
+fn ipv6_fragments(
+    source: Ipv6Addr,
+    destination: Ipv6Addr,
+    identification: u32,
+    datagram: &[u8],
+) -> Vec<Vec<u8>> {
+    let header = |payload_length: usize, next_header: u8| {
+        let mut packet = Vec::with_capacity(40 + payload_length);
+        packet.extend_from_slice(&[0x60, 0, 0, 0]);
+        packet.extend_from_slice(&(payload_length as u16).to_be_bytes());
+        packet.extend_from_slice(&[next_header, 64]);
+        packet.extend_from_slice(&source.octets());
+        packet.extend_from_slice(&destination.octets());
+        packet
+    };
+
+    if datagram.len() <= ETHERNET_MTU - 40 {
+        let mut packet = header(datagram.len(), IP_PROTOCOL_UDP);
+        packet.extend_from_slice(datagram);
+        return vec![packet];
+    }
+
+    let chunk_size = (ETHERNET_MTU - 40 - 8) / 8 * 8;
+    let chunks: Vec<&[u8]> = datagram.chunks(chunk_size).collect();
+    let count = chunks.len();
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let more_fragments = i + 1 < count;
+            let offset_flags = ((i * chunk_size / 8) as u16) << 3 | more_fragments as u16;
+
+            let mut packet = header(8 + chunk.len(), IPV6_FRAGMENT);
+            packet.extend_from_slice(&[IP_PROTOCOL_UDP, 0]);
+            packet.extend_from_slice(&offset_flags.to_be_bytes());
+            packet.extend_from_slice(&identification.to_be_bytes());
+            packet.extend_from_slice(chunk);
+            packet
+        })
+        .collect()
+}
+
+/// 目的IP对应的以太网地址
+fn destination_mac(destination: IpAddr) -> [u8; 6] {
+    match destination {
+        IpAddr::V4(v4) if v4.is_broadcast() => [0xFF; 6],
+        IpAddr::V4(v4) if v4.is_multicast() => {
+            let o = v4.octets();
+            [0x01, 0x00, 0x5E, o[1] & 0x7F, o[2], o[3]]
+        }
+        IpAddr::V6(v6) if v6.is_multicast() => {
+            let o = v6.octets();
+            [0x33, 0x33, o[12], o[13], o[14], o[15]]
+        }
+        _ => UNICAST_MAC,
+    }
+}
+
+/// 互联网校验和（RFC 1071）
+fn internet_checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum: u32 = 0;
+    let mut pending: Option<u8> = None;
+
+    for part in parts {
+        for &byte in part.iter() {
+            match pending.take() {
+                Some(high) => sum += u16::from_be_bytes([high, byte]) as u32,
+                None => pending = Some(byte),
+            }
+        }
+    }
+    if let Some(high) = pending {
+        sum += u16::from_be_bytes([high, 0]) as u32;
+    }
+
+    while sum >> 16 != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([
+        *data.get(offset)?,
+        *data.get(offset + 1)?,
+    ]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 10);
+    const DESTINATION: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 20);
+
+    /// 构造IPv4分片，`offset` 为字节偏移（8的倍数）
+    fn fragment(identification: u16, offset: usize, more: bool, data: &[u8]) -> Vec<u8> {
+        let flags_offset = (offset / 8) as u16 | if more { 0x2000 } else { 0 };
+        let mut packet = vec![0x45, 0];
+        packet.extend_from_slice(&((20 + data.len()) as u16).to_be_bytes());
+        packet.extend_from_slice(&identification.to_be_bytes());
+        packet.extend_from_slice(&flags_offset.to_be_bytes());
+        packet.extend_from_slice(&[64, IP_PROTOCOL_UDP, 0, 0]);
+        packet.extend_from_slice(&SOURCE.octets());
+        packet.extend_from_slice(&DESTINATION.octets());
+        packet.extend_from_slice(data);
+        packet
+    }
+
+    /// UDP首部加负载，共24字节，按8字节拆成3个分片
+    fn udp_datagram() -> Vec<u8> {
+        let mut datagram = Vec::new();
+        datagram.extend_from_slice(&5000u16.to_be_bytes());
+        datagram.extend_from_slice(&6000u16.to_be_bytes());
+        datagram.extend_from_slice(&24u16.to_be_bytes());
+        datagram.extend_from_slice(&[0, 0]);
+        datagram.extend((0..16).map(|i| i as u8));
+        datagram
+    }
+
+    fn fragments(datagram: &[u8]) -> Vec<Vec<u8>> {
+        datagram
+            .chunks(8)
+            .enumerate()
+            .map(|(i, chunk)| fragment(1, i * 8, (i + 1) * 8 < datagram.len(), chunk))
+            .collect()
+    }
+
+    fn decode_all(decoder: &mut UdpDecoder, frames: &[Vec<u8>]) -> Vec<UdpDatagram> {
+        frames
+            .iter()
+            .enumerate()
+            .filter_map(|(i, frame)| decoder.decode(LINKTYPE_RAW, i as u64, frame))
+            .collect()
+    }
+
+    #[test]
+    fn reassembles_out_of_order_fragments() {
+        let datagram = udp_datagram();
+        let frames = fragments(&datagram);
+
+        for order in [[0, 1, 2], [2, 0, 1], [1, 2, 0]] {
+            let mut decoder = UdpDecoder::new();
+            let shuffled: Vec<Vec<u8>> = order.iter().map(|&i| frames[i].clone()).collect();
+            let decoded = decode_all(&mut decoder, &shuffled);
+            assert_eq!(decoded.len(), 1, "顺序 {:?}", order);
+            assert_eq!(decoded[0].payload, &datagram[8..]);
+            assert_eq!(decoded[0].source_port, 5000);
+            assert_eq!(decoded[0].destination_port, 6000);
+            // 时间戳取首个到达分片的时间
+            assert_eq!(decoded[0].timestamp_ns, 0);
+            assert_eq!(decoder.pending_count(), 0);
+        }
+    }
+
+    #[test]
+    fn accepts_consistent_overlaps() {
+        let datagram = udp_datagram();
+        let mut frames = fragments(&datagram);
+        // 重复分片和与相邻分片内容一致的重叠分片
+        frames.insert(1, frames[0].clone());
+        frames.insert(2, fragment(1, 8, true, &datagram[8..24]));
+
+        let mut decoder = UdpDecoder::new();
+        let decoded = decode_all(&mut decoder, &frames);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].payload, &datagram[8..]);
+    }
+
+    #[test]
+    fn drops_conflicting_overlaps() {
+        let datagram = udp_datagram();
+        let frames = fragments(&datagram);
+        let mut altered = datagram[8..16].to_vec();
+        altered[0] ^= 0xFF;
+
+        let mut decoder = UdpDecoder::new();
+        assert!(decoder.decode(LINKTYPE_RAW, 0, &frames[1]).is_none());
+        assert!(decoder
+            .decode(LINKTYPE_RAW, 1, &fragment(1, 8, true, &altered))
+            .is_none());
+        assert_eq!(decoder.pending_count(), 0);
+
+        // 丢弃后剩余分片不能拼出数据报
+        assert!(decode_all(&mut decoder, &[frames[0].clone(), frames[2].clone()]).is_empty());
+    }
+
+    #[test]
+    fn drops_fragments_beyond_total_length() {
+        let datagram = udp_datagram();
+        let frames = fragments(&datagram);
+
+        // 最后分片之后到达超出总长度的分片
+        let mut decoder = UdpDecoder::new();
+        assert!(decoder.decode(LINKTYPE_RAW, 0, &frames[2]).is_none());
+        assert!(decoder
+            .decode(LINKTYPE_RAW, 1, &fragment(1, 32, true, &[0; 8]))
+            .is_none());
+        assert_eq!(decoder.pending_count(), 0);
+
+        // 最后分片给出的总长度小于已收到的分片
+        let mut decoder = UdpDecoder::new();
+        assert!(decoder
+            .decode(LINKTYPE_RAW, 0, &fragment(1, 16, true, &[0; 16]))
+            .is_none());
+        assert!(decoder.decode(LINKTYPE_RAW, 1, &frames[2]).is_none());
+        assert_eq!(decoder.pending_count(), 0);
+
+        // 总长度不一致的多个最后分片
+        let mut decoder = UdpDecoder::new();
+        assert!(decoder.decode(LINKTYPE_RAW, 0, &frames[2]).is_none());
+        assert!(decoder
+            .decode(LINKTYPE_RAW, 1, &fragment(1, 24, false, &[0; 8]))
+            .is_none());
+        assert_eq!(decoder.pending_count(), 0);
+    }
+
+    #[test]
+    fn frame_builder_round_trips_fragmented_datagrams() {
+        let mut builder = UdpFrameBuilder::new(
+            SocketAddr::from((SOURCE, 5000)),
+            SocketAddr::from((DESTINATION, 6000)),
+        )
+        .unwrap();
+        let payload: Vec<u8> = (0..4000).map(|i| i as u8).collect();
+        let mut frames = builder.frames(&payload).unwrap();
+        assert!(frames.len() > 1);
+        frames.reverse();
+
+        let mut decoder = UdpDecoder::new();
+        let decoded: Vec<UdpDatagram> = frames
+            .iter()
+            .filter_map(|frame| decoder.decode(LINKTYPE_ETHERNET, 0, frame))
+            .collect();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].payload, payload);
+    }
+}
@@ -0,0 +1,861 @@
+//! 损坏数据文件修复
+//!
+//! 先按完整性校验结果找出损坏的文件，再逐包扫描：遇到无效数据包时向后逐字节查找下一个
+//! 包头字段合理、长度不超出文件、时间戳与前一个数据包相近且校验和匹配的位置重新同步，
+//! 有效数据包写入新文件，经 pcapfile-io 读取确认后替换原文件，丢弃的字节范围记入修复日志，
+//! 完成后重建数据集索引
+
+use log::{info, warn};
+use pcapfile_io::{Configuration, PcapReader, Read as _};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::dataset::format::{
+    DataFileWriter, FileHeader, PacketHeader, FILE_HEADER_SIZE, PACKET_HEADER_SIZE,
+};
+use crate::dataset::index::{data_files, DatasetIndex};
+use crate::dataset::verify::{verify_file, IssueKind};
+use crate::project::structure::{STATUS_DIR, TRASH_DIR};
+use crate::project::validator::ProjectValidator;
+use crate::state::task_state::CancelToken;
+use crate::types::{PlaybackError, Result};
+
+/// 重新同步时每次读入的字节数
+const RESYNC_WINDOW: usize = 64 * 1024;
+
+/// 修复选项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairOptions {
+    /// 相邻数据包时间戳允许的最大差值（纳秒），超出时视为无效数据
+    #[serde(default = "default_max_time_jump")]
+    pub max_time_jump: u64,
+    /// 原文件移到工程的 `.trash` 目录保留，为 `false` 时直接删除
+    #[serde(default = "default_keep_original")]
+    pub keep_original: bool,
+    /// 只分析不写入
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn default_max_time_jump() -> u64 {
+    24 * 3600 * 1_000_000_000
+}
+
+fn default_keep_original() -> bool {
+    true
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        Self {
+            max_time_jump: default_max_time_jump(),
+            keep_original: default_keep_original(),
+            dry_run: false,
+        }
+    }
+}
+
+/// 丢弃的字节范围
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedRange {
+    /// 在原文件中的偏移
+    pub offset: u64,
+    pub length: u64,
+    /// 范围起始处数据无效的原因
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+/// 单个文件的修复结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileRepairResult {
+    pub file_name: String,
+    pub original_size: u64,
+    pub repaired_size: u64,
+    /// 保留的数据包数
+    pub packets: u64,
+    pub dropped: Vec<DroppedRange>,
+    pub dropped_bytes: u64,
+    /// 是否已写入修复后的文件（预演时为 `false`）
+    pub repaired: bool,
+    /// 原文件的保留位置
+    pub backup_path: Option<String>,
+    /// 无法修复的原因
+    pub error: Option<String>,
+}
+
+/// 修复阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairStage {
+    Verifying,
+    Repairing,
+    Indexing,
+    Completed,
+}
+
+impl RepairStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RepairStage::Verifying => "verifying",
+            RepairStage::Repairing => "repairing",
+            RepairStage::Indexing => "indexing",
+            RepairStage::Completed => "completed",
+        }
+    }
+}
+
+/// 修复结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairSummary {
+    pub dataset_name: String,
+    /// 已校验的文件数
+    pub checked_files: u64,
+    /// 损坏的文件及其修复结果
+    pub files: Vec<FileRepairResult>,
+    pub index_path: Option<String>,
+    /// 修复日志路径
+    pub log_path: Option<String>,
+    pub dry_run: bool,
+    pub cancelled: bool,
+}
+
+/// 数据集修复器
+#[derive(Debug)]
+pub struct DatasetRepairer {
+    project_path: PathBuf,
+    dataset_name: String,
+    files: Vec<PathBuf>,
+    options: RepairOptions,
+}
+
+impl DatasetRepairer {
+    /// 修复数据集中的数据文件，`file_names` 不为空时只处理其中的文件
+    pub fn new<P: AsRef<Path>>(
+        project_path: P,
+        dataset_name: &str,
+        file_names: &[String],
+        options: RepairOptions,
+    ) -> Result<Self> {
+        ProjectValidator::validate_name(dataset_name)?;
+        let dataset_dir = project_path.as_ref().join(dataset_name);
+        if !dataset_dir.is_dir() {
+            return Err(PlaybackError::DatasetNotFound(dataset_name.to_string()));
+        }
+        if options.max_time_jump == 0 {
+            return Err(PlaybackError::ParseError(
+                "时间戳最大差值必须大于0".to_string(),
+            ));
+        }
+
+        let mut files = data_files(&dataset_dir)?;
+        if !file_names.is_empty() {
+            if let Some(missing) = file_names
+                .iter()
+                .find(|name| !files.iter().any(|f| f.ends_with(name.as_str())))
+            {
+                return Err(PlaybackError::FormatError(format!(
+                    "数据文件不存在: {}/{}",
+                    dataset_name, missing
+                )));
+            }
+            files.retain(|f| file_names.iter().any(|name| f.ends_with(name)));
+        }
+
+        Ok(Self {
+            project_path: project_path.as_ref().to_path_buf(),
+            dataset_name: dataset_name.to_string(),
+            files,
+            options,
+        })
+    }
+
+    /// 执行修复，`progress` 参数依次为阶段、已处理文件数、文件总数和当前文件
+    pub fn repair<F>(&self, cancel: &CancelToken, mut progress: F) -> Result<RepairSummary>
+    where
+        F: FnMut(RepairStage, u64, u64, Option<&str>),
+    {
+        let total = self.files.len() as u64;
+        let mut summary = RepairSummary {
+            dataset_name: self.dataset_name.clone(),
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+        let backup_dir = self.project_path.join(TRASH_DIR).join(format!(
+            "{}_repair_{}",
+            self.dataset_name,
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        ));
+
+        for (i, path) in self.files.iter().enumerate() {
+            let item = path.to_string_lossy().to_string();
+            progress(RepairStage::Verifying, i as u64, total, Some(&item));
+            let report = verify_file(path, cancel);
+            if cancel.is_cancelled() {
+                summary.cancelled = true;
+                break;
+            }
+            summary.checked_files += 1;
+            if !report.is_damaged() {
+                continue;
+            }
+
+            progress(RepairStage::Repairing, i as u64, total, Some(&item));
+            let Some(mut result) = repair_file(path, &self.options, cancel)? else {
+                summary.cancelled = true;
+                break;
+            };
+            if result.error.is_none() && !self.options.dry_run {
+                self.replace_original(path, &backup_dir, &mut result)?;
+            }
+            summary.files.push(result);
+        }
+
+        let repaired = summary.files.iter().any(|f| f.repaired);
+        if repaired {
+            progress(RepairStage::Indexing, total, total, None);
+            let index_path = DatasetIndex::rebuild(self.project_path.join(&self.dataset_name))?;
+            summary.index_path = Some(index_path.to_string_lossy().to_string());
+            summary.log_path = Some(self.write_log(&summary)?.to_string_lossy().to_string());
+        }
+
+        info!(
+            "数据集 '{}' 修复完成: 校验 {} 个文件, 损坏 {} 个, 已修复 {} 个",
+            self.dataset_name,
+            summary.checked_files,
+            summary.files.len(),
+            summary.files.iter().filter(|f| f.repaired).count()
+        );
+        progress(RepairStage::Completed, total, total, None);
+        Ok(summary)
+    }
+
+    /// 用修复后的文件替换原文件，原文件按工程目录结构移到回收目录或直接删除
+    ///
+    /// 原文件先移开再放入修复后的文件，放入失败时原文件移回原处
+    fn replace_original(
+        &self,
+        path: &Path,
+        backup_dir: &Path,
+        result: &mut FileRepairResult,
+    ) -> Result<()> {
+        let repaired_path = repaired_path(path);
+        let held_path = if self.options.keep_original {
+            let backup_path = backup_dir.join(&self.dataset_name).join(&result.file_name);
+            if let Some(parent) = backup_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            backup_path
+        } else {
+            with_suffix(path, ".original")
+        };
+        fs::rename(path, &held_path)?;
+
+        if let Err(e) = fs::rename(&repaired_path, path) {
+            if let Err(e) = fs::rename(&held_path, path) {
+                warn!("还原原文件失败 {:?} -> {:?}: {}", held_path, path, e);
+            }
+            return Err(e.into());
+        }
+
+        if self.options.keep_original {
+            result.backup_path = Some(held_path.to_string_lossy().to_string());
+        } else if let Err(e) = fs::remove_file(&held_path) {
+            warn!("删除原文件失败 {:?}: {}", held_path, e);
+        }
+        result.repaired = true;
+        Ok(())
+    }
+
+    /// 修复日志写入 `.status/<数据集>/repair_<时间>.json`
+    fn write_log(&self, summary: &RepairSummary) -> Result<PathBuf> {
+        let dir = self.project_path.join(STATUS_DIR).join(&self.dataset_name);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "repair_{}.json",
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        ));
+        fs::write(&path, serde_json::to_string_pretty(summary)?)?;
+        Ok(path)
+    }
+}
+
+/// 修复后文件的临时路径
+fn repaired_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".repaired")
+}
+
+/// 在文件名后追加后缀
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 用 pcapfile-io 读取修复后的文件，确认数据包数与写入的一致
+fn validate_repaired(path: &Path, expected_packets: u64) -> Result<()> {
+    let unreadable = |e: &dyn std::fmt::Display| {
+        PlaybackError::FormatError(format!("修复后的文件无法读取 {:?}: {}", path, e))
+    };
+    let mut reader = PcapReader::new(path, Configuration::default()).map_err(|e| unreadable(&e))?;
+    let mut packets = 0u64;
+    while reader.read_packet().map_err(|e| unreadable(&e))?.is_some() {
+        packets += 1;
+    }
+    if packets != expected_packets {
+        return Err(PlaybackError::FormatError(format!(
+            "修复后的文件有 {} 个数据包，应为 {}: {:?}",
+            packets, expected_packets, path
+        )));
+    }
+    Ok(())
+}
+
+/// 扫描并修复单个文件，有效数据包写入临时文件，取消时返回 `None`
+///
+/// 文件中找不到任何有效数据包且文件头也无效时不写入，写入的文件读取确认失败时删除，
+/// 两种情况都在结果中记录错误
+pub fn repair_file(
+    path: &Path,
+    options: &RepairOptions,
+    cancel: &CancelToken,
+) -> Result<Option<FileRepairResult>> {
+    let mut scanner = PacketScanner::open(path, options.max_time_jump)?;
+    let mut result = FileRepairResult {
+        file_name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        original_size: scanner.file_size,
+        repaired_size: FILE_HEADER_SIZE,
+        ..Default::default()
+    };
+
+    let file_header = scanner.file_header();
+    if file_header.is_none() {
+        result.dropped.push(DroppedRange {
+            offset: 0,
+            length: FILE_HEADER_SIZE.min(scanner.file_size),
+            kind: IssueKind::FileHeader,
+            message: "文件头无效，使用默认文件头".to_string(),
+        });
+    }
+
+    let mut writer = if options.dry_run {
+        None
+    } else {
+        let repaired_path = repaired_path(path);
+        if repaired_path.exists() {
+            fs::remove_file(&repaired_path)?;
+        }
+        Some(DataFileWriter::create_with_header(
+            &repaired_path,
+            &file_header.unwrap_or_default(),
+        )?)
+    };
+
+    let scanned = scan_packets(&mut scanner, &mut result, writer.as_mut(), cancel);
+    if let Err(e) = scanned {
+        if let Some(writer) = writer {
+            writer.discard();
+        }
+        return Err(e);
+    }
+    if cancel.is_cancelled() {
+        if let Some(writer) = writer {
+            writer.discard();
+        }
+        return Ok(None);
+    }
+
+    result.dropped_bytes = result.dropped.iter().map(|r| r.length).sum();
+    if result.packets == 0 && file_header.is_none() {
+        result.repaired_size = 0;
+        result.error = Some("未找到有效的文件头和数据包，无法修复".to_string());
+        if let Some(writer) = writer {
+            writer.discard();
+        }
+    } else if let Some(writer) = writer {
+        let repaired_path = writer.finish()?;
+        if let Err(e) = validate_repaired(&repaired_path, result.packets) {
+            warn!("{}", e);
+            fs::remove_file(&repaired_path)?;
+            result.error = Some(e.to_string());
+        }
+    }
+
+    for range in &result.dropped {
+        warn!(
+            "{:?} 丢弃偏移 {} 起 {} 字节: {}",
+            path, range.offset, range.length, range.message
+        );
+    }
+    Ok(Some(result))
+}
+
+/// 逐包扫描，无效数据处重新同步
+fn scan_packets(
+    scanner: &mut PacketScanner,
+    result: &mut FileRepairResult,
+    mut writer: Option<&mut DataFileWriter>,
+    cancel: &CancelToken,
+) -> Result<()> {
+    let mut offset = FILE_HEADER_SIZE;
+    while offset < scanner.file_size && !cancel.is_cancelled() {
+        match scanner.packet_at(offset)? {
+            Ok(header) => {
+                if let Some(writer) = writer.as_mut() {
+                    writer.write_packet(header.timestamp_ns(), &scanner.data)?;
+                }
+                scanner.last_timestamp = Some(header.timestamp_ns());
+                let size = PACKET_HEADER_SIZE + header.packet_length as u64;
+                result.packets += 1;
+                result.repaired_size += size;
+                offset += size;
+            }
+            Err((kind, message)) => {
+                let next = scanner.resync(offset + 1, cancel)?;
+                let end = next.unwrap_or(scanner.file_size);
+                result.dropped.push(DroppedRange {
+                    offset,
+                    length: end - offset,
+                    kind,
+                    message,
+                });
+                offset = end;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 按偏移检查数据包的扫描器
+struct PacketScanner {
+    reader: BufReader<File>,
+    /// 读取器当前位置，与目标偏移相同时不再定位以保留缓冲
+    position: u64,
+    file_size: u64,
+    max_time_jump: u64,
+    last_timestamp: Option<u64>,
+    /// 最近一次检查通过的数据包内容
+    data: Vec<u8>,
+}
+
+impl PacketScanner {
+    fn open(path: &Path, max_time_jump: u64) -> Result<Self> {
+        let file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        Ok(Self {
+            reader: BufReader::new(file),
+            position: 0,
+            file_size,
+            max_time_jump,
+            last_timestamp: None,
+            data: Vec::new(),
+        })
+    }
+
+    /// 读取文件头，无效时返回 `None`
+    fn file_header(&mut self) -> Option<FileHeader> {
+        self.seek(0).ok()?;
+        let header = FileHeader::read_from(&mut self.reader).ok()?;
+        self.position = FILE_HEADER_SIZE;
+        header.validate().ok().map(|_| header)
+    }
+
+    fn seek(&mut self, offset: u64) -> Result<()> {
+        if offset != self.position {
+            self.reader.seek(SeekFrom::Start(offset))?;
+            self.position = offset;
+        }
+        Ok(())
+    }
+
+    /// 包头字段是否合理：纳秒和长度有效、内容不超出文件、时间戳非零且与前一个数据包相近
+    fn plausible(
+        &self,
+        header: &PacketHeader,
+        offset: u64,
+    ) -> std::result::Result<(), (IssueKind, String)> {
+        header
+            .validate()
+            .map_err(|e| (IssueKind::PacketHeader, e.to_string()))?;
+
+        let end = offset + PACKET_HEADER_SIZE + header.packet_length as u64;
+        if end > self.file_size {
+            return Err((
+                IssueKind::Truncated,
+                format!(
+                    "数据内容不完整: 应为 {} 字节，文件剩余 {} 字节",
+                    header.packet_length,
+                    self.file_size.saturating_sub(offset + PACKET_HEADER_SIZE)
+                ),
+            ));
+        }
+
+        let timestamp = header.timestamp_ns();
+        if timestamp == 0 {
+            return Err((IssueKind::PacketHeader, "时间戳为0".to_string()));
+        }
+        if let Some(last) = self.last_timestamp {
+            if timestamp.abs_diff(last) > self.max_time_jump {
+                return Err((
+                    IssueKind::TimestampJump,
+                    format!("时间戳 {} 与前一个数据包 {} 相差过大", timestamp, last),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查偏移处是否为有效数据包，有效时内容读入 `data`
+    fn packet_at(
+        &mut self,
+        offset: u64,
+    ) -> Result<std::result::Result<PacketHeader, (IssueKind, String)>> {
+        if offset + PACKET_HEADER_SIZE > self.file_size {
+            return Ok(Err((
+                IssueKind::Truncated,
+                format!("包头不完整: 文件剩余 {} 字节", self.file_size - offset),
+            )));
+        }
+
+        self.seek(offset)?;
+        let Some(header) = PacketHeader::read_from(&mut self.reader)? else {
+            return Ok(Err((IssueKind::Truncated, "包头不完整".to_string())));
+        };
+        self.position += PACKET_HEADER_SIZE;
+        if let Err(reason) = self.plausible(&header, offset) {
+            return Ok(Err(reason));
+        }
+
+        self.data.resize(header.packet_length as usize, 0);
+        self.reader.read_exact(&mut self.data)?;
+        self.position += header.packet_length as u64;
+
+        let checksum = crc32fast::hash(&self.data);
+        if checksum != header.checksum {
+            return Ok(Err((
+                IssueKind::ChecksumMismatch,
+                format!(
+                    "校验和不匹配: 记录 {:08X}，实际 {:08X}",
+                    header.checksum, checksum
+                ),
+            )));
+        }
+        Ok(Ok(header))
+    }
+
+    /// 从 `from` 起查找下一个有效数据包的偏移，没有时返回 `None`
+    fn resync(&mut self, from: u64, cancel: &CancelToken) -> Result<Option<u64>> {
+        let header_size = PACKET_HEADER_SIZE as usize;
+        let mut window = vec![0u8; RESYNC_WINDOW];
+        let mut start = from;
+
+        while start + PACKET_HEADER_SIZE <= self.file_size && !cancel.is_cancelled() {
+            let len = (self.file_size - start).min(RESYNC_WINDOW as u64) as usize;
+            self.seek(start)?;
+            self.reader.read_exact(&mut window[..len])?;
+            self.position += len as u64;
+
+            for i in 0..=len - header_size {
+                let header = PacketHeader::read_from(&mut &window[i..i + header_size])?;
+                let offset = start + i as u64;
+                // 空数据包的校验和恒为0，无法据此确认同步位置
+                let candidate = header
+                    .is_some_and(|h| h.packet_length > 0 && self.plausible(&h, offset).is_ok());
+                if candidate && self.packet_at(offset)?.is_ok() {
+                    return Ok(Some(offset));
+                }
+            }
+            // 相邻窗口重叠一个包头长度减一，避免漏掉跨窗口的包头
+            start += (len - header_size + 1) as u64;
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::PacketReader;
+    use crate::dataset::index::PIDX_FILE_NAME;
+    use crate::dataset::naming::data_file_name;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    /// 按文件格式拼出文件内容，`garbage` 为 (插在第几个数据包之前, 内容)
+    fn file_bytes(packets: &[(u64, &[u8])], garbage: Option<(usize, &[u8])>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        FileHeader::default().write_to(&mut bytes).unwrap();
+        for (i, (timestamp, data)) in packets.iter().enumerate() {
+            if let Some((_, junk)) = garbage.filter(|(at, _)| *at == i) {
+                bytes.extend_from_slice(junk);
+            }
+            PacketHeader::new(*timestamp, data)
+                .write_to(&mut bytes)
+                .unwrap();
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    fn packets() -> Vec<(u64, &'static [u8])> {
+        vec![
+            (BASE_TIMESTAMP, b"first".as_slice()),
+            (BASE_TIMESTAMP + 1_000, b"second".as_slice()),
+            (BASE_TIMESTAMP + 2_000, b"third".as_slice()),
+        ]
+    }
+
+    /// 数据包在文件中的偏移
+    fn packet_offset(packets: &[(u64, &[u8])], index: usize) -> u64 {
+        FILE_HEADER_SIZE
+            + packets[..index]
+                .iter()
+                .map(|(_, data)| PACKET_HEADER_SIZE + data.len() as u64)
+                .sum::<u64>()
+    }
+
+    fn read_packets(path: &Path) -> Vec<(u64, Vec<u8>)> {
+        let mut reader = PacketReader::open(path).unwrap();
+        let mut packets = Vec::new();
+        while let Some(packet) = reader.next_packet().unwrap() {
+            packets.push((packet.header.timestamp_ns(), packet.data));
+        }
+        packets
+    }
+
+    fn dry_run() -> RepairOptions {
+        RepairOptions {
+            dry_run: true,
+            ..Default::default()
+        }
+    }
+
+    fn repair(path: &Path, options: &RepairOptions) -> FileRepairResult {
+        repair_file(path, options, &CancelToken::new())
+            .unwrap()
+            .unwrap()
+    }
+
+    /// 在工程的 `radar` 数据集中写入数据文件，返回文件路径
+    fn dataset_file(project: &Path, bytes: &[u8]) -> PathBuf {
+        let dir = project.join("radar");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(data_file_name(BASE_TIMESTAMP));
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn resync_finds_next_valid_packet() {
+        let dir = tempfile::tempdir().unwrap();
+        let packets = packets();
+        let junk = [0xFFu8; 37];
+        let path = dir.path().join("data.pcap");
+        fs::write(&path, file_bytes(&packets, Some((1, &junk)))).unwrap();
+
+        let mut scanner = PacketScanner::open(&path, default_max_time_jump()).unwrap();
+        let second = packet_offset(&packets, 1) + junk.len() as u64;
+        let junk_start = packet_offset(&packets, 1);
+        assert_eq!(
+            scanner.resync(junk_start, &CancelToken::new()).unwrap(),
+            Some(second)
+        );
+        // 最后一个数据包之后没有可同步的位置
+        let third = second + PACKET_HEADER_SIZE + packets[1].1.len() as u64;
+        assert_eq!(
+            scanner.resync(third + 1, &CancelToken::new()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn drops_garbage_run_in_the_middle() {
+        let dir = tempfile::tempdir().unwrap();
+        let packets = packets();
+        let junk = [0xFFu8; 37];
+        let path = dir.path().join("data.pcap");
+        fs::write(&path, file_bytes(&packets, Some((1, &junk)))).unwrap();
+
+        let result = repair(&path, &RepairOptions::default());
+
+        assert!(result.error.is_none());
+        assert_eq!(result.packets, 3);
+        assert_eq!(result.dropped.len(), 1);
+        assert_eq!(result.dropped[0].offset, packet_offset(&packets, 1));
+        assert_eq!(result.dropped[0].length, junk.len() as u64);
+        assert_eq!(result.dropped[0].kind, IssueKind::PacketHeader);
+        assert_eq!(result.dropped_bytes, junk.len() as u64);
+
+        let repaired = read_packets(&repaired_path(&path));
+        let expected: Vec<(u64, Vec<u8>)> = packets.iter().map(|(t, d)| (*t, d.to_vec())).collect();
+        assert_eq!(repaired, expected);
+        assert_eq!(
+            result.repaired_size,
+            fs::metadata(repaired_path(&path)).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn drops_truncated_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let packets = packets();
+        let mut bytes = file_bytes(&packets, None);
+        bytes.truncate(bytes.len() - 2);
+        let path = dir.path().join("data.pcap");
+        fs::write(&path, &bytes).unwrap();
+
+        let result = repair(&path, &RepairOptions::default());
+
+        assert_eq!(result.packets, 2);
+        let last = packet_offset(&packets, 2);
+        assert_eq!(result.dropped.len(), 1);
+        assert_eq!(result.dropped[0].offset, last);
+        assert_eq!(result.dropped[0].length, bytes.len() as u64 - last);
+        assert_eq!(result.dropped[0].kind, IssueKind::Truncated);
+        assert_eq!(read_packets(&repaired_path(&path)).len(), 2);
+
+        // 只剩半个包头
+        let mut bytes = file_bytes(&packets, None);
+        bytes.extend_from_slice(&[1, 2, 3]);
+        fs::write(&path, &bytes).unwrap();
+        let result = repair(&path, &dry_run());
+        assert_eq!(result.packets, 3);
+        assert_eq!(result.dropped[0].length, 3);
+        assert_eq!(result.dropped[0].kind, IssueKind::Truncated);
+    }
+
+    #[test]
+    fn replaces_invalid_file_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let packets = packets();
+        let mut bytes = file_bytes(&packets, None);
+        bytes[..4].copy_from_slice(&[0; 4]);
+        let path = dir.path().join("data.pcap");
+        fs::write(&path, &bytes).unwrap();
+
+        let result = repair(&path, &RepairOptions::default());
+
+        assert!(result.error.is_none());
+        assert_eq!(result.packets, 3);
+        assert_eq!(result.dropped.len(), 1);
+        assert_eq!(result.dropped[0].offset, 0);
+        assert_eq!(result.dropped[0].kind, IssueKind::FileHeader);
+        assert_eq!(read_packets(&repaired_path(&path)).len(), 3);
+    }
+
+    #[test]
+    fn gives_up_without_header_or_packets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.pcap");
+        fs::write(&path, [0xFFu8; 100]).unwrap();
+
+        let result = repair(&path, &RepairOptions::default());
+
+        assert_eq!(result.packets, 0);
+        assert!(result.error.is_some());
+        assert!(!repaired_path(&path).exists());
+    }
+
+    #[test]
+    fn dry_run_leaves_files_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let packets = packets();
+        let bytes = file_bytes(&packets, Some((2, &[0xFF; 20])));
+        let path = dataset_file(dir.path(), &bytes);
+
+        let summary = DatasetRepairer::new(dir.path(), "radar", &[], dry_run())
+            .unwrap()
+            .repair(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+
+        assert!(summary.dry_run);
+        assert_eq!(summary.files.len(), 1);
+        assert_eq!(summary.files[0].packets, 3);
+        assert!(!summary.files[0].repaired);
+        assert!(summary.index_path.is_none() && summary.log_path.is_none());
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+        assert!(!repaired_path(&path).exists());
+        assert!(!dir.path().join(TRASH_DIR).exists());
+        assert!(!path.with_file_name(PIDX_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn repair_keeps_original_in_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let packets = packets();
+        let bytes = file_bytes(&packets, Some((1, &[0xFF; 20])));
+        let path = dataset_file(dir.path(), &bytes);
+        // 完好的文件不处理
+        let intact = file_bytes(&[(BASE_TIMESTAMP + 10_000, b"ok")], None);
+        let intact_path = path.with_file_name(data_file_name(BASE_TIMESTAMP + 10_000));
+        fs::write(&intact_path, &intact).unwrap();
+
+        let summary = DatasetRepairer::new(dir.path(), "radar", &[], RepairOptions::default())
+            .unwrap()
+            .repair(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+
+        assert_eq!(summary.checked_files, 2);
+        assert_eq!(summary.files.len(), 1);
+        let result = &summary.files[0];
+        assert!(result.repaired);
+        assert_eq!(read_packets(&path).len(), 3);
+        assert_eq!(fs::read(&intact_path).unwrap(), intact);
+        assert!(!repaired_path(&path).exists());
+
+        let backup = PathBuf::from(result.backup_path.as_ref().unwrap());
+        assert!(backup.starts_with(dir.path().join(TRASH_DIR)));
+        assert!(backup.ends_with(Path::new("radar").join(&result.file_name)));
+        assert_eq!(fs::read(&backup).unwrap(), bytes);
+
+        assert!(Path::new(summary.index_path.as_ref().unwrap()).is_file());
+        let log_path = PathBuf::from(summary.log_path.as_ref().unwrap());
+        assert!(log_path.starts_with(dir.path().join(STATUS_DIR).join("radar")));
+    }
+
+    #[test]
+    fn repair_without_backup_removes_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let packets = packets();
+        let mut bytes = file_bytes(&packets, None);
+        bytes.truncate(bytes.len() - 1);
+        let path = dataset_file(dir.path(), &bytes);
+        let options = RepairOptions {
+            keep_original: false,
+            ..Default::default()
+        };
+
+        let summary = DatasetRepairer::new(dir.path(), "radar", &[], options)
+            .unwrap()
+            .repair(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+
+        assert!(summary.files[0].repaired);
+        assert!(summary.files[0].backup_path.is_none());
+        assert_eq!(read_packets(&path).len(), 2);
+        assert!(!with_suffix(&path, ".original").exists());
+        assert!(!dir.path().join(TRASH_DIR).exists());
+    }
+
+    #[test]
+    fn rejects_unknown_files_and_zero_time_jump() {
+        let dir = tempfile::tempdir().unwrap();
+        dataset_file(dir.path(), &file_bytes(&packets(), None));
+
+        let missing = vec!["data_missing.pcap".to_string()];
+        assert!(DatasetRepairer::new(dir.path(), "radar", &missing, dry_run()).is_err());
+        let options = RepairOptions {
+            max_time_jump: 0,
+            ..Default::default()
+        };
+        assert!(DatasetRepairer::new(dir.path(), "radar", &[], options).is_err());
+        assert!(matches!(
+            DatasetRepairer::new(dir.path(), "missing", &[], dry_run()),
+            Err(PlaybackError::DatasetNotFound(_))
+        ));
+    }
+}
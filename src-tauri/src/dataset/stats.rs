@@ -0,0 +1,840 @@
+//! 数据集统计
+//!
+//! 按数据文件统计包长分布、按时间间隔的速率以及超过阈值的数据中断，再汇总到数据集。
+//! 逐文件结果缓存在 `.status/<数据集>/stats.json`，文件大小和修改时间不变时直接复用，
+//! 重新打开工程时无需再次扫描，打开工程的数据文件扫描同样复用其中的文件信息（见 [`CachedFileInfos`]）。
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::dataset::format::PacketReader;
+use crate::dataset::index::{data_files, DatasetIndex};
+use crate::dataset::info::{
+    dataset_info, file_index_entry, file_metadata_info, read_dataset_index,
+};
+use crate::project::structure::STATUS_DIR;
+use crate::project::validator::ProjectValidator;
+use crate::types::{DatasetInfo, FileInfo, PlaybackError, Result};
+
+/// 统计缓存文件名
+pub const STATS_CACHE_FILE_NAME: &str = "stats.json";
+
+/// 包长分布的分桶数（按2的幂划分，最大桶覆盖10MB）
+const SIZE_BUCKETS: usize = 25;
+
+/// 统计选项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsOptions {
+    /// 速率统计的时间间隔（纳秒）
+    #[serde(default = "default_rate_interval")]
+    pub rate_interval: u64,
+    /// 相邻数据包间隔超过该值（纳秒）时记为数据中断
+    #[serde(default = "default_gap_threshold")]
+    pub gap_threshold: u64,
+}
+
+fn default_rate_interval() -> u64 {
+    1_000_000_000
+}
+
+fn default_gap_threshold() -> u64 {
+    1_000_000_000
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self {
+            rate_interval: default_rate_interval(),
+            gap_threshold: default_gap_threshold(),
+        }
+    }
+}
+
+impl StatsOptions {
+    fn validate(&self) -> Result<()> {
+        if self.rate_interval == 0 || self.gap_threshold == 0 {
+            return Err(PlaybackError::ParseError(
+                "统计间隔和中断阈值必须大于0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 包长分布桶，包含 `min_size..=max_size` 的数据包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeBucket {
+    pub min_size: u32,
+    pub max_size: u32,
+    pub count: u64,
+}
+
+/// 一个时间间隔内的速率
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatePoint {
+    /// 间隔起始时间（纳秒，按间隔对齐）
+    pub timestamp: u64,
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+/// 数据中断
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gap {
+    /// 中断前最后一个数据包的时间戳（纳秒）
+    pub start_timestamp: u64,
+    /// 中断后第一个数据包的时间戳（纳秒）
+    pub end_timestamp: u64,
+    pub duration: u64,
+    /// 中断后第一个数据包所在的文件
+    pub file_name: String,
+}
+
+/// 数据文件统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileStats {
+    pub info: FileInfo,
+    /// 数据内容总字节数
+    pub total_bytes: u64,
+    pub min_packet_size: Option<u32>,
+    pub max_packet_size: Option<u32>,
+    pub size_histogram: Vec<SizeBucket>,
+    pub rates: Vec<RatePoint>,
+    pub gaps: Vec<Gap>,
+}
+
+/// 数据集统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatasetStats {
+    pub info: DatasetInfo,
+    pub files: Vec<FileInfo>,
+    pub total_bytes: u64,
+    pub min_packet_size: Option<u32>,
+    pub max_packet_size: Option<u32>,
+    pub size_histogram: Vec<SizeBucket>,
+    pub rates: Vec<RatePoint>,
+    /// 文件内及相邻文件之间的数据中断（按时间排序）
+    pub gaps: Vec<Gap>,
+}
+
+/// 统计缓存
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StatsCache {
+    options: StatsOptions,
+    files: Vec<CachedFileStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedFileStats {
+    size: u64,
+    /// 修改时间（自UNIX纪元的纳秒数）
+    modified: u64,
+    stats: FileStats,
+}
+
+/// 统计服务
+#[derive(Debug, Clone)]
+pub struct StatsService {
+    project_path: PathBuf,
+}
+
+impl StatsService {
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Self {
+        Self {
+            project_path: project_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// 统计数据集，未变化的文件使用缓存结果
+    pub fn dataset_stats(
+        &self,
+        dataset_name: &str,
+        options: &StatsOptions,
+    ) -> Result<DatasetStats> {
+        options.validate()?;
+        let dataset_dir = self.dataset_dir(dataset_name)?;
+        let index = read_dataset_index(&dataset_dir);
+        let mut cache = self.load_cache(dataset_name, options);
+
+        let mut changed = false;
+        let mut files = Vec::new();
+        for path in data_files(&dataset_dir)? {
+            let (stats, computed) = cached_or_compute(&mut cache, &path, index.as_ref(), options);
+            changed |= computed;
+            files.push(stats);
+        }
+
+        // 删除的文件不再保留缓存
+        let count = cache.files.len();
+        cache.files.retain(|c| {
+            files
+                .iter()
+                .any(|f| f.info.file_name == c.stats.info.file_name)
+        });
+        if changed || cache.files.len() != count {
+            self.save_cache(dataset_name, &cache);
+        }
+
+        let file_infos: Vec<FileInfo> = files.iter().map(|f| f.info.clone()).collect();
+        let info = dataset_info(dataset_name, &dataset_dir, &file_infos, index.is_some())?;
+        Ok(merge_file_stats(info, files, options))
+    }
+
+    /// 统计单个数据文件
+    pub fn file_stats(
+        &self,
+        dataset_name: &str,
+        file_name: &str,
+        options: &StatsOptions,
+    ) -> Result<FileStats> {
+        options.validate()?;
+        let dataset_dir = self.dataset_dir(dataset_name)?;
+        let path = data_files(&dataset_dir)?
+            .into_iter()
+            .find(|p| p.file_name().is_some_and(|n| n == file_name))
+            .ok_or_else(|| {
+                PlaybackError::FormatError(format!(
+                    "数据文件不存在: {}/{}",
+                    dataset_name, file_name
+                ))
+            })?;
+
+        let index = read_dataset_index(&dataset_dir);
+        let mut cache = self.load_cache(dataset_name, options);
+        let (stats, computed) = cached_or_compute(&mut cache, &path, index.as_ref(), options);
+        if computed {
+            self.save_cache(dataset_name, &cache);
+        }
+        Ok(stats)
+    }
+
+    fn dataset_dir(&self, dataset_name: &str) -> Result<PathBuf> {
+        ProjectValidator::validate_name(dataset_name)?;
+        let dataset_dir = self.project_path.join(dataset_name);
+        if !dataset_dir.is_dir() {
+            return Err(PlaybackError::DatasetNotFound(dataset_name.to_string()));
+        }
+        Ok(dataset_dir)
+    }
+
+    fn cache_path(&self, dataset_name: &str) -> Result<PathBuf> {
+        ProjectValidator::validate_name(dataset_name)?;
+        Ok(self
+            .project_path
+            .join(STATUS_DIR)
+            .join(dataset_name)
+            .join(STATS_CACHE_FILE_NAME))
+    }
+
+    /// 读取缓存，缓存不存在、无法解析或统计选项不同时返回空缓存
+    fn load_cache(&self, dataset_name: &str, options: &StatsOptions) -> StatsCache {
+        let empty = || StatsCache {
+            options: options.clone(),
+            files: Vec::new(),
+        };
+
+        let Ok(content) = self
+            .cache_path(dataset_name)
+            .and_then(|path| Ok(fs::read_to_string(path)?))
+        else {
+            return empty();
+        };
+        match serde_json::from_str::<StatsCache>(&content) {
+            Ok(cache) if cache.options == *options => cache,
+            Ok(_) => empty(),
+            Err(e) => {
+                warn!("统计缓存无法解析，重新统计 {}: {}", dataset_name, e);
+                empty()
+            }
+        }
+    }
+
+    /// 保存缓存，失败时只记录日志
+    fn save_cache(&self, dataset_name: &str, cache: &StatsCache) {
+        let path = match self.cache_path(dataset_name) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("保存统计缓存失败 {}: {}", dataset_name, e);
+                return;
+            }
+        };
+        let temp_path = path.with_extension("json.tmp");
+        let result: Result<()> = (|| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&temp_path, serde_json::to_vec(cache)?)?;
+            fs::rename(&temp_path, &path)?;
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            warn!("保存统计缓存失败 {:?}: {}", path, e);
+        }
+    }
+}
+
+/// 统计缓存中记录的文件信息，打开工程扫描数据文件时大小和修改时间不变的文件直接使用
+#[derive(Debug, Default)]
+pub struct CachedFileInfos(Vec<(u64, u64, FileInfo)>);
+
+impl CachedFileInfos {
+    /// 读取数据集的统计缓存，缓存不存在、无法解析或名称无效时为空；与统计选项无关
+    pub fn load<P: AsRef<Path>>(project_path: P, dataset_name: &str) -> Self {
+        let cache = StatsService::new(project_path)
+            .cache_path(dataset_name)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<StatsCache>(&content).ok());
+        match cache {
+            Some(cache) => Self(
+                cache
+                    .files
+                    .into_iter()
+                    .map(|c| (c.size, c.modified, c.stats.info))
+                    .collect(),
+            ),
+            None => Self::default(),
+        }
+    }
+
+    /// 文件大小和修改时间与缓存一致时返回缓存的文件信息
+    pub fn get(&self, path: &Path) -> Option<FileInfo> {
+        let file_name = path.file_name()?.to_string_lossy();
+        let (size, modified) = file_stamp(path);
+        self.0
+            .iter()
+            .find(|(s, m, info)| info.file_name == file_name && *s == size && *m == modified)
+            .map(|(_, _, info)| FileInfo {
+                file_path: path.to_string_lossy().to_string(),
+                ..info.clone()
+            })
+    }
+}
+
+/// 文件大小和修改时间与缓存一致时返回缓存结果，否则重新统计并更新缓存；
+/// 第二个返回值表示是否重新统计
+fn cached_or_compute(
+    cache: &mut StatsCache,
+    path: &Path,
+    index: Option<&DatasetIndex>,
+    options: &StatsOptions,
+) -> (FileStats, bool) {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (size, modified) = file_stamp(path);
+
+    let cached = cache
+        .files
+        .iter()
+        .find(|c| c.stats.info.file_name == file_name);
+    if let Some(cached) = cached.filter(|c| c.size == size && c.modified == modified) {
+        return (cached.stats.clone(), false);
+    }
+
+    debug!("统计数据文件: {:?}", path);
+    let stats = compute_file_stats(path, index, options);
+    cache.files.retain(|c| c.stats.info.file_name != file_name);
+    cache.files.push(CachedFileStats {
+        size,
+        modified,
+        stats: stats.clone(),
+    });
+    (stats, true)
+}
+
+/// 文件大小和修改时间（纳秒），无法读取时为0
+fn file_stamp(path: &Path) -> (u64, u64) {
+    let Ok(metadata) = fs::metadata(path) else {
+        return (0, 0);
+    };
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos() as u64);
+    (metadata.len(), modified)
+}
+
+/// 统计单个数据文件，索引有效时直接使用索引中的包记录，否则只读取包头
+fn compute_file_stats(
+    path: &Path,
+    index: Option<&DatasetIndex>,
+    options: &StatsOptions,
+) -> FileStats {
+    let mut info = FileInfo {
+        is_valid: true,
+        ..file_metadata_info(path)
+    };
+    let mut accumulator = StatsAccumulator::new(options);
+
+    match index.and_then(|index| file_index_entry(index, &info.file_name, info.file_size)) {
+        Some(entry) => {
+            info.file_hash = Some(entry.hash.clone());
+            for packet in &entry.packets {
+                accumulator.add(&info.file_name, packet.timestamp_ns, packet.packet_size);
+            }
+        }
+        None => {
+            let result: Result<()> = (|| {
+                let mut reader = PacketReader::open(path)?;
+                while let Some((header, _)) = reader.skip_packet()? {
+                    accumulator.add(&info.file_name, header.timestamp_ns(), header.packet_length);
+                }
+                Ok(())
+            })();
+            if let Err(e) = result {
+                warn!("统计数据文件时遇到错误 {:?}: {}", path, e);
+                info.is_valid = false;
+            }
+        }
+    }
+
+    info.packet_count = accumulator.packets;
+    if accumulator.packets > 0 {
+        info.start_timestamp = accumulator.first_timestamp;
+        info.end_timestamp = accumulator.last_timestamp;
+    }
+    accumulator.into_file_stats(info)
+}
+
+/// 汇总各文件统计，补充相邻文件之间的数据中断
+fn merge_file_stats(
+    info: DatasetInfo,
+    files: Vec<FileStats>,
+    options: &StatsOptions,
+) -> DatasetStats {
+    let mut histogram = [0u64; SIZE_BUCKETS];
+    let mut rates: BTreeMap<u64, (u64, u64)> = BTreeMap::new();
+    let mut gaps = Vec::new();
+    let mut stats = DatasetStats {
+        info,
+        ..Default::default()
+    };
+
+    for file in &files {
+        stats.total_bytes += file.total_bytes;
+        stats.min_packet_size = min_option(stats.min_packet_size, file.min_packet_size);
+        stats.max_packet_size = stats.max_packet_size.max(file.max_packet_size);
+        for bucket in &file.size_histogram {
+            histogram[bucket_index(bucket.min_size)] += bucket.count;
+        }
+        for point in &file.rates {
+            let entry = rates.entry(point.timestamp).or_default();
+            entry.0 += point.packets;
+            entry.1 += point.bytes;
+        }
+        gaps.extend(file.gaps.iter().cloned());
+    }
+
+    // 相邻文件之间的中断
+    let mut ranges: Vec<(u64, u64, &str)> = files
+        .iter()
+        .filter_map(|f| {
+            Some((
+                f.info.start_timestamp?,
+                f.info.end_timestamp?,
+                f.info.file_name.as_str(),
+            ))
+        })
+        .collect();
+    ranges.sort();
+    let mut previous_end: Option<u64> = None;
+    for (start, end, file_name) in ranges {
+        if let Some(previous) = previous_end {
+            if start > previous && start - previous > options.gap_threshold {
+                gaps.push(Gap {
+                    start_timestamp: previous,
+                    end_timestamp: start,
+                    duration: start - previous,
+                    file_name: file_name.to_string(),
+                });
+            }
+        }
+        previous_end = Some(previous_end.map_or(end, |p| p.max(end)));
+    }
+    gaps.sort_by_key(|g| g.start_timestamp);
+
+    stats.size_histogram = histogram_buckets(&histogram);
+    stats.rates = rates
+        .into_iter()
+        .map(|(timestamp, (packets, bytes))| RatePoint {
+            timestamp,
+            packets,
+            bytes,
+        })
+        .collect();
+    stats.gaps = gaps;
+    stats.files = files.into_iter().map(|f| f.info).collect();
+    stats
+}
+
+/// 逐包累计统计
+struct StatsAccumulator<'a> {
+    options: &'a StatsOptions,
+    packets: u64,
+    total_bytes: u64,
+    min_size: Option<u32>,
+    max_size: Option<u32>,
+    first_timestamp: Option<u64>,
+    last_timestamp: Option<u64>,
+    previous_timestamp: Option<u64>,
+    histogram: [u64; SIZE_BUCKETS],
+    rates: BTreeMap<u64, (u64, u64)>,
+    gaps: Vec<Gap>,
+}
+
+impl<'a> StatsAccumulator<'a> {
+    fn new(options: &'a StatsOptions) -> Self {
+        Self {
+            options,
+            packets: 0,
+            total_bytes: 0,
+            min_size: None,
+            max_size: None,
+            first_timestamp: None,
+            last_timestamp: None,
+            previous_timestamp: None,
+            histogram: [0; SIZE_BUCKETS],
+            rates: BTreeMap::new(),
+            gaps: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, file_name: &str, timestamp: u64, size: u32) {
+        self.packets += 1;
+        self.total_bytes += size as u64;
+        self.min_size = min_option(self.min_size, Some(size));
+        self.max_size = self.max_size.max(Some(size));
+        self.first_timestamp = min_option(self.first_timestamp, Some(timestamp));
+        self.last_timestamp = self.last_timestamp.max(Some(timestamp));
+        self.histogram[bucket_index(size)] += 1;
+
+        let interval = timestamp - timestamp % self.options.rate_interval;
+        let entry = self.rates.entry(interval).or_default();
+        entry.0 += 1;
+        entry.1 += size as u64;
+
+        // 时间戳回退不计为中断
+        if let Some(previous) = self.previous_timestamp {
+            if timestamp > previous && timestamp - previous > self.options.gap_threshold {
+                self.gaps.push(Gap {
+                    start_timestamp: previous,
+                    end_timestamp: timestamp,
+                    duration: timestamp - previous,
+                    file_name: file_name.to_string(),
+                });
+            }
+        }
+        self.previous_timestamp = Some(timestamp);
+    }
+
+    fn into_file_stats(self, info: FileInfo) -> FileStats {
+        FileStats {
+            info,
+            total_bytes: self.total_bytes,
+            min_packet_size: self.min_size,
+            max_packet_size: self.max_size,
+            size_histogram: histogram_buckets(&self.histogram),
+            rates: self
+                .rates
+                .into_iter()
+                .map(|(timestamp, (packets, bytes))| RatePoint {
+                    timestamp,
+                    packets,
+                    bytes,
+                })
+                .collect(),
+            gaps: self.gaps,
+        }
+    }
+}
+
+/// 包长所在的分桶：0字节为第0桶，第 k 桶包含 `2^(k-1)..=2^k-1` 字节
+fn bucket_index(size: u32) -> usize {
+    ((u32::BITS - size.leading_zeros()) as usize).min(SIZE_BUCKETS - 1)
+}
+
+/// 非空分桶
+fn histogram_buckets(histogram: &[u64; SIZE_BUCKETS]) -> Vec<SizeBucket> {
+    histogram
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(k, count)| SizeBucket {
+            min_size: if k == 0 { 0 } else { 1 << (k - 1) },
+            max_size: if k == 0 { 0 } else { (1u32 << k) - 1 },
+            count: *count,
+        })
+        .collect()
+}
+
+fn min_option<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::{DataFileWriter, PacketHeader};
+    use crate::dataset::naming::data_file_name;
+    use std::io::Write;
+    use std::time::{Duration, SystemTime};
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+    const SECOND: u64 = 1_000_000_000;
+
+    fn write_data_file(dir: &Path, packets: &[(u64, &[u8])]) -> PathBuf {
+        fs::create_dir_all(dir).unwrap();
+        let mut writer = DataFileWriter::create(dir.join(data_file_name(packets[0].0))).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn cache_path(project: &Path) -> PathBuf {
+        project
+            .join(STATUS_DIR)
+            .join("radar")
+            .join(STATS_CACHE_FILE_NAME)
+    }
+
+    /// 改写缓存中各文件的总字节数，用于确认结果来自缓存
+    fn tamper_cache(project: &Path, total_bytes: u64) {
+        let path = cache_path(project);
+        let mut cache: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        for file in cache["files"].as_array_mut().unwrap() {
+            file["stats"]["total_bytes"] = total_bytes.into();
+        }
+        fs::write(&path, cache.to_string()).unwrap();
+    }
+
+    fn stats(project: &Path, options: &StatsOptions) -> DatasetStats {
+        StatsService::new(project)
+            .dataset_stats("radar", options)
+            .unwrap()
+    }
+
+    #[test]
+    fn bucket_bounds_cover_sizes() {
+        assert_eq!(bucket_index(0), 0);
+        assert_eq!(bucket_index(1), 1);
+        assert_eq!(bucket_index(2), 2);
+        assert_eq!(bucket_index(3), 2);
+        assert_eq!(bucket_index(4), 3);
+        assert_eq!(bucket_index(1023), 10);
+        assert_eq!(bucket_index(1024), 11);
+        assert_eq!(bucket_index(u32::MAX), SIZE_BUCKETS - 1);
+
+        // 每个分桶的边界与分桶规则一致，最大桶覆盖最大包长
+        let buckets = histogram_buckets(&[1; SIZE_BUCKETS]);
+        assert_eq!(buckets.len(), SIZE_BUCKETS);
+        for bucket in &buckets {
+            assert_eq!(bucket_index(bucket.min_size), bucket_index(bucket.max_size));
+        }
+        for pair in buckets.windows(2) {
+            assert_eq!(pair[0].max_size + 1, pair[1].min_size);
+        }
+        assert!(buckets.last().unwrap().max_size >= crate::dataset::format::MAX_PACKET_SIZE);
+    }
+
+    #[test]
+    fn histogram_and_rates_span_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = dir.path().join("radar");
+        write_data_file(
+            &dataset,
+            &[
+                (BASE_TIMESTAMP, b""),
+                (BASE_TIMESTAMP + 1, b"a"),
+                (BASE_TIMESTAMP + 2, b"bb"),
+            ],
+        );
+        write_data_file(
+            &dataset,
+            &[
+                (BASE_TIMESTAMP + SECOND / 2, b"ccc"),
+                (BASE_TIMESTAMP + SECOND, b"dddd"),
+            ],
+        );
+
+        let stats = stats(dir.path(), &StatsOptions::default());
+
+        assert_eq!(stats.info.total_packets, 5);
+        assert_eq!(stats.total_bytes, 10);
+        assert_eq!(
+            (stats.min_packet_size, stats.max_packet_size),
+            (Some(0), Some(4))
+        );
+        let buckets: Vec<(u32, u32, u64)> = stats
+            .size_histogram
+            .iter()
+            .map(|b| (b.min_size, b.max_size, b.count))
+            .collect();
+        assert_eq!(buckets, vec![(0, 0, 1), (1, 1, 1), (2, 3, 2), (4, 7, 1)]);
+
+        let interval = BASE_TIMESTAMP - BASE_TIMESTAMP % SECOND;
+        let rates: Vec<(u64, u64, u64)> = stats
+            .rates
+            .iter()
+            .map(|r| (r.timestamp, r.packets, r.bytes))
+            .collect();
+        assert_eq!(rates, vec![(interval, 4, 6), (interval + SECOND, 1, 4)]);
+        assert!(stats.gaps.is_empty());
+    }
+
+    #[test]
+    fn reports_gaps_within_and_between_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = dir.path().join("radar");
+        let first = write_data_file(
+            &dataset,
+            &[
+                (BASE_TIMESTAMP, b"a"),
+                (BASE_TIMESTAMP + SECOND / 2, b"a"),
+                (BASE_TIMESTAMP + 2 * SECOND, b"a"),
+                // 时间戳回退不计为中断
+                (BASE_TIMESTAMP + SECOND, b"a"),
+            ],
+        );
+        // 间隔恰好等于阈值不计为中断
+        let second = write_data_file(
+            &dataset,
+            &[
+                (BASE_TIMESTAMP + 5 * SECOND, b"a"),
+                (BASE_TIMESTAMP + 6 * SECOND, b"a"),
+            ],
+        );
+        let name = |path: &Path| path.file_name().unwrap().to_string_lossy().to_string();
+
+        let stats = stats(dir.path(), &StatsOptions::default());
+
+        let gaps: Vec<(u64, u64, u64, String)> = stats
+            .gaps
+            .iter()
+            .map(|g| {
+                (
+                    g.start_timestamp - BASE_TIMESTAMP,
+                    g.end_timestamp - BASE_TIMESTAMP,
+                    g.duration,
+                    g.file_name.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                (SECOND / 2, 2 * SECOND, 3 * SECOND / 2, name(&first)),
+                (2 * SECOND, 5 * SECOND, 3 * SECOND, name(&second)),
+            ]
+        );
+
+        // 阈值调大后都不是中断
+        let options = StatsOptions {
+            gap_threshold: 3 * SECOND,
+            ..Default::default()
+        };
+        assert!(self::stats(dir.path(), &options).gaps.is_empty());
+    }
+
+    #[test]
+    fn reuses_cache_until_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_data_file(&dir.path().join("radar"), &[(BASE_TIMESTAMP, b"abc")]);
+        let options = StatsOptions::default();
+
+        assert_eq!(stats(dir.path(), &options).total_bytes, 3);
+        assert!(cache_path(dir.path()).is_file());
+
+        // 文件未变化时使用缓存结果
+        tamper_cache(dir.path(), 100);
+        assert_eq!(stats(dir.path(), &options).total_bytes, 100);
+        let file_stats = StatsService::new(dir.path())
+            .file_stats("radar", &data_file_name(BASE_TIMESTAMP), &options)
+            .unwrap();
+        assert_eq!(file_stats.total_bytes, 100);
+
+        // 统计选项不同时不使用缓存
+        let other = StatsOptions {
+            rate_interval: SECOND / 10,
+            ..Default::default()
+        };
+        assert_eq!(stats(dir.path(), &other).total_bytes, 3);
+
+        // 修改时间变化
+        assert_eq!(stats(dir.path(), &options).total_bytes, 3);
+        tamper_cache(dir.path(), 100);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(stats(dir.path(), &options).total_bytes, 3);
+
+        // 文件大小变化
+        tamper_cache(dir.path(), 100);
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        PacketHeader::new(BASE_TIMESTAMP + 1, b"def")
+            .write_to(&mut file)
+            .unwrap();
+        file.write_all(b"def").unwrap();
+        assert_eq!(stats(dir.path(), &options).total_bytes, 6);
+    }
+
+    #[test]
+    fn drops_cache_of_removed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = dir.path().join("radar");
+        let first = write_data_file(&dataset, &[(BASE_TIMESTAMP, b"a")]);
+        write_data_file(&dataset, &[(BASE_TIMESTAMP + SECOND, b"b")]);
+        let options = StatsOptions::default();
+        stats(dir.path(), &options);
+
+        fs::remove_file(&first).unwrap();
+        let stats = stats(dir.path(), &options);
+
+        assert_eq!(stats.files.len(), 1);
+        let cache: StatsCache =
+            serde_json::from_str(&fs::read_to_string(cache_path(dir.path())).unwrap()).unwrap();
+        assert_eq!(cache.files.len(), 1);
+        assert_eq!(
+            cache.files[0].stats.info.file_name,
+            data_file_name(BASE_TIMESTAMP + SECOND)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_options_and_names() {
+        let dir = tempfile::tempdir().unwrap();
+        write_data_file(&dir.path().join("radar"), &[(BASE_TIMESTAMP, b"a")]);
+        let service = StatsService::new(dir.path());
+
+        let zero = StatsOptions {
+            gap_threshold: 0,
+            ..Default::default()
+        };
+        assert!(service.dataset_stats("radar", &zero).is_err());
+        assert!(service
+            .dataset_stats("../radar", &StatsOptions::default())
+            .is_err());
+        assert!(matches!(
+            service.dataset_stats("missing", &StatsOptions::default()),
+            Err(PlaybackError::DatasetNotFound(_))
+        ));
+        assert!(service
+            .file_stats("radar", "data_missing.pcap", &StatsOptions::default())
+            .is_err());
+    }
+}
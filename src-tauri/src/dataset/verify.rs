@@ -0,0 +1,473 @@
+//! 数据文件完整性校验
+//!
+//! 按文件协议检查文件头魔数和版本、数据包长度上限、每个数据包的CRC32校验和以及时间戳是否单调不减，
+//! 报告中列出有问题的数据包偏移。可校验单个文件、一个数据集或整个工程。
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+
+use crate::dataset::format::{
+    FileHeader, PacketHeader, FILE_HEADER_SIZE, MAX_PACKET_SIZE, PACKET_HEADER_SIZE,
+};
+use crate::dataset::index::data_files;
+use crate::state::task_state::CancelToken;
+use crate::types::{PlaybackError, Result};
+
+/// 每个文件最多记录的问题数，超出部分只计数
+pub const MAX_ISSUES_PER_FILE: usize = 1000;
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// 文件头魔数或版本无效
+    FileHeader,
+    /// 包头字段无效（纳秒越界或长度超过10MB）
+    PacketHeader,
+    /// 文件末尾的数据包不完整
+    Truncated,
+    /// CRC32校验和不匹配
+    ChecksumMismatch,
+    /// 时间戳早于前一个数据包
+    TimestampRegression,
+    /// 时间戳与前一个数据包相差过大（修复时判定）
+    TimestampJump,
+}
+
+/// 校验发现的问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyIssue {
+    pub kind: IssueKind,
+    /// 数据包头在文件中的偏移，文件头问题为0
+    pub offset: u64,
+    /// 数据包序号（从0开始）
+    pub packet_index: u64,
+    pub message: String,
+}
+
+/// 单个文件的校验结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileVerifyReport {
+    pub file_name: String,
+    pub file_path: String,
+    pub file_size: u64,
+    /// 已检查的数据包数
+    pub packets: u64,
+    /// 包头无效、内容不完整或校验和不匹配的数据包数
+    pub corrupt_packets: u64,
+    pub timestamp_regressions: u64,
+    pub issues: Vec<VerifyIssue>,
+    /// 超出记录上限而未列出的问题数
+    pub omitted_issues: u64,
+    pub first_timestamp: Option<u64>,
+    pub last_timestamp: Option<u64>,
+}
+
+impl FileVerifyReport {
+    /// 文件是否没有任何问题
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty() && self.omitted_issues == 0
+    }
+
+    /// 文件是否损坏（文件头无效或存在损坏的数据包），时间戳倒退不算损坏
+    pub fn is_damaged(&self) -> bool {
+        self.corrupt_packets > 0 || self.issues.iter().any(|i| i.kind == IssueKind::FileHeader)
+    }
+
+    /// 记录当前数据包的问题
+    fn add_issue(&mut self, kind: IssueKind, offset: u64, message: String) {
+        self.add_issue_at(kind, offset, self.packets, message);
+    }
+
+    fn add_issue_at(&mut self, kind: IssueKind, offset: u64, packet_index: u64, message: String) {
+        match kind {
+            IssueKind::PacketHeader
+            | IssueKind::Truncated
+            | IssueKind::ChecksumMismatch
+            | IssueKind::TimestampJump => self.corrupt_packets += 1,
+            IssueKind::TimestampRegression => self.timestamp_regressions += 1,
+            IssueKind::FileHeader => {}
+        }
+
+        if self.issues.len() < MAX_ISSUES_PER_FILE {
+            self.issues.push(VerifyIssue {
+                kind,
+                offset,
+                packet_index,
+                message,
+            });
+        } else {
+            self.omitted_issues += 1;
+        }
+    }
+}
+
+/// 数据集的校验结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatasetVerifyReport {
+    pub name: String,
+    pub files: Vec<FileVerifyReport>,
+}
+
+/// 校验报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub datasets: Vec<DatasetVerifyReport>,
+    pub total_files: u64,
+    pub total_packets: u64,
+    /// 有问题的文件数
+    pub invalid_files: u64,
+    pub corrupt_packets: u64,
+    pub timestamp_regressions: u64,
+    pub verified_time: String,
+    pub cancelled: bool,
+}
+
+/// 校验进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyStage {
+    Verifying,
+    Completed,
+}
+
+impl VerifyStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerifyStage::Verifying => "verifying",
+            VerifyStage::Completed => "completed",
+        }
+    }
+}
+
+/// 数据校验器
+#[derive(Debug)]
+pub struct DataVerifier {
+    /// 待校验的数据集及其数据文件
+    targets: Vec<(String, Vec<PathBuf>)>,
+}
+
+impl DataVerifier {
+    /// 校验工程下的全部数据集（跳过隐藏目录）
+    pub fn for_project<P: AsRef<Path>>(project_path: P) -> Result<Self> {
+        let mut names: Vec<String> = fs::read_dir(project_path.as_ref())?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+        names.sort();
+
+        let targets = names
+            .into_iter()
+            .map(|name| {
+                let files = data_files(project_path.as_ref().join(&name))?;
+                Ok((name, files))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { targets })
+    }
+
+    /// 校验单个数据集，`file_names` 不为空时只校验其中的数据文件
+    pub fn for_dataset<P: AsRef<Path>>(
+        project_path: P,
+        dataset_name: &str,
+        file_names: &[String],
+    ) -> Result<Self> {
+        let dataset_dir = project_path.as_ref().join(dataset_name);
+        if !dataset_dir.is_dir() {
+            return Err(PlaybackError::DatasetNotFound(dataset_name.to_string()));
+        }
+
+        let mut files = data_files(&dataset_dir)?;
+        if !file_names.is_empty() {
+            if let Some(missing) = file_names.iter().find(|name| {
+                !files
+                    .iter()
+                    .any(|f| f.file_name().is_some_and(|n| n == name.as_str()))
+            }) {
+                return Err(PlaybackError::FormatError(format!(
+                    "数据文件不存在: {}/{}",
+                    dataset_name, missing
+                )));
+            }
+            files.retain(|f| {
+                f.file_name()
+                    .is_some_and(|n| file_names.iter().any(|name| n == name.as_str()))
+            });
+        }
+
+        Ok(Self {
+            targets: vec![(dataset_name.to_string(), files)],
+        })
+    }
+
+    /// 执行校验，`progress` 参数依次为阶段、已校验文件数、文件总数和当前文件
+    pub fn verify<F>(&self, cancel: &CancelToken, mut progress: F) -> VerifyReport
+    where
+        F: FnMut(VerifyStage, u64, u64, Option<&str>),
+    {
+        let total = self.targets.iter().map(|(_, f)| f.len() as u64).sum();
+        let mut done = 0;
+        let mut report = VerifyReport::default();
+
+        'datasets: for (name, files) in &self.targets {
+            let mut dataset = DatasetVerifyReport {
+                name: name.clone(),
+                files: Vec::new(),
+            };
+            let mut previous_end: Option<u64> = None;
+
+            for path in files {
+                if cancel.is_cancelled() {
+                    report.datasets.push(dataset);
+                    report.cancelled = true;
+                    break 'datasets;
+                }
+                let item = path.to_string_lossy().to_string();
+                progress(VerifyStage::Verifying, done, total, Some(&item));
+
+                let mut file = verify_file(path, cancel);
+                // 文件之间按文件名顺序，首包不应早于前一个文件的末包
+                if let (Some(previous), Some(first)) = (previous_end, file.first_timestamp) {
+                    if first < previous {
+                        let message = format!(
+                            "首个数据包时间戳 {} 早于前一个文件的末包 {}",
+                            first, previous
+                        );
+                        file.add_issue_at(
+                            IssueKind::TimestampRegression,
+                            FILE_HEADER_SIZE,
+                            0,
+                            message,
+                        );
+                        file.issues.sort_by_key(|issue| issue.offset);
+                    }
+                }
+                previous_end = file.last_timestamp.or(previous_end);
+
+                report.total_files += 1;
+                report.total_packets += file.packets;
+                report.corrupt_packets += file.corrupt_packets;
+                report.timestamp_regressions += file.timestamp_regressions;
+                if !file.is_valid() {
+                    report.invalid_files += 1;
+                }
+                dataset.files.push(file);
+                done += 1;
+            }
+            report.datasets.push(dataset);
+        }
+
+        report.cancelled |= cancel.is_cancelled();
+        report.verified_time = chrono::Utc::now().to_rfc3339();
+        progress(VerifyStage::Completed, done, total, None);
+        report
+    }
+}
+
+/// 校验单个数据文件，取消时返回已检查部分的结果
+pub fn verify_file(path: &Path, cancel: &CancelToken) -> FileVerifyReport {
+    let mut report = FileVerifyReport {
+        file_name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        file_path: path.to_string_lossy().to_string(),
+        file_size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        ..Default::default()
+    };
+
+    let mut reader = match File::open(path) {
+        Ok(file) => BufReader::new(file),
+        Err(e) => {
+            report.add_issue(IssueKind::FileHeader, 0, format!("无法打开文件: {}", e));
+            return report;
+        }
+    };
+    if let Err(e) = FileHeader::read_from(&mut reader).and_then(|header| header.validate()) {
+        report.add_issue(IssueKind::FileHeader, 0, e.to_string());
+        return report;
+    }
+
+    let mut offset = FILE_HEADER_SIZE;
+    let mut data = Vec::new();
+    while !cancel.is_cancelled() {
+        let header = match PacketHeader::read_from(&mut reader) {
+            Ok(Some(header)) => header,
+            Ok(None) => break,
+            Err(e) => {
+                report.add_issue(IssueKind::Truncated, offset, e.to_string());
+                break;
+            }
+        };
+
+        if let Err(e) = header.validate() {
+            report.add_issue(IssueKind::PacketHeader, offset, e.to_string());
+            // 长度超限时无法定位后续数据包
+            if header.packet_length > MAX_PACKET_SIZE {
+                break;
+            }
+        } else if header.timestamp_ns() == 0 {
+            // 记录程序异常退出时常留下全0的预分配区域，会被解析为空数据包
+            report.add_issue(IssueKind::PacketHeader, offset, "时间戳为0".to_string());
+        }
+
+        data.resize(header.packet_length as usize, 0);
+        if let Err(e) = reader.read_exact(&mut data) {
+            let message = match e.kind() {
+                ErrorKind::UnexpectedEof => format!(
+                    "数据内容不完整: 应为 {} 字节，文件剩余 {} 字节",
+                    header.packet_length,
+                    report.file_size.saturating_sub(offset + PACKET_HEADER_SIZE)
+                ),
+                _ => e.to_string(),
+            };
+            report.add_issue(IssueKind::Truncated, offset, message);
+            break;
+        }
+
+        if crc32fast::hash(&data) != header.checksum {
+            report.add_issue(
+                IssueKind::ChecksumMismatch,
+                offset,
+                format!(
+                    "校验和不匹配: 记录 {:08X}，实际 {:08X}",
+                    header.checksum,
+                    crc32fast::hash(&data)
+                ),
+            );
+        }
+
+        let timestamp = header.timestamp_ns();
+        if let Some(previous) = report.last_timestamp {
+            if timestamp < previous {
+                report.add_issue(
+                    IssueKind::TimestampRegression,
+                    offset,
+                    format!("时间戳 {} 早于前一个数据包 {}", timestamp, previous),
+                );
+            }
+        }
+        report.first_timestamp.get_or_insert(timestamp);
+        report.last_timestamp = Some(timestamp);
+        report.packets += 1;
+        offset += PACKET_HEADER_SIZE + header.packet_length as u64;
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::DataFileWriter;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    /// 写入数据文件，返回文件路径
+    fn write_file(dir: &Path, packets: &[(u64, &[u8])]) -> PathBuf {
+        let path = dir.join("data.pcap");
+        let mut writer = DataFileWriter::create(&path).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn sample_packets() -> Vec<(u64, &'static [u8])> {
+        vec![
+            (BASE_TIMESTAMP, b"first"),
+            (BASE_TIMESTAMP + 10, b"second"),
+            (BASE_TIMESTAMP + 20, b"third"),
+        ]
+    }
+
+    #[test]
+    fn intact_file_is_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &sample_packets());
+
+        let report = verify_file(&path, &CancelToken::new());
+
+        assert!(report.is_valid());
+        assert_eq!(report.packets, 3);
+        assert_eq!(report.first_timestamp, Some(BASE_TIMESTAMP));
+        assert_eq!(report.last_timestamp, Some(BASE_TIMESTAMP + 20));
+    }
+
+    #[test]
+    fn reports_checksum_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &sample_packets());
+        // 改写第二个数据包内容的首字节
+        let second = FILE_HEADER_SIZE + PACKET_HEADER_SIZE + 5;
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[(second + PACKET_HEADER_SIZE) as usize] ^= 0xFF;
+        fs::write(&path, bytes).unwrap();
+
+        let report = verify_file(&path, &CancelToken::new());
+
+        assert!(report.is_damaged());
+        assert_eq!(report.packets, 3);
+        assert_eq!(report.corrupt_packets, 1);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, IssueKind::ChecksumMismatch);
+        assert_eq!(report.issues[0].offset, second);
+        assert_eq!(report.issues[0].packet_index, 1);
+    }
+
+    #[test]
+    fn reports_truncated_packet_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &sample_packets());
+        // 截断到第二个数据包包头的中间
+        let second = FILE_HEADER_SIZE + PACKET_HEADER_SIZE + 5;
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..(second + 6) as usize]).unwrap();
+
+        let report = verify_file(&path, &CancelToken::new());
+
+        assert!(report.is_damaged());
+        assert_eq!(report.packets, 1);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, IssueKind::Truncated);
+        assert_eq!(report.issues[0].offset, second);
+    }
+
+    #[test]
+    fn reports_truncated_file_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &sample_packets());
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..(FILE_HEADER_SIZE - 4) as usize]).unwrap();
+
+        let report = verify_file(&path, &CancelToken::new());
+
+        assert!(report.is_damaged());
+        assert_eq!(report.packets, 0);
+        assert_eq!(report.issues[0].kind, IssueKind::FileHeader);
+    }
+
+    #[test]
+    fn reports_timestamp_regression_without_damage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(
+            dir.path(),
+            &[
+                (BASE_TIMESTAMP + 10, b"first"),
+                (BASE_TIMESTAMP, b"second"),
+                (BASE_TIMESTAMP + 20, b"third"),
+            ],
+        );
+
+        let report = verify_file(&path, &CancelToken::new());
+
+        assert!(!report.is_valid());
+        assert!(!report.is_damaged());
+        assert_eq!(report.timestamp_regressions, 1);
+        assert_eq!(report.issues[0].kind, IssueKind::TimestampRegression);
+        assert_eq!(report.issues[0].packet_index, 1);
+    }
+}
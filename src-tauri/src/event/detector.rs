@@ -0,0 +1,558 @@
+//! 事件检测
+//!
+//! 逐个数据集按时间顺序读取数据包，时间戳按数据集的时间校正映射后交给各规则判定，
+//! 检出的事件合并排序后写入工程的事件列表
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::dataset::format::PacketReader;
+use crate::dataset::import::ImportFailure;
+use crate::dataset::index::data_files;
+use crate::dataset::info::file_time_range;
+use crate::event::rule::{CompiledRule, EventRule, RuleState};
+use crate::event::store::{DetectedEvent, EventList, EventStore};
+use crate::project::structure::ProjectStructure;
+use crate::project::validator::ProjectValidator;
+use crate::state::task_state::CancelToken;
+use crate::types::{PlaybackError, Result, TimeCorrection};
+
+/// 检查取消标记的数据包间隔
+const CANCEL_CHECK_INTERVAL: u64 = 1024;
+
+/// 事件检测请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRequest {
+    pub rules: Vec<EventRule>,
+    /// 参与检测的数据集，为空时检测工程中的所有数据集
+    #[serde(default)]
+    pub datasets: Vec<String>,
+    /// 检测的时间窗口（纳秒，含两端，按校正后的时间）
+    #[serde(default)]
+    pub start_timestamp: Option<u64>,
+    #[serde(default)]
+    pub end_timestamp: Option<u64>,
+}
+
+/// 检测结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventSummary {
+    pub events: u64,
+    pub datasets: u64,
+    /// 读取的数据文件数
+    pub source_files: u64,
+    pub packets: u64,
+    /// 校验和不匹配而跳过的数据包数
+    pub corrupt_packets: u64,
+    /// 读取中断的数据文件，中断前的数据包已检测
+    pub failed: Vec<ImportFailure>,
+    pub cancelled: bool,
+    pub events_path: Option<String>,
+}
+
+/// 检测进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectStage {
+    Scanning,
+    Completed,
+}
+
+impl DetectStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetectStage::Scanning => "scanning",
+            DetectStage::Completed => "completed",
+        }
+    }
+}
+
+/// 参与检测的数据集
+#[derive(Debug)]
+struct DetectSource {
+    name: String,
+    dir: PathBuf,
+    time_correction: Option<TimeCorrection>,
+}
+
+/// 事件检测器
+#[derive(Debug)]
+pub struct EventDetector {
+    project_path: PathBuf,
+    request: EventRequest,
+    rules: Vec<CompiledRule>,
+    sources: Vec<DetectSource>,
+}
+
+impl EventDetector {
+    /// 校验规则和数据集，读取各数据集的时间校正
+    pub fn new<P: AsRef<Path>>(project_path: P, request: EventRequest) -> Result<Self> {
+        let project_path = project_path.as_ref().to_path_buf();
+        if request.rules.is_empty() {
+            return Err(PlaybackError::ParseError("未指定检测规则".to_string()));
+        }
+        let mut rules: Vec<CompiledRule> = Vec::new();
+        for rule in &request.rules {
+            if rules.iter().any(|r| r.rule.name == rule.name) {
+                return Err(PlaybackError::ParseError(format!(
+                    "规则名称重复: {}",
+                    rule.name
+                )));
+            }
+            rules.push(rule.compile()?);
+        }
+        if let (Some(start), Some(end)) = (request.start_timestamp, request.end_timestamp) {
+            if start > end {
+                return Err(PlaybackError::ParseError(format!(
+                    "检测起始时间晚于结束时间: {} > {}",
+                    start, end
+                )));
+            }
+        }
+
+        let names = if request.datasets.is_empty() {
+            ProjectStructure::from_path(&project_path)?
+                .datasets
+                .into_iter()
+                .map(|d| d.name)
+                .collect()
+        } else {
+            request.datasets.clone()
+        };
+        let mut sources = Vec::new();
+        for name in names {
+            ProjectValidator::validate_name(&name)?;
+            let config = ProjectStructure::dataset_config(&project_path, &name)?;
+            sources.push(DetectSource {
+                dir: project_path.join(&name),
+                name,
+                time_correction: config.time_correction,
+            });
+        }
+        if sources.is_empty() {
+            return Err(PlaybackError::ProjectError(
+                "工程中没有可检测的数据集".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            project_path,
+            request,
+            rules,
+            sources,
+        })
+    }
+
+    /// 执行检测并保存事件列表，取消时保存取消前检出的事件
+    ///
+    /// `progress` 参数依次为阶段、已读取数据文件数、数据文件总数和当前文件
+    pub fn detect<F>(&self, cancel: &CancelToken, mut progress: F) -> Result<EventSummary>
+    where
+        F: FnMut(DetectStage, u64, u64, Option<&str>),
+    {
+        let mut files = Vec::new();
+        for (i, source) in self.sources.iter().enumerate() {
+            for path in data_files(&source.dir)? {
+                if self.file_overlaps(source, &path) {
+                    files.push((i, path));
+                }
+            }
+        }
+        let total = files.len() as u64;
+
+        let mut list = EventList::new(
+            self.request.rules.clone(),
+            self.sources.iter().map(|s| s.name.clone()).collect(),
+        );
+        list.start_timestamp = self.request.start_timestamp;
+        list.end_timestamp = self.request.end_timestamp;
+
+        let mut summary = EventSummary {
+            datasets: self.sources.len() as u64,
+            ..Default::default()
+        };
+        let mut states: Vec<RuleState> = Vec::new();
+        let mut current_source = None;
+        // 读取的数据包数，包括时间窗口外和损坏的数据包
+        let mut packets_read = 0u64;
+
+        'files: for (source_index, path) in &files {
+            let source = &self.sources[*source_index];
+            // 规则状态按数据集维护，跨文件延续
+            if current_source != Some(*source_index) {
+                current_source = Some(*source_index);
+                states = self.rules.iter().map(|_| RuleState::default()).collect();
+            }
+
+            let item = path.to_string_lossy().to_string();
+            progress(
+                DetectStage::Scanning,
+                summary.source_files,
+                total,
+                Some(&item),
+            );
+            summary.source_files += 1;
+
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut reader = match PacketReader::open(path) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    warn!("打开数据文件失败 {:?}: {}", path, e);
+                    summary.failed.push(ImportFailure {
+                        path: item,
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let mut packet_index = 0u64;
+            loop {
+                if packets_read.is_multiple_of(CANCEL_CHECK_INTERVAL) && cancel.is_cancelled() {
+                    summary.cancelled = true;
+                    break 'files;
+                }
+                packets_read += 1;
+
+                let packet = match reader.next_packet() {
+                    Ok(Some(packet)) => packet,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("读取数据文件中断 {}/{}: {}", source.name, file_name, e);
+                        summary.failed.push(ImportFailure {
+                            path: format!("{}/{}", source.name, file_name),
+                            reason: e.to_string(),
+                        });
+                        break;
+                    }
+                };
+                let index = packet_index;
+                packet_index += 1;
+
+                let recorded = packet.header.timestamp_ns();
+                let timestamp = source
+                    .time_correction
+                    .as_ref()
+                    .map_or(recorded, |c| c.apply(recorded));
+                if self
+                    .request
+                    .end_timestamp
+                    .is_some_and(|end| timestamp > end)
+                {
+                    // 文件内按时间顺序存储，超出时间窗口后不必继续读取
+                    break;
+                }
+                if self
+                    .request
+                    .start_timestamp
+                    .is_some_and(|start| timestamp < start)
+                {
+                    continue;
+                }
+                if !packet.checksum_matches() {
+                    summary.corrupt_packets += 1;
+                    continue;
+                }
+                summary.packets += 1;
+
+                for (rule, state) in self.rules.iter().zip(states.iter_mut()) {
+                    if !rule.rule.applies_to(&source.name) {
+                        continue;
+                    }
+                    if let Some(detection) = rule.observe(state, timestamp, &packet.data) {
+                        list.events.push(DetectedEvent {
+                            id: 0,
+                            timestamp: detection.timestamp,
+                            end_timestamp: detection.end_timestamp,
+                            rule: rule.rule.name.clone(),
+                            kind: rule.rule.kind.event_kind(),
+                            level: rule.rule.level,
+                            dataset: source.name.clone(),
+                            title: rule.rule.title().to_string(),
+                            description: detection.description,
+                            value: detection.value,
+                            previous: detection.previous,
+                            file_name: file_name.clone(),
+                            packet_index: index,
+                        });
+                    }
+                }
+            }
+        }
+
+        list.cancelled = summary.cancelled;
+        summary.events = list.events.len() as u64;
+        let events_path = EventStore::new(&self.project_path).write(&mut list)?;
+        summary.events_path = Some(events_path.to_string_lossy().to_string());
+
+        progress(DetectStage::Completed, summary.source_files, total, None);
+        info!(
+            "事件检测{}: {} 个数据集，{} 个数据包，检出 {} 个事件",
+            if summary.cancelled {
+                "已取消"
+            } else {
+                "完成"
+            },
+            summary.datasets,
+            summary.packets,
+            summary.events
+        );
+        Ok(summary)
+    }
+
+    /// 数据文件是否与检测时间窗口重叠，读不出时间范围的文件仍然逐包读取
+    fn file_overlaps(&self, source: &DetectSource, path: &Path) -> bool {
+        if self.request.start_timestamp.is_none() && self.request.end_timestamp.is_none() {
+            return true;
+        }
+        let corrected = |t: u64| source.time_correction.as_ref().map_or(t, |c| c.apply(t));
+        match file_time_range(path) {
+            Ok(Some((start, end))) => {
+                let (start, end) = (corrected(start), corrected(end));
+                self.request.start_timestamp.is_none_or(|s| end >= s)
+                    && self.request.end_timestamp.is_none_or(|e| start <= e)
+            }
+            Ok(None) => false,
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::DataFileWriter;
+    use crate::dataset::naming::data_file_name;
+    use crate::event::rule::{ByteOrder, EventLevel, FieldSpec, FieldType, RuleKind};
+    use std::fs;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+    const SECOND: u64 = 1_000_000_000;
+
+    fn create_project(parent: &Path) -> PathBuf {
+        let names = vec!["radar".to_string(), "ais".to_string()];
+        ProjectStructure::create(parent, "proj", None, &names)
+            .unwrap()
+            .root_path
+    }
+
+    /// 写入单字节负载的数据文件，负载即状态值
+    fn write_data_file(dir: &Path, packets: &[(u64, u8)]) -> PathBuf {
+        let mut writer = DataFileWriter::create(dir.join(data_file_name(packets[0].0))).unwrap();
+        for (timestamp, value) in packets {
+            writer.write_packet(*timestamp, &[*value]).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn state_rule(name: &str, datasets: &[&str]) -> EventRule {
+        EventRule {
+            name: name.to_string(),
+            kind: RuleKind::StateChange {
+                field: FieldSpec {
+                    offset: 0,
+                    field_type: FieldType::U8,
+                    byte_order: ByteOrder::Big,
+                },
+            },
+            datasets: datasets.iter().map(|d| d.to_string()).collect(),
+            pattern: None,
+            pattern_offset: None,
+            level: EventLevel::Normal,
+            title: None,
+        }
+    }
+
+    fn request(rules: Vec<EventRule>) -> EventRequest {
+        EventRequest {
+            rules,
+            datasets: Vec::new(),
+            start_timestamp: None,
+            end_timestamp: None,
+        }
+    }
+
+    fn detect(project: &Path, request: EventRequest) -> EventSummary {
+        EventDetector::new(project, request)
+            .unwrap()
+            .detect(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap()
+    }
+
+    fn events(project: &Path) -> Vec<DetectedEvent> {
+        EventStore::new(project).read().unwrap().unwrap().events
+    }
+
+    #[test]
+    fn rule_state_carries_across_files_and_resets_per_dataset() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path());
+        let radar = project.join("radar");
+        write_data_file(&radar, &[(BASE_TIMESTAMP, 1), (BASE_TIMESTAMP + 1, 1)]);
+        write_data_file(&radar, &[(BASE_TIMESTAMP + SECOND, 2)]);
+        // ais 的第一个值与 radar 的最后一个值不同，但状态按数据集重新开始
+        write_data_file(
+            &project.join("ais"),
+            &[
+                (BASE_TIMESTAMP + 2 * SECOND, 5),
+                (BASE_TIMESTAMP + 3 * SECOND, 5),
+            ],
+        );
+
+        let summary = detect(&project, request(vec![state_rule("状态", &[])]));
+        assert_eq!(
+            (summary.datasets, summary.source_files, summary.packets),
+            (2, 3, 5)
+        );
+        assert_eq!(summary.events, 1);
+        assert!(summary.failed.is_empty());
+
+        let events = events(&project);
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(
+            (event.dataset.as_str(), event.timestamp, event.packet_index),
+            ("radar", BASE_TIMESTAMP + SECOND, 0)
+        );
+        assert_eq!(event.file_name, data_file_name(BASE_TIMESTAMP + SECOND));
+        assert_eq!((event.previous, event.value), (Some(1.0), Some(2.0)));
+    }
+
+    #[test]
+    fn rules_apply_only_to_their_datasets() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path());
+        for name in ["radar", "ais"] {
+            write_data_file(
+                &project.join(name),
+                &[(BASE_TIMESTAMP, 1), (BASE_TIMESTAMP + SECOND, 2)],
+            );
+        }
+
+        detect(
+            &project,
+            request(vec![
+                state_rule("雷达", &["radar"]),
+                state_rule("全部", &[]),
+            ]),
+        );
+        let mut fired: Vec<(String, String)> = events(&project)
+            .into_iter()
+            .map(|e| (e.rule, e.dataset))
+            .collect();
+        fired.sort();
+        let expected = [("全部", "ais"), ("全部", "radar"), ("雷达", "radar")];
+        assert_eq!(
+            fired,
+            expected
+                .iter()
+                .map(|(r, d)| (r.to_string(), d.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn time_window_limits_packets_and_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path());
+        let radar = project.join("radar");
+        write_data_file(
+            &radar,
+            &[
+                (BASE_TIMESTAMP, 1),
+                (BASE_TIMESTAMP + SECOND, 2),
+                (BASE_TIMESTAMP + 2 * SECOND, 3),
+                (BASE_TIMESTAMP + 3 * SECOND, 4),
+            ],
+        );
+        // 完全在窗口之后的文件不读取
+        write_data_file(&radar, &[(BASE_TIMESTAMP + 10 * SECOND, 9)]);
+
+        let mut request = request(vec![state_rule("状态", &[])]);
+        request.start_timestamp = Some(BASE_TIMESTAMP + SECOND);
+        request.end_timestamp = Some(BASE_TIMESTAMP + 2 * SECOND);
+        let summary = detect(&project, request);
+        assert_eq!((summary.source_files, summary.packets), (1, 2));
+
+        // 窗口前的数据包不参与判定，窗口内第一个数据包只记录状态
+        let events = events(&project);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].timestamp, BASE_TIMESTAMP + 2 * SECOND);
+        assert_eq!(
+            (events[0].previous, events[0].value),
+            (Some(2.0), Some(3.0))
+        );
+
+        let list = EventStore::new(&project).read().unwrap().unwrap();
+        assert_eq!(
+            (list.start_timestamp, list.end_timestamp),
+            (
+                Some(BASE_TIMESTAMP + SECOND),
+                Some(BASE_TIMESTAMP + 2 * SECOND)
+            )
+        );
+    }
+
+    #[test]
+    fn stops_reading_file_after_time_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path());
+        let path = write_data_file(
+            &project.join("radar"),
+            &[
+                (BASE_TIMESTAMP, 1),
+                (BASE_TIMESTAMP + SECOND, 2),
+                (BASE_TIMESTAMP + 2 * SECOND, 3),
+                (BASE_TIMESTAMP + 3 * SECOND, 4),
+            ],
+        );
+        // 截断最后一个数据包，读到它时会报错
+        let len = fs::metadata(&path).unwrap().len();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 1)
+            .unwrap();
+
+        let summary = detect(&project, request(vec![state_rule("状态", &[])]));
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.packets, 3);
+
+        // 读到窗口之后的数据包即停止，截断的尾部不会被读到
+        let mut request = request(vec![state_rule("状态", &[])]);
+        request.end_timestamp = Some(BASE_TIMESTAMP + SECOND);
+        let summary = detect(&project, request);
+        assert!(summary.failed.is_empty());
+        assert_eq!((summary.packets, summary.events), (2, 1));
+    }
+
+    #[test]
+    fn new_rejects_invalid_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path());
+        let rule = state_rule("状态", &[]);
+
+        assert!(matches!(
+            EventDetector::new(&project, request(Vec::new())),
+            Err(PlaybackError::ParseError(_))
+        ));
+        assert!(matches!(
+            EventDetector::new(&project, request(vec![rule.clone(), rule.clone()])),
+            Err(PlaybackError::ParseError(_))
+        ));
+        let mut reversed = request(vec![rule.clone()]);
+        reversed.start_timestamp = Some(BASE_TIMESTAMP + 1);
+        reversed.end_timestamp = Some(BASE_TIMESTAMP);
+        assert!(matches!(
+            EventDetector::new(&project, reversed),
+            Err(PlaybackError::ParseError(_))
+        ));
+        let mut missing = request(vec![rule]);
+        missing.datasets = vec!["missing".to_string()];
+        assert!(EventDetector::new(&project, missing).is_err());
+    }
+}
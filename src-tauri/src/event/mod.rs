@@ -0,0 +1,8 @@
+//! 事件检测
+//!
+//! 按规则扫描数据集中的数据包，检出阈值穿越、状态变化、新目标出现和数据中断等关键事件，
+//! 并将按时间索引的事件列表保存在工程目录中
+
+pub mod detector;
+pub mod rule;
+pub mod store;
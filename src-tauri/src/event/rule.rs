@@ -0,0 +1,587 @@
+//! 事件检测规则
+//!
+//! 数据包负载按字段定义（偏移、类型、字节序）解码出数值，规则按数值变化或数据包间隔判定事件
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::dataset::extract::BytePattern;
+use crate::types::{PlaybackError, Result};
+
+/// 新目标规则最多记住的目标数，超出时遗忘过期和最久未出现的目标，它们再次出现时视为新目标
+const MAX_TARGETS: usize = 65_536;
+
+/// 字段类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl FieldType {
+    /// 字段字节数
+    pub fn size(&self) -> usize {
+        match self {
+            FieldType::U8 | FieldType::I8 => 1,
+            FieldType::U16 | FieldType::I16 => 2,
+            FieldType::U32 | FieldType::I32 | FieldType::F32 => 4,
+            FieldType::U64 | FieldType::I64 | FieldType::F64 => 8,
+        }
+    }
+}
+
+/// 字节序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteOrder {
+    /// 网络字节序
+    #[default]
+    Big,
+    Little,
+}
+
+/// 负载中的数值字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSpec {
+    /// 在数据包负载中的字节偏移
+    pub offset: usize,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub byte_order: ByteOrder,
+}
+
+impl FieldSpec {
+    /// 读取字段值，负载长度不足时返回 `None`
+    pub fn read(&self, data: &[u8]) -> Option<f64> {
+        let size = self.field_type.size();
+        let bytes = data.get(self.offset..self.offset.checked_add(size)?)?;
+        let fold = |acc: u64, byte: &u8| acc << 8 | *byte as u64;
+        let raw = match self.byte_order {
+            ByteOrder::Big => bytes.iter().fold(0, fold),
+            ByteOrder::Little => bytes.iter().rev().fold(0, fold),
+        };
+        let bits = (size * 8) as u32;
+        let signed = |raw: u64| ((raw << (64 - bits)) as i64 >> (64 - bits)) as f64;
+
+        let value = match self.field_type {
+            FieldType::U8 | FieldType::U16 | FieldType::U32 | FieldType::U64 => raw as f64,
+            FieldType::I8 | FieldType::I16 | FieldType::I32 | FieldType::I64 => signed(raw),
+            FieldType::F32 => f32::from_bits(raw as u32) as f64,
+            FieldType::F64 => f64::from_bits(raw),
+        };
+        value.is_finite().then_some(value)
+    }
+}
+
+/// 阈值穿越方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Crossing {
+    /// 由低于阈值变为不低于阈值
+    Rising,
+    /// 由不低于阈值变为低于阈值
+    Falling,
+    #[default]
+    Both,
+}
+
+/// 规则类型及参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleKind {
+    /// 字段值穿越阈值
+    Threshold {
+        field: FieldSpec,
+        threshold: f64,
+        #[serde(default)]
+        crossing: Crossing,
+    },
+    /// 字段值变化
+    StateChange { field: FieldSpec },
+    /// 字段值（目标编号）首次出现；设置 `expire_ns` 时目标超过该时长未出现后再次出现也视为新目标
+    NewTarget {
+        field: FieldSpec,
+        #[serde(default)]
+        expire_ns: Option<u64>,
+    },
+    /// 相邻数据包间隔超过 `min_gap_ns`
+    DataGap { min_gap_ns: u64 },
+}
+
+/// 事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Threshold,
+    StateChange,
+    NewTarget,
+    DataGap,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Threshold => "threshold",
+            EventKind::StateChange => "state_change",
+            EventKind::NewTarget => "new_target",
+            EventKind::DataGap => "data_gap",
+        }
+    }
+}
+
+impl RuleKind {
+    pub fn event_kind(&self) -> EventKind {
+        match self {
+            RuleKind::Threshold { .. } => EventKind::Threshold,
+            RuleKind::StateChange { .. } => EventKind::StateChange,
+            RuleKind::NewTarget { .. } => EventKind::NewTarget,
+            RuleKind::DataGap { .. } => EventKind::DataGap,
+        }
+    }
+}
+
+/// 事件级别，与前端事件列表的级别一致
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum EventLevel {
+    Low,
+    #[default]
+    Normal,
+    Warning,
+    High,
+}
+
+/// 事件检测规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRule {
+    /// 规则名称，在同一次检测中唯一
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: RuleKind,
+    /// 适用的数据集，为空时适用于所有参与检测的数据集
+    #[serde(default)]
+    pub datasets: Vec<String>,
+    /// 只检查匹配该字节模式的数据包（十六进制，`??` 匹配任意字节），如按消息类型筛选
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// 字节模式在负载中的偏移，未设置时在任意位置查找
+    #[serde(default)]
+    pub pattern_offset: Option<usize>,
+    #[serde(default)]
+    pub level: EventLevel,
+    /// 事件标题，未设置时使用规则名称
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+impl EventRule {
+    /// 校验规则参数并编译字节模式
+    pub fn compile(&self) -> Result<CompiledRule> {
+        if self.name.trim().is_empty() {
+            return Err(PlaybackError::ParseError("规则名称不能为空".to_string()));
+        }
+        let invalid = |message: &str| {
+            Err(PlaybackError::ParseError(format!(
+                "规则 {}: {}",
+                self.name, message
+            )))
+        };
+        match &self.kind {
+            RuleKind::Threshold { threshold, .. } if !threshold.is_finite() => {
+                return invalid("阈值必须是有限数值");
+            }
+            RuleKind::NewTarget {
+                expire_ns: Some(0), ..
+            } => return invalid("目标过期时长必须大于0"),
+            RuleKind::DataGap { min_gap_ns: 0 } => return invalid("数据中断间隔必须大于0"),
+            _ => {}
+        }
+
+        let pattern = match &self.pattern {
+            Some(pattern) if !pattern.trim().is_empty() => {
+                Some(BytePattern::parse(pattern, self.pattern_offset)?)
+            }
+            _ => None,
+        };
+        Ok(CompiledRule {
+            rule: self.clone(),
+            pattern,
+        })
+    }
+
+    /// 是否适用于数据集
+    pub fn applies_to(&self, dataset: &str) -> bool {
+        self.datasets.is_empty() || self.datasets.iter().any(|d| d == dataset)
+    }
+
+    /// 事件标题
+    pub fn title(&self) -> &str {
+        self.title
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or(&self.name)
+    }
+}
+
+/// 规则判定出的事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    pub timestamp: u64,
+    /// 区间事件（数据中断）的结束时间
+    pub end_timestamp: Option<u64>,
+    pub value: Option<f64>,
+    pub previous: Option<f64>,
+    pub description: String,
+}
+
+/// 规则在单个数据集上的判定状态
+#[derive(Debug, Default)]
+pub struct RuleState {
+    /// 上一个字段值
+    last: Option<f64>,
+    /// 上一个数据包的时间戳
+    last_timestamp: Option<u64>,
+    /// 已出现的目标及最后出现时间
+    targets: HashMap<u64, u64>,
+}
+
+impl RuleState {
+    /// 记录目标出现，返回上次出现的时间；目标数达到上限时先遗忘过期和最久未出现的四分之一目标
+    fn see_target(&mut self, target: u64, timestamp: u64, expire_ns: Option<u64>) -> Option<u64> {
+        if self.targets.len() >= MAX_TARGETS && !self.targets.contains_key(&target) {
+            if let Some(expire) = expire_ns {
+                self.targets
+                    .retain(|_, last| timestamp.saturating_sub(*last) <= expire);
+            }
+            if self.targets.len() >= MAX_TARGETS {
+                let mut seen: Vec<u64> = self.targets.values().copied().collect();
+                let cutoff = *seen.select_nth_unstable(MAX_TARGETS / 4).1;
+                self.targets.retain(|_, last| *last > cutoff);
+            }
+        }
+        self.targets.insert(target, timestamp)
+    }
+}
+
+/// 编译后的规则
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub rule: EventRule,
+    pattern: Option<BytePattern>,
+}
+
+impl CompiledRule {
+    /// 检查一个数据包，判定为事件时返回事件内容
+    pub fn observe(&self, state: &mut RuleState, timestamp: u64, data: &[u8]) -> Option<Detection> {
+        if self.pattern.as_ref().is_some_and(|p| !p.matches(data)) {
+            return None;
+        }
+
+        let detection = |value: Option<f64>, previous: Option<f64>, description: String| {
+            Some(Detection {
+                timestamp,
+                end_timestamp: None,
+                value,
+                previous,
+                description,
+            })
+        };
+
+        match &self.rule.kind {
+            RuleKind::Threshold {
+                field,
+                threshold,
+                crossing,
+            } => {
+                let value = field.read(data)?;
+                let previous = state.last.replace(value)?;
+                let rising = previous < *threshold && value >= *threshold;
+                let falling = previous >= *threshold && value < *threshold;
+                let matched = match crossing {
+                    Crossing::Rising => rising,
+                    Crossing::Falling => falling,
+                    Crossing::Both => rising || falling,
+                };
+                if !matched {
+                    return None;
+                }
+                detection(
+                    Some(value),
+                    Some(previous),
+                    format!(
+                        "{} → {}，{}阈值 {}",
+                        previous,
+                        value,
+                        if rising { "上穿" } else { "下穿" },
+                        threshold
+                    ),
+                )
+            }
+            RuleKind::StateChange { field } => {
+                let value = field.read(data)?;
+                let previous = state.last.replace(value)?;
+                if previous == value {
+                    return None;
+                }
+                detection(
+                    Some(value),
+                    Some(previous),
+                    format!("状态 {} → {}", previous, value),
+                )
+            }
+            RuleKind::NewTarget { field, expire_ns } => {
+                let value = field.read(data)?;
+                let last_seen = state.see_target(value.to_bits(), timestamp, *expire_ns);
+                let description = match (last_seen, expire_ns) {
+                    (None, _) => format!("新目标 {}", value),
+                    (Some(last), Some(expire)) if timestamp.saturating_sub(last) > *expire => {
+                        format!(
+                            "目标 {} 重新出现（{:.1} 秒未出现）",
+                            value,
+                            (timestamp - last) as f64 / 1e9
+                        )
+                    }
+                    _ => return None,
+                };
+                detection(Some(value), None, description)
+            }
+            RuleKind::DataGap { min_gap_ns } => {
+                let last = state.last_timestamp.replace(timestamp)?;
+                let gap = timestamp.saturating_sub(last);
+                if gap < *min_gap_ns {
+                    return None;
+                }
+                Some(Detection {
+                    timestamp: last,
+                    end_timestamp: Some(timestamp),
+                    value: Some(gap as f64),
+                    previous: None,
+                    description: format!("数据中断 {:.3} 秒", gap as f64 / 1e9),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(offset: usize, field_type: FieldType, byte_order: ByteOrder) -> FieldSpec {
+        FieldSpec {
+            offset,
+            field_type,
+            byte_order,
+        }
+    }
+
+    fn rule(kind: RuleKind) -> CompiledRule {
+        EventRule {
+            name: "测试".to_string(),
+            kind,
+            datasets: Vec::new(),
+            pattern: None,
+            pattern_offset: None,
+            level: EventLevel::Normal,
+            title: None,
+        }
+        .compile()
+        .unwrap()
+    }
+
+    /// 依次检查单字节负载，返回判定出事件的时间戳
+    fn detect(rule: &CompiledRule, packets: &[(u64, u8)]) -> Vec<u64> {
+        let mut state = RuleState::default();
+        packets
+            .iter()
+            .filter_map(|&(timestamp, value)| rule.observe(&mut state, timestamp, &[value]))
+            .map(|d| d.timestamp)
+            .collect()
+    }
+
+    #[test]
+    fn field_reads_byte_orders_and_signs() {
+        let data = [0x12, 0x34, 0xFF, 0xFE];
+        assert_eq!(
+            field(0, FieldType::U16, ByteOrder::Big).read(&data),
+            Some(0x1234 as f64)
+        );
+        assert_eq!(
+            field(0, FieldType::U16, ByteOrder::Little).read(&data),
+            Some(0x3412 as f64)
+        );
+        assert_eq!(
+            field(2, FieldType::I16, ByteOrder::Big).read(&data),
+            Some(-2.0)
+        );
+        assert_eq!(
+            field(2, FieldType::U8, ByteOrder::Big).read(&data),
+            Some(255.0)
+        );
+        assert_eq!(
+            field(2, FieldType::I8, ByteOrder::Big).read(&data),
+            Some(-1.0)
+        );
+        assert_eq!(
+            field(0, FieldType::I64, ByteOrder::Little).read(&(-5i64).to_le_bytes()),
+            Some(-5.0)
+        );
+    }
+
+    #[test]
+    fn field_reads_floats() {
+        assert_eq!(
+            field(0, FieldType::F32, ByteOrder::Big).read(&1.5f32.to_be_bytes()),
+            Some(1.5)
+        );
+        assert_eq!(
+            field(1, FieldType::F64, ByteOrder::Little)
+                .read(&[[0u8].as_slice(), &(-2.25f64).to_le_bytes()].concat()),
+            Some(-2.25)
+        );
+        // 非有限值不参与判定
+        assert_eq!(
+            field(0, FieldType::F32, ByteOrder::Big).read(&f32::NAN.to_be_bytes()),
+            None
+        );
+    }
+
+    #[test]
+    fn field_out_of_range_is_none() {
+        let data = [0u8; 4];
+        assert_eq!(field(3, FieldType::U16, ByteOrder::Big).read(&data), None);
+        assert_eq!(field(4, FieldType::U8, ByteOrder::Big).read(&data), None);
+        assert_eq!(
+            field(usize::MAX, FieldType::U32, ByteOrder::Big).read(&data),
+            None
+        );
+    }
+
+    #[test]
+    fn threshold_detects_crossings() {
+        let packets = [(1, 5), (2, 12), (3, 15), (4, 8), (5, 10)];
+        let kind = |crossing| RuleKind::Threshold {
+            field: field(0, FieldType::U8, ByteOrder::Big),
+            threshold: 10.0,
+            crossing,
+        };
+        assert_eq!(detect(&rule(kind(Crossing::Both)), &packets), vec![2, 4, 5]);
+        assert_eq!(detect(&rule(kind(Crossing::Rising)), &packets), vec![2, 5]);
+        assert_eq!(detect(&rule(kind(Crossing::Falling)), &packets), vec![4]);
+    }
+
+    #[test]
+    fn state_change_reports_previous_value() {
+        let rule = rule(RuleKind::StateChange {
+            field: field(0, FieldType::U8, ByteOrder::Big),
+        });
+        let mut state = RuleState::default();
+        assert_eq!(rule.observe(&mut state, 1, &[1]), None);
+        assert_eq!(rule.observe(&mut state, 2, &[1]), None);
+        let detection = rule.observe(&mut state, 3, &[2]).unwrap();
+        assert_eq!(detection.value, Some(2.0));
+        assert_eq!(detection.previous, Some(1.0));
+        // 负载过短的数据包不改变状态
+        assert_eq!(rule.observe(&mut state, 4, &[]), None);
+        assert_eq!(rule.observe(&mut state, 5, &[2]), None);
+    }
+
+    #[test]
+    fn new_target_reappears_after_expiry() {
+        let packets = [(0, 1), (10, 2), (20, 1), (100, 1), (105, 2)];
+        let kind = |expire_ns| RuleKind::NewTarget {
+            field: field(0, FieldType::U8, ByteOrder::Big),
+            expire_ns,
+        };
+        assert_eq!(detect(&rule(kind(None)), &packets), vec![0, 10]);
+        assert_eq!(
+            detect(&rule(kind(Some(50))), &packets),
+            vec![0, 10, 100, 105]
+        );
+    }
+
+    #[test]
+    fn new_target_memory_is_bounded() {
+        let rule = rule(RuleKind::NewTarget {
+            field: field(0, FieldType::U32, ByteOrder::Big),
+            expire_ns: None,
+        });
+        let mut state = RuleState::default();
+        for target in 0..(MAX_TARGETS as u32 + 10) {
+            assert!(rule
+                .observe(&mut state, target as u64, &target.to_be_bytes())
+                .is_some());
+            assert!(state.targets.len() <= MAX_TARGETS);
+        }
+        // 最近出现的目标仍被记住
+        let last = MAX_TARGETS as u32 + 9;
+        assert!(rule
+            .observe(&mut state, last as u64 + 1, &last.to_be_bytes())
+            .is_none());
+    }
+
+    #[test]
+    fn data_gap_reports_interval() {
+        let rule = rule(RuleKind::DataGap { min_gap_ns: 100 });
+        let mut state = RuleState::default();
+        assert_eq!(rule.observe(&mut state, 0, &[]), None);
+        assert_eq!(rule.observe(&mut state, 50, &[]), None);
+        let detection = rule.observe(&mut state, 200, &[]).unwrap();
+        assert_eq!(detection.timestamp, 50);
+        assert_eq!(detection.end_timestamp, Some(200));
+        assert_eq!(detection.value, Some(150.0));
+    }
+
+    #[test]
+    fn pattern_filters_packets() {
+        let mut rule = rule(RuleKind::StateChange {
+            field: field(1, FieldType::U8, ByteOrder::Big),
+        });
+        rule.pattern = Some(BytePattern::parse("AA", Some(0)).unwrap());
+        let mut state = RuleState::default();
+        assert_eq!(rule.observe(&mut state, 1, &[0xAA, 1]), None);
+        // 其他消息类型的数据包不参与判定
+        assert_eq!(rule.observe(&mut state, 2, &[0xBB, 2]), None);
+        assert!(rule.observe(&mut state, 3, &[0xAA, 3]).is_some());
+    }
+
+    #[test]
+    fn compile_rejects_invalid_parameters() {
+        let compile = |kind| {
+            EventRule {
+                name: "测试".to_string(),
+                kind,
+                datasets: Vec::new(),
+                pattern: None,
+                pattern_offset: None,
+                level: EventLevel::Normal,
+                title: None,
+            }
+            .compile()
+        };
+        assert!(compile(RuleKind::DataGap { min_gap_ns: 0 }).is_err());
+        assert!(compile(RuleKind::Threshold {
+            field: field(0, FieldType::U8, ByteOrder::Big),
+            threshold: f64::NAN,
+            crossing: Crossing::Both,
+        })
+        .is_err());
+        assert!(compile(RuleKind::NewTarget {
+            field: field(0, FieldType::U8, ByteOrder::Big),
+            expire_ns: Some(0),
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! 事件列表的保存与查询
+//!
+//! 最近一次检测的事件按时间顺序保存在工程目录的 `.events.json`，同时记录检测使用的规则
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::event::rule::{EventKind, EventLevel, EventRule};
+use crate::types::{PlaybackError, Result};
+
+/// 事件列表文件名
+pub const EVENTS_FILE_NAME: &str = ".events.json";
+
+/// 事件列表文件格式版本
+const EVENTS_VERSION: u32 = 1;
+
+/// 串行化事件列表的读改写
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 检测出的事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedEvent {
+    /// 按时间顺序的编号，从1开始
+    pub id: u64,
+    /// 事件时间（纳秒，已按数据集时间校正映射），区间事件为起点
+    pub timestamp: u64,
+    /// 区间事件的终点
+    pub end_timestamp: Option<u64>,
+    /// 判定出事件的规则名称
+    pub rule: String,
+    pub kind: EventKind,
+    pub level: EventLevel,
+    pub dataset: String,
+    pub title: String,
+    pub description: String,
+    /// 触发事件的字段值，数据中断为间隔（纳秒）
+    pub value: Option<f64>,
+    /// 变化前的字段值
+    pub previous: Option<f64>,
+    /// 触发事件的数据包所在文件及其在文件中的序号
+    pub file_name: String,
+    pub packet_index: u64,
+}
+
+/// 事件列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventList {
+    pub version: u32,
+    pub created_time: String,
+    pub rules: Vec<EventRule>,
+    /// 参与检测的数据集
+    pub datasets: Vec<String>,
+    /// 检测的时间窗口
+    pub start_timestamp: Option<u64>,
+    pub end_timestamp: Option<u64>,
+    /// 检测被取消时只包含取消前检出的事件
+    pub cancelled: bool,
+    /// 按时间排序
+    pub events: Vec<DetectedEvent>,
+}
+
+impl EventList {
+    pub fn new(rules: Vec<EventRule>, datasets: Vec<String>) -> Self {
+        Self {
+            version: EVENTS_VERSION,
+            created_time: chrono::Utc::now().to_rfc3339(),
+            rules,
+            datasets,
+            start_timestamp: None,
+            end_timestamp: None,
+            cancelled: false,
+            events: Vec::new(),
+        }
+    }
+}
+
+/// 事件查询条件，未设置的条件不参与筛选
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventQuery {
+    /// 与时间窗口（纳秒，含两端）重叠的事件
+    #[serde(default)]
+    pub start_timestamp: Option<u64>,
+    #[serde(default)]
+    pub end_timestamp: Option<u64>,
+    #[serde(default)]
+    pub kinds: Vec<EventKind>,
+    #[serde(default)]
+    pub levels: Vec<EventLevel>,
+    #[serde(default)]
+    pub rules: Vec<String>,
+    #[serde(default)]
+    pub datasets: Vec<String>,
+    /// 最多返回的事件数
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl EventQuery {
+    fn matches(&self, event: &DetectedEvent) -> bool {
+        let end = event.end_timestamp.unwrap_or(event.timestamp);
+        self.start_timestamp.is_none_or(|start| end >= start)
+            && (self.kinds.is_empty() || self.kinds.contains(&event.kind))
+            && (self.levels.is_empty() || self.levels.contains(&event.level))
+            && (self.rules.is_empty() || self.rules.contains(&event.rule))
+            && (self.datasets.is_empty() || self.datasets.contains(&event.dataset))
+    }
+}
+
+/// 工程的事件列表
+#[derive(Debug)]
+pub struct EventStore {
+    project_path: PathBuf,
+}
+
+impl EventStore {
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Self {
+        Self {
+            project_path: project_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// 事件列表文件路径
+    pub fn file_path(&self) -> PathBuf {
+        self.project_path.join(EVENTS_FILE_NAME)
+    }
+
+    /// 读取事件列表，尚未检测过时返回 `None`
+    pub fn read(&self) -> Result<Option<EventList>> {
+        let path = self.file_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let list: EventList = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        if list.version > EVENTS_VERSION {
+            return Err(PlaybackError::FormatError(format!(
+                "不支持的事件列表版本: {}",
+                list.version
+            )));
+        }
+        Ok(Some(list))
+    }
+
+    /// 保存事件列表，按时间排序并重新编号
+    pub fn write(&self, list: &mut EventList) -> Result<PathBuf> {
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.save(list)
+    }
+
+    /// 读取、修改并保存事件列表，尚未检测过时不写入并返回 `None`
+    fn modify<T>(&self, update: impl FnOnce(&mut EventList) -> T) -> Result<Option<T>> {
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(mut list) = self.read()? else {
+            return Ok(None);
+        };
+        let result = update(&mut list);
+        self.save(&mut list)?;
+        Ok(Some(result))
+    }
+
+    fn save(&self, list: &mut EventList) -> Result<PathBuf> {
+        list.events
+            .sort_by_key(|e| (e.timestamp, e.end_timestamp, e.dataset.clone()));
+        for (i, event) in list.events.iter_mut().enumerate() {
+            event.id = i as u64 + 1;
+        }
+
+        // 先写临时文件再替换，避免写入中断损坏事件列表
+        let path = self.file_path();
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(list)?)?;
+        fs::rename(&temp_path, &path)?;
+        Ok(path)
+    }
+
+    /// 按时间顺序列出满足条件的事件
+    pub fn list(&self, query: &EventQuery) -> Result<Vec<DetectedEvent>> {
+        let Some(list) = self.read()? else {
+            return Ok(Vec::new());
+        };
+
+        // 事件按起点排序，窗口终点之后的事件不必检查
+        let end = match query.end_timestamp {
+            Some(end) => list.events.partition_point(|e| e.timestamp <= end),
+            None => list.events.len(),
+        };
+        let events = list.events[..end]
+            .iter()
+            .filter(|e| query.matches(e))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        Ok(events)
+    }
+
+    /// 数据集重命名后更新事件、规则和参与检测的数据集中的名称，返回更新的事件数
+    pub fn rename_dataset(&self, old_name: &str, new_name: &str) -> Result<usize> {
+        let count = self.modify(|list| {
+            let rename = |name: &mut String| {
+                if name == old_name {
+                    *name = new_name.to_string();
+                }
+            };
+            list.datasets.iter_mut().for_each(rename);
+            list.rules
+                .iter_mut()
+                .flat_map(|rule| rule.datasets.iter_mut())
+                .for_each(rename);
+            let mut count = 0;
+            for event in list.events.iter_mut().filter(|e| e.dataset == old_name) {
+                event.dataset = new_name.to_string();
+                count += 1;
+            }
+            count
+        })?;
+        Ok(count.unwrap_or(0))
+    }
+
+    /// 数据集移除后删除它的事件，并从规则和参与检测的数据集中去掉它；
+    /// 只适用于该数据集的规则一并删除。返回删除的事件数
+    pub fn remove_dataset(&self, dataset_name: &str) -> Result<usize> {
+        let count = self.modify(|list| {
+            list.datasets.retain(|d| d != dataset_name);
+            list.rules.retain_mut(|rule| {
+                if rule.datasets.is_empty() {
+                    return true;
+                }
+                rule.datasets.retain(|d| d != dataset_name);
+                !rule.datasets.is_empty()
+            });
+            let count = list.events.len();
+            list.events.retain(|e| e.dataset != dataset_name);
+            count - list.events.len()
+        })?;
+        Ok(count.unwrap_or(0))
+    }
+
+    /// 读取单个事件
+    pub fn get(&self, id: u64) -> Result<DetectedEvent> {
+        self.read()?
+            .and_then(|list| list.events.into_iter().find(|e| e.id == id))
+            .ok_or(PlaybackError::EventNotFound(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::rule::{ByteOrder, FieldSpec, FieldType, RuleKind};
+
+    fn event(timestamp: u64, dataset: &str, rule: &str) -> DetectedEvent {
+        DetectedEvent {
+            id: 0,
+            timestamp,
+            end_timestamp: None,
+            rule: rule.to_string(),
+            kind: EventKind::StateChange,
+            level: EventLevel::Normal,
+            dataset: dataset.to_string(),
+            title: rule.to_string(),
+            description: String::new(),
+            value: Some(1.0),
+            previous: Some(0.0),
+            file_name: "data.pcap".to_string(),
+            packet_index: 0,
+        }
+    }
+
+    fn rule(name: &str, datasets: &[&str]) -> EventRule {
+        EventRule {
+            name: name.to_string(),
+            kind: RuleKind::StateChange {
+                field: FieldSpec {
+                    offset: 0,
+                    field_type: FieldType::U8,
+                    byte_order: ByteOrder::Big,
+                },
+            },
+            datasets: datasets.iter().map(|d| d.to_string()).collect(),
+            pattern: None,
+            pattern_offset: None,
+            level: EventLevel::Normal,
+            title: None,
+        }
+    }
+
+    /// 写入包含 radar 和 ais 两个数据集事件的列表
+    fn write_list(store: &EventStore) {
+        let mut list = EventList::new(
+            vec![
+                rule("全部", &[]),
+                rule("雷达", &["radar"]),
+                rule("两者", &["radar", "ais"]),
+            ],
+            vec!["radar".to_string(), "ais".to_string()],
+        );
+        list.events = vec![
+            event(300, "radar", "雷达"),
+            event(100, "radar", "全部"),
+            event(200, "ais", "两者"),
+        ];
+        store.write(&mut list).unwrap();
+    }
+
+    #[test]
+    fn write_sorts_and_numbers_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::new(dir.path());
+        assert!(store.read().unwrap().is_none());
+        assert!(store.list(&EventQuery::default()).unwrap().is_empty());
+        write_list(&store);
+
+        let events = store.list(&EventQuery::default()).unwrap();
+        let order: Vec<(u64, u64)> = events.iter().map(|e| (e.id, e.timestamp)).collect();
+        assert_eq!(order, vec![(1, 100), (2, 200), (3, 300)]);
+        assert_eq!(store.get(2).unwrap().dataset, "ais");
+        assert!(matches!(store.get(4), Err(PlaybackError::EventNotFound(4))));
+        assert!(!dir.path().join(".events.json.tmp").exists());
+    }
+
+    #[test]
+    fn list_filters_by_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::new(dir.path());
+        write_list(&store);
+
+        let timestamps = |query: EventQuery| -> Vec<u64> {
+            store
+                .list(&query)
+                .unwrap()
+                .iter()
+                .map(|e| e.timestamp)
+                .collect()
+        };
+        assert_eq!(
+            timestamps(EventQuery {
+                start_timestamp: Some(150),
+                end_timestamp: Some(250),
+                ..Default::default()
+            }),
+            vec![200]
+        );
+        assert_eq!(
+            timestamps(EventQuery {
+                datasets: vec!["radar".to_string()],
+                ..Default::default()
+            }),
+            vec![100, 300]
+        );
+        assert_eq!(
+            timestamps(EventQuery {
+                rules: vec!["两者".to_string()],
+                ..Default::default()
+            }),
+            vec![200]
+        );
+        assert_eq!(
+            timestamps(EventQuery {
+                limit: Some(2),
+                ..Default::default()
+            }),
+            vec![100, 200]
+        );
+        assert!(timestamps(EventQuery {
+            levels: vec![EventLevel::High],
+            ..Default::default()
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn rename_dataset_updates_events_rules_and_datasets() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::new(dir.path());
+        write_list(&store);
+
+        assert_eq!(store.rename_dataset("radar", "radar2").unwrap(), 2);
+        let list = store.read().unwrap().unwrap();
+        assert_eq!(list.datasets, vec!["radar2", "ais"]);
+        assert!(list.rules[0].datasets.is_empty());
+        assert_eq!(list.rules[1].datasets, vec!["radar2"]);
+        assert_eq!(list.rules[2].datasets, vec!["radar2", "ais"]);
+        let datasets: Vec<&str> = list.events.iter().map(|e| e.dataset.as_str()).collect();
+        assert_eq!(datasets, vec!["radar2", "ais", "radar2"]);
+
+        // 没有该数据集的事件时不改动事件
+        assert_eq!(store.rename_dataset("missing", "other").unwrap(), 0);
+    }
+
+    #[test]
+    fn remove_dataset_drops_events_and_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::new(dir.path());
+        write_list(&store);
+
+        assert_eq!(store.remove_dataset("radar").unwrap(), 2);
+        let list = store.read().unwrap().unwrap();
+        assert_eq!(list.datasets, vec!["ais"]);
+        // 只适用于 radar 的规则被删除，适用于所有数据集的规则保留
+        let rules: Vec<(&str, &[String])> = list
+            .rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.datasets.as_slice()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![("全部", &[][..]), ("两者", &["ais".to_string()][..])]
+        );
+        assert_eq!(list.events.len(), 1);
+        assert_eq!(
+            (list.events[0].id, list.events[0].dataset.as_str()),
+            (1, "ais")
+        );
+    }
+
+    #[test]
+    fn dataset_updates_without_event_list_do_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::new(dir.path());
+        assert_eq!(store.rename_dataset("radar", "radar2").unwrap(), 0);
+        assert_eq!(store.remove_dataset("radar").unwrap(), 0);
+        assert!(!store.file_path().exists());
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::new(dir.path());
+        let mut list = EventList::new(Vec::new(), Vec::new());
+        list.events = (0..8)
+            .map(|i| event(i, &format!("ds{}", i), "规则"))
+            .collect();
+        store.write(&mut list).unwrap();
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let store = &store;
+                scope.spawn(move || {
+                    store
+                        .rename_dataset(&format!("ds{}", i), &format!("renamed{}", i))
+                        .unwrap()
+                });
+            }
+        });
+        let list = store.read().unwrap().unwrap();
+        assert!(list.events.iter().all(|e| e.dataset.starts_with("renamed")));
+    }
+}
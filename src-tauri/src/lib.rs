@@ -1,21 +1,33 @@
 // 模块声明
 pub mod api;
+pub mod control;
+pub mod dataset;
+pub mod event;
 pub mod geo;
 pub mod playback;
+pub mod product;
 pub mod project;
 pub mod state;
 pub mod streaming;
 pub mod types;
 
 // 重新导出应用类型
-pub use project::loader::ProjectLoader;
+pub use project::manager::ProjectManager;
 pub use state::app_state::AppState;
 pub use state::playback_state::PlaybackState;
 pub use types::{AppDataPacket, PacketType, PlaybackError, Result};
 
 // Tauri相关导入
+use crate::control::control_service::ControlService;
 use crate::geo::tile_service::TileService;
-use tauri::Manager;
+use std::time::Duration;
+use tauri::{Emitter, Manager, Runtime};
+
+/// 回放状态事件名称
+pub const PLAYBACK_STATE_EVENT: &str = "playback://state";
+
+/// 回放状态事件的最小推送间隔
+const PLAYBACK_STATE_EMIT_INTERVAL: Duration = Duration::from_millis(100);
 
 /// 启动瓦片代理服务
 async fn start_tile_service() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -27,21 +39,82 @@ async fn start_tile_service() -> std::result::Result<(), Box<dyn std::error::Err
     Ok(())
 }
 
-/// 初始化Tauri应用
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_dialog::init())
+/// 启动本地控制服务（未启用时直接返回）
+async fn start_control_service(
+    state: AppState,
+) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let control_service = ControlService::new(state)?;
+    if !control_service.is_enabled() {
+        log::info!("控制服务未启用");
+        return Ok(());
+    }
+
+    control_service.start_server().await?;
+
+    Ok(())
+}
+
+/// 将回放状态变化转发为前端事件
+fn spawn_playback_state_forwarder<R: Runtime>(app: tauri::AppHandle<R>, state: &AppState) {
+    let mut receiver = state.playback_engine.subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        while receiver.changed().await.is_ok() {
+            let snapshot = receiver.borrow_and_update().clone();
+            if let Err(e) = app.emit(PLAYBACK_STATE_EVENT, &snapshot) {
+                log::warn!("推送回放状态失败: {}", e);
+            }
+            // 节流，避免高频刷新前端
+            tokio::time::sleep(PLAYBACK_STATE_EMIT_INTERVAL).await;
+        }
+    });
+}
+
+/// 注册应用状态和命令处理器
+///
+/// 应用入口和集成测试共用，保证测试覆盖的命令与实际注册的一致
+pub fn register_handlers<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
+    builder
+        .manage(AppState::new())
         .invoke_handler(tauri::generate_handler![
             api::project_commands::select_project_directory,
             api::project_commands::open_project,
+            api::project_commands::validate_project,
             api::project_commands::get_project_info,
             api::project_commands::close_project,
             api::project_commands::get_project_structure,
+            api::project_commands::create_project,
             api::project_commands::create_dataset,
-            api::config_commands::list_datasets,
-            api::config_commands::get_dataset_stats,
-            api::config_commands::get_dataset_info,
+            api::project_commands::remove_dataset,
+            api::project_commands::rename_dataset,
+            api::project_commands::fit_time_correction,
+            api::project_commands::set_time_correction,
+            api::dataset_commands::list_datasets,
+            api::dataset_commands::get_dataset_stats,
+            api::dataset_commands::get_file_stats,
+            api::dataset_commands::get_dataset_info,
+            api::dataset_commands::import_pcap_files,
+            api::dataset_commands::import_capture_files,
+            api::dataset_commands::export_dataset_pcapng,
+            api::dataset_commands::verify_data,
+            api::dataset_commands::repair_dataset,
+            api::dataset_commands::extract_dataset,
+            api::dataset_commands::merge_datasets,
+            api::dataset_commands::split_dataset,
+            api::dataset_commands::bake_time_correction,
+            api::product_commands::list_products,
+            api::product_commands::open_product,
+            api::product_commands::generate_product,
+            api::product_commands::delete_product,
+            api::annotation_commands::list_annotations,
+            api::annotation_commands::create_annotation,
+            api::annotation_commands::update_annotation,
+            api::annotation_commands::delete_annotation,
+            api::event_commands::detect_events,
+            api::event_commands::list_events,
+            api::event_commands::get_event_rules,
+            api::event_commands::seek_to_event,
+            api::task_commands::cancel_task,
             api::playback_commands::start_playback,
             api::playback_commands::pause_playback,
             api::playback_commands::stop_playback,
@@ -49,15 +122,22 @@ pub fn run() {
             api::playback_commands::set_playback_speed,
             api::playback_commands::get_playback_state,
         ])
+}
+
+/// 初始化Tauri应用
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    register_handlers(tauri::Builder::default())
+        .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             // 初始化日志
             env_logger::Builder::from_default_env()
                 .filter_level(log::LevelFilter::Info)
                 .init();
 
-            // 初始化应用状态
-            let app_state = std::sync::Mutex::new(AppState::new());
-            app.manage(app_state);
+            // 命令、状态事件与控制服务共享同一应用状态
+            let shared_state = app.state::<AppState>().inner().clone();
+            spawn_playback_state_forwarder(app.handle().clone(), &shared_state);
 
             // 在窗口创建后启动瓦片代理服务
             std::thread::spawn(move || {
@@ -70,6 +150,17 @@ pub fn run() {
                 });
             });
 
+            // 启动本地控制服务
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                rt.block_on(async {
+                    match start_control_service(shared_state).await {
+                        Ok(_) => log::info!("控制服务正常退出"),
+                        Err(e) => log::error!("控制服务启动失败: {}", e),
+                    }
+                });
+            });
+
             Ok(())
         })
         .run(tauri::generate_context!())
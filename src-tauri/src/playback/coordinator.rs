@@ -1,49 +1,129 @@
 //! 数据协调器 - 协调PCAP读取和UDP发送
 
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use log::info;
+use std::collections::HashMap;
 
-use crate::playback::scheduler::EventScheduler;
+use crate::playback::reader::{CorruptPacketPolicy, DatasetCursor};
+use crate::playback::scheduler::{EventScheduler, ScheduledEvent};
 use crate::state::config_state::DatasetConfigState;
 use crate::streaming::udp_sender::UDPSender;
+use crate::types::{PlaybackError, Result};
 
 #[derive(Debug)]
 pub struct DataCoordinator {
-    scheduler: Arc<Mutex<EventScheduler>>,
-    sender: Arc<Mutex<Option<UDPSender>>>,
+    scheduler: EventScheduler,
+    cursors: Vec<DatasetCursor>,
+    senders: HashMap<String, UDPSender>,
+    sent_packets: u64,
 }
 
 impl DataCoordinator {
     pub fn new() -> Self {
         Self {
-            scheduler: Arc::new(Mutex::new(EventScheduler::new())),
-            sender: Arc::new(Mutex::new(None)),
+            scheduler: EventScheduler::new(),
+            cursors: Vec::new(),
+            senders: HashMap::new(),
+            sent_packets: 0,
         }
     }
 
-    /// 加载数据集到调度器
-    pub async fn load_dataset(
-        &self,
-        _dataset_name: &str,
-        _config: &DatasetConfigState,
-    ) -> Result<(), String> {
-        // TODO: 实现数据集加载逻辑
+    /// 加载数据集到调度器，`follow` 为真时以跟随模式打开，数据集可以还没有数据文件
+    pub fn load_dataset(
+        &mut self,
+        dataset_name: &str,
+        config: &DatasetConfigState,
+        sender: UDPSender,
+        policy: CorruptPacketPolicy,
+        follow: bool,
+    ) -> Result<()> {
+        let mut cursor = if follow {
+            DatasetCursor::open_following(dataset_name, &config.path, policy)?
+        } else {
+            DatasetCursor::open(dataset_name, &config.path, policy)?
+        };
+        info!(
+            "加载数据集 '{}': {} 个PCAP文件",
+            dataset_name,
+            cursor.files().len()
+        );
+        if let Some(correction) = &config.time_correction {
+            info!("数据集 '{}' 应用时间校正: {:?}", dataset_name, correction);
+        }
+        cursor.set_time_correction(config.time_correction.clone());
+
+        self.cursors.push(cursor);
+        self.senders.insert(dataset_name.to_string(), sender);
         Ok(())
     }
 
+    /// 设置各数据集的跟随模式，见 [`DatasetCursor::set_follow`]
+    pub fn set_follow(&mut self, follow: bool) {
+        for cursor in &mut self.cursors {
+            cursor.set_follow(follow);
+        }
+    }
+
+    /// 所有已加载数据集的时间范围
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        self.cursors
+            .iter()
+            .filter_map(|c| c.time_range())
+            .reduce(|(s1, e1), (s2, e2)| (s1.min(s2), e1.max(e2)))
+    }
+
+    /// 定位到指定时间点
+    pub fn seek(&mut self, timestamp: u64) {
+        self.scheduler.clear();
+        for cursor in &mut self.cursors {
+            cursor.seek(timestamp);
+        }
+    }
+
+    /// 已发送的数据包数量
+    pub fn sent_packets(&self) -> u64 {
+        self.sent_packets
+    }
+
+    /// 各数据集已遇到的损坏数据包数量之和
+    pub fn corrupt_packets(&self) -> u64 {
+        self.cursors.iter().map(|c| c.corrupt_packets()).sum()
+    }
+
+    /// 因损坏数据包停止读取时的错误
+    pub fn take_error(&mut self) -> Option<PlaybackError> {
+        self.cursors.iter_mut().find_map(|c| c.take_error())
+    }
+
     /// 发送当前时间点的数据
-    pub async fn send_current_data(&mut self, current_time: u64) -> Result<(), String> {
-        let mut scheduler = self.scheduler.lock().await;
+    pub fn send_current_data(&mut self, current_time: u64) -> Result<()> {
+        self.fill_scheduler(current_time);
 
-        while let Some(event) = scheduler.get_next_event(current_time) {
+        while let Some(event) = self.scheduler.get_next_event(current_time) {
             // 发送事件数据
-            if let Some(sender) = &*self.sender.lock().await {
-                sender
-                    .send_data(&event.data)
-                    .map_err(|e| format!("发送失败: {}", e))?;
+            if let Some(sender) = self.senders.get(&event.dataset) {
+                sender.send_data(&event.data)?;
             }
+            self.sent_packets += 1;
         }
 
         Ok(())
     }
+
+    /// 将到期的数据包从各数据集读入调度器
+    fn fill_scheduler(&mut self, current_time: u64) {
+        for cursor in &mut self.cursors {
+            while let Some(ts) = cursor.peek_timestamp() {
+                if ts > current_time {
+                    break;
+                }
+                if let Some(packet) = cursor.next_packet() {
+                    self.scheduler.add_event(ScheduledEvent {
+                        timestamp: ts,
+                        data: packet.data,
+                        dataset: cursor.dataset_name().to_string(),
+                    });
+                }
+            }
+        }
+    }
 }
@@ -1,116 +1,287 @@
-use log::{debug, info};
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::time::{interval, Duration};
+use log::{debug, error, info, warn};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
 
 use crate::playback::coordinator::DataCoordinator;
+use crate::playback::reader::CorruptPacketPolicy;
 use crate::playback::timeline::TimelineController;
 use crate::state::playback_state::{PlaybackState, PlaybackStatus};
 use crate::streaming::config_manager::ConfigManager;
+use crate::types::{PlaybackError, Result};
+
+/// 回放选项
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackOptions {
+    /// 起始偏移（纳秒，相对数据起点）
+    pub start_offset: Option<u64>,
+    /// 结束偏移（纳秒，相对数据起点）
+    pub end_offset: Option<u64>,
+    /// 回放速度
+    pub speed: Option<f64>,
+    /// 损坏数据包的处理方式
+    pub corrupt_packets: CorruptPacketPolicy,
+    /// 实时跟随：回放位置落后当前时间的延迟（纳秒）
+    ///
+    /// 设置后从最新数据附近开始回放，持续发送追加的数据包和新建的数据文件，
+    /// 不会自动结束；起止偏移不再生效。要求数据包时间戳为录制时的系统时间
+    pub live_latency: Option<u64>,
+}
+
+/// 默认实时跟随延迟（纳秒）
+pub const DEFAULT_LIVE_LATENCY: u64 = 1_000_000_000;
+
+/// 回放循环的推进间隔
+const TICK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// 当前系统时间（纳秒）
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
 
 /// 回放引擎 - 核心回放控制
+///
+/// 引擎内部自行同步，可通过 `Arc<PlaybackEngine>` 在命令、回放任务和
+/// 事件推送之间共享；回放状态通过 watch 通道发布，读取状态无需加锁等待。
 #[derive(Debug)]
 pub struct PlaybackEngine {
-    state: Arc<Mutex<PlaybackState>>,
-    coordinator: DataCoordinator,
-    config_manager: ConfigManager,
-    timeline: Option<TimelineController>,
-    is_running: Arc<Mutex<bool>>,
+    state: Arc<watch::Sender<PlaybackState>>,
+    config_manager: RwLock<ConfigManager>,
+    timeline: Arc<Mutex<Option<TimelineController>>>,
+    running: Mutex<Option<Arc<AtomicBool>>>,
+    start_lock: tokio::sync::Mutex<()>,
+    /// 跳转计数，回放循环据此发现外部跳转，并避免用旧位置覆盖跳转后的位置
+    seek_generation: Arc<AtomicU64>,
 }
 
 impl PlaybackEngine {
-    pub fn new(state: Arc<Mutex<PlaybackState>>) -> Self {
+    pub fn new() -> Self {
+        let (state, _) = watch::channel(PlaybackState::new());
+
         Self {
-            state,
-            coordinator: DataCoordinator::new(),
-            config_manager: ConfigManager::new(),
-            timeline: None,
-            is_running: Arc::new(Mutex::new(false)),
+            state: Arc::new(state),
+            config_manager: RwLock::new(ConfigManager::new()),
+            timeline: Arc::new(Mutex::new(None)),
+            running: Mutex::new(None),
+            start_lock: tokio::sync::Mutex::new(()),
+            seek_generation: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// 订阅回放状态变化
+    pub fn subscribe(&self) -> watch::Receiver<PlaybackState> {
+        self.state.subscribe()
+    }
+
+    /// 配置管理器
+    pub fn config_manager(&self) -> RwLockReadGuard<'_, ConfigManager> {
+        self.config_manager.read().unwrap()
+    }
+
+    /// 可写配置管理器
+    pub fn config_manager_mut(&self) -> RwLockWriteGuard<'_, ConfigManager> {
+        self.config_manager.write().unwrap()
+    }
+
     /// 开始回放
-    pub async fn start(&mut self, dataset_name: String) -> Result<(), String> {
-        info!("开始回放数据集: {}", dataset_name);
+    pub async fn start(&self, dataset_name: String) -> Result<()> {
+        // 同一时刻只允许一个启动流程，继续回放也须等待正在进行的启动完成
+        let _start_guard = self.start_lock.lock().await;
+
+        // 暂停状态下继续同一数据集的回放
+        let resumed = self.state.send_if_modified(|state| {
+            if state.is_paused() && state.datasets == [dataset_name.as_str()] {
+                state.status = PlaybackStatus::Playing;
+                true
+            } else {
+                false
+            }
+        });
+        if resumed {
+            info!("继续回放数据集: {}", dataset_name);
+            return Ok(());
+        }
+
+        self.start_locked(vec![dataset_name], PlaybackOptions::default())
+            .await
+    }
 
-        // 首先释放状态锁，然后再调用其他方法
+    /// 按选项开始回放一个或多个数据集
+    pub async fn start_with_options(
+        &self,
+        dataset_names: Vec<String>,
+        options: PlaybackOptions,
+    ) -> Result<()> {
+        let _start_guard = self.start_lock.lock().await;
+        self.start_locked(dataset_names, options).await
+    }
+
+    /// 开始回放，调用方须持有 `start_lock`
+    async fn start_locked(
+        &self,
+        dataset_names: Vec<String>,
+        options: PlaybackOptions,
+    ) -> Result<()> {
+        if dataset_names.is_empty() {
+            return Err(PlaybackError::PlaybackEngineError(
+                "未指定回放数据集".to_string(),
+            ));
+        }
+        info!("开始回放数据集: {:?}", dataset_names);
+
+        // 停止之前的回放循环
+        self.stop_playback_loop();
+
+        // 准备数据集配置和发送器
+        let mut datasets = Vec::with_capacity(dataset_names.len());
         {
-            let mut state = self.state.lock().await;
-            state.current_dataset = Some(dataset_name.clone());
-            state.status = PlaybackStatus::Playing;
+            let config_manager = self.config_manager();
+            for dataset_name in &dataset_names {
+                let config = config_manager
+                    .get_config()
+                    .get_dataset_config(dataset_name)
+                    .ok_or_else(|| PlaybackError::DatasetNotFound(dataset_name.clone()))?
+                    .clone();
+                let sender = config_manager.create_udp_sender_for_dataset(dataset_name)?;
+                datasets.push((dataset_name.clone(), config, sender));
+            }
         }
 
-        // 加载数据集配置
-        let _config = self
-            .config_manager
-            .get_config()
-            .get_dataset_config(&dataset_name)
-            .ok_or_else(|| format!("数据集配置不存在: {}", dataset_name))?
-            .clone();
+        // 在阻塞线程中加载数据集，避免占用异步运行时
+        let live_latency = options.live_latency;
+        let (coordinator, start, end, position) = tokio::task::spawn_blocking(move || {
+            let mut coordinator = DataCoordinator::new();
+            for (dataset_name, config, sender) in datasets {
+                coordinator.load_dataset(
+                    &dataset_name,
+                    &config,
+                    sender,
+                    options.corrupt_packets,
+                    live_latency.is_some(),
+                )?;
+            }
+
+            let (start, end, position) = match live_latency {
+                // 实时跟随时从当前时间减去延迟处开始，之前的数据可通过跳转回看
+                Some(latency) => {
+                    coordinator.set_follow(true);
+                    let live_edge = now_ns().saturating_sub(latency);
+                    let start = coordinator
+                        .time_range()
+                        .map_or(live_edge, |(data_start, _)| data_start.min(live_edge));
+                    (start, live_edge, live_edge)
+                }
+                None => {
+                    let (data_start, data_end) = coordinator.time_range().ok_or_else(|| {
+                        PlaybackError::FormatError("数据集中没有可回放的数据包".to_string())
+                    })?;
+                    let start = data_start
+                        .saturating_add(options.start_offset.unwrap_or(0))
+                        .min(data_end);
+                    let end = options
+                        .end_offset
+                        .map(|offset| data_start.saturating_add(offset))
+                        .unwrap_or(data_end)
+                        .clamp(start, data_end);
+                    (start, end, start)
+                }
+            };
+
+            coordinator.seek(position);
+            Ok::<_, PlaybackError>((coordinator, start, end, position))
+        })
+        .await
+        .map_err(|e| PlaybackError::PlaybackEngineError(format!("加载数据集任务失败: {}", e)))??;
 
         // 初始化时间轴
-        self.timeline = Some(TimelineController::new(0, 1000)); // 临时时间范围
+        let mut timeline = TimelineController::new(start, end);
+        timeline.set_current_time(position);
+        if let Some(speed) = options.speed {
+            timeline.set_playback_speed(speed);
+        }
 
-        // 启动回放循环
-        self.start_playback_loop(dataset_name).await?;
+        self.state.send_modify(|state| {
+            state.datasets = dataset_names;
+            state.start_timestamp = start;
+            state.current_timestamp = position;
+            state.total_duration = timeline.get_duration();
+            state.playback_speed = timeline.get_playback_speed();
+            state.current_packet_index = 0;
+            state.corrupt_packets = 0;
+            state.error = None;
+            state.live = live_latency.is_some();
+            state.status = PlaybackStatus::Playing;
+        });
+        *self.timeline.lock().unwrap() = Some(timeline);
 
-        Ok(())
+        // 启动回放循环
+        self.start_playback_loop(coordinator, position, end, live_latency)
     }
 
     /// 暂停回放
-    pub async fn pause(&mut self) -> Result<(), String> {
+    pub async fn pause(&self) -> Result<()> {
         info!("暂停回放");
 
-        let mut state = self.state.lock().await;
-        state.status = PlaybackStatus::Paused;
-
-        let mut is_running = self.is_running.lock().await;
-        *is_running = false;
+        self.state.send_if_modified(|state| {
+            if state.is_playing() {
+                state.status = PlaybackStatus::Paused;
+                true
+            } else {
+                false
+            }
+        });
 
         Ok(())
     }
 
     /// 停止回放
-    pub async fn stop(&mut self) -> Result<(), String> {
+    pub async fn stop(&self) -> Result<()> {
         info!("停止回放");
 
-        let mut state = self.state.lock().await;
-        state.status = PlaybackStatus::Stopped;
-        state.current_timestamp = 0;
+        self.stop_playback_loop();
 
-        let mut is_running = self.is_running.lock().await;
-        *is_running = false;
-
-        if let Some(timeline) = &mut self.timeline {
+        if let Some(timeline) = self.timeline.lock().unwrap().as_mut() {
             timeline.reset();
         }
 
+        self.state.send_modify(|state| {
+            state.status = PlaybackStatus::Stopped;
+            state.current_timestamp = state.start_timestamp;
+            state.current_packet_index = 0;
+        });
+
         Ok(())
     }
 
     /// 跳转到指定时间点
-    pub async fn seek_to(&mut self, timestamp: u64) -> Result<(), String> {
+    pub async fn seek_to(&self, timestamp: u64) -> Result<()> {
         info!("跳转到时间戳: {}", timestamp);
 
-        if let Some(timeline) = &mut self.timeline {
+        if let Some(timeline) = self.timeline.lock().unwrap().as_mut() {
             timeline.set_current_time(timestamp);
-
-            let mut state = self.state.lock().await;
-            state.current_timestamp = timestamp;
+            let current = timeline.get_current_time();
+            // 与跳转位置在同一次状态修改中递增计数，回放循环读取状态时两者一致
+            self.state.send_modify(|state| {
+                state.current_timestamp = current;
+                self.seek_generation.fetch_add(1, Ordering::SeqCst);
+            });
         }
 
         Ok(())
     }
 
     /// 设置回放速度
-    pub async fn set_speed(&mut self, speed: f64) -> Result<(), String> {
+    pub async fn set_speed(&self, speed: f64) -> Result<()> {
         info!("设置回放速度: {}", speed);
 
-        if let Some(timeline) = &mut self.timeline {
+        if let Some(timeline) = self.timeline.lock().unwrap().as_mut() {
             timeline.set_playback_speed(speed);
-
-            let mut state = self.state.lock().await;
-            state.playback_speed = speed;
+            let speed = timeline.get_playback_speed();
+            self.state.send_modify(|state| state.playback_speed = speed);
         }
 
         Ok(())
@@ -118,67 +289,142 @@ impl PlaybackEngine {
 
     /// 获取当前状态
     pub async fn get_state(&self) -> PlaybackState {
-        let state = self.state.lock().await;
-        state.clone()
+        self.state.borrow().clone()
     }
 
-    /// 启动回放循环
-    async fn start_playback_loop(&mut self, dataset_name: String) -> Result<(), String> {
-        let is_running = self.is_running.clone();
-        let state = self.state.clone();
-
-        // 加载数据集到协调器
-        let config = self
-            .config_manager
-            .get_config()
-            .get_dataset_config(&dataset_name)
-            .ok_or_else(|| format!("数据集配置不存在: {}", dataset_name))?;
-
-        self.coordinator.load_dataset(&dataset_name, config).await?;
-
-        let mut interval = interval(Duration::from_millis(10)); // 10ms间隔
+    /// 停止正在运行的回放循环
+    fn stop_playback_loop(&self) {
+        if let Some(running) = self.running.lock().unwrap().take() {
+            running.store(false, Ordering::SeqCst);
+        }
+    }
 
-        // 需要将coordinator移动到异步任务中
-        let coordinator = Arc::new(Mutex::new(std::mem::replace(
-            &mut self.coordinator,
-            DataCoordinator::new(),
-        )));
+    /// 启动回放循环，`live_latency` 不为空时结束时间随当前时间增长
+    ///
+    /// 循环运行在独立线程中，定位和读取数据文件不占用异步运行时
+    fn start_playback_loop(
+        &self,
+        mut coordinator: DataCoordinator,
+        start: u64,
+        mut end: u64,
+        live_latency: Option<u64>,
+    ) -> Result<()> {
+        let running = Arc::new(AtomicBool::new(true));
+        *self.running.lock().unwrap() = Some(running.clone());
+        let state = self.state.clone();
+        let timeline = self.timeline.clone();
+        let seek_generation = self.seek_generation.clone();
+        let mut seen_generation = seek_generation.load(Ordering::SeqCst);
+        let loop_running = running.clone();
+
+        let spawned = thread::Builder::new()
+            .name("playback".to_string())
+            .spawn(move || {
+                let running = loop_running;
+                let mut last_tick = Instant::now();
+                let mut position = start;
+
+                while running.load(Ordering::SeqCst) {
+                    thread::sleep(TICK_INTERVAL);
+                    let now = Instant::now();
+                    let elapsed = now.duration_since(last_tick).as_nanos() as u64;
+                    last_tick = now;
+
+                    let (status, current, speed, generation) = {
+                        let state_ref = state.borrow();
+                        (
+                            state_ref.status.clone(),
+                            state_ref.current_timestamp,
+                            state_ref.playback_speed,
+                            seek_generation.load(Ordering::SeqCst),
+                        )
+                    };
+                    // 实时跟随时数据结束时间随当前时间增长，暂停期间同样增长
+                    if let Some(latency) = live_latency {
+                        end = end.max(now_ns().saturating_sub(latency));
+                        if let Some(timeline) = timeline.lock().unwrap().as_mut() {
+                            timeline.extend_end_time(end);
+                        }
+                        state.send_if_modified(|state| {
+                            let duration = end.saturating_sub(state.start_timestamp);
+                            let changed = state.total_duration != duration;
+                            state.total_duration = duration;
+                            changed && status != PlaybackStatus::Playing
+                        });
+                    }
 
-        tokio::spawn(async move {
-            *is_running.lock().await = true;
+                    if status != PlaybackStatus::Playing {
+                        continue;
+                    }
 
-            while *is_running.lock().await {
-                interval.tick().await;
+                    // 外部跳转后重新定位数据读取位置
+                    if generation != seen_generation {
+                        coordinator.seek(current);
+                        position = current;
+                        seen_generation = generation;
+                    }
 
-                let state_guard = state.lock().await;
-                if state_guard.status != PlaybackStatus::Playing {
-                    continue;
-                }
+                    let next = position
+                        .saturating_add((elapsed as f64 * speed) as u64)
+                        .min(end);
 
-                let current_timestamp = state_guard.current_timestamp;
-                drop(state_guard); // 释放锁以避免死锁
+                    // 使用协调器发送当前时间点的数据
+                    if let Err(e) = coordinator.send_current_data(next) {
+                        warn!("发送数据失败: {}", e);
+                    }
+                    position = next;
 
-                // 使用协调器发送当前时间点的数据
-                if let Ok(mut coord) = coordinator.try_lock() {
-                    if let Err(e) = coord.send_current_data(current_timestamp).await {
-                        debug!("发送数据失败: {}", e);
+                    // 遇到损坏数据包且处理方式为停止时结束回放
+                    let error = coordinator.take_error();
+                    if let Some(e) = &error {
+                        error!("回放因数据损坏停止: {}", e);
                     }
-                }
 
-                // 更新播放进度
-                let mut state_guard = state.lock().await;
-                state_guard.current_timestamp += 1; // 模拟进度
-                if state_guard.current_timestamp >= 1000 {
-                    state_guard.current_timestamp = 1000;
-                    state_guard.status = PlaybackStatus::Stopped;
-                    break;
+                    // 更新播放进度（停止后不再覆盖状态）
+                    let sent_packets = coordinator.sent_packets();
+                    let corrupt_packets = coordinator.corrupt_packets();
+                    state.send_if_modified(|state| {
+                        if !running.load(Ordering::SeqCst) {
+                            return false;
+                        }
+                        // 本轮期间发生跳转时保留跳转位置，下一轮重新定位
+                        if seek_generation.load(Ordering::SeqCst) == seen_generation {
+                            state.current_timestamp = position;
+                        }
+                        state.current_packet_index = sent_packets;
+                        state.corrupt_packets = corrupt_packets;
+                        if let Some(e) = &error {
+                            state.status = PlaybackStatus::Stopped;
+                            state.error = Some(e.to_string());
+                        } else if live_latency.is_none() && position >= end {
+                            state.status = PlaybackStatus::Completed;
+                        }
+                        true
+                    });
+                    if error.is_some() || (live_latency.is_none() && position >= end) {
+                        break;
+                    }
                 }
-            }
-
-            *is_running.lock().await = false;
-            debug!("回放循环结束");
-        });
 
+                running.store(false, Ordering::SeqCst);
+                debug!("回放循环结束");
+            });
+
+        if let Err(e) = spawned {
+            running.store(false, Ordering::SeqCst);
+            let message = format!("启动回放线程失败: {}", e);
+            self.state.send_modify(|state| {
+                state.status = PlaybackStatus::Stopped;
+                state.error = Some(message.clone());
+            });
+            return Err(PlaybackError::PlaybackEngineError(message));
+        }
         Ok(())
     }
 }
+
+impl Default for PlaybackEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
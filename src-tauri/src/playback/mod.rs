@@ -4,5 +4,6 @@
 
 pub mod coordinator;
 pub mod engine;
+pub mod reader;
 pub mod scheduler;
 pub mod timeline;
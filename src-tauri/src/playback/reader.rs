@@ -0,0 +1,558 @@
+//! 数据集读取游标 - 按时间顺序遍历数据集内的PCAP文件
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::dataset::format::{PacketReader, RawPacket, FILE_HEADER_SIZE};
+use crate::dataset::index::{data_files, DatasetIndex, FileIndex};
+use crate::dataset::info::{file_index_entry, file_time_range, read_dataset_index};
+use crate::types::{PlaybackError, Result, TimeCorrection};
+
+/// 回放时遇到损坏数据包（校验和不匹配、包头无效或内容不完整）的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CorruptPacketPolicy {
+    /// 停止回放并报告错误
+    #[default]
+    Abort,
+    /// 跳过损坏的数据包继续回放
+    Skip,
+    /// 照常发送校验和不匹配的数据包，只记录数量
+    Flag,
+}
+
+impl FromStr for CorruptPacketPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(Self::Abort),
+            "skip" => Ok(Self::Skip),
+            "flag" => Ok(Self::Flag),
+            _ => Err(format!("无效的损坏数据包处理方式: {}", s)),
+        }
+    }
+}
+
+/// 数据集读取游标
+pub struct DatasetCursor {
+    dataset_name: String,
+    /// 数据集目录，跟随模式下从中查找新文件
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+    file_index: usize,
+    reader: Option<PacketReader<BufReader<File>>>,
+    pending: Option<RawPacket>,
+    policy: CorruptPacketPolicy,
+    corrupt_packets: u64,
+    error: Option<PlaybackError>,
+    /// 跟随模式：最后一个文件读完后等待追加的数据和新文件
+    follow: bool,
+    time_correction: Option<TimeCorrection>,
+    /// 数据集索引，定位时用于查找文件内的数据包偏移
+    index: Option<DatasetIndex>,
+    /// 各文件首个数据包的时间戳（未校正），定位时按需读取
+    file_starts: HashMap<PathBuf, u64>,
+}
+
+impl std::fmt::Debug for DatasetCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatasetCursor")
+            .field("dataset_name", &self.dataset_name)
+            .field("files", &self.files.len())
+            .field("file_index", &self.file_index)
+            .field("policy", &self.policy)
+            .field("corrupt_packets", &self.corrupt_packets)
+            .field("follow", &self.follow)
+            .field("time_correction", &self.time_correction)
+            .field("indexed", &self.index.is_some())
+            .finish()
+    }
+}
+
+impl DatasetCursor {
+    /// 打开数据集目录，目录中没有数据文件时失败
+    pub fn open<P: AsRef<Path>>(
+        dataset_name: &str,
+        dataset_path: P,
+        policy: CorruptPacketPolicy,
+    ) -> Result<Self> {
+        let cursor = Self::open_dir(dataset_name, dataset_path.as_ref(), policy)?;
+        if cursor.files.is_empty() {
+            return Err(PlaybackError::ProjectError(format!(
+                "数据集目录中未找到PCAP文件: {:?}",
+                dataset_path.as_ref()
+            )));
+        }
+        Ok(cursor)
+    }
+
+    /// 以跟随模式打开数据集目录，目录中还没有数据文件时等待记录程序创建
+    pub fn open_following<P: AsRef<Path>>(
+        dataset_name: &str,
+        dataset_path: P,
+        policy: CorruptPacketPolicy,
+    ) -> Result<Self> {
+        let mut cursor = Self::open_dir(dataset_name, dataset_path.as_ref(), policy)?;
+        cursor.follow = true;
+        Ok(cursor)
+    }
+
+    fn open_dir(
+        dataset_name: &str,
+        dataset_path: &Path,
+        policy: CorruptPacketPolicy,
+    ) -> Result<Self> {
+        let files = data_files(dataset_path)?;
+        Ok(Self {
+            dataset_name: dataset_name.to_string(),
+            dir: dataset_path.to_path_buf(),
+            files,
+            file_index: 0,
+            reader: None,
+            pending: None,
+            policy,
+            corrupt_packets: 0,
+            error: None,
+            follow: false,
+            time_correction: None,
+            index: read_dataset_index(dataset_path),
+            file_starts: HashMap::new(),
+        })
+    }
+
+    /// 数据集名称
+    pub fn dataset_name(&self) -> &str {
+        &self.dataset_name
+    }
+
+    /// 数据集文件列表
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// 查看下一个数据包的时间戳（纳秒，已按时间校正映射）
+    pub fn peek_timestamp(&mut self) -> Option<u64> {
+        if self.pending.is_none() {
+            self.pending = self.read_next();
+        }
+        let timestamp = self.pending.as_ref()?.header.timestamp_ns();
+        Some(self.corrected(timestamp))
+    }
+
+    /// 取出下一个数据包
+    pub fn next_packet(&mut self) -> Option<RawPacket> {
+        match self.pending.take() {
+            Some(packet) => Some(packet),
+            None => self.read_next(),
+        }
+    }
+
+    /// 设置跟随模式
+    ///
+    /// 开启后读到最后一个文件末尾时不结束：只读取已完整写入的数据包，暂无新数据时
+    /// `peek_timestamp`/`next_packet` 返回 `None`，之后再次调用可读到追加的数据包，
+    /// 数据集目录中出现新文件时继续读取新文件
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+    }
+
+    /// 设置时间校正，之后的时间戳、定位和时间范围均使用校正后的时间
+    pub fn set_time_correction(&mut self, time_correction: Option<TimeCorrection>) {
+        self.time_correction = time_correction;
+    }
+
+    /// 是否处于跟随模式
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    /// 已遇到的损坏数据包数量
+    pub fn corrupt_packets(&self) -> u64 {
+        self.corrupt_packets
+    }
+
+    /// 按 [`CorruptPacketPolicy::Abort`] 停止读取时的错误
+    pub fn take_error(&mut self) -> Option<PlaybackError> {
+        self.error.take()
+    }
+
+    /// 定位到不早于指定时间戳的第一个数据包
+    ///
+    /// 按文件首包时间二分查找起始文件，有索引时直接跳到文件内的数据包偏移，
+    /// 否则从起始文件开头逐包读取
+    pub fn seek(&mut self, timestamp: u64) {
+        self.reader = None;
+        self.pending = None;
+        self.file_index = self.first_file_for(timestamp);
+
+        if let Some(offset) = self.indexed_offset(timestamp) {
+            let path = &self.files[self.file_index];
+            let opened = PacketReader::open(path).and_then(|mut reader| {
+                reader.seek_to(offset)?;
+                Ok(reader)
+            });
+            match opened {
+                Ok(reader) => self.reader = Some(reader),
+                Err(e) => warn!("按索引定位失败 {:?}: {}", path, e),
+            }
+        }
+
+        while let Some(ts) = self.peek_timestamp() {
+            if ts >= timestamp {
+                break;
+            }
+            self.pending = None;
+        }
+        debug!("数据集 '{}' 定位到时间戳: {}", self.dataset_name, timestamp);
+    }
+
+    /// 数据集的时间范围（首包和末包时间戳，已按时间校正映射）
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        let range = |path: &PathBuf| file_time_range(path).ok().flatten();
+        let (first, _) = self.files.iter().find_map(range)?;
+        let (_, last) = self.files.iter().rev().find_map(range)?;
+        let (first, last) = (self.corrected(first), self.corrected(last));
+        Some((first, last.max(first)))
+    }
+
+    /// 最后一个首包时间早于 `timestamp` 的文件，不存在时为第一个文件
+    ///
+    /// 读不出首包时间的文件视为晚于 `timestamp`，结果只会偏早，不会跳过数据包
+    fn first_file_for(&mut self, timestamp: u64) -> usize {
+        let (mut low, mut high) = (0, self.files.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.file_start(mid) {
+                Some(start) if self.corrected(start) < timestamp => low = mid + 1,
+                _ => high = mid,
+            }
+        }
+        low.saturating_sub(1)
+    }
+
+    /// 文件首个数据包的时间戳，优先取自索引
+    fn file_start(&mut self, file_index: usize) -> Option<u64> {
+        let path = &self.files[file_index];
+        if let Some(start) = self.file_starts.get(path) {
+            return Some(*start);
+        }
+
+        let start = match self.index_entry(file_index) {
+            Some(entry) if entry.packet_count > 0 => entry.start_timestamp,
+            _ => PacketReader::open(path)
+                .and_then(|mut reader| reader.skip_packet())
+                .ok()
+                .flatten()?
+                .0
+                .timestamp_ns(),
+        };
+        self.file_starts.insert(path.clone(), start);
+        Some(start)
+    }
+
+    /// 当前文件中不早于 `timestamp` 的第一个数据包的偏移，没有这样的数据包时为文件末尾；
+    /// 索引与文件不一致时返回 `None`
+    fn indexed_offset(&self, timestamp: u64) -> Option<u64> {
+        let entry = self
+            .index_entry(self.file_index)
+            .filter(|e| e.packets.len() as u64 == e.packet_count)?;
+        let first = entry
+            .packets
+            .partition_point(|p| self.corrected(p.timestamp_ns) < timestamp);
+        Some(
+            entry
+                .packets
+                .get(first)
+                .map_or(entry.size, |p| p.byte_offset),
+        )
+    }
+
+    /// 与文件名和大小一致的索引条目
+    fn index_entry(&self, file_index: usize) -> Option<&FileIndex> {
+        let index = self.index.as_ref()?;
+        let path = self.files.get(file_index)?;
+        let name = path.file_name()?.to_string_lossy();
+        let size = fs::metadata(path).ok()?.len();
+        file_index_entry(index, &name, size)
+    }
+
+    fn corrected(&self, timestamp: u64) -> u64 {
+        self.time_correction
+            .as_ref()
+            .map_or(timestamp, |c| c.apply(timestamp))
+    }
+
+    fn read_next(&mut self) -> Option<RawPacket> {
+        loop {
+            if self.error.is_some() {
+                return None;
+            }
+
+            let tailing = self.follow && self.file_index + 1 >= self.files.len();
+            if self.reader.is_none() {
+                let Some(path) = self.files.get(self.file_index) else {
+                    // 跟随时最后一个文件读完或因损坏跳过后，等待记录程序创建新文件
+                    if self.follow && self.refresh_files() {
+                        continue;
+                    }
+                    return None;
+                };
+                // 正在写入的文件可能还没有完整的文件头
+                if tailing && fs::metadata(path).map_or(true, |m| m.len() < FILE_HEADER_SIZE) {
+                    return None;
+                }
+                match PacketReader::open(path) {
+                    Ok(reader) => self.reader = Some(reader),
+                    Err(e) => {
+                        self.handle_corrupt_file(format!("打开PCAP文件失败 {:?}: {}", path, e));
+                        continue;
+                    }
+                }
+            }
+
+            let path = &self.files[self.file_index];
+            let reader = self.reader.as_mut()?;
+            let result = if tailing {
+                match fs::metadata(path) {
+                    Ok(metadata) => reader.next_complete_packet(metadata.len()),
+                    Err(e) => Err(e.into()),
+                }
+            } else {
+                reader.next_packet()
+            };
+            match result {
+                Ok(Some(packet)) if packet.checksum_matches() => return Some(packet),
+                Ok(Some(packet)) => {
+                    self.corrupt_packets += 1;
+                    let message = format!("数据包校验和不匹配 {:?} 偏移 {}", path, packet.offset);
+                    match self.policy {
+                        CorruptPacketPolicy::Abort => {
+                            self.error = Some(PlaybackError::FormatError(message));
+                            return None;
+                        }
+                        CorruptPacketPolicy::Skip => {
+                            warn!("{}，已跳过", message);
+                            continue;
+                        }
+                        CorruptPacketPolicy::Flag => {
+                            warn!("{}", message);
+                            return Some(packet);
+                        }
+                    }
+                }
+                Ok(None) if tailing => {
+                    // 已读到正在写入的数据末尾；出现新文件时先按普通方式读完当前文件
+                    if !self.refresh_files() {
+                        return None;
+                    }
+                }
+                Ok(None) => {
+                    // 当前文件结束，切换到下一个文件
+                    self.reader = None;
+                    self.file_index += 1;
+                }
+                Err(e) => {
+                    // 包头无效或内容不完整时无法定位后续数据包
+                    self.corrupt_packets += 1;
+                    self.handle_corrupt_file(format!("读取PCAP文件失败 {:?}: {}", path, e));
+                }
+            }
+        }
+    }
+
+    /// 重新扫描数据集目录，返回是否有排在已知文件之后的新文件
+    fn refresh_files(&mut self) -> bool {
+        let files = match data_files(&self.dir) {
+            Ok(files) => files,
+            Err(e) => {
+                warn!("扫描数据集 '{}' 新文件失败: {}", self.dataset_name, e);
+                return false;
+            }
+        };
+
+        let last = self.files.last();
+        let newer: Vec<PathBuf> = files
+            .into_iter()
+            .filter(|f| last.is_none_or(|last| f > last))
+            .collect();
+        if newer.is_empty() {
+            return false;
+        }
+        debug!(
+            "数据集 '{}' 出现 {} 个新文件",
+            self.dataset_name,
+            newer.len()
+        );
+        self.files.extend(newer);
+        true
+    }
+
+    /// 当前文件无法继续读取：按处理方式停止或跳到下一个文件
+    fn handle_corrupt_file(&mut self, message: String) {
+        if self.policy == CorruptPacketPolicy::Abort {
+            self.error = Some(PlaybackError::FormatError(message));
+            return;
+        }
+
+        warn!("{}，跳过文件剩余内容", message);
+        self.reader = None;
+        self.file_index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::{DataFileWriter, PacketHeader};
+    use crate::dataset::naming::data_file_name;
+    use std::io::Write;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    fn write_file(dir: &Path, packets: &[(u64, &[u8])]) -> PathBuf {
+        let mut writer = DataFileWriter::create(dir.join(data_file_name(packets[0].0))).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    /// 模拟记录程序向文件末尾追加数据包
+    fn append_packet(path: &Path, timestamp: u64, data: &[u8]) {
+        let mut file = File::options().append(true).open(path).unwrap();
+        PacketHeader::new(timestamp, data)
+            .write_to(&mut file)
+            .unwrap();
+        file.write_all(data).unwrap();
+    }
+
+    fn next_timestamp(cursor: &mut DatasetCursor) -> Option<u64> {
+        cursor.next_packet().map(|p| p.header.timestamp_ns())
+    }
+
+    #[test]
+    fn reads_files_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), &[(BASE_TIMESTAMP, b"p0")]);
+        write_file(dir.path(), &[(BASE_TIMESTAMP + 1_000, b"p1")]);
+        let mut cursor =
+            DatasetCursor::open("radar", dir.path(), CorruptPacketPolicy::Abort).unwrap();
+
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP));
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP + 1_000));
+        assert_eq!(next_timestamp(&mut cursor), None);
+    }
+
+    #[test]
+    fn open_rejects_empty_dataset_unless_following() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(DatasetCursor::open("radar", dir.path(), CorruptPacketPolicy::Abort).is_err());
+
+        let mut cursor =
+            DatasetCursor::open_following("radar", dir.path(), CorruptPacketPolicy::Abort).unwrap();
+        assert!(cursor.is_following());
+        assert_eq!(cursor.time_range(), None);
+        assert_eq!(next_timestamp(&mut cursor), None);
+
+        // 记录程序创建第一个文件后开始读取
+        write_file(dir.path(), &[(BASE_TIMESTAMP, b"p0")]);
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP));
+    }
+
+    #[test]
+    fn follows_appended_packets_and_growing_end_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &[(BASE_TIMESTAMP, b"p0")]);
+        let mut cursor =
+            DatasetCursor::open_following("radar", dir.path(), CorruptPacketPolicy::Abort).unwrap();
+
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP));
+        assert_eq!(next_timestamp(&mut cursor), None);
+        assert_eq!(cursor.time_range(), Some((BASE_TIMESTAMP, BASE_TIMESTAMP)));
+
+        append_packet(&path, BASE_TIMESTAMP + 10, b"p1");
+        assert_eq!(cursor.peek_timestamp(), Some(BASE_TIMESTAMP + 10));
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP + 10));
+        assert_eq!(
+            cursor.time_range(),
+            Some((BASE_TIMESTAMP, BASE_TIMESTAMP + 10))
+        );
+    }
+
+    #[test]
+    fn waits_for_incomplete_packet_while_following() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &[(BASE_TIMESTAMP, b"p0")]);
+        let mut cursor =
+            DatasetCursor::open_following("radar", dir.path(), CorruptPacketPolicy::Abort).unwrap();
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP));
+
+        // 只写入包头，数据内容尚未写入
+        let mut file = File::options().append(true).open(&path).unwrap();
+        PacketHeader::new(BASE_TIMESTAMP + 10, b"p1")
+            .write_to(&mut file)
+            .unwrap();
+        assert_eq!(next_timestamp(&mut cursor), None);
+        assert!(cursor.take_error().is_none());
+
+        file.write_all(b"p1").unwrap();
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP + 10));
+    }
+
+    #[test]
+    fn rolls_over_into_new_file_while_following() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_file(dir.path(), &[(BASE_TIMESTAMP, b"p0")]);
+        let mut cursor =
+            DatasetCursor::open_following("radar", dir.path(), CorruptPacketPolicy::Abort).unwrap();
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP));
+        assert_eq!(next_timestamp(&mut cursor), None);
+
+        // 新文件出现前追加到旧文件的数据包仍按顺序读出
+        append_packet(&first, BASE_TIMESTAMP + 10, b"p1");
+        write_file(dir.path(), &[(BASE_TIMESTAMP + 1_000, b"p2")]);
+
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP + 10));
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP + 1_000));
+        assert_eq!(cursor.files().len(), 2);
+    }
+
+    #[test]
+    fn picks_up_new_file_after_corrupt_last_file() {
+        // 记录程序崩溃留下损坏的文件，重启后写入新文件
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &[(BASE_TIMESTAMP, b"p0")]);
+        let mut file = File::options().append(true).open(&path).unwrap();
+        file.write_all(&[0xFF; 16]).unwrap();
+
+        let mut cursor =
+            DatasetCursor::open_following("radar", dir.path(), CorruptPacketPolicy::Skip).unwrap();
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP));
+        assert_eq!(next_timestamp(&mut cursor), None);
+        assert_eq!(cursor.corrupt_packets(), 1);
+        assert_eq!(next_timestamp(&mut cursor), None);
+
+        write_file(dir.path(), &[(BASE_TIMESTAMP + 1_000, b"p1")]);
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP + 1_000));
+    }
+
+    #[test]
+    fn corrupt_file_aborts_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_file(dir.path(), &[(BASE_TIMESTAMP, b"p0")]);
+        let mut file = File::options().append(true).open(&path).unwrap();
+        file.write_all(&[0xFF; 16]).unwrap();
+
+        let mut cursor =
+            DatasetCursor::open("radar", dir.path(), CorruptPacketPolicy::Abort).unwrap();
+        assert_eq!(next_timestamp(&mut cursor), Some(BASE_TIMESTAMP));
+        assert_eq!(next_timestamp(&mut cursor), None);
+        assert!(cursor.take_error().is_some());
+    }
+}
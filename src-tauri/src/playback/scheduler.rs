@@ -55,6 +55,10 @@ impl EventScheduler {
         None
     }
 
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
     pub fn is_empty(&self) -> bool {
         self.events.is_empty()
     }
@@ -42,8 +42,9 @@ impl TimelineController {
         self.playback_speed
     }
 
-    pub fn advance_time(&mut self, delta_ms: u64) -> bool {
-        let advance_amount = (delta_ms as f64 * self.playback_speed) as u64;
+    /// 按实际经过的时间推进（纳秒），返回是否到达结尾
+    pub fn advance_time(&mut self, delta_ns: u64) -> bool {
+        let advance_amount = (delta_ns as f64 * self.playback_speed) as u64;
         let new_time = self.current_time + advance_amount;
 
         if new_time >= self.end_time {
@@ -55,6 +56,19 @@ impl TimelineController {
         }
     }
 
+    /// 延长结束时间（实时跟随时数据不断追加），不会缩短
+    pub fn extend_end_time(&mut self, end: u64) {
+        self.end_time = self.end_time.max(end);
+    }
+
+    pub fn get_start_time(&self) -> u64 {
+        self.start_time
+    }
+
+    pub fn get_end_time(&self) -> u64 {
+        self.end_time
+    }
+
     pub fn get_duration(&self) -> u64 {
         self.end_time - self.start_time
     }
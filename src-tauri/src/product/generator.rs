@@ -0,0 +1,400 @@
+//! 数据产品生成
+//!
+//! 从选定数据集中抽取时间窗口内的数据包，分别写入 `.result/<数据集>/<产品名>/`，
+//! 抽取过程与数据抽取相同（见 [`crate::dataset::extract`]）。
+//! 重新生成同名产品时先清空原有的抽取结果。
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dataset::extract::{DatasetExtractor, ExtractFilter, ExtractStage, ExtractSummary};
+use crate::product::pprod::{PprodWriter, ProductConfig};
+use crate::product::store::{DatasetProductStatus, ProductState, ProductStore};
+use crate::project::validator::ProjectValidator;
+use crate::state::task_state::CancelToken;
+use crate::types::{PlaybackError, Result};
+
+/// 数据产品生成请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductRequest {
+    /// 产品名称，同时作为产品文件名和结果目录名
+    pub name: String,
+    /// 显示名称，未设置时使用产品名称
+    #[serde(default)]
+    pub caption: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 起始时间戳（纳秒，含）
+    pub start_timestamp: u64,
+    /// 结束时间戳（纳秒，含）
+    pub end_timestamp: u64,
+    pub datasets: Vec<String>,
+}
+
+/// 生成进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProductStage {
+    Extracting,
+    Indexing,
+    Completed,
+}
+
+impl ProductStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProductStage::Extracting => "extracting",
+            ProductStage::Indexing => "indexing",
+            ProductStage::Completed => "completed",
+        }
+    }
+}
+
+/// 生成结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductSummary {
+    pub name: String,
+    pub file_path: String,
+    pub datasets: Vec<DatasetProductStatus>,
+    pub cancelled: bool,
+}
+
+/// 数据产品生成器
+#[derive(Debug)]
+pub struct ProductGenerator {
+    project_path: PathBuf,
+    name: String,
+    config: ProductConfig,
+}
+
+impl ProductGenerator {
+    /// 校验请求，产品名称或数据集无效时失败；同名产品已存在时沿用其创建时间
+    pub fn new<P: AsRef<Path>>(project_path: P, request: ProductRequest) -> Result<Self> {
+        let project_path = project_path.as_ref().to_path_buf();
+        ProjectValidator::validate_name(&request.name)?;
+
+        let mut config = ProductConfig::new(
+            request.caption.unwrap_or_else(|| request.name.clone()),
+            request.start_timestamp,
+            request.end_timestamp,
+        );
+        config.description = request.description;
+        config.datasets = request.datasets;
+        config.validate()?;
+
+        for dataset in &config.datasets {
+            ProjectValidator::validate_name(dataset)?;
+            if !project_path.join(dataset).is_dir() {
+                return Err(PlaybackError::DatasetNotFound(dataset.clone()));
+            }
+        }
+
+        let store = ProductStore::new(&project_path);
+        if let Ok(existing) = store.open(&request.name) {
+            config.created_time = existing.config.created_time;
+        }
+
+        Ok(Self {
+            project_path,
+            name: request.name,
+            config,
+        })
+    }
+
+    /// 参与抽取的数据集
+    pub fn datasets(&self) -> &[String] {
+        &self.config.datasets
+    }
+
+    /// 生成数据产品，`progress` 参数依次为阶段、已处理数据集数、数据集总数和当前数据集
+    ///
+    /// 单个数据集失败不会中断其他数据集，失败原因记录在生成状态中
+    pub fn generate<F>(&self, cancel: &CancelToken, mut progress: F) -> Result<ProductSummary>
+    where
+        F: FnMut(ProductStage, u64, u64, Option<&str>),
+    {
+        let store = ProductStore::new(&self.project_path);
+        let product_path = store.product_path(&self.name);
+        PprodWriter::write(&product_path, &self.config)?;
+
+        let total = self.config.datasets.len() as u64;
+        let mut summary = ProductSummary {
+            name: self.name.clone(),
+            file_path: product_path.to_string_lossy().to_string(),
+            datasets: Vec::new(),
+            cancelled: false,
+        };
+
+        for (i, dataset) in self.config.datasets.iter().enumerate() {
+            let mut status = DatasetProductStatus {
+                dataset: dataset.clone(),
+                state: if summary.cancelled {
+                    ProductState::Cancelled
+                } else {
+                    ProductState::Generating
+                },
+                ..Default::default()
+            };
+            store.write_status(&self.name, &mut status)?;
+            if summary.cancelled {
+                summary.datasets.push(status);
+                continue;
+            }
+
+            progress(ProductStage::Extracting, i as u64, total, Some(dataset));
+            let result_dir = store.result_dir(dataset, &self.name);
+            let result = self.extract_dataset(dataset, &result_dir, cancel, |stage| {
+                if stage == ExtractStage::Indexing {
+                    progress(ProductStage::Indexing, i as u64, total, Some(dataset));
+                }
+            });
+
+            status.state = match result {
+                Ok(extracted) => {
+                    status.files = extracted.files;
+                    status.packets = extracted.packets;
+                    status.total_size = extracted.total_size;
+                    status.source_files = extracted.source_files;
+                    status.corrupt_packets = extracted.corrupt_packets;
+                    if let Some(failure) = extracted.failed.first() {
+                        status.error = Some(format!("{}: {}", failure.path, failure.reason));
+                    }
+
+                    if extracted.cancelled {
+                        ProductState::Cancelled
+                    } else if status.error.is_some() {
+                        ProductState::Failed
+                    } else {
+                        ProductState::Completed
+                    }
+                }
+                Err(e) => {
+                    warn!("抽取数据集 {} 失败: {}", dataset, e);
+                    status.error = Some(e.to_string());
+                    ProductState::Failed
+                }
+            };
+            summary.cancelled |= status.state == ProductState::Cancelled;
+            store.write_status(&self.name, &mut status)?;
+            summary.datasets.push(status);
+        }
+
+        progress(ProductStage::Completed, total, total, None);
+        info!(
+            "数据产品 {} 生成{}: {} 个数据包",
+            self.name,
+            if summary.cancelled {
+                "已取消"
+            } else {
+                "完成"
+            },
+            summary.datasets.iter().map(|d| d.packets).sum::<u64>()
+        );
+        Ok(summary)
+    }
+
+    /// 抽取单个数据集时间窗口内的数据包，清空原有的抽取结果
+    fn extract_dataset<F>(
+        &self,
+        dataset: &str,
+        result_dir: &Path,
+        cancel: &CancelToken,
+        mut on_stage: F,
+    ) -> Result<ExtractSummary>
+    where
+        F: FnMut(ExtractStage),
+    {
+        if result_dir.exists() {
+            fs::remove_dir_all(result_dir)?;
+        }
+
+        let filter =
+            ExtractFilter::time_window(self.config.start_timestamp, self.config.end_timestamp);
+        DatasetExtractor::new(
+            vec![(dataset.to_string(), self.project_path.join(dataset))],
+            filter,
+        )?
+        .extract_into(result_dir, cancel, |stage, _, _, _| on_stage(stage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::{DataFileWriter, PacketReader};
+    use crate::dataset::index::data_files;
+    use crate::dataset::naming::data_file_name;
+    use crate::product::pprod::PprodReader;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    /// 在数据集目录中写入一个数据文件
+    fn write_data_file(dir: &Path, packets: &[(u64, &[u8])]) {
+        fs::create_dir_all(dir).unwrap();
+        let mut writer = DataFileWriter::create(dir.join(data_file_name(packets[0].0))).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    /// 按文件顺序读出目录中所有数据包的时间戳
+    fn read_timestamps(dir: &Path) -> Vec<u64> {
+        let mut timestamps = Vec::new();
+        for path in data_files(dir).unwrap() {
+            let mut reader = PacketReader::open(&path).unwrap();
+            while let Some(packet) = reader.next_packet().unwrap() {
+                timestamps.push(packet.header.timestamp_ns());
+            }
+        }
+        timestamps
+    }
+
+    /// 两个数据集，各有间隔1毫秒的5个数据包
+    fn write_project(project: &Path) {
+        for name in ["radar", "ais"] {
+            let packets: Vec<(u64, &[u8])> = (0..5)
+                .map(|i| (BASE_TIMESTAMP + i * 1_000_000, b"data".as_slice()))
+                .collect();
+            write_data_file(&project.join(name), &packets);
+        }
+    }
+
+    fn request(name: &str, start: u64, end: u64, datasets: &[&str]) -> ProductRequest {
+        ProductRequest {
+            name: name.to_string(),
+            caption: None,
+            description: None,
+            start_timestamp: start,
+            end_timestamp: end,
+            datasets: datasets.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path());
+        let new = |request| ProductGenerator::new(dir.path(), request);
+
+        assert!(new(request("../p", 0, 1, &["radar"])).is_err());
+        assert!(new(request("p", 0, 1, &[])).is_err());
+        assert!(new(request("p", 2, 1, &["radar"])).is_err());
+        assert!(new(request("p", 0, 1, &["radar", "radar"])).is_err());
+        assert!(matches!(
+            new(request("p", 0, 1, &["missing"])),
+            Err(PlaybackError::DatasetNotFound(_))
+        ));
+        assert!(new(request("p", 0, 1, &["radar", "ais"])).is_ok());
+    }
+
+    #[test]
+    fn extracts_time_window_for_each_dataset() {
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path());
+        let start = BASE_TIMESTAMP + 1_000_000;
+        let end = BASE_TIMESTAMP + 3_000_000;
+        let generator =
+            ProductGenerator::new(dir.path(), request("p", start, end, &["radar", "ais"])).unwrap();
+
+        let mut stages = Vec::new();
+        let summary = generator
+            .generate(&CancelToken::new(), |stage, current, total, _| {
+                stages.push((stage, current, total))
+            })
+            .unwrap();
+
+        assert!(!summary.cancelled);
+        assert_eq!(summary.datasets.len(), 2);
+        assert_eq!(stages.first(), Some(&(ProductStage::Extracting, 0, 2)));
+        assert_eq!(stages.last(), Some(&(ProductStage::Completed, 2, 2)));
+
+        let store = ProductStore::new(dir.path());
+        for status in &summary.datasets {
+            assert_eq!(status.state, ProductState::Completed);
+            assert_eq!(status.packets, 3);
+            assert_eq!(status.source_files, 1);
+            assert_eq!(
+                read_timestamps(&store.result_dir(&status.dataset, "p")),
+                vec![start, start + 1_000_000, end]
+            );
+            assert_eq!(
+                store.read_status(&status.dataset, "p").state,
+                ProductState::Completed
+            );
+        }
+
+        let config = PprodReader::read(store.product_path("p")).unwrap();
+        assert_eq!(config.caption, "p");
+        assert_eq!(config.datasets, vec!["radar", "ais"]);
+        assert_eq!((config.start_timestamp, config.end_timestamp), (start, end));
+    }
+
+    #[test]
+    fn regeneration_replaces_results_and_keeps_created_time() {
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path());
+        ProductGenerator::new(dir.path(), request("p", 0, u64::MAX, &["radar"]))
+            .unwrap()
+            .generate(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+        let store = ProductStore::new(dir.path());
+        let created_time = PprodReader::read(store.product_path("p"))
+            .unwrap()
+            .created_time;
+
+        let window = request("p", BASE_TIMESTAMP + 4_000_000, u64::MAX, &["radar"]);
+        let summary = ProductGenerator::new(dir.path(), window)
+            .unwrap()
+            .generate(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+
+        assert_eq!(summary.datasets[0].packets, 1);
+        assert_eq!(
+            read_timestamps(&store.result_dir("radar", "p")),
+            vec![BASE_TIMESTAMP + 4_000_000]
+        );
+        let config = PprodReader::read(store.product_path("p")).unwrap();
+        assert_eq!(config.created_time, created_time);
+        assert_eq!(config.start_timestamp, BASE_TIMESTAMP + 4_000_000);
+    }
+
+    #[test]
+    fn empty_window_completes_without_packets() {
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path());
+        let summary = ProductGenerator::new(dir.path(), request("p", 0, 1, &["radar"]))
+            .unwrap()
+            .generate(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+
+        assert_eq!(summary.datasets[0].state, ProductState::Completed);
+        assert_eq!(summary.datasets[0].packets, 0);
+        assert!(summary.datasets[0].files.is_empty());
+    }
+
+    #[test]
+    fn cancelled_generation_marks_remaining_datasets() {
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path());
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let summary =
+            ProductGenerator::new(dir.path(), request("p", 0, u64::MAX, &["radar", "ais"]))
+                .unwrap()
+                .generate(&cancel, |_, _, _, _| {})
+                .unwrap();
+
+        assert!(summary.cancelled);
+        let store = ProductStore::new(dir.path());
+        for dataset in ["radar", "ais"] {
+            assert_eq!(
+                store.read_status(dataset, "p").state,
+                ProductState::Cancelled
+            );
+        }
+        assert_eq!(store.open("p").unwrap().state, ProductState::Cancelled);
+    }
+}
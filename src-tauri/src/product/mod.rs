@@ -0,0 +1,8 @@
+//! 数据产品
+//!
+//! 读写工程目录下的 `.pprod` 数据产品文件，从选定数据集中按时间窗口抽取数据生成产品，
+//! 并管理产品的抽取结果与生成状态
+
+pub mod generator;
+pub mod pprod;
+pub mod store;
@@ -0,0 +1,317 @@
+//! PPROD数据产品文件读写
+//!
+//! 数据产品文件为XML格式，保存在工程目录下，文件名（不含扩展名）即产品名称，根元素为 `product`：
+//!
+//! ```xml
+//! <product>
+//!   <version>2</version>
+//!   <caption>数据产品</caption>
+//!   <description>数据产品描述</description>
+//!   <start_timestamp>1753430778000000000</start_timestamp>
+//!   <end_timestamp>1753430779000000000</end_timestamp>
+//!   <created_time>...</created_time>
+//!   <modified_time>...</modified_time>
+//!   <datasets>
+//!     <dataset>test_dataset_01</dataset>
+//!   </datasets>
+//! </product>
+//! ```
+//!
+//! 版本1（示例工程使用的格式）没有 `version` 和 `datasets` 元素，时间范围以秒为单位，
+//! 读取时换算为纳秒（结束时间取该秒的最后一纳秒）。
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::project::xml::XmlWriter;
+use crate::types::{PlaybackError, Result};
+
+/// 数据产品文件扩展名
+pub const PPROD_EXTENSION: &str = "pprod";
+
+/// 当前PPROD文件格式版本
+pub const PPROD_VERSION: u32 = 2;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// 数据产品配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductConfig {
+    /// 文件格式版本
+    pub version: u32,
+    /// 显示名称（对应 `caption` 元素）
+    pub caption: String,
+    pub description: Option<String>,
+    /// 抽取的起始时间戳（纳秒，含）
+    pub start_timestamp: u64,
+    /// 抽取的结束时间戳（纳秒，含）
+    pub end_timestamp: u64,
+    pub created_time: String,
+    pub modified_time: String,
+    /// 参与抽取的数据集
+    pub datasets: Vec<String>,
+}
+
+impl ProductConfig {
+    /// 创建新的数据产品配置
+    pub fn new(caption: String, start_timestamp: u64, end_timestamp: u64) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            version: PPROD_VERSION,
+            caption,
+            description: None,
+            start_timestamp,
+            end_timestamp,
+            created_time: now.clone(),
+            modified_time: now,
+            datasets: Vec::new(),
+        }
+    }
+
+    /// 验证时间范围和数据集列表
+    pub fn validate(&self) -> Result<()> {
+        if self.end_timestamp < self.start_timestamp {
+            return Err(PlaybackError::ProjectError(format!(
+                "数据产品的结束时间 {} 早于起始时间 {}",
+                self.end_timestamp, self.start_timestamp
+            )));
+        }
+        if self.datasets.is_empty() {
+            return Err(PlaybackError::ProjectError(
+                "数据产品未指定数据集".to_string(),
+            ));
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for dataset in &self.datasets {
+            if !names.insert(dataset) {
+                return Err(PlaybackError::ProjectError(format!(
+                    "重复的数据集名称: {}",
+                    dataset
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProductDocument {
+    #[serde(default)]
+    version: Option<u32>,
+    #[serde(default)]
+    caption: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    start_timestamp: Option<u64>,
+    #[serde(default)]
+    end_timestamp: Option<u64>,
+    #[serde(default)]
+    created_time: Option<String>,
+    #[serde(default)]
+    modified_time: Option<String>,
+    #[serde(default)]
+    datasets: DatasetsDocument,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DatasetsDocument {
+    #[serde(default, rename = "dataset")]
+    items: Vec<String>,
+}
+
+/// PPROD文件读取器
+pub struct PprodReader;
+
+impl PprodReader {
+    /// 读取数据产品文件，未设置标题时使用产品名称
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<ProductConfig> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+
+        let mut config = Self::parse(&content)?;
+        if config.caption.is_empty() {
+            config.caption = product_name(path);
+        }
+        Ok(config)
+    }
+
+    /// 解析数据产品文件内容并迁移到当前版本
+    pub fn parse(content: &str) -> Result<ProductConfig> {
+        let document: ProductDocument = serde_xml_rs::from_str(content)
+            .map_err(|e| PlaybackError::XmlError(format!("解析数据产品文件失败: {}", e)))?;
+
+        let version = document.version.unwrap_or(1);
+        if version > PPROD_VERSION {
+            return Err(PlaybackError::FormatError(format!(
+                "不支持的数据产品文件版本: {}（当前支持 {}）",
+                version, PPROD_VERSION
+            )));
+        }
+
+        let (start, end) = match version {
+            1 => (
+                document
+                    .start_timestamp
+                    .map(|s| s.saturating_mul(NANOS_PER_SEC)),
+                document
+                    .end_timestamp
+                    .map(|s| s.saturating_add(1).saturating_mul(NANOS_PER_SEC) - 1),
+            ),
+            _ => (document.start_timestamp, document.end_timestamp),
+        };
+
+        let mut config = ProductConfig::new(
+            non_empty(document.caption).unwrap_or_default(),
+            start.unwrap_or(0),
+            end.unwrap_or(u64::MAX),
+        );
+        config.description = non_empty(document.description);
+        config.datasets = document.datasets.items;
+        if let Some(created_time) = non_empty(document.created_time) {
+            config.created_time = created_time;
+        }
+        if let Some(modified_time) = non_empty(document.modified_time) {
+            config.modified_time = modified_time;
+        }
+
+        if version < PPROD_VERSION {
+            info!(
+                "数据产品文件已从版本 {} 迁移到版本 {}",
+                version, PPROD_VERSION
+            );
+        }
+        Ok(config)
+    }
+}
+
+/// PPROD文件写入器
+pub struct PprodWriter;
+
+impl PprodWriter {
+    /// 写入数据产品文件
+    pub fn write<P: AsRef<Path>>(path: P, config: &ProductConfig) -> Result<()> {
+        let path = path.as_ref();
+
+        // 先写临时文件再替换，避免写入中断损坏产品文件
+        let temp_path = path.with_extension("pprod.tmp");
+        fs::write(&temp_path, Self::to_xml(config))?;
+        fs::rename(&temp_path, path)?;
+
+        info!("保存数据产品文件: {:?}", path);
+        Ok(())
+    }
+
+    /// 生成数据产品文件内容
+    pub fn to_xml(config: &ProductConfig) -> String {
+        let mut writer = XmlWriter::new();
+        writer
+            .open("product")
+            .element("version", PPROD_VERSION)
+            .element("caption", &config.caption)
+            .optional_element("description", config.description.as_ref())
+            .element("start_timestamp", config.start_timestamp)
+            .element("end_timestamp", config.end_timestamp)
+            .element("created_time", &config.created_time)
+            .element("modified_time", &config.modified_time)
+            .open("datasets");
+        for dataset in &config.datasets {
+            writer.element("dataset", dataset);
+        }
+        writer.close("datasets").close("product");
+
+        writer.finish()
+    }
+}
+
+/// 工程目录中的数据产品文件（按文件名排序）
+pub fn find_pprod_files<P: AsRef<Path>>(project_dir: P) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(project_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(|e| e.to_str()) == Some(PPROD_EXTENSION)
+        })
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// 数据产品文件对应的产品名称（文件名去掉扩展名）
+pub fn product_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 空字符串视为未设置
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_product_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(
+            "../docs/详细设计文档/01-工程管理模块/example/data_product/data_product_001.pprod",
+        )
+    }
+
+    fn to_json(config: &ProductConfig) -> serde_json::Value {
+        serde_json::to_value(config).unwrap()
+    }
+
+    #[test]
+    fn example_v1_product_round_trips() {
+        let config = PprodReader::read(example_product_path()).unwrap();
+        assert_eq!(config.version, PPROD_VERSION);
+        assert_eq!(config.caption, "数据产品");
+        assert_eq!(config.description.as_deref(), Some("数据产品描述"));
+        // 版本1以秒为单位，结束时间取该秒的最后一纳秒
+        assert_eq!(config.start_timestamp, 1_753_430_778_000_000_000);
+        assert_eq!(config.end_timestamp, 1_753_430_779_999_999_999);
+        assert_eq!(config.created_time, "2025-07-25T08:06:19");
+        assert_eq!(config.modified_time, "2025-07-25T08:06:19");
+        assert!(config.datasets.is_empty());
+
+        let xml = PprodWriter::to_xml(&config);
+        let reread = PprodReader::parse(&xml).unwrap();
+        assert_eq!(to_json(&reread), to_json(&config));
+    }
+
+    #[test]
+    fn current_version_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("产品.pprod");
+        let mut config = ProductConfig::new("A&B <产品>".to_string(), 100, 200);
+        config.description = Some("描述".to_string());
+        config.datasets = vec!["test_dataset_01".to_string(), "test_dataset_02".to_string()];
+
+        PprodWriter::write(&path, &config).unwrap();
+        let reread = PprodReader::read(&path).unwrap();
+
+        assert_eq!(to_json(&reread), to_json(&config));
+        assert_eq!(find_pprod_files(dir.path()).unwrap(), vec![path.clone()]);
+        assert_eq!(product_name(&path), "产品");
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let xml = format!(
+            "<product><version>{}</version><caption>产品</caption></product>",
+            PPROD_VERSION + 1
+        );
+
+        assert!(matches!(
+            PprodReader::parse(&xml),
+            Err(PlaybackError::FormatError(_))
+        ));
+    }
+}
@@ -0,0 +1,472 @@
+//! 数据产品的查找、生成状态与删除
+//!
+//! 数据产品 `<产品名>` 的抽取结果存放在 `.result/<数据集>/<产品名>/`，
+//! 各数据集的生成状态保存在 `.status/<数据集>/product_<产品名>.json`
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dataset::info::scan_dataset;
+use crate::product::pprod::{
+    find_pprod_files, product_name, PprodReader, PprodWriter, ProductConfig, PPROD_EXTENSION,
+};
+use crate::project::structure::{RESULT_DIR, STATUS_DIR};
+use crate::project::validator::ProjectValidator;
+use crate::types::{DatasetInfo, PlaybackError, Result};
+
+/// 生成状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProductState {
+    /// 尚未生成
+    #[default]
+    Pending,
+    Generating,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 单个数据集的生成状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatasetProductStatus {
+    pub dataset: String,
+    pub state: ProductState,
+    /// 生成的数据文件名
+    pub files: Vec<String>,
+    pub packets: u64,
+    pub total_size: u64,
+    /// 读取的源数据文件数
+    pub source_files: u64,
+    /// 校验和不匹配而未写入的数据包数
+    pub corrupt_packets: u64,
+    pub error: Option<String>,
+    pub updated_time: String,
+}
+
+/// 数据产品中的一个数据集
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductDataset {
+    pub name: String,
+    /// 抽取结果目录
+    pub result_path: String,
+    pub status: DatasetProductStatus,
+    /// 抽取结果的数据集信息，仅打开产品时提供
+    pub info: Option<DatasetInfo>,
+}
+
+/// 数据产品信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductInfo {
+    /// 产品名称（产品文件名）
+    pub name: String,
+    pub file_path: String,
+    pub config: ProductConfig,
+    /// 汇总状态：任一数据集失败为失败，全部完成为完成
+    pub state: ProductState,
+    pub datasets: Vec<ProductDataset>,
+}
+
+/// 工程中的数据产品
+#[derive(Debug)]
+pub struct ProductStore {
+    project_path: PathBuf,
+}
+
+impl ProductStore {
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Self {
+        Self {
+            project_path: project_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// 列出工程中的数据产品，无法读取的产品文件跳过
+    pub fn list(&self) -> Result<Vec<ProductInfo>> {
+        let products = find_pprod_files(&self.project_path)?
+            .into_iter()
+            .filter_map(|path| {
+                let name = product_name(&path);
+                self.load(&name, false)
+                    .map_err(|e| warn!("读取数据产品失败 {:?}: {}", path, e))
+                    .ok()
+            })
+            .collect();
+        Ok(products)
+    }
+
+    /// 打开数据产品，附带各数据集抽取结果的统计信息
+    pub fn open(&self, name: &str) -> Result<ProductInfo> {
+        self.load(name, true)
+    }
+
+    /// 删除数据产品文件、抽取结果和生成状态
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let path = self.existing_product_path(name)?;
+        let config = PprodReader::read(&path)?;
+
+        for dataset in &config.datasets {
+            let result_dir = self.result_dir(dataset, name);
+            if result_dir.exists() {
+                fs::remove_dir_all(&result_dir)?;
+            }
+            let status_path = self.status_path(dataset, name);
+            if status_path.exists() {
+                fs::remove_file(&status_path)?;
+            }
+        }
+        fs::remove_file(&path)?;
+
+        info!("数据产品已删除: {}", name);
+        Ok(())
+    }
+
+    /// 数据集重命名后更新引用它的数据产品文件，返回更新的产品数
+    ///
+    /// 抽取结果和生成状态随 `.result`、`.status` 下的数据集目录一起移动，只需改写产品文件
+    pub fn rename_dataset(&self, old_name: &str, new_name: &str) -> Result<usize> {
+        self.update_datasets(|datasets| {
+            let mut changed = false;
+            for dataset in datasets.iter_mut().filter(|d| d.as_str() == old_name) {
+                *dataset = new_name.to_string();
+                changed = true;
+            }
+            changed
+        })
+    }
+
+    /// 数据集移除后从引用它的数据产品文件中去掉该数据集，返回更新的产品数
+    pub fn remove_dataset(&self, dataset_name: &str) -> Result<usize> {
+        self.update_datasets(|datasets| {
+            let count = datasets.len();
+            datasets.retain(|d| d != dataset_name);
+            datasets.len() != count
+        })
+    }
+
+    /// 数据产品文件路径
+    pub fn product_path(&self, name: &str) -> PathBuf {
+        self.project_path
+            .join(format!("{}.{}", name, PPROD_EXTENSION))
+    }
+
+    /// 数据集在数据产品中的抽取结果目录
+    pub fn result_dir(&self, dataset: &str, name: &str) -> PathBuf {
+        self.project_path.join(RESULT_DIR).join(dataset).join(name)
+    }
+
+    /// 读取数据集的生成状态，不存在或无法解析时为待生成
+    pub fn read_status(&self, dataset: &str, name: &str) -> DatasetProductStatus {
+        fs::read_to_string(self.status_path(dataset, name))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| DatasetProductStatus {
+                dataset: dataset.to_string(),
+                ..Default::default()
+            })
+    }
+
+    /// 保存数据集的生成状态
+    pub fn write_status(&self, name: &str, status: &mut DatasetProductStatus) -> Result<()> {
+        let path = self.status_path(&status.dataset, name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        status.updated_time = chrono::Utc::now().to_rfc3339();
+        fs::write(&path, serde_json::to_string_pretty(status)?)?;
+        Ok(())
+    }
+
+    fn load(&self, name: &str, with_info: bool) -> Result<ProductInfo> {
+        let path = self.existing_product_path(name)?;
+        let config = PprodReader::read(&path)?;
+
+        let datasets: Vec<ProductDataset> = config
+            .datasets
+            .iter()
+            .map(|dataset| {
+                let result_dir = self.result_dir(dataset, name);
+                let info = (with_info && result_dir.is_dir())
+                    .then(|| scan_dataset(dataset, &result_dir, &|_, _, _| {}))
+                    .and_then(|result| {
+                        result
+                            .map_err(|e| warn!("扫描抽取结果失败 {:?}: {}", result_dir, e))
+                            .ok()
+                    })
+                    .map(|(info, _)| info);
+
+                ProductDataset {
+                    name: dataset.clone(),
+                    result_path: result_dir.to_string_lossy().to_string(),
+                    status: self.read_status(dataset, name),
+                    info,
+                }
+            })
+            .collect();
+
+        let states: Vec<ProductState> = datasets.iter().map(|d| d.status.state).collect();
+        let state = [
+            ProductState::Failed,
+            ProductState::Generating,
+            ProductState::Cancelled,
+            ProductState::Pending,
+        ]
+        .into_iter()
+        .find(|state| states.contains(state))
+        .unwrap_or(if states.is_empty() {
+            ProductState::Pending
+        } else {
+            ProductState::Completed
+        });
+
+        Ok(ProductInfo {
+            name: name.to_string(),
+            file_path: path.to_string_lossy().to_string(),
+            config,
+            state,
+            datasets,
+        })
+    }
+
+    /// 逐个修改数据产品文件的数据集列表，`update` 返回 `false` 时不写入；无法读取的产品文件跳过
+    fn update_datasets(&self, mut update: impl FnMut(&mut Vec<String>) -> bool) -> Result<usize> {
+        let mut count = 0;
+        for path in find_pprod_files(&self.project_path)? {
+            let mut config = match PprodReader::read(&path) {
+                Ok(config) => config,
+                Err(e) => {
+                    warn!("读取数据产品失败 {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            if update(&mut config.datasets) {
+                config.modified_time = chrono::Utc::now().to_rfc3339();
+                PprodWriter::write(&path, &config)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn existing_product_path(&self, name: &str) -> Result<PathBuf> {
+        // 名称来自前端，拒绝路径分隔符等，避免操作工程目录以外的内容
+        ProjectValidator::validate_name(name)?;
+
+        let path = self.product_path(name);
+        if !path.is_file() {
+            return Err(PlaybackError::ProductNotFound(name.to_string()));
+        }
+        Ok(path)
+    }
+
+    fn status_path(&self, dataset: &str, name: &str) -> PathBuf {
+        self.project_path
+            .join(STATUS_DIR)
+            .join(dataset)
+            .join(format!("product_{}.json", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::DataFileWriter;
+    use crate::dataset::naming::data_file_name;
+    use crate::product::generator::{ProductGenerator, ProductRequest};
+    use crate::product::pprod::PprodWriter;
+    use crate::project::structure::ProjectStructure;
+    use crate::state::task_state::CancelToken;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    fn write_product(project: &Path, name: &str, datasets: &[&str]) {
+        let mut config = ProductConfig::new(name.to_string(), 0, u64::MAX);
+        config.datasets = datasets.iter().map(|d| d.to_string()).collect();
+        PprodWriter::write(
+            project.join(format!("{}.{}", name, PPROD_EXTENSION)),
+            &config,
+        )
+        .unwrap();
+    }
+
+    fn set_state(store: &ProductStore, name: &str, dataset: &str, state: ProductState) {
+        let mut status = DatasetProductStatus {
+            dataset: dataset.to_string(),
+            state,
+            ..Default::default()
+        };
+        store.write_status(name, &mut status).unwrap();
+    }
+
+    /// 新建含 `radar`、`ais` 两个数据集的工程并生成包含两者的数据产品 `p`
+    fn generated_project(parent: &Path) -> PathBuf {
+        let names = vec!["radar".to_string(), "ais".to_string()];
+        let project = ProjectStructure::create(parent, "proj", None, &names).unwrap();
+        let project_path = project.root_path;
+        for name in &names {
+            let dir = project_path.join(name);
+            let mut writer =
+                DataFileWriter::create(dir.join(data_file_name(BASE_TIMESTAMP))).unwrap();
+            writer.write_packet(BASE_TIMESTAMP, b"data").unwrap();
+            writer.finish().unwrap();
+        }
+
+        let request = ProductRequest {
+            name: "p".to_string(),
+            caption: None,
+            description: None,
+            start_timestamp: 0,
+            end_timestamp: u64::MAX,
+            datasets: names,
+        };
+        ProductGenerator::new(&project_path, request)
+            .unwrap()
+            .generate(&CancelToken::new(), |_, _, _, _| {})
+            .unwrap();
+        project_path
+    }
+
+    #[test]
+    fn list_skips_unreadable_products() {
+        let dir = tempfile::tempdir().unwrap();
+        write_product(dir.path(), "a", &["radar"]);
+        write_product(dir.path(), "b", &["ais"]);
+        fs::write(dir.path().join("broken.pprod"), "not xml").unwrap();
+
+        let names: Vec<String> = ProductStore::new(dir.path())
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn summarizes_dataset_states() {
+        let dir = tempfile::tempdir().unwrap();
+        write_product(dir.path(), "p", &["radar", "ais"]);
+        let store = ProductStore::new(dir.path());
+        let state = || store.open("p").unwrap().state;
+
+        assert_eq!(state(), ProductState::Pending);
+        assert_eq!(store.read_status("radar", "p").dataset, "radar");
+
+        set_state(&store, "p", "radar", ProductState::Completed);
+        assert_eq!(state(), ProductState::Pending);
+        set_state(&store, "p", "ais", ProductState::Completed);
+        assert_eq!(state(), ProductState::Completed);
+        set_state(&store, "p", "ais", ProductState::Cancelled);
+        assert_eq!(state(), ProductState::Cancelled);
+        set_state(&store, "p", "radar", ProductState::Failed);
+        assert_eq!(state(), ProductState::Failed);
+    }
+
+    #[test]
+    fn open_rejects_missing_or_invalid_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProductStore::new(dir.path());
+        assert!(matches!(
+            store.open("missing"),
+            Err(PlaybackError::ProductNotFound(_))
+        ));
+        assert!(store.open("../p").is_err());
+        assert!(store.delete("../p").is_err());
+    }
+
+    #[test]
+    fn open_reports_generated_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = generated_project(dir.path());
+
+        let product = ProductStore::new(&project).open("p").unwrap();
+        assert_eq!(product.state, ProductState::Completed);
+        for dataset in &product.datasets {
+            let info = dataset.info.as_ref().unwrap();
+            assert_eq!(info.total_packets, 1);
+            assert_eq!(info.start_timestamp, Some(BASE_TIMESTAMP));
+        }
+        // 列出产品时不扫描抽取结果
+        let listed = ProductStore::new(&project).list().unwrap();
+        assert!(listed[0].datasets.iter().all(|d| d.info.is_none()));
+    }
+
+    #[test]
+    fn delete_removes_results_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = generated_project(dir.path());
+        let store = ProductStore::new(&project);
+
+        store.delete("p").unwrap();
+
+        assert!(!store.product_path("p").exists());
+        for dataset in ["radar", "ais"] {
+            assert!(!store.result_dir(dataset, "p").exists());
+            assert!(!store.status_path(dataset, "p").exists());
+            // 数据集本身不受影响
+            assert!(project.join(dataset).is_dir());
+        }
+    }
+
+    #[test]
+    fn renamed_dataset_keeps_product_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = generated_project(dir.path());
+
+        ProjectStructure::rename_dataset(&project, "radar", "radar2").unwrap();
+
+        let store = ProductStore::new(&project);
+        let product = store.open("p").unwrap();
+        assert_eq!(product.config.datasets, vec!["radar2", "ais"]);
+        assert_eq!(product.state, ProductState::Completed);
+        assert!(product.datasets[0].info.is_some());
+
+        store.delete("p").unwrap();
+        assert!(!store.result_dir("radar2", "p").exists());
+        assert!(!project.join(RESULT_DIR).join("radar2").join("p").exists());
+    }
+
+    #[test]
+    fn removed_dataset_leaves_product() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = generated_project(dir.path());
+
+        ProjectStructure::remove_dataset(&project, "radar", true).unwrap();
+
+        let product = ProductStore::new(&project).open("p").unwrap();
+        assert_eq!(product.config.datasets, vec!["ais"]);
+        assert_eq!(product.state, ProductState::Completed);
+    }
+
+    #[test]
+    fn rename_and_remove_only_touch_referencing_products() {
+        let dir = tempfile::tempdir().unwrap();
+        write_product(dir.path(), "a", &["radar", "ais"]);
+        write_product(dir.path(), "b", &["ais"]);
+        fs::write(dir.path().join("broken.pprod"), "not xml").unwrap();
+        let store = ProductStore::new(dir.path());
+        let before = fs::read_to_string(store.product_path("b")).unwrap();
+
+        assert_eq!(store.rename_dataset("radar", "radar2").unwrap(), 1);
+        assert_eq!(
+            PprodReader::read(store.product_path("a")).unwrap().datasets,
+            vec!["radar2", "ais"]
+        );
+        assert_eq!(fs::read_to_string(store.product_path("b")).unwrap(), before);
+
+        assert_eq!(store.remove_dataset("ais").unwrap(), 2);
+        assert_eq!(
+            PprodReader::read(store.product_path("a")).unwrap().datasets,
+            vec!["radar2"]
+        );
+        assert!(PprodReader::read(store.product_path("b"))
+            .unwrap()
+            .datasets
+            .is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("broken.pprod")).unwrap(),
+            "not xml"
+        );
+    }
+}
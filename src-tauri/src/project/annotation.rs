@@ -0,0 +1,599 @@
+//! 工程标注
+//!
+//! 回放过程中添加的标注和书签保存在工程目录的 `.annotations.json`，
+//! 每条标注对应一个时间点或时间区间，可关联数据集及其中的数据包
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::project::validator::ProjectValidator;
+use crate::types::{PlaybackError, Result};
+
+/// 标注文件名
+pub const ANNOTATIONS_FILE_NAME: &str = ".annotations.json";
+
+/// 标注文件格式版本
+const ANNOTATIONS_VERSION: u32 = 1;
+
+/// 串行化标注文件的读改写
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 标注
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: u64,
+    /// 时间点或区间起点（纳秒）
+    pub start_timestamp: u64,
+    /// 区间终点（纳秒），时间点标注为空
+    pub end_timestamp: Option<u64>,
+    pub text: String,
+    pub category: Option<String>,
+    /// 显示颜色，`#RGB`、`#RRGGBB` 或 `#RRGGBBAA`
+    pub color: Option<String>,
+    pub author: Option<String>,
+    /// 关联的数据集
+    pub dataset: Option<String>,
+    /// 关联的数据文件，须同时关联数据集
+    pub file_name: Option<String>,
+    /// 关联的数据包在数据文件中的序号，须同时关联数据文件
+    pub packet_index: Option<u64>,
+    pub created_time: String,
+    pub modified_time: String,
+}
+
+impl Annotation {
+    /// 是否为时间区间标注
+    pub fn is_range(&self) -> bool {
+        self.end_timestamp.is_some()
+    }
+
+    /// 标注的结束时间，时间点标注为其本身
+    pub fn end(&self) -> u64 {
+        self.end_timestamp.unwrap_or(self.start_timestamp)
+    }
+}
+
+/// 新建或修改标注时提交的内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnnotationDraft {
+    pub start_timestamp: u64,
+    #[serde(default)]
+    pub end_timestamp: Option<u64>,
+    pub text: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub dataset: Option<String>,
+    #[serde(default)]
+    pub file_name: Option<String>,
+    #[serde(default)]
+    pub packet_index: Option<u64>,
+}
+
+impl AnnotationDraft {
+    /// 校验时间、颜色和关联关系，关联的数据集须存在于工程中
+    fn validate(&self, project_path: &Path) -> Result<()> {
+        if self.text.trim().is_empty() {
+            return Err(PlaybackError::ParseError("标注内容不能为空".to_string()));
+        }
+        if self
+            .end_timestamp
+            .is_some_and(|end| end < self.start_timestamp)
+        {
+            return Err(PlaybackError::ParseError(format!(
+                "标注结束时间早于开始时间: {} < {}",
+                self.end_timestamp.unwrap_or_default(),
+                self.start_timestamp
+            )));
+        }
+        if let Some(color) = self.color.as_ref().filter(|c| !c.trim().is_empty()) {
+            validate_color(color.trim())?;
+        }
+
+        match (&self.dataset, &self.file_name, self.packet_index) {
+            (None, None, None) => Ok(()),
+            (None, _, _) => Err(PlaybackError::ParseError(
+                "关联数据文件或数据包时须同时关联数据集".to_string(),
+            )),
+            (Some(_), None, Some(_)) => Err(PlaybackError::ParseError(
+                "关联数据包时须同时关联数据文件".to_string(),
+            )),
+            (Some(dataset), file_name, _) => {
+                ProjectValidator::validate_name(dataset)?;
+                if !project_path.join(dataset).is_dir() {
+                    return Err(PlaybackError::DatasetNotFound(dataset.clone()));
+                }
+                if let Some(file_name) = file_name {
+                    ProjectValidator::validate_name(file_name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 标注查询条件，未设置的条件不参与筛选
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnnotationQuery {
+    /// 与时间窗口（纳秒，含两端）重叠的标注
+    #[serde(default)]
+    pub start_timestamp: Option<u64>,
+    #[serde(default)]
+    pub end_timestamp: Option<u64>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub dataset: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+impl AnnotationQuery {
+    fn matches(&self, annotation: &Annotation) -> bool {
+        self.start_timestamp
+            .is_none_or(|start| annotation.end() >= start)
+            && self
+                .end_timestamp
+                .is_none_or(|end| annotation.start_timestamp <= end)
+            && self
+                .category
+                .as_ref()
+                .is_none_or(|c| annotation.category.as_ref() == Some(c))
+            && self
+                .dataset
+                .as_ref()
+                .is_none_or(|d| annotation.dataset.as_ref() == Some(d))
+            && self
+                .author
+                .as_ref()
+                .is_none_or(|a| annotation.author.as_ref() == Some(a))
+    }
+}
+
+/// 标注文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AnnotationFile {
+    version: u32,
+    /// 下一条标注的编号，删除标注后编号不复用
+    next_id: u64,
+    annotations: Vec<Annotation>,
+}
+
+impl Default for AnnotationFile {
+    fn default() -> Self {
+        Self {
+            version: ANNOTATIONS_VERSION,
+            next_id: 1,
+            annotations: Vec::new(),
+        }
+    }
+}
+
+/// 工程中的标注
+#[derive(Debug)]
+pub struct AnnotationStore {
+    project_path: PathBuf,
+}
+
+impl AnnotationStore {
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Self {
+        Self {
+            project_path: project_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// 标注文件路径
+    pub fn file_path(&self) -> PathBuf {
+        self.project_path.join(ANNOTATIONS_FILE_NAME)
+    }
+
+    /// 按开始时间列出满足条件的标注
+    pub fn list(&self, query: &AnnotationQuery) -> Result<Vec<Annotation>> {
+        let mut annotations: Vec<Annotation> = self
+            .read()?
+            .annotations
+            .into_iter()
+            .filter(|a| query.matches(a))
+            .collect();
+        annotations.sort_by_key(|a| (a.start_timestamp, a.id));
+        Ok(annotations)
+    }
+
+    /// 读取单条标注
+    pub fn get(&self, id: u64) -> Result<Annotation> {
+        self.read()?
+            .annotations
+            .into_iter()
+            .find(|a| a.id == id)
+            .ok_or(PlaybackError::AnnotationNotFound(id))
+    }
+
+    /// 新建标注
+    pub fn create(&self, draft: AnnotationDraft) -> Result<Annotation> {
+        draft.validate(&self.project_path)?;
+        self.modify(|file| {
+            let now = chrono::Utc::now().to_rfc3339();
+            let annotation = build(file.next_id, draft, now.clone(), now);
+            file.next_id += 1;
+            file.annotations.push(annotation.clone());
+            info!("新建标注 {}: {}", annotation.id, annotation.text);
+            Ok(annotation)
+        })
+    }
+
+    /// 修改标注，保留编号和创建时间
+    pub fn update(&self, id: u64, draft: AnnotationDraft) -> Result<Annotation> {
+        draft.validate(&self.project_path)?;
+        self.modify(|file| {
+            let existing = file
+                .annotations
+                .iter_mut()
+                .find(|a| a.id == id)
+                .ok_or(PlaybackError::AnnotationNotFound(id))?;
+            let created_time = std::mem::take(&mut existing.created_time);
+            *existing = build(id, draft, created_time, chrono::Utc::now().to_rfc3339());
+            info!("修改标注 {}", id);
+            Ok(existing.clone())
+        })
+    }
+
+    /// 删除标注
+    pub fn delete(&self, id: u64) -> Result<()> {
+        self.modify(|file| {
+            let count = file.annotations.len();
+            file.annotations.retain(|a| a.id != id);
+            if file.annotations.len() == count {
+                return Err(PlaybackError::AnnotationNotFound(id));
+            }
+            info!("删除标注 {}", id);
+            Ok(())
+        })
+    }
+
+    /// 数据集重命名后更新标注的关联，返回更新的标注数
+    pub fn rename_dataset(&self, old_name: &str, new_name: &str) -> Result<usize> {
+        if !self.file_path().exists() {
+            return Ok(0);
+        }
+        self.modify(|file| {
+            let mut count = 0;
+            for annotation in file
+                .annotations
+                .iter_mut()
+                .filter(|a| a.dataset.as_deref() == Some(old_name))
+            {
+                annotation.dataset = Some(new_name.to_string());
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    /// 数据集移除后解除标注与它的关联，保留标注的时间和内容，返回更新的标注数
+    pub fn detach_dataset(&self, dataset_name: &str) -> Result<usize> {
+        if !self.file_path().exists() {
+            return Ok(0);
+        }
+        self.modify(|file| {
+            let mut count = 0;
+            for annotation in file
+                .annotations
+                .iter_mut()
+                .filter(|a| a.dataset.as_deref() == Some(dataset_name))
+            {
+                annotation.dataset = None;
+                annotation.file_name = None;
+                annotation.packet_index = None;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    /// 读取标注文件，不存在时为空
+    fn read(&self) -> Result<AnnotationFile> {
+        let path = self.file_path();
+        if !path.exists() {
+            return Ok(AnnotationFile::default());
+        }
+        let file: AnnotationFile = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        if file.version > ANNOTATIONS_VERSION {
+            return Err(PlaybackError::FormatError(format!(
+                "不支持的标注文件版本: {}",
+                file.version
+            )));
+        }
+        Ok(file)
+    }
+
+    /// 读取、修改并保存标注文件，修改失败时不写入
+    fn modify<T>(&self, update: impl FnOnce(&mut AnnotationFile) -> Result<T>) -> Result<T> {
+        if !self.project_path.is_dir() {
+            return Err(PlaybackError::ProjectError(format!(
+                "工程目录不存在: {:?}",
+                self.project_path
+            )));
+        }
+
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = self.read()?;
+        let result = update(&mut file)?;
+
+        // 先写临时文件再替换，避免写入中断损坏标注文件
+        let path = self.file_path();
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(&file)?)?;
+        fs::rename(&temp_path, &path)?;
+        Ok(result)
+    }
+}
+
+fn build(
+    id: u64,
+    draft: AnnotationDraft,
+    created_time: String,
+    modified_time: String,
+) -> Annotation {
+    let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+    Annotation {
+        id,
+        start_timestamp: draft.start_timestamp,
+        end_timestamp: draft.end_timestamp,
+        text: draft.text.trim().to_string(),
+        category: non_empty(draft.category),
+        color: non_empty(draft.color).map(|c| c.trim().to_string()),
+        author: non_empty(draft.author),
+        dataset: draft.dataset,
+        file_name: draft.file_name,
+        packet_index: draft.packet_index,
+        created_time,
+        modified_time,
+    }
+}
+
+fn validate_color(color: &str) -> Result<()> {
+    let digits = color.strip_prefix('#').unwrap_or_default();
+    if !matches!(digits.len(), 3 | 6 | 8) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(PlaybackError::ParseError(format!(
+            "颜色格式无效，应为 #RGB、#RRGGBB 或 #RRGGBBAA: {}",
+            color
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 创建包含 radar 和 ais 数据集目录的工程目录
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("radar")).unwrap();
+        fs::create_dir(dir.path().join("ais")).unwrap();
+        dir
+    }
+
+    fn draft(start_timestamp: u64, text: &str) -> AnnotationDraft {
+        AnnotationDraft {
+            start_timestamp,
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn on_dataset(start_timestamp: u64, dataset: &str) -> AnnotationDraft {
+        AnnotationDraft {
+            dataset: Some(dataset.to_string()),
+            file_name: Some("data.pcap".to_string()),
+            packet_index: Some(3),
+            ..draft(start_timestamp, dataset)
+        }
+    }
+
+    fn ids(annotations: &[Annotation]) -> Vec<u64> {
+        annotations.iter().map(|a| a.id).collect()
+    }
+
+    #[test]
+    fn create_update_and_delete() {
+        let dir = project();
+        let store = AnnotationStore::new(dir.path());
+        assert!(store.list(&AnnotationQuery::default()).unwrap().is_empty());
+
+        let first = store
+            .create(AnnotationDraft {
+                category: Some("  ".to_string()),
+                color: Some(" #FF0000 ".to_string()),
+                ..draft(200, "  目标出现 ")
+            })
+            .unwrap();
+        assert_eq!(first.id, 1);
+        assert_eq!(first.text, "目标出现");
+        assert_eq!(first.category, None);
+        assert_eq!(first.color.as_deref(), Some("#FF0000"));
+        assert_eq!(store.get(1).unwrap(), first);
+
+        let second = store.create(draft(100, "开始")).unwrap();
+        assert_eq!(second.id, 2);
+
+        let updated = store
+            .update(
+                1,
+                AnnotationDraft {
+                    end_timestamp: Some(300),
+                    ..draft(200, "目标消失")
+                },
+            )
+            .unwrap();
+        assert_eq!(updated.text, "目标消失");
+        assert!(updated.is_range());
+        assert_eq!(updated.created_time, first.created_time);
+
+        store.delete(2).unwrap();
+        assert!(matches!(
+            store.get(2),
+            Err(PlaybackError::AnnotationNotFound(2))
+        ));
+        assert!(matches!(
+            store.delete(2),
+            Err(PlaybackError::AnnotationNotFound(2))
+        ));
+        assert!(matches!(
+            store.update(2, draft(100, "开始")),
+            Err(PlaybackError::AnnotationNotFound(2))
+        ));
+
+        // 删除后编号不复用
+        assert_eq!(store.create(draft(400, "结束")).unwrap().id, 3);
+        assert!(!dir.path().join(".annotations.json.tmp").exists());
+    }
+
+    #[test]
+    fn create_rejects_invalid_drafts() {
+        let dir = project();
+        let store = AnnotationStore::new(dir.path());
+        let invalid = [
+            draft(100, " "),
+            AnnotationDraft {
+                end_timestamp: Some(99),
+                ..draft(100, "区间")
+            },
+            AnnotationDraft {
+                color: Some("#12345".to_string()),
+                ..draft(100, "颜色")
+            },
+            AnnotationDraft {
+                file_name: Some("data.pcap".to_string()),
+                ..draft(100, "缺少数据集")
+            },
+            AnnotationDraft {
+                dataset: Some("radar".to_string()),
+                packet_index: Some(1),
+                ..draft(100, "缺少数据文件")
+            },
+        ];
+        for draft in invalid {
+            assert!(matches!(
+                store.create(draft),
+                Err(PlaybackError::ParseError(_))
+            ));
+        }
+        assert!(matches!(
+            store.create(on_dataset(100, "missing")),
+            Err(PlaybackError::DatasetNotFound(_))
+        ));
+        assert!(!store.file_path().exists());
+
+        let missing = AnnotationStore::new(dir.path().join("missing"));
+        assert!(matches!(
+            missing.create(draft(100, "工程不存在")),
+            Err(PlaybackError::ProjectError(_))
+        ));
+    }
+
+    #[test]
+    fn list_filters_by_query() {
+        let dir = project();
+        let store = AnnotationStore::new(dir.path());
+        store
+            .create(AnnotationDraft {
+                end_timestamp: Some(250),
+                category: Some("目标".to_string()),
+                author: Some("张三".to_string()),
+                ..on_dataset(150, "radar")
+            })
+            .unwrap();
+        store.create(draft(300, "书签")).unwrap();
+        store
+            .create(AnnotationDraft {
+                category: Some("目标".to_string()),
+                ..on_dataset(100, "ais")
+            })
+            .unwrap();
+
+        let list = |query: AnnotationQuery| ids(&store.list(&query).unwrap());
+        assert_eq!(list(AnnotationQuery::default()), vec![3, 1, 2]);
+        // 区间标注与时间窗口重叠即返回
+        assert_eq!(
+            list(AnnotationQuery {
+                start_timestamp: Some(200),
+                end_timestamp: Some(280),
+                ..Default::default()
+            }),
+            vec![1]
+        );
+        assert_eq!(
+            list(AnnotationQuery {
+                category: Some("目标".to_string()),
+                ..Default::default()
+            }),
+            vec![3, 1]
+        );
+        assert_eq!(
+            list(AnnotationQuery {
+                dataset: Some("radar".to_string()),
+                ..Default::default()
+            }),
+            vec![1]
+        );
+        assert_eq!(
+            list(AnnotationQuery {
+                author: Some("李四".to_string()),
+                ..Default::default()
+            }),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn rename_dataset_updates_associations() {
+        let dir = project();
+        let store = AnnotationStore::new(dir.path());
+        assert_eq!(store.rename_dataset("radar", "radar2").unwrap(), 0);
+        assert!(!store.file_path().exists());
+
+        store.create(on_dataset(100, "radar")).unwrap();
+        store.create(on_dataset(200, "ais")).unwrap();
+        store.create(on_dataset(300, "radar")).unwrap();
+
+        assert_eq!(store.rename_dataset("radar", "radar2").unwrap(), 2);
+        let renamed = store
+            .list(&AnnotationQuery {
+                dataset: Some("radar2".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(ids(&renamed), vec![1, 3]);
+        assert!(renamed
+            .iter()
+            .all(|a| a.file_name.as_deref() == Some("data.pcap") && a.packet_index == Some(3)));
+        assert_eq!(store.get(2).unwrap().dataset.as_deref(), Some("ais"));
+    }
+
+    #[test]
+    fn detach_dataset_keeps_annotations() {
+        let dir = project();
+        let store = AnnotationStore::new(dir.path());
+        assert_eq!(store.detach_dataset("radar").unwrap(), 0);
+
+        store.create(on_dataset(100, "radar")).unwrap();
+        store.create(on_dataset(200, "ais")).unwrap();
+
+        assert_eq!(store.detach_dataset("radar").unwrap(), 1);
+        let detached = store.get(1).unwrap();
+        assert_eq!(detached.text, "radar");
+        assert_eq!(
+            (detached.dataset, detached.file_name, detached.packet_index),
+            (None, None, None)
+        );
+        assert_eq!(store.get(2).unwrap().dataset.as_deref(), Some("ais"));
+    }
+}
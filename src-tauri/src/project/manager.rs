@@ -0,0 +1,327 @@
+use log::{info, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::dataset::index::data_files;
+use crate::dataset::info::{scan_dataset, ScanProgress};
+use crate::project::pproj::{find_pproj_file, PprojReader, PprojWriter, PPROJ_EXTENSION};
+use crate::project::structure::{update_time_range, ProjectStructure};
+use crate::project::watcher::ProjectChangeSet;
+use crate::types::{DatasetConfig, DatasetInfo, PlaybackError, PprojConfig, ProjectInfo, Result};
+
+/// 数据集读取器信息
+#[derive(Debug, Clone)]
+pub struct DatasetReader {
+    /// 数据集配置
+    pub config: DatasetConfig,
+    /// PCAP文件路径列表
+    pub pcap_files: Vec<PathBuf>,
+    /// 数据包总数（缓存）
+    pub total_packets: u64,
+}
+
+/// 工程管理器
+#[derive(Debug, Default)]
+pub struct ProjectManager {
+    /// 当前工程信息
+    current_project: Option<ProjectInfo>,
+    /// 工程配置
+    project_config: Option<PprojConfig>,
+    /// 数据集读取器映射
+    dataset_readers: HashMap<String, DatasetReader>,
+    /// 工程目录路径
+    project_path: Option<PathBuf>,
+}
+
+impl ProjectManager {
+    /// 创建新的工程管理器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 打开工程目录
+    pub async fn open_project<P: AsRef<Path>>(&mut self, project_path: P) -> Result<ProjectInfo> {
+        self.open_project_with_progress(project_path, |_, _, _| {})
+            .await
+    }
+
+    /// 打开工程目录，扫描数据文件时通过 `progress` 报告进度
+    ///
+    /// 数据文件扫描在阻塞线程池中执行，不占用异步运行时的工作线程
+    pub async fn open_project_with_progress<P, F>(
+        &mut self,
+        project_path: P,
+        progress: F,
+    ) -> Result<ProjectInfo>
+    where
+        P: AsRef<Path>,
+        F: Fn(u64, u64, &Path) + Send + Sync + 'static,
+    {
+        let path = project_path.as_ref();
+
+        if !path.exists() {
+            return Err(PlaybackError::ProjectError(format!(
+                "工程目录不存在: {:?}",
+                path
+            )));
+        }
+
+        if !path.is_dir() {
+            return Err(PlaybackError::ProjectError(format!(
+                "指定路径不是目录: {:?}",
+                path
+            )));
+        }
+
+        info!("正在打开工程目录: {:?}", path);
+
+        // 1. 构建工程结构
+        let structure = ProjectStructure::from_path(path)?;
+
+        // 2. 加载工程配置
+        let pproj_config = self.load_or_generate_pproj_config(&structure)?;
+
+        // 3. 生成工程信息（统计各数据集的数据包数量和时间范围）
+        let config = pproj_config.clone();
+        let project_info = tokio::task::spawn_blocking(move || {
+            Self::generate_project_info(&structure, &config, &progress)
+        })
+        .await
+        .map_err(|e| PlaybackError::ProjectError(format!("扫描工程数据文件失败: {}", e)))??;
+
+        // 4. 初始化数据集读取器
+        self.initialize_dataset_readers(&pproj_config, &project_info);
+
+        // 5. 保存状态
+        self.project_path = Some(path.to_path_buf());
+        self.project_config = Some(pproj_config);
+        self.current_project = Some(project_info.clone());
+
+        info!("工程打开成功: {}", project_info.name);
+        Ok(project_info)
+    }
+
+    /// 加载或生成PPROJ配置
+    ///
+    /// 工程文件中未登记的数据集目录按默认配置补充，
+    /// 已登记但目录不存在的数据集保留配置，在初始化读取器时给出警告
+    fn load_or_generate_pproj_config(&self, structure: &ProjectStructure) -> Result<PprojConfig> {
+        let mut config = match find_pproj_file(&structure.root_path) {
+            Some(pproj_path) => PprojReader::read(&pproj_path)?,
+            None => {
+                info!("工程目录中没有工程文件，根据目录结构生成配置");
+                PprojConfig::new(structure.name.clone())
+            }
+        };
+
+        for dataset in &structure.datasets {
+            if config.get_dataset(&dataset.name).is_none() {
+                config
+                    .datasets
+                    .push(DatasetConfig::new(dataset.name.clone(), &dataset.path));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// 保存当前工程配置到工程文件
+    pub fn save_project_config(&mut self) -> Result<PathBuf> {
+        let project_path = self
+            .project_path
+            .clone()
+            .ok_or_else(|| PlaybackError::ProjectError("没有打开的工程".to_string()))?;
+        let config = self
+            .project_config
+            .as_mut()
+            .ok_or_else(|| PlaybackError::ProjectError("没有打开的工程".to_string()))?;
+
+        let pproj_path = find_pproj_file(&project_path)
+            .unwrap_or_else(|| project_path.join(format!("{}.{}", config.name, PPROJ_EXTENSION)));
+
+        config.modified_time = chrono::Utc::now().to_rfc3339();
+        PprojWriter::write(&pproj_path, config)?;
+        Ok(pproj_path)
+    }
+
+    /// 初始化数据集读取器
+    fn initialize_dataset_readers(&mut self, config: &PprojConfig, project_info: &ProjectInfo) {
+        self.dataset_readers.clear();
+
+        for dataset_config in &config.datasets {
+            let scanned = project_info
+                .datasets
+                .iter()
+                .find(|d| d.name == dataset_config.name);
+            match self.create_dataset_reader(dataset_config, scanned) {
+                Ok(reader) => {
+                    self.dataset_readers
+                        .insert(dataset_config.name.clone(), reader);
+                    info!("数据集读取器初始化成功: {}", dataset_config.name);
+                }
+                Err(e) => {
+                    warn!(
+                        "数据集读取器初始化失败: {}, 错误: {}",
+                        dataset_config.name, e
+                    );
+                    // 继续处理其他数据集
+                }
+            }
+        }
+
+        info!("已初始化 {} 个数据集读取器", self.dataset_readers.len());
+    }
+
+    /// 创建单个数据集读取器
+    ///
+    /// 数据包总数优先使用生成工程信息时的扫描结果，工程目录外的数据集单独扫描
+    fn create_dataset_reader(
+        &self,
+        dataset_config: &DatasetConfig,
+        scanned: Option<&DatasetInfo>,
+    ) -> Result<DatasetReader> {
+        let dataset_path = Path::new(&dataset_config.path);
+
+        // 扫描数据集目录中的所有PCAP文件
+        let pcap_files = data_files(dataset_path)?;
+
+        // 统计总数据包数
+        let total_packets = match scanned {
+            Some(info) => info.total_packets,
+            None => {
+                scan_dataset(&dataset_config.name, dataset_path, &|_, _, _| {})?
+                    .0
+                    .total_packets
+            }
+        };
+
+        Ok(DatasetReader {
+            config: dataset_config.clone(),
+            pcap_files,
+            total_packets,
+        })
+    }
+
+    /// 生成工程信息
+    fn generate_project_info(
+        structure: &ProjectStructure,
+        config: &PprojConfig,
+        progress: ScanProgress,
+    ) -> Result<ProjectInfo> {
+        let mut project_info = structure.to_project_info(progress)?;
+
+        // 设置元数据
+        project_info.metadata.description = config.description.clone();
+        if let Some(author) = &config.author {
+            project_info.metadata.participants.push(author.clone());
+        }
+        project_info.metadata.tags = config.tags.clone();
+
+        Ok(project_info)
+    }
+
+    /// 应用工程目录监视发现的变化，返回新增的数据集名称
+    ///
+    /// 有变化的数据集直接使用变化中携带的信息，不重新扫描整个工程；
+    /// 工程文件中未登记的新数据集按默认配置补充（不写入工程文件）
+    pub fn apply_changes(&mut self, changes: &ProjectChangeSet) -> Vec<String> {
+        let (Some(project), Some(config)) =
+            (self.current_project.as_mut(), self.project_config.as_mut())
+        else {
+            return Vec::new();
+        };
+
+        for name in &changes.removed_datasets {
+            project.datasets.retain(|d| &d.name != name);
+            if self.dataset_readers.remove(name).is_some() {
+                info!("数据集目录已删除: {}", name);
+            }
+        }
+
+        let mut added = Vec::new();
+        for dataset in &changes.datasets {
+            match project.datasets.iter_mut().find(|d| d.name == dataset.name) {
+                Some(existing) => *existing = dataset.clone(),
+                None => project.datasets.push(dataset.clone()),
+            }
+
+            let pcap_files = data_files(&dataset.path).unwrap_or_default();
+            if let Some(reader) = self.dataset_readers.get_mut(&dataset.name) {
+                reader.pcap_files = pcap_files;
+                reader.total_packets = dataset.total_packets;
+                continue;
+            }
+
+            if config.get_dataset(&dataset.name).is_none() {
+                config
+                    .datasets
+                    .push(DatasetConfig::new(dataset.name.clone(), &dataset.path));
+            }
+            if let Some(dataset_config) = config.get_dataset(&dataset.name) {
+                self.dataset_readers.insert(
+                    dataset.name.clone(),
+                    DatasetReader {
+                        config: dataset_config.clone(),
+                        pcap_files,
+                        total_packets: dataset.total_packets,
+                    },
+                );
+                info!("发现新数据集: {}", dataset.name);
+                added.push(dataset.name.clone());
+            }
+        }
+
+        let mut names: Vec<&String> = self.dataset_readers.keys().collect();
+        names.sort();
+        project.pcap_files = names
+            .into_iter()
+            .flat_map(|name| &self.dataset_readers[name].pcap_files)
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        project.file_count = project.pcap_files.len();
+        update_time_range(project);
+
+        added
+    }
+
+    /// 获取当前工程信息
+    pub fn get_current_project(&self) -> Option<&ProjectInfo> {
+        self.current_project.as_ref()
+    }
+
+    /// 获取当前工程配置
+    pub fn get_project_config(&self) -> Option<&PprojConfig> {
+        self.project_config.as_ref()
+    }
+
+    /// 获取当前工程目录
+    pub fn get_project_path(&self) -> Option<&Path> {
+        self.project_path.as_deref()
+    }
+
+    /// 获取数据集读取器
+    pub fn get_dataset_reader(&self, dataset_name: &str) -> Option<&DatasetReader> {
+        self.dataset_readers.get(dataset_name)
+    }
+
+    /// 列出所有数据集名称（按名称排序）
+    pub fn list_dataset_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.dataset_readers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// 是否已打开工程
+    pub fn is_open(&self) -> bool {
+        self.current_project.is_some()
+    }
+
+    /// 关闭当前工程
+    pub fn close_project(&mut self) {
+        self.current_project = None;
+        self.project_config = None;
+        self.dataset_readers.clear();
+        self.project_path = None;
+        info!("工程已关闭");
+    }
+}
@@ -1,7 +1,11 @@
 //! 工程管理
 //!
-//! 处理回放工程的加载、保存、验证
+//! 处理回放工程的加载、保存、验证、工程目录变化的监视以及工程标注
 
-pub mod loader;
+pub mod annotation;
+pub mod manager;
+pub mod pproj;
 pub mod structure;
 pub mod validator;
+pub mod watcher;
+pub mod xml;
@@ -0,0 +1,475 @@
+//! PPROJ工程文件读写
+//!
+//! 工程文件为XML格式，根元素为 `project`：
+//!
+//! ```xml
+//! <project>
+//!   <version>2</version>
+//!   <caption>回放工程</caption>
+//!   <description>回放工程描述</description>
+//!   <start_timestamp>1753430778</start_timestamp>
+//!   <end_timestamp>1753430779</end_timestamp>
+//!   <created_time>...</created_time>
+//!   <modified_time>...</modified_time>
+//!   <author>...</author>
+//!   <tags><tag>...</tag></tags>
+//!   <datasets>
+//!     <dataset>
+//!       <name>test_dataset_01</name>
+//!       <path>test_dataset_01</path>
+//!       <network>
+//!         <type>multicast</type>
+//!         <address>224.0.0.1</address>
+//!         <port>8080</port>
+//!       </network>
+//!       <time_correction>
+//!         <kind>linear</kind>
+//!         <offset_ns>-1500000</offset_ns>
+//!         <origin_ns>1753430778000000000</origin_ns>
+//!         <drift_ppm>12.5</drift_ppm>
+//!       </time_correction>
+//!     </dataset>
+//!   </datasets>
+//! </project>
+//! ```
+//!
+//! `time_correction` 可选，`kind` 为 `offset`（只有 `offset_ns`）、`linear` 或 `piecewise`
+//! （`points` 下若干 `point`，各含 `recorded_ns` 和 `actual_ns`）。
+//!
+//! 版本1（示例工程使用的格式）没有 `version` 元素，只包含名称、描述和时间范围，
+//! 读取时自动迁移到当前版本，数据集由工程目录扫描结果补全。
+
+use log::info;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::project::xml::XmlWriter;
+use crate::types::{
+    DatasetConfig, NetworkConfig, NetworkType, PlaybackError, PprojConfig, Result, TimeCorrection,
+    TimeReference, PPROJ_VERSION,
+};
+
+/// 工程文件扩展名
+pub const PPROJ_EXTENSION: &str = "pproj";
+
+#[derive(Debug, Deserialize)]
+struct ProjectDocument {
+    #[serde(default)]
+    version: Option<u32>,
+    #[serde(default)]
+    caption: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    start_timestamp: Option<u64>,
+    #[serde(default)]
+    end_timestamp: Option<u64>,
+    #[serde(default)]
+    created_time: Option<String>,
+    #[serde(default)]
+    modified_time: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    tags: TagsDocument,
+    #[serde(default)]
+    datasets: DatasetsDocument,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TagsDocument {
+    #[serde(default, rename = "tag")]
+    items: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DatasetsDocument {
+    #[serde(default, rename = "dataset")]
+    items: Vec<DatasetDocument>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DatasetDocument {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    network: Option<NetworkDocument>,
+    #[serde(default)]
+    time_correction: Option<TimeCorrectionDocument>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NetworkDocument {
+    #[serde(default, rename = "type")]
+    network_type: Option<String>,
+    #[serde(default)]
+    address: Option<String>,
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    interface: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimeCorrectionDocument {
+    kind: String,
+    #[serde(default)]
+    offset_ns: Option<i64>,
+    #[serde(default)]
+    origin_ns: Option<u64>,
+    #[serde(default)]
+    drift_ppm: Option<f64>,
+    #[serde(default)]
+    points: PointsDocument,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PointsDocument {
+    #[serde(default, rename = "point")]
+    items: Vec<TimeReference>,
+}
+
+/// PPROJ文件读取器
+pub struct PprojReader;
+
+impl PprojReader {
+    /// 读取工程文件，数据集路径相对于文件所在目录解析
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<PprojConfig> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let project_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        let config = Self::parse(&content, project_dir)?;
+        info!("读取工程文件: {:?}", path);
+        Ok(config)
+    }
+
+    /// 解析工程文件内容并迁移到当前版本
+    pub fn parse(content: &str, project_dir: &Path) -> Result<PprojConfig> {
+        let document: ProjectDocument = serde_xml_rs::from_str(content)
+            .map_err(|e| PlaybackError::XmlError(format!("解析工程文件失败: {}", e)))?;
+
+        let version = document.version.unwrap_or(1);
+        if version > PPROJ_VERSION {
+            return Err(PlaybackError::FormatError(format!(
+                "不支持的工程文件版本: {}（当前支持 {}）",
+                version, PPROJ_VERSION
+            )));
+        }
+
+        let datasets = document
+            .datasets
+            .items
+            .into_iter()
+            .map(|dataset| Self::convert_dataset(dataset, project_dir))
+            .collect::<Result<Vec<_>>>()?;
+
+        let name = non_empty(document.caption).unwrap_or_else(|| {
+            project_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "untitled".to_string())
+        });
+
+        let mut config = PprojConfig::new(name);
+        config.description = non_empty(document.description);
+        config.start_timestamp = document.start_timestamp;
+        config.end_timestamp = document.end_timestamp;
+        config.author = non_empty(document.author);
+        config.tags = document.tags.items;
+        config.datasets = datasets;
+        // 保留文件中的时间，文件中没有记录时（如版本1）为空，不以读取时间代替
+        config.created_time = non_empty(document.created_time).unwrap_or_default();
+        config.modified_time = non_empty(document.modified_time).unwrap_or_default();
+
+        if version < PPROJ_VERSION {
+            info!("工程文件已从版本 {} 迁移到版本 {}", version, PPROJ_VERSION);
+        }
+
+        Ok(config)
+    }
+
+    fn convert_dataset(dataset: DatasetDocument, project_dir: &Path) -> Result<DatasetConfig> {
+        let relative = non_empty(dataset.path).unwrap_or_else(|| dataset.name.clone());
+        let path = resolve_path(project_dir, &relative);
+
+        let mut config = DatasetConfig::new(dataset.name, path);
+        config.description = non_empty(dataset.description);
+
+        if let Some(network) = dataset.network {
+            let defaults = NetworkConfig::default();
+            config.network_config = NetworkConfig {
+                network_type: match non_empty(network.network_type) {
+                    Some(network_type) => network_type.parse::<NetworkType>()?,
+                    None => defaults.network_type,
+                },
+                ip_address: non_empty(network.address).unwrap_or(defaults.ip_address),
+                port: network.port.unwrap_or(defaults.port),
+                interface: non_empty(network.interface),
+            };
+        }
+
+        if let Some(correction) = dataset.time_correction {
+            let correction = Self::convert_time_correction(correction)?;
+            correction.validate().map_err(|e| {
+                PlaybackError::ProjectError(format!("数据集 {} 的时间校正无效: {}", config.name, e))
+            })?;
+            config.time_correction = Some(correction);
+        }
+
+        Ok(config)
+    }
+
+    fn convert_time_correction(document: TimeCorrectionDocument) -> Result<TimeCorrection> {
+        let offset_ns = document.offset_ns.unwrap_or(0);
+        match document.kind.trim() {
+            "offset" => Ok(TimeCorrection::Offset { offset_ns }),
+            "linear" => Ok(TimeCorrection::Linear {
+                offset_ns,
+                origin_ns: document.origin_ns.unwrap_or(0),
+                drift_ppm: document.drift_ppm.unwrap_or(0.0),
+            }),
+            "piecewise" => Ok(TimeCorrection::Piecewise {
+                points: document.points.items,
+            }),
+            kind => Err(PlaybackError::ParseError(format!(
+                "未知的时间校正类型: {}",
+                kind
+            ))),
+        }
+    }
+}
+
+/// PPROJ文件写入器
+pub struct PprojWriter;
+
+impl PprojWriter {
+    /// 写入工程文件，工程目录内的数据集路径保存为相对路径
+    pub fn write<P: AsRef<Path>>(path: P, config: &PprojConfig) -> Result<()> {
+        let path = path.as_ref();
+        let project_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let content = Self::to_xml(config, project_dir);
+
+        // 先写临时文件再替换，避免写入中断损坏工程文件
+        let temp_path = path.with_extension("pproj.tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, path)?;
+
+        info!("保存工程文件: {:?}", path);
+        Ok(())
+    }
+
+    /// 生成工程文件内容
+    pub fn to_xml(config: &PprojConfig, project_dir: &Path) -> String {
+        let mut writer = XmlWriter::new();
+        writer
+            .open("project")
+            .element("version", PPROJ_VERSION)
+            .element("caption", &config.name)
+            .optional_element("description", config.description.as_ref())
+            .optional_element("start_timestamp", config.start_timestamp)
+            .optional_element("end_timestamp", config.end_timestamp)
+            .optional_element(
+                "created_time",
+                Some(&config.created_time).filter(|t| !t.is_empty()),
+            )
+            .optional_element(
+                "modified_time",
+                Some(&config.modified_time).filter(|t| !t.is_empty()),
+            )
+            .optional_element("author", config.author.as_ref());
+
+        if !config.tags.is_empty() {
+            writer.open("tags");
+            for tag in &config.tags {
+                writer.element("tag", tag);
+            }
+            writer.close("tags");
+        }
+
+        writer.open("datasets");
+        for dataset in &config.datasets {
+            let network = &dataset.network_config;
+            writer
+                .open("dataset")
+                .element("name", &dataset.name)
+                .optional_element("description", dataset.description.as_ref())
+                .element("path", relative_path(project_dir, Path::new(&dataset.path)))
+                .open("network")
+                .element("type", &network.network_type)
+                .element("address", &network.ip_address)
+                .element("port", network.port)
+                .optional_element("interface", network.interface.as_ref())
+                .close("network");
+            if let Some(correction) = &dataset.time_correction {
+                write_time_correction(&mut writer, correction);
+            }
+            writer.close("dataset");
+        }
+        writer.close("datasets").close("project");
+
+        writer.finish()
+    }
+}
+
+fn write_time_correction(writer: &mut XmlWriter, correction: &TimeCorrection) {
+    writer
+        .open("time_correction")
+        .element("kind", correction.kind().as_str());
+    match correction {
+        TimeCorrection::Offset { offset_ns } => {
+            writer.element("offset_ns", offset_ns);
+        }
+        TimeCorrection::Linear {
+            offset_ns,
+            origin_ns,
+            drift_ppm,
+        } => {
+            writer
+                .element("offset_ns", offset_ns)
+                .element("origin_ns", origin_ns)
+                .element("drift_ppm", drift_ppm);
+        }
+        TimeCorrection::Piecewise { points } => {
+            writer.open("points");
+            for point in points {
+                writer
+                    .open("point")
+                    .element("recorded_ns", point.recorded_ns)
+                    .element("actual_ns", point.actual_ns)
+                    .close("point");
+            }
+            writer.close("points");
+        }
+    }
+    writer.close("time_correction");
+}
+
+/// 查找工程目录中的工程文件（按文件名取第一个）
+pub fn find_pproj_file<P: AsRef<Path>>(project_dir: P) -> Option<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(project_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(|e| e.to_str()) == Some(PPROJ_EXTENSION)
+        })
+        .collect();
+
+    files.sort();
+    files.into_iter().next()
+}
+
+/// 空字符串视为未设置
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn resolve_path(project_dir: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        project_dir.join(path)
+    }
+}
+
+fn relative_path(project_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(project_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_project_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../docs/详细设计文档/01-工程管理模块/example/playback_project")
+    }
+
+    fn to_json(config: &PprojConfig) -> serde_json::Value {
+        serde_json::to_value(config).unwrap()
+    }
+
+    #[test]
+    fn example_v1_project_round_trips() {
+        let dir = example_project_dir();
+        let path = dir.join("data_product_001.pproj");
+        let config = PprojReader::read(&path).unwrap();
+        assert_eq!(config.version, PPROJ_VERSION);
+        assert_eq!(config.name, "回放工程");
+        assert_eq!(config.description.as_deref(), Some("回放工程描述"));
+        assert_eq!(config.start_timestamp, Some(1753430778));
+        assert_eq!(config.end_timestamp, Some(1753430779));
+        // 版本1没有记录时间，读取后不能以当前时间代替
+        assert!(config.created_time.is_empty());
+        assert!(config.modified_time.is_empty());
+
+        let xml = PprojWriter::to_xml(&config, &dir);
+        assert!(!xml.contains("created_time"));
+        let reread = PprojReader::parse(&xml, &dir).unwrap();
+        assert_eq!(to_json(&reread), to_json(&config));
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let dir = example_project_dir();
+        let mut config = PprojConfig::new("工程".to_string());
+        config.created_time = "2025-07-25T08:00:00+00:00".to_string();
+        config.modified_time = "2025-07-26T09:30:00+00:00".to_string();
+        config.author = Some("张三".to_string());
+        config.tags = vec!["试验".to_string(), "A&B".to_string()];
+
+        let mut dataset =
+            DatasetConfig::new("test_dataset_01".to_string(), dir.join("test_dataset_01"));
+        dataset.network_config = NetworkConfig {
+            network_type: NetworkType::Unicast,
+            ip_address: "10.0.0.2".to_string(),
+            port: 9000,
+            interface: Some("eth0".to_string()),
+        };
+        dataset.time_correction = Some(TimeCorrection::Linear {
+            offset_ns: -1_500_000,
+            origin_ns: 1_753_430_778_000_000_000,
+            drift_ppm: 12.5,
+        });
+        config.datasets.push(dataset);
+        let mut piecewise =
+            DatasetConfig::new("test_dataset_02".to_string(), dir.join("test_dataset_02"));
+        piecewise.time_correction = Some(TimeCorrection::Piecewise {
+            points: vec![
+                TimeReference {
+                    recorded_ns: 1_000,
+                    actual_ns: 1_500,
+                },
+                TimeReference {
+                    recorded_ns: 2_000,
+                    actual_ns: 2_600,
+                },
+            ],
+        });
+        config.datasets.push(piecewise);
+
+        let xml = PprojWriter::to_xml(&config, &dir);
+        assert!(xml.contains("<path>test_dataset_01</path>"));
+        let reread = PprojReader::parse(&xml, &dir).unwrap();
+        assert_eq!(to_json(&reread), to_json(&config));
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let xml = format!(
+            "<project><version>{}</version></project>",
+            PPROJ_VERSION + 1
+        );
+        assert!(PprojReader::parse(&xml, Path::new(".")).is_err());
+    }
+}
@@ -2,7 +2,24 @@ use log::{debug, info, warn};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::types::common::{ProjectInfo, Result};
+use crate::dataset::info::{iso_timestamp, scan_dataset, ScanProgress};
+use crate::event::store::EventStore;
+use crate::product::pprod::find_pprod_files;
+use crate::product::store::ProductStore;
+use crate::project::annotation::AnnotationStore;
+use crate::project::pproj::{find_pproj_file, PprojReader, PprojWriter, PPROJ_EXTENSION};
+use crate::project::validator::ProjectValidator;
+use crate::types::common::{PlaybackError, ProjectInfo, Result};
+use crate::types::{DatasetConfig, NetworkConfig, PprojConfig, TimeCorrection};
+
+/// 工程结果目录（按数据集存放分析结果）
+pub const RESULT_DIR: &str = ".result";
+
+/// 工程状态目录（按数据集存放处理状态）
+pub const STATUS_DIR: &str = ".status";
+
+/// 工程回收目录（存放移除的数据集）
+pub const TRASH_DIR: &str = ".trash";
 
 /// 工程结构表示
 pub struct ProjectStructure {
@@ -37,7 +54,9 @@ impl ProjectStructure {
             for entry in entries {
                 if let Ok(entry) = entry {
                     let path = entry.path();
-                    if path.is_dir() {
+                    if is_hidden(&path) {
+                        debug!("跳过工程内部目录: {:?}", path);
+                    } else if path.is_dir() {
                         debug!("发现目录: {:?}", path);
                         match Self::scan_dataset(&path) {
                             Ok(dataset) => {
@@ -82,6 +101,371 @@ impl ProjectStructure {
         })
     }
 
+    /// 新建工程
+    ///
+    /// 在 `parent_dir` 下创建名为 `name` 的工程目录，生成工程文件、
+    /// `.result` 和 `.status` 目录以及初始数据集。目标目录已存在时必须为空。
+    pub fn create<P: AsRef<Path>>(
+        parent_dir: P,
+        name: &str,
+        description: Option<String>,
+        dataset_names: &[String],
+    ) -> Result<Self> {
+        ProjectValidator::validate_name(name)?;
+        for (i, dataset_name) in dataset_names.iter().enumerate() {
+            ProjectValidator::validate_name(dataset_name)?;
+            if dataset_names[..i].contains(dataset_name) {
+                return Err(PlaybackError::InvalidName(format!(
+                    "数据集名称重复: {}",
+                    dataset_name
+                )));
+            }
+        }
+
+        let parent_dir = parent_dir.as_ref();
+        if !parent_dir.is_dir() {
+            return Err(PlaybackError::ProjectError(format!(
+                "上级目录不存在: {:?}",
+                parent_dir
+            )));
+        }
+
+        let root_path = parent_dir.join(name);
+        let existed = root_path.exists();
+        if existed {
+            if !root_path.is_dir() {
+                return Err(PlaybackError::ProjectError(format!(
+                    "同名文件已存在: {:?}",
+                    root_path
+                )));
+            }
+            if fs::read_dir(&root_path)?.next().is_some() {
+                return Err(PlaybackError::ProjectError(format!(
+                    "目录已存在且不为空: {:?}",
+                    root_path
+                )));
+            }
+        }
+
+        info!("新建工程: {:?}", root_path);
+        if let Err(e) = Self::scaffold(&root_path, name, description, dataset_names) {
+            // 清理已创建的内容，保留原有的空目录
+            let _ = fs::remove_dir_all(&root_path);
+            if existed {
+                let _ = fs::create_dir(&root_path);
+            }
+            return Err(e);
+        }
+
+        Self::from_path(&root_path)
+    }
+
+    /// 生成工程目录内容
+    fn scaffold(
+        root_path: &Path,
+        name: &str,
+        description: Option<String>,
+        dataset_names: &[String],
+    ) -> Result<()> {
+        fs::create_dir_all(root_path.join(RESULT_DIR))?;
+        fs::create_dir_all(root_path.join(STATUS_DIR))?;
+
+        let mut config = PprojConfig::new(name.to_string());
+        config.description = description.filter(|d| !d.trim().is_empty());
+
+        for dataset_name in dataset_names {
+            let dataset_path = Self::create_dataset_dirs(root_path, dataset_name)?;
+            config
+                .datasets
+                .push(DatasetConfig::new(dataset_name.clone(), dataset_path));
+        }
+
+        let pproj_path = root_path.join(format!("{}.{}", name, PPROJ_EXTENSION));
+        PprojWriter::write(pproj_path, &config)
+    }
+
+    /// 在工程中创建数据集
+    ///
+    /// 同时创建 `.result`、`.status` 下的对应目录，并登记到工程文件（如存在）
+    pub fn create_dataset<P: AsRef<Path>>(project_path: P, dataset_name: &str) -> Result<PathBuf> {
+        ProjectValidator::validate_name(dataset_name)?;
+
+        let project_path = project_path.as_ref();
+        if !project_path.is_dir() {
+            return Err(PlaybackError::ProjectError(format!(
+                "工程目录不存在: {:?}",
+                project_path
+            )));
+        }
+
+        if project_path.join(dataset_name).exists() {
+            return Err(PlaybackError::ProjectError(format!(
+                "数据集已存在: {}",
+                dataset_name
+            )));
+        }
+
+        let dataset_path = Self::create_dataset_dirs(project_path, dataset_name)?;
+
+        Self::update_pproj(project_path, |config| {
+            if config.get_dataset(dataset_name).is_some() {
+                return false;
+            }
+            config
+                .datasets
+                .push(DatasetConfig::new(dataset_name.to_string(), &dataset_path));
+            true
+        })?;
+
+        info!("数据集目录创建成功: {:?}", dataset_path);
+        Ok(dataset_path)
+    }
+
+    /// 读取数据集配置，工程文件中未登记时按默认配置返回
+    pub fn dataset_config<P: AsRef<Path>>(
+        project_path: P,
+        dataset_name: &str,
+    ) -> Result<DatasetConfig> {
+        let project_path = project_path.as_ref();
+        let dataset_path = project_path.join(dataset_name);
+        if !dataset_path.is_dir() {
+            return Err(PlaybackError::DatasetNotFound(dataset_name.to_string()));
+        }
+
+        let registered = find_pproj_file(project_path)
+            .map(PprojReader::read)
+            .transpose()?
+            .and_then(|config| config.get_dataset(dataset_name).cloned());
+        let config = registered
+            .unwrap_or_else(|| DatasetConfig::new(dataset_name.to_string(), dataset_path));
+        Ok(config)
+    }
+
+    /// 更新工程文件中数据集的网络配置，工程文件不存在或未登记该数据集时返回 `false`
+    pub fn update_dataset_network<P: AsRef<Path>>(
+        project_path: P,
+        dataset_name: &str,
+        network_config: NetworkConfig,
+    ) -> Result<bool> {
+        Self::update_pproj(project_path, |config| {
+            match config.datasets.iter_mut().find(|d| d.name == dataset_name) {
+                Some(dataset) => {
+                    dataset.network_config = network_config;
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+
+    /// 更新工程文件中数据集的时间校正，未登记的数据集先按默认配置登记；工程文件不存在时返回 `false`
+    pub fn update_dataset_time_correction<P: AsRef<Path>>(
+        project_path: P,
+        dataset_name: &str,
+        time_correction: Option<TimeCorrection>,
+    ) -> Result<bool> {
+        let project_path = project_path.as_ref();
+        let dataset_path = Self::existing_dataset_path(project_path, dataset_name)?;
+        if let Some(correction) = &time_correction {
+            correction.validate()?;
+        }
+
+        Self::update_pproj(project_path, |config| {
+            if config.get_dataset(dataset_name).is_none() {
+                config
+                    .datasets
+                    .push(DatasetConfig::new(dataset_name.to_string(), &dataset_path));
+            }
+            if let Some(dataset) = config.datasets.iter_mut().find(|d| d.name == dataset_name) {
+                dataset.time_correction = time_correction;
+            }
+            true
+        })
+    }
+
+    /// 移除数据集
+    ///
+    /// 连同 `.result`、`.status` 下的对应目录移到工程的 `.trash` 目录，保持原有的目录结构以便手动恢复，
+    /// 再从工程文件中注销数据集，解除标注与它的关联，删除它的事件并从数据产品中去掉它，任一步骤失败时还原上述文件并移回原处。
+    /// `permanent` 为 `false` 时返回回收位置；为 `true` 时完成后删除回收目录。
+    pub fn remove_dataset<P: AsRef<Path>>(
+        project_path: P,
+        dataset_name: &str,
+        permanent: bool,
+    ) -> Result<Option<PathBuf>> {
+        let project_path = project_path.as_ref();
+        let dataset_path = Self::existing_dataset_path(project_path, dataset_name)?;
+        let mirrors = Self::mirror_dirs(project_path, dataset_name);
+
+        let trash_path = project_path.join(TRASH_DIR).join(format!(
+            "{}_{}",
+            dataset_name,
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        ));
+        if trash_path.exists() {
+            return Err(PlaybackError::ProjectError(format!(
+                "回收目录已存在: {:?}",
+                trash_path
+            )));
+        }
+
+        let mut moves = vec![(dataset_path, trash_path.join(dataset_name))];
+        for (dir, mirror) in [RESULT_DIR, STATUS_DIR].iter().zip(mirrors) {
+            if mirror.exists() {
+                moves.push((mirror, trash_path.join(dir).join(dataset_name)));
+            }
+        }
+        let snapshot = Self::reference_snapshot(project_path)?;
+        move_all(&moves)?;
+
+        let updated = Self::update_pproj(project_path, |config| {
+            let count = config.datasets.len();
+            config.datasets.retain(|d| d.name != dataset_name);
+            config.datasets.len() != count
+        })
+        .and_then(|_| AnnotationStore::new(project_path).detach_dataset(dataset_name))
+        .and_then(|_| EventStore::new(project_path).remove_dataset(dataset_name))
+        .and_then(|_| ProductStore::new(project_path).remove_dataset(dataset_name));
+        if let Err(e) = updated {
+            snapshot.restore();
+            undo_moves(&moves);
+            return Err(e);
+        }
+
+        if permanent {
+            // 数据集已注销，回收目录删除失败时只留下可手动清理的残留
+            if let Err(e) = fs::remove_dir_all(&trash_path) {
+                warn!("删除回收目录失败 {:?}: {}", trash_path, e);
+            }
+            info!("数据集已删除: {}", dataset_name);
+            return Ok(None);
+        }
+
+        info!("数据集已移到回收目录: {} -> {:?}", dataset_name, trash_path);
+        Ok(Some(trash_path))
+    }
+
+    /// 重命名数据集
+    ///
+    /// 同时重命名 `.result`、`.status` 下的对应目录，更新工程文件、关联该数据集的标注、事件和数据产品，
+    /// 任一步骤失败时还原已完成的修改
+    pub fn rename_dataset<P: AsRef<Path>>(
+        project_path: P,
+        dataset_name: &str,
+        new_name: &str,
+    ) -> Result<PathBuf> {
+        ProjectValidator::validate_name(new_name)?;
+
+        let project_path = project_path.as_ref();
+        let dataset_path = Self::existing_dataset_path(project_path, dataset_name)?;
+        let new_path = project_path.join(new_name);
+        if new_path.exists() {
+            return Err(PlaybackError::ProjectError(format!(
+                "数据集已存在: {}",
+                new_name
+            )));
+        }
+
+        let mut moves = vec![(dataset_path, new_path.clone())];
+        for (old, new) in Self::mirror_dirs(project_path, dataset_name)
+            .into_iter()
+            .zip(Self::mirror_dirs(project_path, new_name))
+        {
+            if new.exists() {
+                return Err(PlaybackError::ProjectError(format!(
+                    "目录已存在: {:?}",
+                    new
+                )));
+            }
+            if old.exists() {
+                moves.push((old, new));
+            }
+        }
+        let snapshot = Self::reference_snapshot(project_path)?;
+        move_all(&moves)?;
+
+        let updated = Self::update_pproj(project_path, |config| {
+            match config.datasets.iter_mut().find(|d| d.name == dataset_name) {
+                Some(dataset) => {
+                    dataset.name = new_name.to_string();
+                    dataset.path = new_path.to_string_lossy().to_string();
+                    true
+                }
+                None => false,
+            }
+        })
+        .and_then(|_| AnnotationStore::new(project_path).rename_dataset(dataset_name, new_name))
+        .and_then(|_| EventStore::new(project_path).rename_dataset(dataset_name, new_name))
+        .and_then(|_| ProductStore::new(project_path).rename_dataset(dataset_name, new_name));
+        if let Err(e) = updated {
+            snapshot.restore();
+            undo_moves(&moves);
+            return Err(e);
+        }
+
+        info!("数据集已重命名: {} -> {}", dataset_name, new_name);
+        Ok(new_path)
+    }
+
+    /// 记录按数据集名称引用数据集的工程文件、标注文件、事件列表和数据产品文件，移除或重命名失败时写回
+    fn reference_snapshot(project_path: &Path) -> Result<FileSnapshot> {
+        let mut paths = vec![
+            AnnotationStore::new(project_path).file_path(),
+            EventStore::new(project_path).file_path(),
+        ];
+        paths.extend(find_pproj_file(project_path));
+        paths.extend(find_pprod_files(project_path)?);
+        FileSnapshot::take(paths)
+    }
+
+    /// 读取、修改并保存工程文件，`update` 返回 `false` 时不写入；工程文件不存在时返回 `false`
+    fn update_pproj<P: AsRef<Path>>(
+        project_path: P,
+        update: impl FnOnce(&mut PprojConfig) -> bool,
+    ) -> Result<bool> {
+        let Some(pproj_path) = find_pproj_file(project_path) else {
+            return Ok(false);
+        };
+
+        let mut config = PprojReader::read(&pproj_path)?;
+        if !update(&mut config) {
+            return Ok(false);
+        }
+
+        config.modified_time = chrono::Utc::now().to_rfc3339();
+        PprojWriter::write(&pproj_path, &config)?;
+        Ok(true)
+    }
+
+    /// 校验数据集名称并返回已存在的数据集目录
+    fn existing_dataset_path(project_path: &Path, dataset_name: &str) -> Result<PathBuf> {
+        // 名称来自前端，拒绝路径分隔符等，避免操作工程目录以外的内容
+        ProjectValidator::validate_name(dataset_name)?;
+
+        let dataset_path = project_path.join(dataset_name);
+        if !dataset_path.is_dir() {
+            return Err(PlaybackError::DatasetNotFound(dataset_name.to_string()));
+        }
+        Ok(dataset_path)
+    }
+
+    /// 数据集在 `.result`、`.status` 下的对应目录
+    fn mirror_dirs(project_path: &Path, dataset_name: &str) -> [PathBuf; 2] {
+        [
+            project_path.join(RESULT_DIR).join(dataset_name),
+            project_path.join(STATUS_DIR).join(dataset_name),
+        ]
+    }
+
+    /// 创建数据集目录及其结果、状态目录
+    fn create_dataset_dirs(root_path: &Path, dataset_name: &str) -> Result<PathBuf> {
+        let dataset_path = root_path.join(dataset_name);
+        fs::create_dir(&dataset_path)?;
+        fs::create_dir_all(root_path.join(RESULT_DIR).join(dataset_name))?;
+        fs::create_dir_all(root_path.join(STATUS_DIR).join(dataset_name))?;
+        Ok(dataset_path)
+    }
+
     /// 扫描单个数据集
     fn scan_dataset<P: AsRef<Path>>(dataset_path: P) -> Result<DatasetStructure> {
         let path = dataset_path.as_ref().to_path_buf();
@@ -136,7 +520,9 @@ impl ProjectStructure {
     }
 
     /// 转换为项目信息
-    pub fn to_project_info(&self) -> Result<ProjectInfo> {
+    ///
+    /// 扫描各数据集的数据包数量和时间范围，`progress` 按全部数据集的文件总数报告进度
+    pub fn to_project_info(&self, progress: ScanProgress) -> Result<ProjectInfo> {
         let mut project_info = ProjectInfo::new(
             self.name.clone(),
             self.root_path.to_string_lossy().to_string(),
@@ -157,31 +543,343 @@ impl ProjectStructure {
             .map(|p| p.to_string_lossy().to_string())
             .collect();
 
-        // 尝试获取时间范围（从第一个和最后一个文件的元数据）
-        if let Some(first_file) = project_info.pcap_files.first() {
-            if let Ok(metadata) = fs::metadata(first_file) {
-                if let Ok(created) = metadata.created() {
-                    project_info.start_time = created
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs()
-                        .to_string();
-                }
+        // 时间范围取自各数据集首、末数据包的时间戳
+        let total = project_info.file_count as u64;
+        let mut scanned = 0;
+        for dataset in &self.datasets {
+            let dataset_progress =
+                |done: u64, _: u64, path: &Path| progress(scanned + done, total, path);
+            match scan_dataset(&dataset.name, &dataset.path, &dataset_progress) {
+                Ok((info, _)) => project_info.datasets.push(info),
+                Err(e) => warn!("读取数据集信息失败 {}: {}", dataset.name, e),
             }
+            scanned += dataset.pcap_files.len() as u64;
         }
 
-        if let Some(last_file) = project_info.pcap_files.last() {
-            if let Ok(metadata) = fs::metadata(last_file) {
-                if let Ok(modified) = metadata.modified() {
-                    project_info.end_time = modified
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs()
-                        .to_string();
-                }
+        update_time_range(&mut project_info);
+        Ok(project_info)
+    }
+}
+
+/// 按各数据集首、末数据包的时间戳更新工程的时间范围
+pub fn update_time_range(project_info: &mut ProjectInfo) {
+    let start = project_info
+        .datasets
+        .iter()
+        .filter_map(|d| d.start_timestamp)
+        .min();
+    let end = project_info
+        .datasets
+        .iter()
+        .filter_map(|d| d.end_timestamp)
+        .max();
+
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            project_info.total_duration = end.saturating_sub(start);
+            project_info.start_time = iso_timestamp(start);
+            project_info.end_time = iso_timestamp(end);
+        }
+        _ => {
+            project_info.total_duration = 0;
+            project_info.start_time.clear();
+            project_info.end_time.clear();
+        }
+    }
+}
+
+/// 是否为隐藏目录或文件（以 `.` 开头）
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with('.'))
+}
+
+/// 依次移动文件或目录，失败时还原已完成的移动
+pub(crate) fn move_all(moves: &[(PathBuf, PathBuf)]) -> Result<()> {
+    for (i, (from, to)) in moves.iter().enumerate() {
+        let result = to
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::rename(from, to));
+        if let Err(e) = result {
+            undo_moves(&moves[..i]);
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+/// 文件的原始内容，多步修改中后续步骤失败时写回
+struct FileSnapshot(Vec<(PathBuf, Option<Vec<u8>>)>);
+
+impl FileSnapshot {
+    /// 读取文件内容，不存在的文件记为空
+    fn take(paths: Vec<PathBuf>) -> Result<Self> {
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let content = match fs::read(&path) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            };
+            files.push((path, content));
+        }
+        Ok(Self(files))
+    }
+
+    /// 写回原始内容，原本不存在的文件删除，尽力而为
+    fn restore(&self) {
+        for (path, content) in &self.0 {
+            let result = match content {
+                Some(content) => fs::write(path, content),
+                None if path.exists() => fs::remove_file(path),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                warn!("还原文件失败 {:?}: {}", path, e);
             }
         }
+    }
+}
 
-        Ok(project_info)
+/// 逆序还原移动，尽力而为
+pub(crate) fn undo_moves(moves: &[(PathBuf, PathBuf)]) {
+    for (from, to) in moves.iter().rev() {
+        if let Err(e) = fs::rename(to, from) {
+            warn!("还原移动失败 {:?} -> {:?}: {}", to, from, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::store::EventList;
+    use crate::product::pprod::{PprodReader, PprodWriter, ProductConfig};
+    use crate::project::annotation::{AnnotationDraft, AnnotationQuery};
+
+    /// 新建含 `radar`、`ais` 两个数据集的工程，附带引用 `radar` 的标注、事件列表和数据产品
+    fn project_with_references(parent: &Path) -> PathBuf {
+        let names = vec!["radar".to_string(), "ais".to_string()];
+        let project = ProjectStructure::create(parent, "proj", None, &names)
+            .unwrap()
+            .root_path;
+        fs::write(project.join("radar").join("marker"), b"radar").unwrap();
+        fs::write(
+            project.join(RESULT_DIR).join("radar").join("marker"),
+            b"result",
+        )
+        .unwrap();
+
+        AnnotationStore::new(&project)
+            .create(AnnotationDraft {
+                start_timestamp: 1,
+                text: "radar".to_string(),
+                dataset: Some("radar".to_string()),
+                file_name: Some("data.pcap".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        EventStore::new(&project)
+            .write(&mut EventList::new(Vec::new(), names.clone()))
+            .unwrap();
+        let mut product = ProductConfig::new("p".to_string(), 0, 1);
+        product.datasets = names;
+        PprodWriter::write(project.join("p.pprod"), &product).unwrap();
+        project
+    }
+
+    fn pproj_names(project: &Path) -> Vec<String> {
+        PprojReader::read(find_pproj_file(project).unwrap())
+            .unwrap()
+            .datasets
+            .into_iter()
+            .map(|d| d.name)
+            .collect()
+    }
+
+    fn annotation_datasets(project: &Path) -> Vec<Option<String>> {
+        AnnotationStore::new(project)
+            .list(&AnnotationQuery::default())
+            .unwrap()
+            .into_iter()
+            .map(|a| a.dataset)
+            .collect()
+    }
+
+    fn event_datasets(project: &Path) -> Vec<String> {
+        EventStore::new(project).read().unwrap().unwrap().datasets
+    }
+
+    fn product_datasets(project: &Path) -> Vec<String> {
+        PprodReader::read(project.join("p.pprod")).unwrap().datasets
+    }
+
+    /// 引用数据集的文件内容，用于确认回滚后原样还原
+    fn reference_contents(project: &Path) -> Vec<Vec<u8>> {
+        [
+            find_pproj_file(project).unwrap(),
+            AnnotationStore::new(project).file_path(),
+            project.join("p.pprod"),
+        ]
+        .iter()
+        .map(|path| fs::read(path).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn rename_moves_directories_and_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_references(dir.path());
+
+        let new_path = ProjectStructure::rename_dataset(&project, "radar", "radar2").unwrap();
+
+        assert_eq!(new_path, project.join("radar2"));
+        assert!(!project.join("radar").exists());
+        assert_eq!(fs::read(new_path.join("marker")).unwrap(), b"radar");
+        assert_eq!(
+            fs::read(project.join(RESULT_DIR).join("radar2").join("marker")).unwrap(),
+            b"result"
+        );
+        assert!(project.join(STATUS_DIR).join("radar2").is_dir());
+        assert!(!project.join(RESULT_DIR).join("radar").exists());
+
+        let config = PprojReader::read(find_pproj_file(&project).unwrap()).unwrap();
+        let dataset = config.get_dataset("radar2").unwrap();
+        assert_eq!(Path::new(&dataset.path), new_path);
+        assert!(config.get_dataset("radar").is_none());
+        assert_eq!(
+            annotation_datasets(&project),
+            vec![Some("radar2".to_string())]
+        );
+        assert_eq!(event_datasets(&project), vec!["radar2", "ais"]);
+        assert_eq!(product_datasets(&project), vec!["radar2", "ais"]);
+    }
+
+    #[test]
+    fn rename_rejects_invalid_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_references(dir.path());
+        let contents = reference_contents(&project);
+
+        assert!(ProjectStructure::rename_dataset(&project, "radar", "ais").is_err());
+        assert!(ProjectStructure::rename_dataset(&project, "radar", "../radar").is_err());
+        assert!(matches!(
+            ProjectStructure::rename_dataset(&project, "missing", "radar2"),
+            Err(PlaybackError::DatasetNotFound(_))
+        ));
+        // 新名称在 `.result` 下的目录已被占用
+        fs::create_dir_all(project.join(RESULT_DIR).join("radar2")).unwrap();
+        assert!(ProjectStructure::rename_dataset(&project, "radar", "radar2").is_err());
+
+        assert!(project.join("radar").is_dir());
+        assert!(!project.join("radar2").exists());
+        assert_eq!(reference_contents(&project), contents);
+    }
+
+    #[test]
+    fn failed_rename_restores_files_and_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_references(dir.path());
+        let contents = reference_contents(&project);
+        // 事件列表无法解析，更新工程文件和标注之后失败
+        let events = EventStore::new(&project).file_path();
+        fs::write(&events, "{").unwrap();
+
+        assert!(ProjectStructure::rename_dataset(&project, "radar", "radar2").is_err());
+
+        assert_eq!(reference_contents(&project), contents);
+        assert_eq!(fs::read_to_string(&events).unwrap(), "{");
+        assert_eq!(
+            fs::read(project.join("radar").join("marker")).unwrap(),
+            b"radar"
+        );
+        assert!(project
+            .join(RESULT_DIR)
+            .join("radar")
+            .join("marker")
+            .is_file());
+        assert!(!project.join("radar2").exists());
+        assert!(!project.join(RESULT_DIR).join("radar2").exists());
+        assert!(!project.join(STATUS_DIR).join("radar2").exists());
+    }
+
+    #[test]
+    fn remove_moves_dataset_to_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_references(dir.path());
+
+        let trash = ProjectStructure::remove_dataset(&project, "radar", false)
+            .unwrap()
+            .unwrap();
+
+        assert!(trash.starts_with(project.join(TRASH_DIR)));
+        assert!(!project.join("radar").exists());
+        assert!(!project.join(RESULT_DIR).join("radar").exists());
+        assert!(!project.join(STATUS_DIR).join("radar").exists());
+        assert_eq!(
+            fs::read(trash.join("radar").join("marker")).unwrap(),
+            b"radar"
+        );
+        assert_eq!(
+            fs::read(trash.join(RESULT_DIR).join("radar").join("marker")).unwrap(),
+            b"result"
+        );
+        assert!(trash.join(STATUS_DIR).join("radar").is_dir());
+
+        assert_eq!(pproj_names(&project), vec!["ais"]);
+        let annotations = AnnotationStore::new(&project)
+            .list(&AnnotationQuery::default())
+            .unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert!(annotations[0].dataset.is_none() && annotations[0].file_name.is_none());
+        assert_eq!(event_datasets(&project), vec!["ais"]);
+        assert_eq!(product_datasets(&project), vec!["ais"]);
+    }
+
+    #[test]
+    fn permanent_remove_deletes_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_references(dir.path());
+
+        let trash = ProjectStructure::remove_dataset(&project, "radar", true).unwrap();
+
+        assert!(trash.is_none());
+        assert!(!project.join("radar").exists());
+        let trash_dir = project.join(TRASH_DIR);
+        assert!(!trash_dir.exists() || fs::read_dir(&trash_dir).unwrap().next().is_none());
+        assert_eq!(pproj_names(&project), vec!["ais"]);
+    }
+
+    #[test]
+    fn failed_remove_restores_files_and_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_references(dir.path());
+        let contents = reference_contents(&project);
+        let events = EventStore::new(&project).file_path();
+        fs::write(&events, "{").unwrap();
+
+        assert!(ProjectStructure::remove_dataset(&project, "radar", false).is_err());
+
+        assert_eq!(reference_contents(&project), contents);
+        assert_eq!(pproj_names(&project), vec!["radar", "ais"]);
+        assert_eq!(
+            fs::read(project.join("radar").join("marker")).unwrap(),
+            b"radar"
+        );
+        assert!(project
+            .join(RESULT_DIR)
+            .join("radar")
+            .join("marker")
+            .is_file());
+        assert!(project.join(STATUS_DIR).join("radar").is_dir());
+        // 回收目录中不留下数据集
+        let trash_dir = project.join(TRASH_DIR);
+        if trash_dir.exists() {
+            for entry in fs::read_dir(&trash_dir).unwrap() {
+                assert!(!entry.unwrap().path().join("radar").exists());
+            }
+        }
     }
 }
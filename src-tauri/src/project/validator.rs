@@ -1,11 +1,169 @@
 //! 工程验证器
+//!
+//! 除名称、路径等基本校验外，可对整个工程生成分级的检查报告，涵盖工程文件与数据集目录是否一致、
+//! 网络配置、数据文件命名、文件时间范围、索引状态以及数据集之间的目的地址冲突
 
-use crate::types::{PlaybackError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::dataset::format::read_file_header;
+use crate::dataset::index::{data_files, DatasetIndex, PIDX_FILE_NAME};
+use crate::dataset::info::{file_index_entry, file_time_range};
+use crate::dataset::naming::parse_data_file_name;
+use crate::project::pproj::{find_pproj_file, PprojReader};
+use crate::types::{DatasetConfig, PlaybackError, Result};
+
+/// 名称最大长度（字符数）
+const MAX_NAME_LENGTH: usize = 50;
+
+/// 名称中不允许出现的字符
+const INVALID_NAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// 问题级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// 工程或数据无法正常使用
+    Error,
+    /// 可以使用但结果可能不符合预期
+    Warning,
+    /// 提示信息
+    Info,
+}
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationCode {
+    /// 工程目录中没有工程文件
+    MissingPproj,
+    /// 工程文件无法解析
+    InvalidPproj,
+    /// 工程文件中登记的数据集目录不存在
+    MissingDataset,
+    /// 工程文件中重复登记的数据集
+    DuplicateDataset,
+    /// 工程目录中未登记的数据集目录
+    UnregisteredDataset,
+    /// 网络配置无效
+    InvalidNetworkConfig,
+    /// 多个数据集发送到相同的目的地址和端口
+    DestinationConflict,
+    /// 数据集中没有数据文件
+    EmptyDataset,
+    /// 数据文件名不符合命名规范
+    InvalidFileName,
+    /// 数据文件名中的时间与首个数据包不一致
+    FileNameMismatch,
+    /// 数据文件无法读取或文件头无效
+    UnreadableFile,
+    /// 数据文件中没有数据包
+    EmptyFile,
+    /// 按文件名顺序，文件起始时间早于前一个文件
+    OutOfOrderFiles,
+    /// 相邻文件的时间范围重叠
+    OverlappingFiles,
+    /// 数据集没有索引文件
+    MissingIndex,
+    /// 索引文件无法解析或与数据文件不一致
+    StaleIndex,
+}
+
+/// 检查发现的问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub code: ValidationCode,
+    pub dataset: Option<String>,
+    pub file: Option<String>,
+    pub message: String,
+}
+
+/// 工程检查报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub project_path: String,
+    /// 按级别排序的问题列表
+    pub issues: Vec<ValidationIssue>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+    pub validated_time: String,
+}
+
+impl ValidationReport {
+    /// 没有错误级别的问题
+    pub fn is_valid(&self) -> bool {
+        self.errors == 0
+    }
+
+    fn add(
+        &mut self,
+        severity: Severity,
+        code: ValidationCode,
+        dataset: Option<&str>,
+        file: Option<&str>,
+        message: String,
+    ) {
+        match severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+            Severity::Info => self.infos += 1,
+        }
+        self.issues.push(ValidationIssue {
+            severity,
+            code,
+            dataset: dataset.map(str::to_string),
+            file: file.map(str::to_string),
+            message,
+        });
+    }
+}
 
 /// 验证工程结构
 pub struct ProjectValidator;
 
 impl ProjectValidator {
+    /// 验证工程或数据集名称
+    ///
+    /// 名称直接用作目录名，需在各平台上合法，且不能以 `.` 开头
+    /// （与 `.result`、`.status` 等工程内部目录区分）
+    pub fn validate_name(name: &str) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(PlaybackError::InvalidName("名称不能为空".to_string()));
+        }
+
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(PlaybackError::InvalidName(format!(
+                "名称长度不能超过 {} 个字符: {}",
+                MAX_NAME_LENGTH, name
+            )));
+        }
+
+        if name
+            .chars()
+            .any(|c| INVALID_NAME_CHARS.contains(&c) || c.is_control())
+        {
+            return Err(PlaybackError::InvalidName(format!(
+                "名称包含非法字符: {}",
+                name
+            )));
+        }
+
+        if name.starts_with('.') || name.ends_with('.') || name.ends_with(' ') {
+            return Err(PlaybackError::InvalidName(format!(
+                "名称不能以点号开头或以点号、空格结尾: {}",
+                name
+            )));
+        }
+
+        Ok(())
+    }
+
     /// 验证工程路径是否有效
     pub fn validate_project_path(project_path: &str) -> Result<()> {
         let path = std::path::Path::new(project_path);
@@ -63,4 +221,612 @@ impl ProjectValidator {
 
         Ok(pcap_count)
     }
+
+    /// 检查整个工程并生成报告，工程路径无效时返回错误
+    pub fn validate_project<P: AsRef<Path>>(project_path: P) -> Result<ValidationReport> {
+        let project_path = project_path.as_ref();
+        Self::validate_project_path(&project_path.to_string_lossy())?;
+
+        let mut report = ValidationReport {
+            project_path: project_path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        let pproj = Self::check_pproj(project_path, &mut report);
+        let registered = pproj.as_deref().unwrap_or_default();
+        let mut datasets: Vec<(String, PathBuf)> = Vec::new();
+        for dataset in registered {
+            let path = PathBuf::from(&dataset.path);
+            if datasets
+                .iter()
+                .any(|(name, p)| name == &dataset.name || same_path(p, &path))
+            {
+                continue;
+            }
+            if path.is_dir() {
+                datasets.push((dataset.name.clone(), path));
+            } else {
+                report.add(
+                    Severity::Error,
+                    ValidationCode::MissingDataset,
+                    Some(&dataset.name),
+                    None,
+                    format!("数据集目录不存在: {}", dataset.path),
+                );
+            }
+        }
+
+        for dir in dataset_dirs(project_path)? {
+            if registered
+                .iter()
+                .any(|d| same_path(Path::new(&d.path), &dir))
+            {
+                continue;
+            }
+            let name = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            // 工程文件缺失或无法解析时已单独报告，不再逐个提示未登记
+            if pproj.is_some() {
+                report.add(
+                    Severity::Warning,
+                    ValidationCode::UnregisteredDataset,
+                    Some(&name),
+                    None,
+                    format!("数据集目录未在工程文件中登记: {:?}", dir),
+                );
+            }
+            datasets.push((name, dir));
+        }
+
+        Self::check_destinations(registered, &mut report);
+        for (name, path) in &datasets {
+            Self::check_dataset_files(name, path, &mut report);
+        }
+
+        report.issues.sort_by_key(|issue| issue.severity);
+        report.validated_time = chrono::Utc::now().to_rfc3339();
+        Ok(report)
+    }
+
+    /// 检查工程文件及其中的数据集登记，返回登记的数据集，工程文件缺失或无法解析时返回 `None`
+    fn check_pproj(
+        project_path: &Path,
+        report: &mut ValidationReport,
+    ) -> Option<Vec<DatasetConfig>> {
+        let Some(pproj_path) = find_pproj_file(project_path) else {
+            report.add(
+                Severity::Error,
+                ValidationCode::MissingPproj,
+                None,
+                None,
+                "工程目录中没有工程文件".to_string(),
+            );
+            return None;
+        };
+
+        let config = match PprojReader::read(&pproj_path) {
+            Ok(config) => config,
+            Err(e) => {
+                report.add(
+                    Severity::Error,
+                    ValidationCode::InvalidPproj,
+                    None,
+                    Some(&pproj_path.to_string_lossy()),
+                    e.to_string(),
+                );
+                return None;
+            }
+        };
+
+        for (i, dataset) in config.datasets.iter().enumerate() {
+            let duplicate = config.datasets[..i].iter().find(|d| {
+                d.name == dataset.name || same_path(Path::new(&d.path), Path::new(&dataset.path))
+            });
+            if let Some(first) = duplicate {
+                report.add(
+                    Severity::Error,
+                    ValidationCode::DuplicateDataset,
+                    Some(&dataset.name),
+                    None,
+                    format!(
+                        "数据集与 '{}' 重复登记（名称或目录相同）: {}",
+                        first.name, dataset.path
+                    ),
+                );
+            }
+
+            if let Err(e) = dataset.network_config.validate() {
+                report.add(
+                    Severity::Error,
+                    ValidationCode::InvalidNetworkConfig,
+                    Some(&dataset.name),
+                    None,
+                    e.to_string(),
+                );
+            }
+        }
+
+        Some(config.datasets)
+    }
+
+    /// 检查不同数据集是否发送到相同的目的地址和端口
+    fn check_destinations(datasets: &[DatasetConfig], report: &mut ValidationReport) {
+        let mut destinations: HashMap<(IpAddr, u16), Vec<&str>> = HashMap::new();
+        for dataset in datasets {
+            let network = &dataset.network_config;
+            if let Ok(ip) = network.ip_address.parse::<IpAddr>() {
+                let names = destinations.entry((ip, network.port)).or_default();
+                if !names.contains(&dataset.name.as_str()) {
+                    names.push(&dataset.name);
+                }
+            }
+        }
+
+        let mut conflicts: Vec<_> = destinations
+            .into_iter()
+            .filter(|(_, names)| names.len() > 1)
+            .collect();
+        conflicts.sort();
+        for ((ip, port), names) in conflicts {
+            for name in &names {
+                report.add(
+                    Severity::Warning,
+                    ValidationCode::DestinationConflict,
+                    Some(name),
+                    None,
+                    format!(
+                        "数据集 {} 的目的地址相同: {}:{}",
+                        names.join("、"),
+                        ip,
+                        port
+                    ),
+                );
+            }
+        }
+    }
+
+    /// 检查数据集中的数据文件和索引
+    fn check_dataset_files(name: &str, dataset_dir: &Path, report: &mut ValidationReport) {
+        let files = match data_files(dataset_dir) {
+            Ok(files) => files,
+            Err(e) => {
+                report.add(
+                    Severity::Error,
+                    ValidationCode::MissingDataset,
+                    Some(name),
+                    None,
+                    format!("无法读取数据集目录: {}", e),
+                );
+                return;
+            }
+        };
+        if files.is_empty() {
+            report.add(
+                Severity::Warning,
+                ValidationCode::EmptyDataset,
+                Some(name),
+                None,
+                "数据集中没有数据文件".to_string(),
+            );
+            return;
+        }
+
+        let index = Self::check_index(name, dataset_dir, &files, report);
+
+        let mut previous: Option<(String, u64, u64)> = None;
+        for path in &files {
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let file = Some(file_name.as_str());
+
+            let named_time = parse_data_file_name(&file_name);
+            if named_time.is_none() {
+                report.add(
+                    Severity::Warning,
+                    ValidationCode::InvalidFileName,
+                    Some(name),
+                    file,
+                    "文件名不符合 data_yyMMdd_HHmmss_fffffff.pcap 命名规范".to_string(),
+                );
+            }
+
+            let (start, end) = match file_range(path, &file_name, index.as_ref()) {
+                Ok(Some(range)) => range,
+                Ok(None) => {
+                    report.add(
+                        Severity::Info,
+                        ValidationCode::EmptyFile,
+                        Some(name),
+                        file,
+                        "数据文件中没有数据包".to_string(),
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    report.add(
+                        Severity::Error,
+                        ValidationCode::UnreadableFile,
+                        Some(name),
+                        file,
+                        e.to_string(),
+                    );
+                    continue;
+                }
+            };
+
+            // 文件名中的时间精度为100纳秒
+            if let Some(named) = named_time.filter(|t| *t != start / 100 * 100) {
+                report.add(
+                    Severity::Info,
+                    ValidationCode::FileNameMismatch,
+                    Some(name),
+                    file,
+                    format!("文件名时间 {} 与首个数据包时间 {} 不一致", named, start),
+                );
+            }
+
+            if let Some((previous_name, previous_start, previous_end)) = &previous {
+                if start < *previous_start {
+                    report.add(
+                        Severity::Warning,
+                        ValidationCode::OutOfOrderFiles,
+                        Some(name),
+                        file,
+                        format!("起始时间早于前一个文件 {}", previous_name),
+                    );
+                } else if start < *previous_end {
+                    report.add(
+                        Severity::Warning,
+                        ValidationCode::OverlappingFiles,
+                        Some(name),
+                        file,
+                        format!(
+                            "时间范围与前一个文件 {} 重叠 {} 纳秒",
+                            previous_name,
+                            previous_end - start
+                        ),
+                    );
+                }
+            }
+            previous = Some((file_name, start, end));
+        }
+    }
+
+    /// 检查索引文件是否存在且与数据文件一致，一致时返回索引
+    fn check_index(
+        name: &str,
+        dataset_dir: &Path,
+        files: &[PathBuf],
+        report: &mut ValidationReport,
+    ) -> Option<DatasetIndex> {
+        let index_path = dataset_dir.join(PIDX_FILE_NAME);
+        if !index_path.is_file() {
+            report.add(
+                Severity::Info,
+                ValidationCode::MissingIndex,
+                Some(name),
+                None,
+                "数据集没有索引文件".to_string(),
+            );
+            return None;
+        }
+
+        let index = match DatasetIndex::read(&index_path) {
+            Ok(index) => index,
+            Err(e) => {
+                report.add(
+                    Severity::Warning,
+                    ValidationCode::StaleIndex,
+                    Some(name),
+                    None,
+                    format!("索引文件无法解析: {}", e),
+                );
+                return None;
+            }
+        };
+
+        let index_time = modified(&index_path);
+        let mut stale: Vec<String> = Vec::new();
+        for path in files {
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            let changed = modified(path) > index_time;
+            if changed || file_index_entry(&index, &file_name, size).is_none() {
+                stale.push(file_name);
+            }
+        }
+        let removed = index
+            .files
+            .iter()
+            .filter(|f| !files.iter().any(|p| p.ends_with(&f.name)))
+            .count();
+
+        if stale.is_empty() && removed == 0 {
+            return Some(index);
+        }
+        report.add(
+            Severity::Warning,
+            ValidationCode::StaleIndex,
+            Some(name),
+            None,
+            format!(
+                "索引与数据文件不一致: {} 个文件未索引或已变化，{} 个已索引文件不存在",
+                stale.len(),
+                removed
+            ),
+        );
+        None
+    }
+}
+
+/// 工程目录下的数据集目录（跳过隐藏目录）
+fn dataset_dirs(project_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(project_path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_dir()
+                && !path
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// 数据文件的时间范围，索引一致时直接取自索引
+fn file_range(
+    path: &Path,
+    file_name: &str,
+    index: Option<&DatasetIndex>,
+) -> Result<Option<(u64, u64)>> {
+    let size = fs::metadata(path)?.len();
+    if let Some(entry) = index.and_then(|index| file_index_entry(index, file_name, size)) {
+        return Ok((entry.packet_count > 0).then_some((entry.start_timestamp, entry.end_timestamp)));
+    }
+
+    read_file_header(path)?;
+    file_time_range(path)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::DataFileWriter;
+    use crate::dataset::naming::data_file_name;
+    use crate::project::pproj::PprojWriter;
+    use crate::project::structure::ProjectStructure;
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+    const SECOND: u64 = 1_000_000_000;
+
+    fn create_project(parent: &Path, names: &[&str]) -> PathBuf {
+        let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        ProjectStructure::create(parent, "proj", None, &names)
+            .unwrap()
+            .root_path
+    }
+
+    fn write_file(dir: &Path, file_name: &str, timestamps: &[u64]) {
+        fs::create_dir_all(dir).unwrap();
+        let mut writer = DataFileWriter::create(dir.join(file_name)).unwrap();
+        for timestamp in timestamps {
+            writer.write_packet(*timestamp, &[1, 2, 3]).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn write_data_file(dir: &Path, timestamps: &[u64]) -> String {
+        let file_name = data_file_name(timestamps[0]);
+        write_file(dir, &file_name, timestamps);
+        file_name
+    }
+
+    fn has_issue(
+        report: &ValidationReport,
+        code: ValidationCode,
+        dataset: Option<&str>,
+        file: Option<&str>,
+    ) -> bool {
+        report
+            .issues
+            .iter()
+            .any(|i| i.code == code && i.dataset.as_deref() == dataset && i.file.as_deref() == file)
+    }
+
+    #[test]
+    fn validate_name_rejects_invalid_names() {
+        assert!(ProjectValidator::validate_name("雷达_01").is_ok());
+        for name in [
+            "",
+            " ",
+            "a/b",
+            "a:b",
+            ".status",
+            "name.",
+            "name ",
+            &"长".repeat(51),
+        ] {
+            assert!(
+                matches!(
+                    ProjectValidator::validate_name(name),
+                    Err(PlaybackError::InvalidName(_))
+                ),
+                "{:?}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn consistent_project_has_no_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path(), &["radar"]);
+        let radar = project.join("radar");
+        write_data_file(&radar, &[BASE_TIMESTAMP, BASE_TIMESTAMP + SECOND]);
+        write_data_file(&radar, &[BASE_TIMESTAMP + 2 * SECOND]);
+        DatasetIndex::rebuild(&radar).unwrap();
+
+        let report = ProjectValidator::validate_project(&project).unwrap();
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_pproj() {
+        let dir = tempfile::tempdir().unwrap();
+        write_data_file(&dir.path().join("radar"), &[BASE_TIMESTAMP]);
+
+        let report = ProjectValidator::validate_project(dir.path()).unwrap();
+        assert!(has_issue(&report, ValidationCode::MissingPproj, None, None));
+        // 工程文件缺失时不逐个提示未登记，但仍检查数据集目录
+        assert!(!report
+            .issues
+            .iter()
+            .any(|i| i.code == ValidationCode::UnregisteredDataset));
+        assert!(has_issue(
+            &report,
+            ValidationCode::MissingIndex,
+            Some("radar"),
+            None
+        ));
+        assert!(!report.is_valid());
+
+        let pproj = dir.path().join("proj.pproj");
+        fs::write(&pproj, "不是工程文件").unwrap();
+        let report = ProjectValidator::validate_project(dir.path()).unwrap();
+        assert!(has_issue(
+            &report,
+            ValidationCode::InvalidPproj,
+            None,
+            Some(&pproj.to_string_lossy())
+        ));
+
+        assert!(matches!(
+            ProjectValidator::validate_project(dir.path().join("missing")),
+            Err(PlaybackError::ProjectError(_))
+        ));
+    }
+
+    #[test]
+    fn reports_pproj_and_dataset_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = create_project(dir.path(), &["radar", "ais", "empty", "gone"]);
+        fs::remove_dir_all(project.join("gone")).unwrap();
+
+        // 重复登记 radar，ais 的端口无效，其余数据集使用相同的默认目的地址
+        let pproj_path = find_pproj_file(&project).unwrap();
+        let mut config = PprojReader::read(&pproj_path).unwrap();
+        let duplicate = config.datasets[0].clone();
+        config.datasets.push(duplicate);
+        config.datasets[1].network_config.port = 0;
+        PprojWriter::write(&pproj_path, &config).unwrap();
+
+        // radar: 文件时间重叠、文件名与首个数据包时间不一致，索引生成后又新增了文件
+        let radar = project.join("radar");
+        write_data_file(&radar, &[BASE_TIMESTAMP, BASE_TIMESTAMP + 2 * SECOND]);
+        let overlapping = write_data_file(&radar, &[BASE_TIMESTAMP + SECOND]);
+        let mismatched = data_file_name(BASE_TIMESTAMP + 3 * SECOND);
+        write_file(&radar, &mismatched, &[BASE_TIMESTAMP + 4 * SECOND]);
+        DatasetIndex::rebuild(&radar).unwrap();
+        write_data_file(&radar, &[BASE_TIMESTAMP + 5 * SECOND]);
+
+        // extra: 未登记，包含无法读取、命名不规范、顺序颠倒和空的数据文件
+        let extra = project.join("extra");
+        fs::create_dir(&extra).unwrap();
+        fs::write(extra.join("broken.pcap"), b"junk").unwrap();
+        write_file(&extra, "capture.pcap", &[BASE_TIMESTAMP + 10 * SECOND]);
+        let out_of_order = write_data_file(&extra, &[BASE_TIMESTAMP]);
+        let empty_file = data_file_name(BASE_TIMESTAMP + 20 * SECOND);
+        write_file(&extra, &empty_file, &[]);
+
+        let report = ProjectValidator::validate_project(&project).unwrap();
+        let expected = [
+            (ValidationCode::MissingDataset, Some("gone"), None),
+            (ValidationCode::DuplicateDataset, Some("radar"), None),
+            (ValidationCode::InvalidNetworkConfig, Some("ais"), None),
+            (ValidationCode::DestinationConflict, Some("radar"), None),
+            (ValidationCode::DestinationConflict, Some("empty"), None),
+            (ValidationCode::UnregisteredDataset, Some("extra"), None),
+            (ValidationCode::EmptyDataset, Some("empty"), None),
+            (ValidationCode::EmptyDataset, Some("ais"), None),
+            (ValidationCode::StaleIndex, Some("radar"), None),
+            (
+                ValidationCode::OverlappingFiles,
+                Some("radar"),
+                Some(overlapping.as_str()),
+            ),
+            (
+                ValidationCode::FileNameMismatch,
+                Some("radar"),
+                Some(mismatched.as_str()),
+            ),
+            (ValidationCode::MissingIndex, Some("extra"), None),
+            (
+                ValidationCode::UnreadableFile,
+                Some("extra"),
+                Some("broken.pcap"),
+            ),
+            (
+                ValidationCode::InvalidFileName,
+                Some("extra"),
+                Some("capture.pcap"),
+            ),
+            (
+                ValidationCode::OutOfOrderFiles,
+                Some("extra"),
+                Some(out_of_order.as_str()),
+            ),
+            (
+                ValidationCode::EmptyFile,
+                Some("extra"),
+                Some(empty_file.as_str()),
+            ),
+        ];
+        for (code, dataset, file) in expected {
+            assert!(
+                has_issue(&report, code, dataset, file),
+                "缺少 {:?} {:?} {:?}: {:?}",
+                code,
+                dataset,
+                file,
+                report.issues
+            );
+        }
+        // ais 端口无效，不与其它数据集冲突
+        assert!(!has_issue(
+            &report,
+            ValidationCode::DestinationConflict,
+            Some("ais"),
+            None
+        ));
+
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.errors + report.warnings + report.infos,
+            report.issues.len()
+        );
+        assert!(report
+            .issues
+            .windows(2)
+            .all(|w| w[0].severity <= w[1].severity));
+    }
 }
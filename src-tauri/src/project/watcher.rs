@@ -0,0 +1,674 @@
+//! 工程目录监视
+//!
+//! 录制程序会在回看期间持续写入数据集，这里定时轮询工程目录，按文件大小和修改时间识别
+//! 新建、删除、增长和被改写的数据文件。增长的文件从上次统计到的位置继续扫描包头，
+//! 文件在一个轮询周期内不再变化后才增量更新数据集索引，避免对正在写入的文件反复计算哈希。
+//! 持续增长的文件用轮询可以按固定频率合并变化，不依赖各平台的文件系统通知。
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::dataset::index::{data_files, DatasetIndex};
+use crate::dataset::info::{
+    dataset_info, file_index_entry, file_metadata_info, read_dataset_index, summarize_from,
+    PacketSummary,
+};
+use crate::types::{DatasetInfo, FileInfo, Result};
+
+/// 工程目录变化事件
+pub const PROJECT_CHANGED_EVENT: &str = "project://changed";
+
+/// 默认轮询间隔
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 工程目录中的一项变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProjectChange {
+    DatasetAdded {
+        dataset: String,
+    },
+    DatasetRemoved {
+        dataset: String,
+    },
+    FileCreated {
+        dataset: String,
+        file_name: String,
+        file_size: u64,
+    },
+    /// 文件在原有内容之后追加了数据
+    FileGrown {
+        dataset: String,
+        file_name: String,
+        previous_size: u64,
+        file_size: u64,
+    },
+    /// 文件变小或大小不变但内容被改写
+    FileModified {
+        dataset: String,
+        file_name: String,
+        file_size: u64,
+    },
+    FileRemoved {
+        dataset: String,
+        file_name: String,
+    },
+    /// 数据集索引已按停止变化的文件增量更新
+    IndexUpdated {
+        dataset: String,
+    },
+}
+
+/// 一次轮询发现的变化（`project://changed` 事件负载）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectChangeSet {
+    pub project_path: String,
+    pub changes: Vec<ProjectChange>,
+    /// 有变化的数据集的最新信息
+    pub datasets: Vec<DatasetInfo>,
+    /// 目录已删除的数据集
+    pub removed_datasets: Vec<String>,
+}
+
+/// 跟踪的数据文件
+#[derive(Debug, Clone)]
+struct TrackedFile {
+    size: u64,
+    modified: Option<SystemTime>,
+    /// 已统计的数据包信息，首次需要时才扫描
+    summary: Option<PacketSummary>,
+    /// 最后一个已统计的完整数据包之后的偏移
+    scanned_offset: u64,
+    /// 扫描遇到无效包头，文件改写前不再继续扫描
+    failed: bool,
+    /// 上次轮询时有变化，索引尚未更新
+    unsettled: bool,
+}
+
+impl TrackedFile {
+    fn new(size: u64, modified: Option<SystemTime>) -> Self {
+        Self {
+            size,
+            modified,
+            summary: None,
+            scanned_offset: 0,
+            failed: false,
+            unsettled: true,
+        }
+    }
+}
+
+/// 跟踪的数据集
+#[derive(Debug, Clone, Default)]
+struct TrackedDataset {
+    path: PathBuf,
+    files: BTreeMap<String, TrackedFile>,
+}
+
+/// 工程目录状态跟踪
+///
+/// 只跟踪工程目录下的数据集目录（跳过隐藏目录），工程目录外的数据集不在监视范围内
+#[derive(Debug)]
+pub struct ProjectTracker {
+    project_path: PathBuf,
+    datasets: BTreeMap<String, TrackedDataset>,
+}
+
+impl ProjectTracker {
+    /// 记录工程目录的当前状态（只读取文件大小和修改时间）
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Result<Self> {
+        let mut tracker = Self {
+            project_path: project_path.as_ref().to_path_buf(),
+            datasets: BTreeMap::new(),
+        };
+
+        for (name, path) in dataset_dirs(&tracker.project_path)? {
+            let files = list_files(&path)
+                .into_iter()
+                .map(|(file_name, (size, modified))| {
+                    let mut file = TrackedFile::new(size, modified);
+                    file.unsettled = false;
+                    (file_name, file)
+                })
+                .collect();
+            tracker
+                .datasets
+                .insert(name, TrackedDataset { path, files });
+        }
+        Ok(tracker)
+    }
+
+    /// 比较工程目录与上次记录的状态，没有变化时返回 `None`
+    pub fn poll(&mut self) -> Result<Option<ProjectChangeSet>> {
+        let mut change_set = ProjectChangeSet {
+            project_path: self.project_path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let current = dataset_dirs(&self.project_path)?;
+
+        let removed: Vec<String> = self
+            .datasets
+            .keys()
+            .filter(|name| !current.contains_key(*name))
+            .cloned()
+            .collect();
+        for dataset in removed {
+            self.datasets.remove(&dataset);
+            change_set.removed_datasets.push(dataset.clone());
+            change_set
+                .changes
+                .push(ProjectChange::DatasetRemoved { dataset });
+        }
+
+        for (name, path) in current {
+            if !self.datasets.contains_key(&name) {
+                change_set.changes.push(ProjectChange::DatasetAdded {
+                    dataset: name.clone(),
+                });
+                self.datasets.insert(
+                    name.clone(),
+                    TrackedDataset {
+                        path,
+                        files: BTreeMap::new(),
+                    },
+                );
+            }
+            let Some(dataset) = self.datasets.get_mut(&name) else {
+                continue;
+            };
+
+            let count = change_set.changes.len();
+            let settled = diff_files(&name, dataset, &mut change_set.changes);
+            if change_set.changes.len() > count {
+                match refresh_dataset(&name, dataset) {
+                    Ok(info) => change_set.datasets.push(info),
+                    Err(e) => warn!("更新数据集信息失败 {}: {}", name, e),
+                }
+            }
+
+            if settled {
+                match DatasetIndex::update(&dataset.path) {
+                    Ok(true) => change_set
+                        .changes
+                        .push(ProjectChange::IndexUpdated { dataset: name }),
+                    Ok(false) => {}
+                    Err(e) => warn!("更新数据集索引失败 {}: {}", name, e),
+                }
+            }
+        }
+
+        Ok((!change_set.changes.is_empty()).then_some(change_set))
+    }
+}
+
+/// 比较数据集目录中的文件，变化记入 `changes`，返回是否有文件停止变化
+fn diff_files(name: &str, dataset: &mut TrackedDataset, changes: &mut Vec<ProjectChange>) -> bool {
+    let listing = list_files(&dataset.path);
+
+    let removed: Vec<String> = dataset
+        .files
+        .keys()
+        .filter(|file_name| !listing.contains_key(*file_name))
+        .cloned()
+        .collect();
+    // 删除文件后同样需要从索引中移除
+    let mut settled = !removed.is_empty();
+    for file_name in removed {
+        dataset.files.remove(&file_name);
+        changes.push(ProjectChange::FileRemoved {
+            dataset: name.to_string(),
+            file_name,
+        });
+    }
+
+    for (file_name, (size, modified)) in listing {
+        let Some(file) = dataset.files.get_mut(&file_name) else {
+            changes.push(ProjectChange::FileCreated {
+                dataset: name.to_string(),
+                file_name: file_name.clone(),
+                file_size: size,
+            });
+            dataset
+                .files
+                .insert(file_name, TrackedFile::new(size, modified));
+            continue;
+        };
+
+        if file.size == size && file.modified == modified {
+            settled |= file.unsettled;
+            file.unsettled = false;
+        } else if size > file.size {
+            changes.push(ProjectChange::FileGrown {
+                dataset: name.to_string(),
+                file_name,
+                previous_size: file.size,
+                file_size: size,
+            });
+            file.size = size;
+            file.modified = modified;
+            file.unsettled = true;
+        } else {
+            changes.push(ProjectChange::FileModified {
+                dataset: name.to_string(),
+                file_name,
+                file_size: size,
+            });
+            *file = TrackedFile::new(size, modified);
+        }
+    }
+    settled
+}
+
+/// 更新数据集中各文件的统计并汇总数据集信息
+///
+/// 未统计过的文件优先使用大小一致的索引条目，增长的文件从上次的位置继续扫描
+fn refresh_dataset(name: &str, dataset: &mut TrackedDataset) -> Result<DatasetInfo> {
+    let index = read_dataset_index(&dataset.path);
+    let mut files: Vec<FileInfo> = Vec::with_capacity(dataset.files.len());
+
+    for (file_name, file) in dataset.files.iter_mut() {
+        let path = dataset.path.join(file_name);
+        if file.summary.is_none() {
+            let entry = index
+                .as_ref()
+                .and_then(|index| file_index_entry(index, file_name, file.size));
+            file.summary = Some(match entry {
+                Some(entry) => {
+                    file.scanned_offset = file.size;
+                    PacketSummary {
+                        packet_count: entry.packet_count,
+                        start_timestamp: (entry.packet_count > 0).then_some(entry.start_timestamp),
+                        end_timestamp: (entry.packet_count > 0).then_some(entry.end_timestamp),
+                    }
+                }
+                None => {
+                    file.scanned_offset = 0;
+                    PacketSummary::default()
+                }
+            });
+        }
+
+        let mut summary = file.summary.unwrap_or_default();
+        if !file.failed && file.scanned_offset < file.size {
+            match summarize_from(&path, file.scanned_offset, &mut summary) {
+                Ok(offset) => {
+                    file.scanned_offset = offset;
+                    file.summary = Some(summary);
+                }
+                Err(e) => {
+                    warn!("扫描数据文件失败 {:?}: {}", path, e);
+                    file.failed = true;
+                }
+            }
+        }
+
+        let summary = file.summary.unwrap_or_default();
+        files.push(FileInfo {
+            packet_count: summary.packet_count,
+            start_timestamp: summary.start_timestamp,
+            end_timestamp: summary.end_timestamp,
+            is_valid: !file.failed,
+            ..file_metadata_info(&path)
+        });
+    }
+
+    dataset_info(name, &dataset.path, &files, index.is_some())
+}
+
+/// 工程目录下的数据集目录（跳过隐藏目录）
+fn dataset_dirs(project_path: &Path) -> Result<BTreeMap<String, PathBuf>> {
+    Ok(fs::read_dir(project_path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            (!name.starts_with('.')).then_some((name, path))
+        })
+        .collect())
+}
+
+/// 数据集目录中数据文件的大小和修改时间，目录无法读取时为空
+fn list_files(dataset_dir: &Path) -> BTreeMap<String, (u64, Option<SystemTime>)> {
+    let files = data_files(dataset_dir).unwrap_or_else(|e| {
+        warn!("读取数据集目录失败 {:?}: {}", dataset_dir, e);
+        Vec::new()
+    });
+
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = fs::metadata(&path).ok()?;
+            let name = path.file_name()?.to_string_lossy().to_string();
+            Some((name, (metadata.len(), metadata.modified().ok())))
+        })
+        .collect()
+}
+
+/// 工程目录监视器，在后台线程中定时轮询，释放时停止
+#[derive(Debug)]
+pub struct ProjectWatcher {
+    project_path: PathBuf,
+    stop: Option<Sender<()>>,
+}
+
+impl ProjectWatcher {
+    /// 开始监视工程目录，每次轮询发现变化时调用 `on_change`
+    pub fn start<P, F>(project_path: P, interval: Duration, mut on_change: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: FnMut(ProjectChangeSet) + Send + 'static,
+    {
+        let project_path = project_path.as_ref().to_path_buf();
+        let mut tracker = ProjectTracker::new(&project_path)?;
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        thread::Builder::new()
+            .name("project-watcher".to_string())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let changes = match tracker.poll() {
+                        Ok(changes) => changes,
+                        Err(e) => {
+                            warn!("检查工程目录失败: {}", e);
+                            continue;
+                        }
+                    };
+                    // 轮询期间已停止监视时丢弃结果
+                    if let Err(TryRecvError::Disconnected) = stopped.try_recv() {
+                        break;
+                    }
+                    if let Some(changes) = changes {
+                        debug!("工程目录变化: {} 项", changes.changes.len());
+                        on_change(changes);
+                    }
+                }
+                debug!("停止监视工程目录: {:?}", tracker.project_path);
+            })?;
+
+        info!("开始监视工程目录: {:?}", project_path);
+        Ok(Self {
+            project_path,
+            stop: Some(stop),
+        })
+    }
+
+    /// 监视的工程目录
+    pub fn project_path(&self) -> &Path {
+        &self.project_path
+    }
+}
+
+impl Drop for ProjectWatcher {
+    /// 通知轮询线程退出，不等待正在进行的轮询完成
+    fn drop(&mut self) {
+        self.stop.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::format::{
+        DataFileWriter, PacketHeader, FILE_HEADER_SIZE, PACKET_HEADER_SIZE,
+    };
+    use crate::dataset::naming::data_file_name;
+    use std::fs::File;
+    use std::io::{Seek, SeekFrom, Write};
+
+    const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    fn write_data_file(dir: &Path, packets: &[(u64, &[u8])]) -> PathBuf {
+        fs::create_dir_all(dir).unwrap();
+        let mut writer = DataFileWriter::create(dir.join(data_file_name(packets[0].0))).unwrap();
+        for (timestamp, data) in packets {
+            writer.write_packet(*timestamp, data).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    /// 在文件末尾追加数据包的前 `len` 个字节，模拟录制程序写入中途
+    fn append_partial(path: &Path, timestamp: u64, data: &[u8], len: usize) {
+        let mut bytes = Vec::new();
+        PacketHeader::new(timestamp, data)
+            .write_to(&mut bytes)
+            .unwrap();
+        bytes.extend_from_slice(data);
+        let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&bytes[..len]).unwrap();
+    }
+
+    fn append_packet(path: &Path, timestamp: u64, data: &[u8]) {
+        append_partial(
+            path,
+            timestamp,
+            data,
+            PACKET_HEADER_SIZE as usize + data.len(),
+        );
+    }
+
+    fn file_name(path: &Path) -> String {
+        path.file_name().unwrap().to_string_lossy().to_string()
+    }
+
+    fn size(path: &Path) -> u64 {
+        fs::metadata(path).unwrap().len()
+    }
+
+    /// 含一个单数据包文件的 `radar` 数据集
+    fn project() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_data_file(&dir.path().join("radar"), &[(BASE_TIMESTAMP, b"a")]);
+        (dir, path)
+    }
+
+    #[test]
+    fn unchanged_project_reports_nothing() {
+        let (dir, _) = project();
+        fs::create_dir_all(dir.path().join(".result").join("radar")).unwrap();
+        let mut tracker = ProjectTracker::new(dir.path()).unwrap();
+
+        assert!(tracker.poll().unwrap().is_none());
+        // 隐藏目录不是数据集
+        fs::create_dir_all(dir.path().join(".trash").join("radar")).unwrap();
+        assert!(tracker.poll().unwrap().is_none());
+    }
+
+    #[test]
+    fn reports_created_grown_and_removed_files() {
+        let (dir, first) = project();
+        let mut tracker = ProjectTracker::new(dir.path()).unwrap();
+
+        let previous_size = size(&first);
+        append_packet(&first, BASE_TIMESTAMP + 1_000, b"b");
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(
+            change_set.changes,
+            vec![ProjectChange::FileGrown {
+                dataset: "radar".to_string(),
+                file_name: file_name(&first),
+                previous_size,
+                file_size: size(&first),
+            }]
+        );
+        assert_eq!(change_set.datasets[0].total_packets, 2);
+        assert_eq!(
+            change_set.datasets[0].end_timestamp,
+            Some(BASE_TIMESTAMP + 1_000)
+        );
+
+        let second = write_data_file(
+            &dir.path().join("radar"),
+            &[(BASE_TIMESTAMP + 1_000_000, b"c")],
+        );
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(
+            change_set.changes,
+            vec![ProjectChange::FileCreated {
+                dataset: "radar".to_string(),
+                file_name: file_name(&second),
+                file_size: size(&second),
+            }]
+        );
+        assert_eq!(change_set.datasets[0].total_packets, 3);
+        assert_eq!(change_set.datasets[0].file_count, 2);
+
+        fs::remove_file(&first).unwrap();
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(
+            change_set.changes,
+            vec![ProjectChange::FileRemoved {
+                dataset: "radar".to_string(),
+                file_name: file_name(&first),
+            }]
+        );
+        assert_eq!(change_set.datasets[0].total_packets, 1);
+        assert_eq!(
+            change_set.datasets[0].start_timestamp,
+            Some(BASE_TIMESTAMP + 1_000_000)
+        );
+    }
+
+    #[test]
+    fn reports_modified_files() {
+        let (dir, path) = project();
+        append_packet(&path, BASE_TIMESTAMP + 1_000, b"b");
+        let mut tracker = ProjectTracker::new(dir.path()).unwrap();
+
+        // 文件变小
+        let len = size(&path) - 1;
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len)
+            .unwrap();
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(
+            change_set.changes,
+            vec![ProjectChange::FileModified {
+                dataset: "radar".to_string(),
+                file_name: file_name(&path),
+                file_size: len,
+            }]
+        );
+        assert_eq!(change_set.datasets[0].total_packets, 1);
+
+        // 大小不变但内容被改写
+        let mut file = File::options().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(FILE_HEADER_SIZE + PACKET_HEADER_SIZE))
+            .unwrap();
+        file.write_all(b"z").unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert!(matches!(
+            change_set.changes.as_slice(),
+            [ProjectChange::FileModified { file_size, .. }] if *file_size == len
+        ));
+    }
+
+    #[test]
+    fn reports_added_and_removed_datasets() {
+        let (dir, _) = project();
+        let mut tracker = ProjectTracker::new(dir.path()).unwrap();
+
+        write_data_file(&dir.path().join("ais"), &[(BASE_TIMESTAMP, b"a")]);
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(
+            change_set.changes[0],
+            ProjectChange::DatasetAdded {
+                dataset: "ais".to_string()
+            }
+        );
+        assert!(matches!(
+            &change_set.changes[1],
+            ProjectChange::FileCreated { dataset, .. } if dataset == "ais"
+        ));
+        assert_eq!(change_set.datasets[0].name, "ais");
+
+        fs::remove_dir_all(dir.path().join("radar")).unwrap();
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(
+            change_set.changes,
+            vec![ProjectChange::DatasetRemoved {
+                dataset: "radar".to_string()
+            }]
+        );
+        assert_eq!(change_set.removed_datasets, vec!["radar"]);
+    }
+
+    #[test]
+    fn settled_files_update_index() {
+        let (dir, path) = project();
+        let dataset_dir = dir.path().join("radar");
+        let index_path = DatasetIndex::rebuild(&dataset_dir).unwrap();
+        let mut tracker = ProjectTracker::new(dir.path()).unwrap();
+
+        append_packet(&path, BASE_TIMESTAMP + 1_000, b"b");
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert!(!change_set
+            .changes
+            .iter()
+            .any(|c| matches!(c, ProjectChange::IndexUpdated { .. })));
+        assert_eq!(DatasetIndex::read(&index_path).unwrap().total_packets, 1);
+
+        // 一个轮询周期内不再变化后更新索引
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(
+            change_set.changes,
+            vec![ProjectChange::IndexUpdated {
+                dataset: "radar".to_string()
+            }]
+        );
+        assert_eq!(DatasetIndex::read(&index_path).unwrap().total_packets, 2);
+        assert!(tracker.poll().unwrap().is_none());
+
+        // 删除文件后同样更新索引
+        fs::remove_file(&path).unwrap();
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert!(change_set.changes.contains(&ProjectChange::IndexUpdated {
+            dataset: "radar".to_string()
+        }));
+        assert!(DatasetIndex::read(&index_path).unwrap().files.is_empty());
+    }
+
+    #[test]
+    fn growing_file_is_summarized_incrementally() {
+        let (dir, path) = project();
+        let mut tracker = ProjectTracker::new(dir.path()).unwrap();
+
+        // 末尾的数据包只写了一部分，不计入
+        append_partial(&path, BASE_TIMESTAMP + 1_000, b"bbbb", 18);
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(change_set.datasets[0].total_packets, 1);
+        assert_eq!(change_set.datasets[0].end_timestamp, Some(BASE_TIMESTAMP));
+
+        // 写完后从上次的位置继续统计
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"bb").unwrap();
+        drop(file);
+        append_packet(&path, BASE_TIMESTAMP + 2_000, b"c");
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(change_set.datasets[0].total_packets, 3);
+        assert_eq!(
+            change_set.datasets[0].end_timestamp,
+            Some(BASE_TIMESTAMP + 2_000)
+        );
+
+        // 已统计的部分不再重新扫描：改写首个包头后追加，统计仍在原有基础上累加
+        let mut file = File::options().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(FILE_HEADER_SIZE + 4)).unwrap();
+        file.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        drop(file);
+        append_packet(&path, BASE_TIMESTAMP + 3_000, b"d");
+        let change_set = tracker.poll().unwrap().unwrap();
+        assert_eq!(change_set.datasets[0].total_packets, 4);
+        assert_eq!(change_set.datasets[0].start_timestamp, Some(BASE_TIMESTAMP));
+    }
+}
@@ -0,0 +1,118 @@
+//! XML写入辅助
+//!
+//! serde-xml-rs 0.6 无法序列化结构体序列，工程相关XML文件统一由此输出，
+//! 格式与示例文件保持一致（两空格缩进）
+
+/// 简单的缩进XML写入器
+#[derive(Debug)]
+pub struct XmlWriter {
+    buffer: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    /// 创建写入器并输出XML声明
+    pub fn new() -> Self {
+        Self {
+            buffer: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"),
+            depth: 0,
+        }
+    }
+
+    /// 开始元素
+    pub fn open(&mut self, name: &str) -> &mut Self {
+        self.indent();
+        self.buffer.push_str(&format!("<{}>\n", name));
+        self.depth += 1;
+        self
+    }
+
+    /// 开始带属性的元素
+    pub fn open_with_attributes(&mut self, name: &str, attributes: &[(&str, String)]) -> &mut Self {
+        self.indent();
+        self.buffer
+            .push_str(&format!("<{}{}>\n", name, format_attributes(attributes)));
+        self.depth += 1;
+        self
+    }
+
+    /// 输出只有属性的空元素
+    pub fn empty_element(&mut self, name: &str, attributes: &[(&str, String)]) -> &mut Self {
+        self.indent();
+        self.buffer
+            .push_str(&format!("<{}{} />\n", name, format_attributes(attributes)));
+        self
+    }
+
+    /// 结束元素
+    pub fn close(&mut self, name: &str) -> &mut Self {
+        self.depth = self.depth.saturating_sub(1);
+        self.indent();
+        self.buffer.push_str(&format!("</{}>\n", name));
+        self
+    }
+
+    /// 输出文本元素
+    pub fn element(&mut self, name: &str, value: impl std::fmt::Display) -> &mut Self {
+        self.indent();
+        self.buffer.push_str(&format!(
+            "<{}>{}</{}>\n",
+            name,
+            escape(&value.to_string()),
+            name
+        ));
+        self
+    }
+
+    /// 输出可选文本元素（为空时省略）
+    pub fn optional_element<T: std::fmt::Display>(
+        &mut self,
+        name: &str,
+        value: Option<T>,
+    ) -> &mut Self {
+        if let Some(value) = value {
+            self.element(name, value);
+        }
+        self
+    }
+
+    /// 完成写入
+    pub fn finish(self) -> String {
+        self.buffer
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.buffer.push_str("  ");
+        }
+    }
+}
+
+impl Default for XmlWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn format_attributes(attributes: &[(&str, String)]) -> String {
+    attributes
+        .iter()
+        .map(|(key, value)| format!(" {}=\"{}\"", key, escape(value)))
+        .collect()
+}
+
+/// 转义XML文本
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
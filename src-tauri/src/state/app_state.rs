@@ -1,34 +1,219 @@
-use crate::playback::engine::PlaybackEngine;
-use crate::state::playback_state::PlaybackState;
-use crate::types::common::ProjectInfo;
-use std::sync::Arc;
+use crate::playback::engine::{PlaybackEngine, PlaybackOptions};
+use crate::project::manager::ProjectManager;
+use crate::project::validator::ProjectValidator;
+use crate::project::watcher::{ProjectChangeSet, ProjectWatcher, DEFAULT_POLL_INTERVAL};
+use crate::state::config_state::UDPConfig;
+use crate::state::task_state::{DatasetGuard, DatasetLocks, TaskRegistry};
+use crate::streaming::config_manager::ConfigManager;
+use crate::types::common::{PlaybackError, ProjectInfo, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
 
 /// 应用全局状态
-#[derive(Debug)]
+///
+/// 各字段均为共享句柄，克隆开销很小，可直接交给Tauri管理或传入控制服务；
+/// 回放控制由引擎自行同步，不需要持有全局锁。
+#[derive(Debug, Clone)]
 pub struct AppState {
-    current_project: Option<ProjectInfo>,
-    pub playback_engine: PlaybackEngine,
+    pub project_manager: Arc<RwLock<ProjectManager>>,
+    pub playback_engine: Arc<PlaybackEngine>,
+    pub tasks: TaskRegistry,
+    /// 当前工程目录的监视器
+    pub watcher: Arc<Mutex<Option<ProjectWatcher>>>,
+    /// 被修改操作占用的数据集
+    pub dataset_locks: DatasetLocks,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        let playback_state = Arc::new(tokio::sync::Mutex::new(PlaybackState::new()));
-
         Self {
-            current_project: None,
-            playback_engine: PlaybackEngine::new(playback_state),
+            project_manager: Arc::new(RwLock::new(ProjectManager::new())),
+            playback_engine: Arc::new(PlaybackEngine::new()),
+            tasks: TaskRegistry::new(),
+            watcher: Arc::new(Mutex::new(None)),
+            dataset_locks: DatasetLocks::new(),
+        }
+    }
+
+    pub async fn current_project(&self) -> Option<ProjectInfo> {
+        self.project_manager
+            .read()
+            .await
+            .get_current_project()
+            .cloned()
+    }
+
+    /// 打开工程并注册数据集的回放配置
+    pub async fn open_project(&self, path: &str) -> Result<ProjectInfo> {
+        self.open_project_with_progress(path, |_, _, _| {}).await
+    }
+
+    /// 打开工程，扫描数据文件时通过 `progress` 报告进度
+    pub async fn open_project_with_progress<F>(
+        &self,
+        path: &str,
+        progress: F,
+    ) -> Result<ProjectInfo>
+    where
+        F: Fn(u64, u64, &Path) + Send + Sync + 'static,
+    {
+        // 在锁外完成耗时的扫描，成功后再替换当前工程
+        let mut manager = ProjectManager::new();
+        let project_info = manager.open_project_with_progress(path, progress).await?;
+
+        {
+            let mut config_manager = self.playback_engine.config_manager_mut();
+            let names = manager.list_dataset_names();
+            for name in &names {
+                register_dataset(&mut config_manager, &manager, name);
+            }
+            // 已移除或重命名的数据集不再保留配置
+            config_manager.retain_datasets(&names);
+        }
+
+        *self.project_manager.write().await = manager;
+        Ok(project_info)
+    }
+
+    /// 工程目录内容变化后重新扫描当前工程，`path` 不是当前工程时忽略
+    pub async fn refresh_project(&self, path: &str) -> Result<()> {
+        if self.is_current_project(path).await {
+            self.open_project(path).await?;
+        }
+        Ok(())
+    }
+
+    /// 开始监视工程目录，替换之前的监视器；`on_change` 在监视线程中调用
+    pub fn watch_project<F>(&self, path: &str, on_change: F) -> Result<()>
+    where
+        F: FnMut(ProjectChangeSet) + Send + 'static,
+    {
+        let watcher = ProjectWatcher::start(path, DEFAULT_POLL_INTERVAL, on_change)?;
+        *self.watcher.lock().unwrap() = Some(watcher);
+        Ok(())
+    }
+
+    /// 把工程目录的变化应用到当前工程，为新增数据集注册回放配置并注销已删除的数据集；
+    /// 变化不属于当前工程时忽略
+    pub async fn apply_project_changes(&self, changes: &ProjectChangeSet) {
+        let mut manager = self.project_manager.write().await;
+        let is_current = manager
+            .get_current_project()
+            .is_some_and(|project| Path::new(&project.path) == Path::new(&changes.project_path));
+        if !is_current {
+            return;
+        }
+
+        let added = manager.apply_changes(changes);
+        let mut config_manager = self.playback_engine.config_manager_mut();
+        for name in &changes.removed_datasets {
+            config_manager.unregister_dataset(name);
         }
+        for name in &added {
+            register_dataset(&mut config_manager, &manager, name);
+        }
+    }
+
+    /// 把当前工程数据集的回放配置转到新名称，重新加载工程时沿用；`path` 不是当前工程时忽略
+    pub async fn rename_dataset_config(&self, path: &str, old_name: &str, new_name: &str) {
+        if self.is_current_project(path).await {
+            self.playback_engine
+                .config_manager_mut()
+                .rename_dataset(old_name, new_name);
+        }
+    }
+
+    /// 占用数据集直到返回的守卫释放，期间拒绝回放该数据集和对它的其他修改操作；
+    /// 数据集正在回放（播放或暂停）或已被占用时返回 [`PlaybackError::DatasetBusy`]
+    pub async fn lock_dataset(&self, path: &str, dataset_name: &str) -> Result<DatasetGuard> {
+        ProjectValidator::validate_name(dataset_name)?;
+        let busy = || PlaybackError::DatasetBusy(dataset_name.to_string());
+
+        let _gate = self.dataset_locks.gate().await;
+        if self.is_current_project(path).await {
+            let state = self.playback_engine.get_state().await;
+            let in_use = (state.is_playing() || state.is_paused())
+                && state.datasets.iter().any(|name| name == dataset_name);
+            if in_use {
+                return Err(busy());
+            }
+        }
+        self.dataset_locks
+            .try_acquire(dataset_dir(path, dataset_name))
+            .ok_or_else(busy)
+    }
+
+    /// 开始回放当前工程的数据集，`options` 为空时暂停状态下继续回放；
+    /// 数据集被修改操作占用时返回 [`PlaybackError::DatasetBusy`]
+    pub async fn start_playback(
+        &self,
+        dataset_name: String,
+        options: Option<PlaybackOptions>,
+    ) -> Result<()> {
+        let _gate = self.dataset_locks.gate().await;
+        if let Some(project) = self.current_project().await {
+            if self
+                .dataset_locks
+                .is_held(&dataset_dir(&project.path, &dataset_name))
+            {
+                return Err(PlaybackError::DatasetBusy(dataset_name));
+            }
+        }
+
+        match options {
+            Some(options) => {
+                self.playback_engine
+                    .start_with_options(vec![dataset_name], options)
+                    .await
+            }
+            None => self.playback_engine.start(dataset_name).await,
+        }
+    }
+
+    async fn is_current_project(&self, path: &str) -> bool {
+        self.current_project()
+            .await
+            .is_some_and(|project| Path::new(&project.path) == Path::new(path))
     }
 
-    pub fn current_project(&self) -> Option<ProjectInfo> {
-        self.current_project.clone()
+    /// 关闭当前工程
+    pub async fn close_project(&self) -> Result<()> {
+        self.playback_engine.stop().await?;
+        self.watcher.lock().unwrap().take();
+        self.project_manager.write().await.close_project();
+        Ok(())
     }
 
-    pub fn set_current_project(&mut self, project: Option<ProjectInfo>) {
-        self.current_project = project;
+    /// 当前工程的数据集名称列表
+    pub async fn list_datasets(&self) -> Vec<String> {
+        self.project_manager.read().await.list_dataset_names()
     }
 }
 
+/// 数据集目录，工程路径按规范形式比较，不同写法指向同一数据集时占用同一项
+fn dataset_dir(project_path: &str, dataset_name: &str) -> PathBuf {
+    fs::canonicalize(project_path)
+        .unwrap_or_else(|_| PathBuf::from(project_path))
+        .join(dataset_name)
+}
+
+/// 注册数据集的回放配置，保留已有的发送配置，时间校正取自工程文件
+fn register_dataset(config_manager: &mut ConfigManager, manager: &ProjectManager, name: &str) {
+    let Some(reader) = manager.get_dataset_reader(name) else {
+        return;
+    };
+    let udp_config = config_manager
+        .get_config()
+        .get_dataset_config(name)
+        .map(|c| c.udp_config.clone())
+        .unwrap_or_else(|| UDPConfig::from(&reader.config.network_config));
+
+    config_manager.register_dataset(name.to_string(), reader.config.path.clone(), udp_config);
+    config_manager.set_time_correction(name, reader.config.time_correction.clone());
+}
+
 impl Default for AppState {
     fn default() -> Self {
         Self::new()
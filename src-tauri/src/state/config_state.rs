@@ -2,6 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::types::{NetworkConfig, TimeCorrection};
 
 /// UDP发送配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,12 +15,66 @@ pub struct UDPConfig {
     pub interface: Option<String>,
 }
 
+impl From<&NetworkConfig> for UDPConfig {
+    fn from(config: &NetworkConfig) -> Self {
+        Self {
+            mode: config.network_type.to_string(),
+            target_ip: config.ip_address.clone(),
+            target_port: config.port,
+            interface: config.interface.clone(),
+        }
+    }
+}
+
+impl std::str::FromStr for UDPConfig {
+    type Err = String;
+
+    /// 解析 `模式:IP:端口` 格式，例如 `multicast:239.0.0.1:5000`，
+    /// IPv6地址可加方括号，例如 `unicast:[::1]:5000`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, ip, port) = s
+            .split_once(':')
+            .and_then(|(mode, address)| {
+                let (ip, port) = address.rsplit_once(':')?;
+                Some((mode, ip, port))
+            })
+            .ok_or_else(|| format!("无效的UDP目标: {}", s))?;
+        let ip = ip
+            .strip_prefix('[')
+            .and_then(|ip| ip.strip_suffix(']'))
+            .unwrap_or(ip);
+        let ip: IpAddr = ip.parse().map_err(|_| format!("无效的IP地址: {}", ip))?;
+
+        let mode = mode.to_lowercase();
+        if !matches!(mode.as_str(), "broadcast" | "multicast" | "unicast") {
+            return Err(format!("不支持的UDP模式: {}", mode));
+        }
+
+        let target_port = port
+            .parse::<u16>()
+            .map_err(|_| format!("无效的端口号: {}", port))?;
+
+        Ok(Self {
+            mode,
+            target_ip: ip.to_string(),
+            target_port,
+            interface: None,
+        })
+    }
+}
+
 /// 数据集配置状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatasetConfigState {
     pub name: String,
+    /// 数据集目录路径
+    #[serde(default)]
+    pub path: String,
     pub udp_config: UDPConfig,
     pub enabled: bool,
+    /// 回放时应用的时间校正
+    #[serde(default)]
+    pub time_correction: Option<TimeCorrection>,
 }
 
 /// 配置状态管理器
@@ -55,3 +112,38 @@ impl Default for ConfigState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_udp_targets() {
+        let v4: UDPConfig = "multicast:239.0.0.1:5000".parse().unwrap();
+        assert_eq!(
+            (v4.mode.as_str(), v4.target_ip.as_str(), v4.target_port),
+            ("multicast", "239.0.0.1", 5000)
+        );
+
+        let bracketed: UDPConfig = "Unicast:[fe80::1]:6000".parse().unwrap();
+        assert_eq!(bracketed.mode, "unicast");
+        assert_eq!(bracketed.target_ip, "fe80::1");
+        assert_eq!(bracketed.target_port, 6000);
+
+        let bare: UDPConfig = "unicast:::1:7000".parse().unwrap();
+        assert_eq!((bare.target_ip.as_str(), bare.target_port), ("::1", 7000));
+    }
+
+    #[test]
+    fn rejects_invalid_udp_targets() {
+        for spec in [
+            "unicast",
+            "unicast:10.0.0.1",
+            "anycast:10.0.0.1:5000",
+            "unicast:host:5000",
+            "unicast:10.0.0.1:70000",
+        ] {
+            assert!(spec.parse::<UDPConfig>().is_err(), "{}", spec);
+        }
+    }
+}
@@ -3,3 +3,4 @@
 pub mod app_state;
 pub mod config_state;
 pub mod playback_state;
+pub mod task_state;
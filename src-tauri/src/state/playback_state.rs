@@ -12,25 +12,37 @@ pub enum PlaybackStatus {
 /// 回放状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaybackState {
-    pub current_dataset: Option<String>,
+    /// 最近一次开始回放的数据集
+    pub datasets: Vec<String>,
+    pub start_timestamp: u64,
     pub current_timestamp: u64,
     pub total_duration: u64,
     pub playback_speed: f64,
     pub status: PlaybackStatus,
     pub current_packet_index: u64,
     pub total_packets: u64,
+    /// 已遇到的损坏数据包数量
+    pub corrupt_packets: u64,
+    /// 回放异常停止的原因
+    pub error: Option<String>,
+    /// 是否实时跟随正在录制的数据（总时长随数据追加增长）
+    pub live: bool,
 }
 
 impl PlaybackState {
     pub fn new() -> Self {
         Self {
-            current_dataset: None,
+            datasets: Vec::new(),
+            start_timestamp: 0,
             current_timestamp: 0,
             total_duration: 0,
             playback_speed: 1.0,
             status: PlaybackStatus::Stopped,
             current_packet_index: 0,
             total_packets: 0,
+            corrupt_packets: 0,
+            error: None,
+            live: false,
         }
     }
 
@@ -45,6 +57,15 @@ impl PlaybackState {
     pub fn is_paused(&self) -> bool {
         matches!(self.status, PlaybackStatus::Paused)
     }
+
+    /// 回放进度（0.0 - 1.0）
+    pub fn progress(&self) -> f64 {
+        if self.total_duration == 0 {
+            return 0.0;
+        }
+        let elapsed = self.current_timestamp.saturating_sub(self.start_timestamp);
+        (elapsed as f64 / self.total_duration as f64).min(1.0)
+    }
 }
 
 impl Default for PlaybackState {
@@ -0,0 +1,125 @@
+//! 后台任务登记与取消，以及数据集的占用
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 取消标记，由任务在处理间隙检查
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// 运行中的后台任务
+#[derive(Debug, Clone, Default)]
+pub struct TaskRegistry {
+    next_id: Arc<AtomicU64>,
+    tasks: Arc<Mutex<HashMap<String, CancelToken>>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记新任务，返回任务ID和取消标记
+    pub fn register(&self, kind: &str) -> (String, CancelToken) {
+        let id = format!(
+            "{}-{}",
+            kind,
+            self.next_id.fetch_add(1, Ordering::SeqCst) + 1
+        );
+        let token = CancelToken::new();
+        self.lock().insert(id.clone(), token.clone());
+        (id, token)
+    }
+
+    /// 请求取消任务，任务不存在时返回 `false`
+    pub fn cancel(&self, task_id: &str) -> bool {
+        match self.lock().get(task_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 任务结束后移除登记
+    pub fn finish(&self, task_id: &str) {
+        self.lock().remove(task_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancelToken>> {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 被修改操作占用的数据集目录
+///
+/// 占用检查与回放启动都在 `gate` 内进行，检查通过后到占用结束前数据集不会开始回放
+#[derive(Debug, Clone, Default)]
+pub struct DatasetLocks {
+    held: Arc<Mutex<HashSet<PathBuf>>>,
+    gate: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl DatasetLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 进入占用检查区，返回的守卫释放前其他检查和回放启动须等待
+    pub async fn gate(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.gate.lock().await
+    }
+
+    /// 占用数据集目录，已被占用时返回 `None`
+    pub fn try_acquire(&self, dir: PathBuf) -> Option<DatasetGuard> {
+        if !self.lock().insert(dir.clone()) {
+            return None;
+        }
+        Some(DatasetGuard {
+            held: self.held.clone(),
+            dir,
+        })
+    }
+
+    /// 数据集目录是否被占用
+    pub fn is_held(&self, dir: &Path) -> bool {
+        self.lock().contains(dir)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<PathBuf>> {
+        self.held.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 数据集占用守卫，释放时解除占用
+#[derive(Debug)]
+pub struct DatasetGuard {
+    held: Arc<Mutex<HashSet<PathBuf>>>,
+    dir: PathBuf,
+}
+
+impl Drop for DatasetGuard {
+    fn drop(&mut self) {
+        self.held
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.dir);
+    }
+}
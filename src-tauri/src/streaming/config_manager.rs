@@ -2,8 +2,9 @@
 
 use crate::state::config_state::{ConfigState, DatasetConfigState, UDPConfig};
 use crate::streaming::udp_sender::{NetworkMode, UDPSender};
+use crate::types::{PlaybackError, Result, TimeCorrection};
 use log::info;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -27,53 +28,37 @@ impl ConfigManager {
     }
 
     /// 根据数据集名称创建UDP发送器
-    pub fn create_udp_sender_for_dataset(&self, dataset_name: &str) -> Result<UDPSender, String> {
+    pub fn create_udp_sender_for_dataset(&self, dataset_name: &str) -> Result<UDPSender> {
         let config = self
             .config
             .get_dataset_config(dataset_name)
-            .ok_or_else(|| format!("数据集配置不存在: {}", dataset_name))?;
+            .ok_or_else(|| PlaybackError::DatasetNotFound(dataset_name.to_string()))?;
 
         let mode = match config.udp_config.mode.as_str() {
             "broadcast" => NetworkMode::Broadcast,
             "multicast" => {
-                let group = std::net::Ipv4Addr::from_str(&config.udp_config.target_ip)
-                    .map_err(|_| format!("无效的组播地址: {}", config.udp_config.target_ip))?;
+                let group =
+                    std::net::Ipv4Addr::from_str(&config.udp_config.target_ip).map_err(|_| {
+                        PlaybackError::NetworkError(format!(
+                            "无效的组播地址: {}",
+                            config.udp_config.target_ip
+                        ))
+                    })?;
                 NetworkMode::Multicast { group }
             }
-            "unicast" => {
-                let addr: SocketAddr = format!(
-                    "{}:{}",
-                    config.udp_config.target_ip, config.udp_config.target_port
-                )
-                .parse()
-                .map_err(|_| {
-                    format!(
-                        "无效的目标地址: {}",
-                        format!(
-                            "{}:{}",
-                            config.udp_config.target_ip, config.udp_config.target_port
-                        )
-                    )
-                })?;
-                NetworkMode::Unicast { target: addr }
+            "unicast" => NetworkMode::Unicast {
+                target: target_addr(&config.udp_config)?,
+            },
+            _ => {
+                return Err(PlaybackError::NetworkError(format!(
+                    "不支持的UDP模式: {}",
+                    config.udp_config.mode
+                )))
             }
-            _ => return Err(format!("不支持的UDP模式: {}", config.udp_config.mode)),
         };
 
-        let target_addr = format!(
-            "{}:{}",
-            config.udp_config.target_ip, config.udp_config.target_port
-        )
-        .parse()
-        .map_err(|_| {
-            format!(
-                "无效的目标地址: {}:{}",
-                config.udp_config.target_ip, config.udp_config.target_port
-            )
-        })?;
-
-        let sender =
-            UDPSender::new(mode, target_addr).map_err(|e| format!("创建UDP发送器失败: {:?}", e))?;
+        let target_addr = target_addr(&config.udp_config)?;
+        let sender = UDPSender::new(mode, target_addr)?;
 
         info!(
             "为数据集 '{}' 创建UDP发送器成功: {}",
@@ -82,17 +67,82 @@ impl ConfigManager {
         Ok(sender)
     }
 
-    /// 更新数据集的UDP配置
-    pub fn update_dataset_config(&mut self, dataset_name: String, udp_config: UDPConfig) {
+    /// 注册数据集（目录路径及UDP配置），已注册的数据集保留原有的时间校正
+    pub fn register_dataset(&mut self, dataset_name: String, path: String, udp_config: UDPConfig) {
+        let time_correction = self
+            .config
+            .get_dataset_config(&dataset_name)
+            .and_then(|c| c.time_correction.clone());
         let config = DatasetConfigState {
             name: dataset_name.clone(),
+            path,
             udp_config,
             enabled: true,
+            time_correction,
         };
 
         self.config.set_dataset_config(dataset_name, config);
     }
 
+    /// 更新数据集的UDP配置
+    pub fn update_dataset_config(&mut self, dataset_name: String, udp_config: UDPConfig) {
+        let path = self
+            .config
+            .get_dataset_config(&dataset_name)
+            .map(|c| c.path.clone())
+            .unwrap_or_default();
+
+        self.register_dataset(dataset_name, path, udp_config);
+    }
+
+    /// 注销数据集，返回是否已注册
+    pub fn unregister_dataset(&mut self, dataset_name: &str) -> bool {
+        let registered = self.config.get_dataset_config(dataset_name).is_some();
+        self.config.remove_dataset_config(dataset_name);
+        registered
+    }
+
+    /// 数据集重命名后把发送配置和时间校正转到新名称，原名称未注册时返回 `false`
+    pub fn rename_dataset(&mut self, old_name: &str, new_name: &str) -> bool {
+        let Some(mut config) = self.config.dataset_configs.remove(old_name) else {
+            return false;
+        };
+        config.name = new_name.to_string();
+        self.config.set_dataset_config(new_name.to_string(), config);
+        true
+    }
+
+    /// 注销不在 `dataset_names` 中的数据集，返回注销的名称
+    pub fn retain_datasets(&mut self, dataset_names: &[String]) -> Vec<String> {
+        let mut removed: Vec<String> = self
+            .config
+            .dataset_configs
+            .keys()
+            .filter(|name| !dataset_names.contains(name))
+            .cloned()
+            .collect();
+        removed.sort();
+        for name in &removed {
+            self.config.remove_dataset_config(name);
+        }
+        removed
+    }
+
+    /// 设置数据集回放时的时间校正，数据集未注册时返回 `false`
+    pub fn set_time_correction(
+        &mut self,
+        dataset_name: &str,
+        time_correction: Option<TimeCorrection>,
+    ) -> bool {
+        match self.config.dataset_configs.get_mut(dataset_name) {
+            Some(config) => {
+                config.time_correction = time_correction;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// 获取所有启用的数据集配置
     pub fn get_enabled_datasets(&self) -> Vec<&DatasetConfigState> {
         self.config
@@ -102,3 +152,69 @@ impl ConfigManager {
             .collect()
     }
 }
+
+/// 发送目标地址，IPv6地址不需要方括号
+fn target_addr(config: &UDPConfig) -> Result<SocketAddr> {
+    let ip = config
+        .target_ip
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let ip = IpAddr::from_str(ip).map_err(|_| {
+        PlaybackError::NetworkError(format!(
+            "无效的目标地址: {}:{}",
+            config.target_ip, config.target_port
+        ))
+    })?;
+    Ok(SocketAddr::new(ip, config.target_port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn udp_config(port: u16) -> UDPConfig {
+        UDPConfig {
+            mode: "unicast".to_string(),
+            target_ip: "127.0.0.1".to_string(),
+            target_port: port,
+            interface: None,
+        }
+    }
+
+    #[test]
+    fn rename_keeps_udp_config_and_time_correction() {
+        let mut manager = ConfigManager::new();
+        manager.register_dataset(
+            "radar".to_string(),
+            "/p/radar".to_string(),
+            udp_config(9000),
+        );
+        let correction = TimeCorrection::Offset { offset_ns: 1_000 };
+        manager.set_time_correction("radar", Some(correction.clone()));
+
+        assert!(manager.rename_dataset("radar", "radar2"));
+        assert!(!manager.rename_dataset("radar", "radar3"));
+
+        let config = manager.get_config();
+        assert!(config.get_dataset_config("radar").is_none());
+        let renamed = config.get_dataset_config("radar2").unwrap();
+        assert_eq!(renamed.name, "radar2");
+        assert_eq!(renamed.udp_config.target_port, 9000);
+        assert_eq!(renamed.time_correction, Some(correction));
+    }
+
+    #[test]
+    fn retain_unregisters_other_datasets() {
+        let mut manager = ConfigManager::new();
+        for name in ["radar", "ais", "adsb"] {
+            manager.register_dataset(name.to_string(), String::new(), udp_config(9000));
+        }
+
+        let removed = manager.retain_datasets(&["ais".to_string()]);
+
+        assert_eq!(removed, vec!["adsb", "radar"]);
+        assert_eq!(manager.get_config().dataset_configs.len(), 1);
+        assert!(manager.unregister_dataset("ais"));
+        assert!(!manager.unregister_dataset("ais"));
+    }
+}
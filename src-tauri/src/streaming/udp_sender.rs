@@ -37,8 +37,14 @@ impl UDPSender {
                     .map_err(|e| PlaybackError::NetworkError(e.to_string()))?;
                 socket
             }
-            NetworkMode::Unicast { target: _ } => UdpSocket::bind("0.0.0.0:0")
-                .map_err(|e| PlaybackError::NetworkError(e.to_string()))?,
+            NetworkMode::Unicast { target } => {
+                let local = if target.is_ipv6() {
+                    "[::]:0"
+                } else {
+                    "0.0.0.0:0"
+                };
+                UdpSocket::bind(local).map_err(|e| PlaybackError::NetworkError(e.to_string()))?
+            }
         };
 
         info!("创建UDP发送器 - 模式: {:?}, 目标: {}", mode, target_addr);
@@ -53,6 +53,7 @@ pub struct ProjectInfo {
     pub end_time: String,   // ISO格式时间字符串
     pub metadata: ProjectMetadata,
     pub pcap_files: Vec<String>, // PCAP文件路径列表
+    pub datasets: Vec<DatasetInfo>,
 }
 
 impl ProjectInfo {
@@ -67,10 +68,46 @@ impl ProjectInfo {
             end_time: String::new(),
             metadata: ProjectMetadata::default(),
             pcap_files: Vec::new(),
+            datasets: Vec::new(),
         }
     }
 }
 
+/// 数据集信息
+///
+/// 时间范围取自数据包时间戳，数据集中没有数据包时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatasetInfo {
+    pub name: String,
+    pub path: String,
+    pub file_count: usize,
+    pub total_packets: u64,
+    pub total_size: u64,              // 总大小（字节）
+    pub start_timestamp: Option<u64>, // 首个数据包时间戳（纳秒）
+    pub end_timestamp: Option<u64>,   // 最后数据包时间戳（纳秒）
+    pub total_duration: u64,          // 时长（纳秒）
+    pub start_time: String,           // ISO格式时间字符串
+    pub end_time: String,             // ISO格式时间字符串
+    pub created_time: String,
+    pub modified_time: String,
+    pub has_index: bool,
+}
+
+/// 数据文件信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileInfo {
+    pub file_name: String,
+    pub file_path: String,
+    pub file_size: u64,
+    pub packet_count: u64, // 数据包数量（取自索引，无有效索引时为0）
+    pub start_timestamp: Option<u64>,
+    pub end_timestamp: Option<u64>,
+    pub file_hash: Option<String>, // SHA-256，取自索引
+    pub created_time: String,
+    pub modified_time: String,
+    pub is_valid: bool, // 文件头有效且能读出时间范围
+}
+
 /// 数据更新负载
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataUpdatePayload {
@@ -104,6 +141,24 @@ pub enum PlaybackError {
     #[error("工程错误: {0}")]
     ProjectError(String),
 
+    #[error("数据集不存在: {0}")]
+    DatasetNotFound(String),
+
+    #[error("名称无效: {0}")]
+    InvalidName(String),
+
+    #[error("数据产品不存在: {0}")]
+    ProductNotFound(String),
+
+    #[error("标注不存在: {0}")]
+    AnnotationNotFound(u64),
+
+    #[error("事件不存在: {0}")]
+    EventNotFound(u64),
+
+    #[error("数据集正在使用: {0}")]
+    DatasetBusy(String),
+
     #[error("播放引擎错误: {0}")]
     PlaybackEngineError(String),
 
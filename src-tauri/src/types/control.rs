@@ -0,0 +1,86 @@
+//! 控制接口类型定义
+//!
+//! 包含本地HTTP/WebSocket控制服务相关的类型定义
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::error::CommandError;
+
+/// 控制服务配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlApiConfig {
+    /// 是否启用控制服务
+    pub enabled: bool,
+    /// 监听地址
+    pub bind_addr: String,
+    /// 访问令牌（为空时不校验）
+    pub token: Option<String>,
+    /// WebSocket状态推送间隔（毫秒）
+    pub push_interval_ms: u64,
+}
+
+impl Default for ControlApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_addr: "127.0.0.1:32032".to_string(),
+            token: None,
+            push_interval_ms: 200,
+        }
+    }
+}
+
+/// 控制接口统一响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse<T> {
+    /// 是否成功
+    pub ok: bool,
+    /// 返回数据
+    pub data: Option<T>,
+    /// 错误信息
+    pub error: Option<CommandError>,
+}
+
+impl<T> ControlResponse<T> {
+    /// 成功响应
+    pub fn success(data: T) -> Self {
+        Self {
+            ok: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    /// 失败响应
+    pub fn failure(error: CommandError) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// 打开工程请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenProjectRequest {
+    pub path: String,
+}
+
+/// 开始回放请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartPlaybackRequest {
+    pub dataset_name: String,
+}
+
+/// 跳转请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeekRequest {
+    pub timestamp: u64,
+}
+
+/// 设置速度请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpeedRequest {
+    pub speed: f64,
+}
@@ -0,0 +1,135 @@
+//! 命令错误 - 返回给前端的结构化错误
+
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+
+use crate::types::common::PlaybackError;
+
+/// 错误类别
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorCategory {
+    File,
+    Format,
+    Network,
+    Project,
+    Engine,
+    Parse,
+}
+
+/// 错误上下文
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ErrorContext {
+    /// 相关文件或目录路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// 相关数据集名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset: Option<String>,
+    /// 相关时间戳（纳秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+}
+
+/// 命令错误
+///
+/// `code` 为稳定的错误码，前端据此显示本地化提示；`message` 为后端生成的说明
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandError {
+    pub code: String,
+    pub category: ErrorCategory,
+    pub message: String,
+    pub context: ErrorContext,
+}
+
+impl CommandError {
+    /// 创建命令错误
+    pub fn new(code: &str, category: ErrorCategory, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            category,
+            message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
+    /// 附加路径上下文
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.context.path = Some(path.into());
+        self
+    }
+
+    /// 附加数据集上下文
+    pub fn with_dataset(mut self, dataset: impl Into<String>) -> Self {
+        self.context.dataset = Some(dataset.into());
+        self
+    }
+
+    /// 附加时间戳上下文
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.context.timestamp = Some(timestamp);
+        self
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<PlaybackError> for CommandError {
+    fn from(error: PlaybackError) -> Self {
+        Self::new(error.code(), error.category(), error.to_string())
+    }
+}
+
+impl PlaybackError {
+    /// 稳定的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            PlaybackError::FileError(e) => match e.kind() {
+                ErrorKind::NotFound => "FILE_NOT_FOUND",
+                ErrorKind::PermissionDenied => "FILE_PERMISSION_DENIED",
+                ErrorKind::AlreadyExists => "FILE_ALREADY_EXISTS",
+                _ => "FILE_IO",
+            },
+            PlaybackError::JsonError(_) => "JSON_INVALID",
+            PlaybackError::XmlError(_) => "XML_INVALID",
+            PlaybackError::EncodingError(_) => "ENCODING_INVALID",
+            PlaybackError::FormatError(_) => "FORMAT_INVALID",
+            PlaybackError::NetworkError(_) => "NETWORK_ERROR",
+            PlaybackError::ProjectError(_) => "PROJECT_ERROR",
+            PlaybackError::DatasetNotFound(_) => "DATASET_NOT_FOUND",
+            PlaybackError::ProductNotFound(_) => "PRODUCT_NOT_FOUND",
+            PlaybackError::AnnotationNotFound(_) => "ANNOTATION_NOT_FOUND",
+            PlaybackError::EventNotFound(_) => "EVENT_NOT_FOUND",
+            PlaybackError::InvalidName(_) => "INVALID_NAME",
+            PlaybackError::DatasetBusy(_) => "DATASET_BUSY",
+            PlaybackError::PlaybackEngineError(_) => "ENGINE_ERROR",
+            PlaybackError::ParseError(_) => "PARSE_ERROR",
+        }
+    }
+
+    /// 错误类别
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            PlaybackError::FileError(_) => ErrorCategory::File,
+            PlaybackError::FormatError(_) => ErrorCategory::Format,
+            PlaybackError::NetworkError(_) => ErrorCategory::Network,
+            PlaybackError::ProjectError(_)
+            | PlaybackError::DatasetNotFound(_)
+            | PlaybackError::ProductNotFound(_)
+            | PlaybackError::AnnotationNotFound(_)
+            | PlaybackError::EventNotFound(_)
+            | PlaybackError::InvalidName(_)
+            | PlaybackError::DatasetBusy(_) => ErrorCategory::Project,
+            PlaybackError::PlaybackEngineError(_) => ErrorCategory::Engine,
+            PlaybackError::JsonError(_)
+            | PlaybackError::XmlError(_)
+            | PlaybackError::EncodingError(_)
+            | PlaybackError::ParseError(_) => ErrorCategory::Parse,
+        }
+    }
+}
@@ -1,14 +1,19 @@
 // types模块 - 统一的数据结构定义
 pub mod common;
+pub mod control;
+pub mod error;
 pub mod geo;
 pub mod pcap;
 pub mod pproj;
+pub mod task;
+pub mod time_correction;
 
 // 重新导出通用类型
 pub use common::*;
+pub use control::*;
+pub use error::*;
 pub use geo::*;
 pub use pcap::*;
 pub use pproj::*;
-
-// 重新导出 pcapfile-io 库中的索引相关类型
-pub use pcapfile_io::{PacketIndexEntry, PcapFileIndex, PidxIndex};
+pub use task::*;
+pub use time_correction::*;
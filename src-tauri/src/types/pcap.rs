@@ -1,5 +1,5 @@
 // 重新导出 pcapfile-io 库的类型，并添加应用特定的扩展
-pub use pcapfile_io::{DataPacket, DataPacketHeader as PcapPacketHeader, PcapFileHeader};
+pub use pcapfile_io::DataPacket;
 
 use serde::{Deserialize, Serialize};
 
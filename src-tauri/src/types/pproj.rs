@@ -1,4 +1,5 @@
 use crate::types::common::PlaybackError;
+use crate::types::time_correction::TimeCorrection;
 use serde::{Deserialize, Serialize};
 
 /// 网络传输类型
@@ -130,6 +131,9 @@ pub struct DatasetConfig {
     pub description: Option<String>,
     pub path: String,
     pub network_config: NetworkConfig,
+    /// 回放时应用的时间校正
+    #[serde(default)]
+    pub time_correction: Option<TimeCorrection>,
 }
 
 impl DatasetConfig {
@@ -140,6 +144,7 @@ impl DatasetConfig {
             description: None,
             path: path.as_ref().to_string_lossy().to_string(),
             network_config: NetworkConfig::default(),
+            time_correction: None,
         }
     }
 
@@ -176,27 +181,38 @@ impl DatasetConfig {
         // 验证网络配置
         self.network_config.validate()?;
 
+        if let Some(correction) = &self.time_correction {
+            correction.validate()?;
+        }
+
         Ok(())
     }
 }
 
+/// 当前PPROJ文件格式版本
+pub const PPROJ_VERSION: u32 = 2;
+
 /// PPROJ工程配置结构
+///
+/// 对应工程目录下的 `.pproj` XML文件，读写见 `project::pproj`
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename = "pproj_config")]
 pub struct PprojConfig {
-    pub version: String,
+    /// 文件格式版本
+    pub version: u32,
+    /// 工程名称（对应 `caption` 元素）
     pub name: String,
     pub description: Option<String>,
+    /// 数据起始时间（秒）
+    pub start_timestamp: Option<u64>,
+    /// 数据结束时间（秒）
+    pub end_timestamp: Option<u64>,
+    /// 创建时间，工程文件中没有记录时为空
     pub created_time: String,
+    /// 修改时间，工程文件中没有记录时为空
     pub modified_time: String,
-    #[serde(rename = "dataset")]
-    pub datasets: Vec<DatasetConfig>,
-
-    // 兼容字段
-    pub project_name: String,
-    pub project_description: Option<String>,
     pub author: Option<String>,
     pub tags: Vec<String>,
+    pub datasets: Vec<DatasetConfig>,
 }
 
 impl PprojConfig {
@@ -204,16 +220,16 @@ impl PprojConfig {
     pub fn new(project_name: String) -> Self {
         let now = chrono::Utc::now().to_rfc3339();
         Self {
-            version: "1.0".to_string(),
-            name: project_name.clone(),
+            version: PPROJ_VERSION,
+            name: project_name,
             description: None,
+            start_timestamp: None,
+            end_timestamp: None,
             created_time: now.clone(),
             modified_time: now,
-            datasets: Vec::new(),
-            project_name,
-            project_description: None,
             author: None,
             tags: Vec::new(),
+            datasets: Vec::new(),
         }
     }
 
@@ -225,8 +241,7 @@ impl PprojConfig {
 
     /// 设置描述
     pub fn with_description(mut self, description: String) -> Self {
-        self.description = Some(description.clone());
-        self.project_description = Some(description);
+        self.description = Some(description);
         self
     }
 
@@ -242,6 +257,11 @@ impl PprojConfig {
         self
     }
 
+    /// 查找数据集配置
+    pub fn get_dataset(&self, name: &str) -> Option<&DatasetConfig> {
+        self.datasets.iter().find(|d| d.name == name)
+    }
+
     /// 验证工程配置
     pub fn validate(&self) -> crate::types::common::Result<()> {
         // 验证数据集名称唯一性
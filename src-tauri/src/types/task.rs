@@ -0,0 +1,21 @@
+//! 后台任务类型定义
+
+use serde::{Deserialize, Serialize};
+
+/// 任务进度事件名称
+pub const TASK_PROGRESS_EVENT: &str = "task://progress";
+
+/// 任务进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub task_id: String,
+    /// 任务类型，如 `import`
+    pub kind: String,
+    /// 当前阶段，如 `copying`、`indexing`、`completed`
+    pub stage: String,
+    pub current: u64,
+    pub total: u64,
+    /// 当前处理的文件或条目
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+}
@@ -0,0 +1,359 @@
+//! 数据集时间校正
+//!
+//! 不同平台的记录设备时钟存在偏差和漂移，按数据集把记录时间映射为统一的基准时间。
+//! 校正映射必须单调递增，回放、定位和抽取均直接使用校正后的时间。
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::common::{PlaybackError, Result};
+
+/// 参考事件：同一事件在数据集中的记录时间和基准时间（纳秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeReference {
+    pub recorded_ns: u64,
+    pub actual_ns: u64,
+}
+
+/// 时间校正
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimeCorrection {
+    /// 固定偏移：校正时间 = 记录时间 + `offset_ns`
+    Offset { offset_ns: i64 },
+    /// 线性漂移：校正时间 = 记录时间 + `offset_ns` + (记录时间 - `origin_ns`) × `drift_ppm` / 10⁶
+    Linear {
+        offset_ns: i64,
+        origin_ns: u64,
+        drift_ppm: f64,
+    },
+    /// 分段线性映射：相邻参考点之间线性插值，首尾参考点之外按首尾两段外推，只有一个参考点时为固定偏移
+    Piecewise { points: Vec<TimeReference> },
+}
+
+/// 由参考事件拟合时间校正时使用的模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionModel {
+    /// 各参考事件偏差的平均值
+    Offset,
+    /// 偏差对记录时间的最小二乘直线
+    Linear,
+    /// 经过全部参考事件的分段线性映射
+    Piecewise,
+}
+
+impl CorrectionModel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorrectionModel::Offset => "offset",
+            CorrectionModel::Linear => "linear",
+            CorrectionModel::Piecewise => "piecewise",
+        }
+    }
+}
+
+impl TimeCorrection {
+    /// 校正类型名称
+    pub fn kind(&self) -> CorrectionModel {
+        match self {
+            TimeCorrection::Offset { .. } => CorrectionModel::Offset,
+            TimeCorrection::Linear { .. } => CorrectionModel::Linear,
+            TimeCorrection::Piecewise { .. } => CorrectionModel::Piecewise,
+        }
+    }
+
+    /// 验证映射单调递增
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            TimeCorrection::Offset { .. } => Ok(()),
+            TimeCorrection::Linear { drift_ppm, .. } => {
+                if !drift_ppm.is_finite() || *drift_ppm <= -1_000_000.0 {
+                    return Err(PlaybackError::ParseError(format!(
+                        "时钟漂移必须大于 -10⁶ ppm: {}",
+                        drift_ppm
+                    )));
+                }
+                Ok(())
+            }
+            TimeCorrection::Piecewise { points } => {
+                if points.is_empty() {
+                    return Err(PlaybackError::ParseError(
+                        "分段时间校正至少需要一个参考点".to_string(),
+                    ));
+                }
+                for pair in points.windows(2) {
+                    if pair[1].recorded_ns <= pair[0].recorded_ns
+                        || pair[1].actual_ns <= pair[0].actual_ns
+                    {
+                        return Err(PlaybackError::ParseError(format!(
+                            "分段时间校正的参考点必须按记录时间和基准时间同时递增: {} -> {}",
+                            pair[0].recorded_ns, pair[1].recorded_ns
+                        )));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 由参考事件拟合时间校正
+    pub fn fit(model: CorrectionModel, references: &[TimeReference]) -> Result<Self> {
+        if references.is_empty() {
+            return Err(PlaybackError::ParseError("未提供参考事件".to_string()));
+        }
+        let mut references = references.to_vec();
+        references.sort_by_key(|r| r.recorded_ns);
+
+        let deviation = |r: &TimeReference| r.actual_ns as i128 - r.recorded_ns as i128;
+        let correction = match model {
+            CorrectionModel::Offset => {
+                let sum: i128 = references.iter().map(deviation).sum();
+                TimeCorrection::Offset {
+                    offset_ns: clamp_i64(sum / references.len() as i128),
+                }
+            }
+            CorrectionModel::Linear => {
+                let origin = references[0].recorded_ns;
+                if references.iter().all(|r| r.recorded_ns == origin) {
+                    return Err(PlaybackError::ParseError(
+                        "线性时间校正至少需要两个记录时间不同的参考事件".to_string(),
+                    ));
+                }
+
+                // 偏差 = 截距 + 斜率 × (记录时间 - 起点)
+                let n = references.len() as f64;
+                let points: Vec<(f64, f64)> = references
+                    .iter()
+                    .map(|r| ((r.recorded_ns - origin) as f64, deviation(r) as f64))
+                    .collect();
+                let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+                let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+                let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+                let sxy: f64 = points
+                    .iter()
+                    .map(|(x, y)| (x - mean_x) * (y - mean_y))
+                    .sum();
+                let slope = sxy / sxx;
+
+                TimeCorrection::Linear {
+                    offset_ns: (mean_y - slope * mean_x).round() as i64,
+                    origin_ns: origin,
+                    drift_ppm: slope * 1_000_000.0,
+                }
+            }
+            CorrectionModel::Piecewise => {
+                let mut points: Vec<TimeReference> = Vec::with_capacity(references.len());
+                for reference in references {
+                    match points.last() {
+                        Some(last) if last.recorded_ns == reference.recorded_ns => {
+                            if last.actual_ns != reference.actual_ns {
+                                return Err(PlaybackError::ParseError(format!(
+                                    "记录时间 {} 对应多个不同的基准时间",
+                                    reference.recorded_ns
+                                )));
+                            }
+                        }
+                        _ => points.push(reference),
+                    }
+                }
+                TimeCorrection::Piecewise { points }
+            }
+        };
+
+        correction.validate()?;
+        Ok(correction)
+    }
+
+    /// 把记录时间映射为校正后的时间
+    pub fn apply(&self, recorded_ns: u64) -> u64 {
+        let recorded = recorded_ns as i128;
+        let corrected = match self {
+            TimeCorrection::Offset { offset_ns } => recorded + *offset_ns as i128,
+            TimeCorrection::Linear {
+                offset_ns,
+                origin_ns,
+                drift_ppm,
+            } => {
+                let elapsed = (recorded - *origin_ns as i128) as f64;
+                recorded + *offset_ns as i128 + (elapsed * drift_ppm / 1_000_000.0).round() as i128
+            }
+            TimeCorrection::Piecewise { points } => match points.len() {
+                0 => recorded,
+                1 => recorded + points[0].actual_ns as i128 - points[0].recorded_ns as i128,
+                len => {
+                    // 所在段，首尾参考点之外取首尾两段
+                    let i = points
+                        .partition_point(|p| p.recorded_ns <= recorded_ns)
+                        .clamp(1, len - 1);
+                    let (a, b) = (points[i - 1], points[i]);
+                    let (r0, a0) = (a.recorded_ns as i128, a.actual_ns as i128);
+                    let (r1, a1) = (b.recorded_ns as i128, b.actual_ns as i128);
+                    a0 + (recorded - r0) * (a1 - a0) / (r1 - r0)
+                }
+            },
+        };
+        corrected.clamp(0, u64::MAX as i128) as u64
+    }
+}
+
+fn clamp_i64(value: i128) -> i64 {
+    value.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: u64 = 1_700_000_000_000_000_000;
+    const SECOND: u64 = 1_000_000_000;
+
+    fn reference(recorded_ns: u64, actual_ns: u64) -> TimeReference {
+        TimeReference {
+            recorded_ns,
+            actual_ns,
+        }
+    }
+
+    #[test]
+    fn fits_offset_as_mean_deviation() {
+        let references = [
+            reference(BASE, BASE + 1_000),
+            reference(BASE + SECOND, BASE + SECOND + 3_000),
+        ];
+        let correction = TimeCorrection::fit(CorrectionModel::Offset, &references).unwrap();
+
+        assert_eq!(correction, TimeCorrection::Offset { offset_ns: 2_000 });
+        assert_eq!(correction.apply(BASE), BASE + 2_000);
+    }
+
+    #[test]
+    fn fits_linear_drift_and_extrapolates() {
+        // 偏移 1µs，漂移 50ppm（每秒多 50µs）
+        let actual = |t: u64| t + 1_000 + (t - BASE) / SECOND * 50_000;
+        let references: Vec<_> = [BASE, BASE + 10 * SECOND, BASE + 20 * SECOND]
+            .iter()
+            .map(|&t| reference(t, actual(t)))
+            .collect();
+        let correction = TimeCorrection::fit(CorrectionModel::Linear, &references).unwrap();
+
+        let TimeCorrection::Linear {
+            offset_ns,
+            origin_ns,
+            drift_ppm,
+        } = correction
+        else {
+            panic!("应拟合为线性校正: {:?}", correction);
+        };
+        assert_eq!(offset_ns, 1_000);
+        assert_eq!(origin_ns, BASE);
+        assert!((drift_ppm - 50.0).abs() < 1e-6);
+
+        // 参考事件范围内外都按同一直线校正
+        assert_eq!(
+            correction.apply(BASE + 5 * SECOND),
+            actual(BASE + 5 * SECOND)
+        );
+        assert_eq!(
+            correction.apply(BASE + 100 * SECOND),
+            actual(BASE + 100 * SECOND)
+        );
+        assert_eq!(
+            correction.apply(BASE - SECOND),
+            BASE - SECOND + 1_000 - 50_000
+        );
+    }
+
+    #[test]
+    fn linear_fit_needs_distinct_recorded_times() {
+        let references = [reference(BASE, BASE + 1), reference(BASE, BASE + 2)];
+
+        assert!(TimeCorrection::fit(CorrectionModel::Linear, &references).is_err());
+    }
+
+    #[test]
+    fn piecewise_passes_through_points_and_extrapolates_end_segments() {
+        let references = [
+            reference(BASE + 2 * SECOND, BASE + 2 * SECOND + 300),
+            reference(BASE, BASE + 100),
+            reference(BASE + SECOND, BASE + SECOND + 200),
+        ];
+        let correction = TimeCorrection::fit(CorrectionModel::Piecewise, &references).unwrap();
+
+        for r in &references {
+            assert_eq!(correction.apply(r.recorded_ns), r.actual_ns);
+        }
+        // 段内插值
+        assert_eq!(correction.apply(BASE + SECOND / 2), BASE + SECOND / 2 + 150);
+        // 首尾参考点之外沿首尾两段外推
+        assert_eq!(correction.apply(BASE - SECOND), BASE - SECOND);
+        assert_eq!(correction.apply(BASE + 4 * SECOND), BASE + 4 * SECOND + 500);
+    }
+
+    #[test]
+    fn piecewise_fit_rejects_conflicting_references() {
+        let references = [reference(BASE, BASE + 1), reference(BASE, BASE + 2)];
+
+        assert!(TimeCorrection::fit(CorrectionModel::Piecewise, &references).is_err());
+    }
+
+    #[test]
+    fn rejects_non_monotonic_mappings() {
+        let backwards = TimeCorrection::Piecewise {
+            points: vec![
+                reference(BASE, BASE + SECOND),
+                reference(BASE + SECOND, BASE + SECOND / 2),
+            ],
+        };
+        assert!(backwards.validate().is_err());
+        // 基准时间倒退的参考事件无法拟合分段映射
+        assert!(TimeCorrection::fit(
+            CorrectionModel::Piecewise,
+            &[
+                reference(BASE, BASE + SECOND),
+                reference(BASE + SECOND, BASE)
+            ]
+        )
+        .is_err());
+
+        let reversed = TimeCorrection::Linear {
+            offset_ns: 0,
+            origin_ns: BASE,
+            drift_ppm: -1_000_000.0,
+        };
+        assert!(reversed.validate().is_err());
+    }
+
+    #[test]
+    fn valid_corrections_are_monotonic() {
+        let corrections = [
+            TimeCorrection::Offset { offset_ns: -500 },
+            TimeCorrection::Linear {
+                offset_ns: 1_000,
+                origin_ns: BASE,
+                drift_ppm: -999_000.0,
+            },
+            TimeCorrection::Piecewise {
+                points: vec![
+                    reference(BASE, BASE + 100),
+                    reference(BASE + SECOND, BASE + SECOND + 10),
+                    reference(BASE + 3 * SECOND, BASE + 5 * SECOND),
+                ],
+            },
+        ];
+
+        for correction in &corrections {
+            correction.validate().unwrap();
+            let mut previous = None;
+            for t in (0..=50).map(|i| BASE - SECOND + i * SECOND / 10) {
+                let corrected = correction.apply(t);
+                assert!(
+                    previous.is_none_or(|p| corrected >= p),
+                    "{:?} 在 {} 处不单调",
+                    correction,
+                    t
+                );
+                previous = Some(corrected);
+            }
+        }
+    }
+}
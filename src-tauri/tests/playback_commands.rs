@@ -0,0 +1,184 @@
+//! 回放命令集成测试
+//!
+//! 通过模拟的Tauri应用调用命令，验证命令能取得共享状态并及时返回
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use playback_engine_lib::register_handlers;
+use serde_json::{json, Value};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{
+    get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY,
+};
+use tauri::webview::InvokeRequest;
+use tauri::{App, WebviewWindow, WebviewWindowBuilder};
+
+type MockWebview = WebviewWindow<MockRuntime>;
+
+/// 命令允许的最长响应时间
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 测试数据起始时间（纳秒）
+const BASE_TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+fn create_app() -> (App<MockRuntime>, MockWebview) {
+    let app = register_handlers(mock_builder())
+        .build(mock_context(noop_assets()))
+        .expect("构建测试应用失败");
+    let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .expect("创建测试窗口失败");
+    (app, webview)
+}
+
+/// 调用命令并检查响应时间
+fn invoke(webview: &MockWebview, cmd: &str, args: Value) -> Result<Value, Value> {
+    let started = Instant::now();
+    let response = get_ipc_response(
+        webview,
+        InvokeRequest {
+            cmd: cmd.into(),
+            callback: CallbackFn(0),
+            error: CallbackFn(1),
+            url: "http://tauri.localhost".parse().unwrap(),
+            body: InvokeBody::Json(args),
+            headers: Default::default(),
+            invoke_key: INVOKE_KEY.to_string(),
+        },
+    );
+    assert!(
+        started.elapsed() < COMMAND_TIMEOUT,
+        "命令 {} 响应超时: {:?}",
+        cmd,
+        started.elapsed()
+    );
+
+    response.map(|body| body.deserialize::<Value>().expect("解析命令响应失败"))
+}
+
+fn playback_state(webview: &MockWebview) -> Value {
+    invoke(webview, "get_playback_state", json!({})).expect("获取回放状态失败")
+}
+
+/// 按文件协议写入测试用PCAP文件
+fn write_pcap(path: &Path, timestamps: &[u64]) {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0xD4C3_B2A1u32.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&4u16.to_le_bytes());
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+
+    for (i, timestamp) in timestamps.iter().enumerate() {
+        let data = format!("packet-{}", i).into_bytes();
+        bytes.extend_from_slice(&((timestamp / 1_000_000_000) as u32).to_le_bytes());
+        bytes.extend_from_slice(&((timestamp % 1_000_000_000) as u32).to_le_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&crc32fast::hash(&data).to_le_bytes());
+        bytes.extend_from_slice(&data);
+    }
+
+    fs::write(path, bytes).expect("写入测试PCAP文件失败");
+}
+
+/// 创建包含单个数据集的测试工程
+fn create_project() -> tempfile::TempDir {
+    let project = tempfile::tempdir().expect("创建临时目录失败");
+    let dataset = project.path().join("radar");
+    fs::create_dir(&dataset).unwrap();
+
+    let timestamps: Vec<u64> = (0..10)
+        .map(|i| BASE_TIMESTAMP + i * 1_000_000_000)
+        .collect();
+    write_pcap(
+        &dataset.join("data_231114_221320_0000000.pcap"),
+        &timestamps,
+    );
+
+    project
+}
+
+#[test]
+fn initial_state_is_stopped() {
+    let (_app, webview) = create_app();
+
+    let state = playback_state(&webview);
+    assert_eq!(state["status"], "Stopped");
+    assert_eq!(state["datasets"], json!([]));
+}
+
+#[test]
+fn start_unknown_dataset_fails() {
+    let (_app, webview) = create_app();
+
+    let result = invoke(
+        &webview,
+        "start_playback",
+        json!({ "datasetName": "missing" }),
+    );
+    let error = result.expect_err("未知数据集应返回错误");
+    assert_eq!(error["code"], "DATASET_NOT_FOUND");
+    assert_eq!(error["category"], "Project");
+    assert_eq!(error["context"]["dataset"], "missing");
+    assert_eq!(playback_state(&webview)["status"], "Stopped");
+}
+
+#[test]
+fn playback_commands_control_engine() {
+    let project = create_project();
+    let (_app, webview) = create_app();
+
+    let info = invoke(
+        &webview,
+        "open_project",
+        json!({ "path": project.path().to_string_lossy() }),
+    )
+    .expect("打开工程失败");
+    assert!(info.is_object());
+
+    let datasets = invoke(&webview, "list_datasets", json!({})).expect("获取数据集失败");
+    assert_eq!(datasets, json!(["radar"]));
+
+    invoke(
+        &webview,
+        "start_playback",
+        json!({ "datasetName": "radar" }),
+    )
+    .expect("开始回放失败");
+    let state = playback_state(&webview);
+    assert_eq!(state["status"], "Playing");
+    assert_eq!(state["datasets"], json!(["radar"]));
+    assert_eq!(state["start_timestamp"], BASE_TIMESTAMP);
+
+    invoke(&webview, "pause_playback", json!({})).expect("暂停回放失败");
+    assert_eq!(playback_state(&webview)["status"], "Paused");
+
+    let target = BASE_TIMESTAMP + 5_000_000_000;
+    invoke(&webview, "seek_to_time", json!({ "timestamp": target })).expect("跳转失败");
+    assert_eq!(playback_state(&webview)["current_timestamp"], target);
+
+    invoke(&webview, "set_playback_speed", json!({ "speed": 2.0 })).expect("设置速度失败");
+    assert_eq!(playback_state(&webview)["playback_speed"], 2.0);
+
+    // 暂停后继续同一数据集，保持跳转位置
+    invoke(
+        &webview,
+        "start_playback",
+        json!({ "datasetName": "radar" }),
+    )
+    .expect("继续回放失败");
+    let state = playback_state(&webview);
+    assert_eq!(state["status"], "Playing");
+    assert!(state["current_timestamp"].as_u64().unwrap() >= target);
+
+    invoke(&webview, "stop_playback", json!({})).expect("停止回放失败");
+    let state = playback_state(&webview);
+    assert_eq!(state["status"], "Stopped");
+    assert_eq!(state["current_timestamp"], BASE_TIMESTAMP);
+
+    // 停止后回放任务不再更新状态
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(playback_state(&webview)["status"], "Stopped");
+}
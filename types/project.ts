@@ -1,3 +1,5 @@
+import type { EventLevel } from './index';
+
 /**
  * 工程元数据接口
  */
@@ -20,6 +22,66 @@ export interface ProjectInfo {
   endTime: string; // ISO格式时间字符串
   metadata: ProjectMetadata;
   pcapFiles: string[]; // PCAP文件路径列表
+  datasets: DatasetInfo[];
+}
+
+/**
+ * 数据集信息接口
+ */
+export interface DatasetInfo {
+  name: string;
+  path: string;
+  file_count: number;
+  total_packets: number;
+  total_size: number; // 总大小（字节）
+  start_timestamp?: number; // 首个数据包时间戳（纳秒）
+  end_timestamp?: number; // 最后数据包时间戳（纳秒）
+  total_duration: number; // 时长（纳秒）
+  start_time: string; // ISO格式时间字符串
+  end_time: string; // ISO格式时间字符串
+  created_time: string;
+  modified_time: string;
+  has_index: boolean;
+}
+
+/**
+ * 数据文件信息接口
+ */
+export interface FileInfo {
+  file_name: string;
+  file_path: string;
+  file_size: number;
+  packet_count: number;
+  start_timestamp?: number;
+  end_timestamp?: number;
+  file_hash?: string;
+  created_time: string;
+  modified_time: string;
+  is_valid: boolean;
+}
+
+/**
+ * 错误类别
+ */
+export type ErrorCategory = 'File' | 'Format' | 'Network' | 'Project' | 'Engine' | 'Parse';
+
+/**
+ * 错误上下文接口
+ */
+export interface ErrorContext {
+  path?: string;
+  dataset?: string;
+  timestamp?: number; // 纳秒
+}
+
+/**
+ * 命令错误接口（后端命令失败时返回）
+ */
+export interface CommandError {
+  code: string; // 稳定错误码，如 DATASET_NOT_FOUND
+  category: ErrorCategory;
+  message: string;
+  context: ErrorContext;
 }
 
 /**
@@ -68,3 +130,633 @@ export interface AppInfo {
   description: string;
   rustVersion: string;
 }
+
+/**
+ * 统计选项
+ */
+export interface StatsOptions {
+  rate_interval?: number; // 速率统计间隔（纳秒），默认1秒
+  gap_threshold?: number; // 数据中断阈值（纳秒），默认1秒
+}
+
+/**
+ * 包长分布桶
+ */
+export interface SizeBucket {
+  min_size: number;
+  max_size: number;
+  count: number;
+}
+
+/**
+ * 速率统计点
+ */
+export interface RatePoint {
+  timestamp: number; // 间隔起始时间（纳秒）
+  packets: number;
+  bytes: number;
+}
+
+/**
+ * 数据中断
+ */
+export interface Gap {
+  start_timestamp: number;
+  end_timestamp: number;
+  duration: number;
+  file_name: string;
+}
+
+/**
+ * 数据文件统计接口
+ */
+export interface FileStats {
+  info: FileInfo;
+  total_bytes: number;
+  min_packet_size?: number;
+  max_packet_size?: number;
+  size_histogram: SizeBucket[];
+  rates: RatePoint[];
+  gaps: Gap[];
+}
+
+/**
+ * 数据集统计接口
+ */
+export interface DatasetStats {
+  info: DatasetInfo;
+  files: FileInfo[];
+  total_bytes: number;
+  min_packet_size?: number;
+  max_packet_size?: number;
+  size_histogram: SizeBucket[];
+  rates: RatePoint[];
+  gaps: Gap[];
+}
+
+/**
+ * 导入方式
+ */
+export type ImportMode = 'copy' | 'move';
+
+/**
+ * 导入结果接口
+ */
+export interface ImportSummary {
+  imported: string[];
+  failed: { path: string; reason: string }[];
+  cancelled: boolean;
+  index_path?: string;
+}
+
+/**
+ * 抓包转换过滤条件
+ */
+export interface CaptureFilter {
+  destination?: string; // 目的地址（组播组或单播地址）
+  port?: number; // 目的端口
+}
+
+/**
+ * 抓包转换结果接口
+ */
+export interface ConvertSummary {
+  files: string[];
+  failed: { path: string; reason: string }[];
+  frames: number;
+  datagrams: number;
+  skipped: number;
+  incomplete: number;
+  out_of_order: number; // 超出重排窗口、未能按时间顺序写入的数据报数
+  cancelled: boolean;
+  network?: {
+    network_type: 'unicast' | 'multicast' | 'broadcast';
+    ip_address: string;
+    port: number;
+    interface?: string;
+  };
+  index_path?: string;
+}
+
+/**
+ * pcapng导出选项
+ */
+export interface ExportOptions {
+  start_timestamp?: number; // 起始时间（纳秒）
+  end_timestamp?: number; // 结束时间（纳秒）
+  files?: string[]; // 仅导出指定数据文件
+  min_size?: number;
+  max_size?: number;
+  source_address?: string;
+  source_port?: number;
+}
+
+/**
+ * pcapng导出结果接口
+ */
+export interface ExportSummary {
+  output_path?: string;
+  packets: number;
+  frames: number;
+  oversized: number;
+  cancelled: boolean;
+}
+
+/**
+ * 后台任务进度接口（task://progress 事件）
+ */
+export interface TaskProgress {
+  task_id: string;
+  kind: string;
+  stage: string;
+  current: number;
+  total: number;
+  item?: string;
+}
+
+/**
+ * 回放时损坏数据包的处理方式
+ */
+export type CorruptPacketPolicy = 'abort' | 'skip' | 'flag';
+
+/**
+ * 数据校验问题类型
+ */
+export type VerifyIssueKind =
+  | 'file_header'
+  | 'packet_header'
+  | 'truncated'
+  | 'checksum_mismatch'
+  | 'timestamp_regression'
+  | 'timestamp_jump';
+
+/**
+ * 数据校验问题接口
+ */
+export interface VerifyIssue {
+  kind: VerifyIssueKind;
+  offset: number; // 数据包头在文件中的偏移
+  packet_index: number;
+  message: string;
+}
+
+/**
+ * 单个文件的校验结果接口
+ */
+export interface FileVerifyReport {
+  file_name: string;
+  file_path: string;
+  file_size: number;
+  packets: number;
+  corrupt_packets: number;
+  timestamp_regressions: number;
+  issues: VerifyIssue[];
+  omitted_issues: number; // 超出记录上限而未列出的问题数
+  first_timestamp?: number;
+  last_timestamp?: number;
+}
+
+/**
+ * 数据集校验结果接口
+ */
+export interface DatasetVerifyReport {
+  name: string;
+  files: FileVerifyReport[];
+}
+
+/**
+ * 数据校验报告接口
+ */
+export interface VerifyReport {
+  datasets: DatasetVerifyReport[];
+  total_files: number;
+  total_packets: number;
+  invalid_files: number;
+  corrupt_packets: number;
+  timestamp_regressions: number;
+  verified_time: string;
+  cancelled: boolean;
+}
+
+/**
+ * 数据文件修复选项
+ */
+export interface RepairOptions {
+  max_time_jump?: number; // 相邻数据包时间戳允许的最大差值（纳秒）
+  keep_original?: boolean; // 原文件移到 .trash 目录保留
+  dry_run?: boolean; // 只分析不写入
+}
+
+/**
+ * 修复时丢弃的字节范围接口
+ */
+export interface DroppedRange {
+  offset: number;
+  length: number;
+  kind: VerifyIssueKind;
+  message: string;
+}
+
+/**
+ * 单个文件的修复结果接口
+ */
+export interface FileRepairResult {
+  file_name: string;
+  original_size: number;
+  repaired_size: number;
+  packets: number;
+  dropped: DroppedRange[];
+  dropped_bytes: number;
+  repaired: boolean;
+  backup_path?: string;
+  error?: string;
+}
+
+/**
+ * 数据集修复结果接口
+ */
+export interface RepairSummary {
+  dataset_name: string;
+  checked_files: number;
+  files: FileRepairResult[];
+  index_path?: string;
+  log_path?: string;
+  dry_run: boolean;
+  cancelled: boolean;
+}
+
+/**
+ * 工程检查问题级别
+ */
+export type Severity = 'error' | 'warning' | 'info';
+
+/**
+ * 工程检查问题类型
+ */
+export type ValidationCode =
+  | 'missing_pproj'
+  | 'invalid_pproj'
+  | 'missing_dataset'
+  | 'duplicate_dataset'
+  | 'unregistered_dataset'
+  | 'invalid_network_config'
+  | 'destination_conflict'
+  | 'empty_dataset'
+  | 'invalid_file_name'
+  | 'file_name_mismatch'
+  | 'unreadable_file'
+  | 'empty_file'
+  | 'out_of_order_files'
+  | 'overlapping_files'
+  | 'missing_index'
+  | 'stale_index';
+
+/**
+ * 工程检查问题接口
+ */
+export interface ValidationIssue {
+  severity: Severity;
+  code: ValidationCode;
+  dataset?: string;
+  file?: string;
+  message: string;
+}
+
+/**
+ * 工程检查报告接口
+ */
+export interface ValidationReport {
+  project_path: string;
+  issues: ValidationIssue[]; // 按级别排序
+  errors: number;
+  warnings: number;
+  infos: number;
+  validated_time: string;
+}
+
+/**
+ * 工程目录变化接口
+ */
+export type ProjectChange =
+  | { kind: 'dataset_added'; dataset: string }
+  | { kind: 'dataset_removed'; dataset: string }
+  | { kind: 'file_created'; dataset: string; file_name: string; file_size: number }
+  | {
+      kind: 'file_grown';
+      dataset: string;
+      file_name: string;
+      previous_size: number;
+      file_size: number;
+    }
+  | { kind: 'file_modified'; dataset: string; file_name: string; file_size: number }
+  | { kind: 'file_removed'; dataset: string; file_name: string }
+  | { kind: 'index_updated'; dataset: string };
+
+/**
+ * 工程目录变化事件（project://changed）负载接口
+ */
+export interface ProjectChangeSet {
+  project_path: string;
+  changes: ProjectChange[];
+  datasets: DatasetInfo[]; // 有变化的数据集的最新信息
+  removed_datasets: string[];
+}
+
+/**
+ * 数据产品配置接口（.pprod 文件）
+ */
+export interface ProductConfig {
+  version: number;
+  caption: string;
+  description?: string;
+  start_timestamp: number; // 起始时间戳（纳秒，含）
+  end_timestamp: number; // 结束时间戳（纳秒，含）
+  created_time: string;
+  modified_time: string;
+  datasets: string[];
+}
+
+/**
+ * 数据产品生成状态
+ */
+export type ProductState = 'pending' | 'generating' | 'completed' | 'failed' | 'cancelled';
+
+/**
+ * 数据集生成状态接口
+ */
+export interface DatasetProductStatus {
+  dataset: string;
+  state: ProductState;
+  files: string[];
+  packets: number;
+  total_size: number;
+  source_files: number;
+  corrupt_packets: number;
+  error?: string;
+  updated_time: string;
+}
+
+/**
+ * 数据产品中的数据集接口
+ */
+export interface ProductDataset {
+  name: string;
+  result_path: string; // .result/<数据集>/<产品名>
+  status: DatasetProductStatus;
+  info?: DatasetInfo; // 仅打开产品时提供
+}
+
+/**
+ * 数据产品信息接口
+ */
+export interface ProductInfo {
+  name: string;
+  file_path: string;
+  config: ProductConfig;
+  state: ProductState;
+  datasets: ProductDataset[];
+}
+
+/**
+ * 数据产品生成请求接口
+ */
+export interface ProductRequest {
+  name: string;
+  caption?: string;
+  description?: string;
+  start_timestamp: number;
+  end_timestamp: number;
+  datasets: string[];
+}
+
+/**
+ * 数据产品生成结果接口
+ */
+export interface ProductSummary {
+  name: string;
+  file_path: string;
+  datasets: DatasetProductStatus[];
+  cancelled: boolean;
+}
+
+/**
+ * 数据抽取过滤条件接口
+ */
+export interface ExtractFilter {
+  start_timestamp?: number; // 纳秒，含
+  end_timestamp?: number; // 纳秒，含
+  pattern?: string; // 十六进制字节模式，?? 匹配任意字节，如 "EB 90 ?? 01"
+  pattern_offset?: number; // 未设置时匹配任意位置
+}
+
+/**
+ * 数据抽取请求接口
+ */
+export interface ExtractRequest {
+  sources: string[];
+  target: string; // 不存在时自动创建
+  filter?: ExtractFilter;
+  max_file_size?: number; // 单个输出文件大小上限，默认1 GiB
+}
+
+/**
+ * 数据抽取结果接口
+ */
+export interface ExtractSummary {
+  files: string[];
+  packets: number;
+  total_size: number;
+  source_files: number;
+  corrupt_packets: number;
+  failed: { path: string; reason: string }[];
+  cancelled: boolean;
+  index_path?: string;
+  provenance_path?: string; // 目标数据集中的 .provenance.json
+}
+
+/**
+ * 数据集合并请求接口
+ */
+export interface MergeRequest {
+  sources: string[]; // 顺序即来源标签
+  target: string;
+  max_file_size?: number;
+  apply_time_corrections?: boolean; // 按工程文件中的时间校正对齐后合并
+}
+
+/**
+ * 数据集拆分规则
+ */
+export type SplitRule =
+  | { kind: 'duration'; value: number } // 每个文件的时长（纳秒）
+  | { kind: 'size'; value: number } // 每个文件的大小上限（字节）
+  | { kind: 'packets'; value: number }; // 每个文件的数据包数
+
+/**
+ * 数据集拆分请求接口
+ */
+export interface SplitRequest {
+  dataset: string;
+  rule: SplitRule;
+  target?: string; // 未设置时原地替换
+  keep_original?: boolean; // 默认true，原文件移到 .trash
+}
+
+/**
+ * 数据集拆分结果接口
+ */
+export interface SplitSummary extends ExtractSummary {
+  dataset: string;
+  backup_path?: string;
+}
+
+/**
+ * 时间校正参考事件（纳秒）
+ */
+export interface TimeReference {
+  recorded_ns: number; // 数据集中的记录时间
+  actual_ns: number; // 基准时间
+}
+
+/**
+ * 数据集时间校正
+ */
+export type TimeCorrection =
+  | { kind: 'offset'; offset_ns: number }
+  | { kind: 'linear'; offset_ns: number; origin_ns: number; drift_ppm: number }
+  | { kind: 'piecewise'; points: TimeReference[] }; // 相邻参考点之间线性插值
+
+/**
+ * 时间校正拟合模型
+ */
+export type CorrectionModel = 'offset' | 'linear' | 'piecewise';
+
+/**
+ * 工程标注接口（保存在工程目录的 .annotations.json）
+ */
+export interface Annotation {
+  id: number;
+  start_timestamp: number; // 时间点或区间起点（纳秒）
+  end_timestamp?: number; // 区间终点（纳秒），时间点标注为空
+  text: string;
+  category?: string;
+  color?: string; // #RGB、#RRGGBB 或 #RRGGBBAA
+  author?: string;
+  dataset?: string; // 关联的数据集
+  file_name?: string; // 关联的数据文件，须同时关联数据集
+  packet_index?: number; // 关联的数据包在数据文件中的序号，须同时关联数据文件
+  created_time: string;
+  modified_time: string;
+}
+
+/**
+ * 新建或修改标注时提交的内容
+ */
+export type AnnotationDraft = Omit<Annotation, 'id' | 'created_time' | 'modified_time'>;
+
+/**
+ * 标注查询条件接口
+ */
+export interface AnnotationQuery {
+  start_timestamp?: number; // 与时间窗口（纳秒，含两端）重叠的标注
+  end_timestamp?: number;
+  category?: string;
+  dataset?: string;
+  author?: string;
+}
+
+/**
+ * 数据包负载中的数值字段
+ */
+export interface FieldSpec {
+  offset: number; // 在负载中的字节偏移
+  type: 'u8' | 'i8' | 'u16' | 'i16' | 'u32' | 'i32' | 'u64' | 'i64' | 'f32' | 'f64';
+  byte_order?: 'big' | 'little'; // 默认网络字节序
+}
+
+/**
+ * 事件检测规则类型及参数
+ */
+export type RuleKind =
+  | {
+      kind: 'threshold';
+      field: FieldSpec;
+      threshold: number;
+      crossing?: 'rising' | 'falling' | 'both'; // 默认both
+    }
+  | { kind: 'state_change'; field: FieldSpec }
+  | { kind: 'new_target'; field: FieldSpec; expire_ns?: number } // 超过expire_ns未出现后再次出现也视为新目标
+  | { kind: 'data_gap'; min_gap_ns: number };
+
+/**
+ * 事件类型
+ */
+export type EventKind = RuleKind['kind'];
+
+/**
+ * 事件检测规则
+ */
+export type EventRule = RuleKind & {
+  name: string; // 同一次检测中唯一
+  datasets?: string[]; // 为空时适用于所有参与检测的数据集
+  pattern?: string; // 只检查匹配该字节模式的数据包（十六进制，?? 匹配任意字节）
+  pattern_offset?: number; // 未设置时在任意位置查找
+  level?: EventLevel; // 默认normal
+  title?: string; // 未设置时使用规则名称
+};
+
+/**
+ * 事件检测请求接口
+ */
+export interface EventRequest {
+  rules: EventRule[];
+  datasets?: string[]; // 为空时检测工程中的所有数据集
+  start_timestamp?: number; // 检测的时间窗口（纳秒，按校正后的时间）
+  end_timestamp?: number;
+}
+
+/**
+ * 事件检测结果接口
+ */
+export interface EventSummary {
+  events: number;
+  datasets: number;
+  source_files: number;
+  packets: number;
+  corrupt_packets: number;
+  failed: { path: string; reason: string }[];
+  cancelled: boolean;
+  events_path?: string; // 工程目录中的 .events.json
+}
+
+/**
+ * 检测出的事件接口
+ */
+export interface DetectedEvent {
+  id: number; // 按时间顺序的编号，从1开始
+  timestamp: number; // 事件时间（纳秒），区间事件为起点
+  end_timestamp?: number; // 区间事件（数据中断）的终点
+  rule: string;
+  kind: EventKind;
+  level: EventLevel;
+  dataset: string;
+  title: string;
+  description: string;
+  value?: number; // 触发事件的字段值，数据中断为间隔（纳秒）
+  previous?: number; // 变化前的字段值
+  file_name: string;
+  packet_index: number;
+}
+
+/**
+ * 事件查询条件接口
+ */
+export interface EventQuery {
+  start_timestamp?: number; // 与时间窗口（纳秒，含两端）重叠的事件
+  end_timestamp?: number;
+  kinds?: EventKind[];
+  levels?: EventLevel[];
+  rules?: string[];
+  datasets?: string[];
+  limit?: number;
+}